@@ -93,5 +93,88 @@ bench_file!(citm_catalog);
 bench_file!(log);
 bench_file!(twitter);
 
-criterion_group!(benches, apache_builds, canada, citm_catalog, log, twitter);
+fn redact_keys(c: &mut Criterion) {
+    let mut vec = Vec::new();
+    File::open("data/citm_catalog.json")
+        .unwrap()
+        .read_to_end(&mut vec)
+        .unwrap();
+    let keys = simd_json::KeyMatcher::new(["name", "logo"]);
+
+    let mut group = c.benchmark_group("redact_keys");
+    group.throughput(Throughput::Bytes(vec.len() as u64));
+
+    let owned = simd_json::to_owned_value(&mut vec.clone()).unwrap();
+    group.bench_function("simd_json::OwnedValue::redact_keys", |b| {
+        b.iter_batched(
+            || owned.clone(),
+            |mut v| v.redact_keys(&keys),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_with_input("simd_json::BorrowedValue::redact_keys", &vec, |b, data| {
+        b.iter_batched(
+            || data.clone(),
+            |mut bytes| {
+                let mut v = simd_json::to_borrowed_value(&mut bytes).unwrap();
+                v.redact_keys(&keys);
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+#[cfg(feature = "known-key")]
+fn known_key_insert(c: &mut Criterion) {
+    use simd_json::prelude::*;
+    use simd_json::{BorrowedValue, KnownKey};
+
+    let mut group = c.benchmark_group("known_key_insert");
+    // 8 keys use the vector backed map, 64 the hash map backed one
+    for size in [8, 64] {
+        let mut object = BorrowedValue::object_with_capacity(size + 1);
+        for i in 0..size {
+            object.insert(format!("key{i}"), i).unwrap();
+        }
+        let known_key = KnownKey::from("answer");
+
+        group.bench_function(format!("KnownKey::insert/{size}"), |b| {
+            b.iter_batched(
+                || object.clone(),
+                |mut o| known_key.insert(&mut o, 42.into()).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(format!("Mutable::insert/{size}"), |b| {
+            b.iter_batched(
+                || object.clone(),
+                |mut o| o.insert("answer", 42).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+#[cfg(not(feature = "known-key"))]
+criterion_group!(
+    benches,
+    apache_builds,
+    canada,
+    citm_catalog,
+    log,
+    twitter,
+    redact_keys
+);
+#[cfg(feature = "known-key")]
+criterion_group!(
+    benches,
+    apache_builds,
+    canada,
+    citm_catalog,
+    log,
+    twitter,
+    redact_keys,
+    known_key_insert
+);
 criterion_main!(benches);
@@ -0,0 +1,28 @@
+#!/usr/bin/env bash
+# Checks that the `paranoid-checks` feature costs nothing when it is disabled:
+# the release assembly of the library has to be identical to the one of a
+# build where the accessors in `safer_unchecked.rs` call `get_unchecked`
+# unconditionally.
+set -euo pipefail
+
+export RUSTFLAGS="${RUSTFLAGS:--C target-cpu=native}"
+FILE=src/safer_unchecked.rs
+CHECK='cfg!(any(debug_assertions, feature = "paranoid-checks"))'
+OUT=$(mktemp -d)
+
+emit() {
+    cargo clean --release -p simd-json
+    cargo rustc --release --lib -- --emit=asm -C codegen-units=1
+    cat target/release/deps/simd_json-*.s > "$OUT/$1.s"
+}
+
+grep -qF "$CHECK" "$FILE"
+emit checked
+
+cp "$FILE" "$OUT/safer_unchecked.rs"
+trap 'cp "$OUT/safer_unchecked.rs" "$FILE"' EXIT
+sed -i "s|$CHECK|false|" "$FILE"
+emit unchecked
+
+cmp "$OUT/checked.s" "$OUT/unchecked.s"
+echo "paranoid-checks disabled: codegen is identical to plain get_unchecked"
@@ -0,0 +1,51 @@
+/*
+ * Parses a document and an invalid one through the C API.
+ *
+ *   cargo rustc --release --features capi --crate-type staticlib
+ *   cc -Iinclude examples/capi.c target/release/libsimd_json.a \
+ *      -lpthread -ldl -lm -o capi
+ *   ./capi
+ */
+#include "simd_json.h"
+#include <stdio.h>
+#include <string.h>
+
+static const simd_json_value *get(const simd_json_value *v, const char *key) {
+  return simd_json_object_get(v, key, strlen(key));
+}
+
+int main(void) {
+  const char *input = "{\"name\": \"ferris\", \"legs\": 10, \"tags\": [\"crab\"]}";
+  simd_json_doc *doc = NULL;
+  simd_json_error error;
+
+  if (simd_json_parse((const uint8_t *)input, strlen(input), &doc, &error) !=
+      SIMD_JSON_OK) {
+    fprintf(stderr, "parse failed: %s\n", error.message);
+    return 1;
+  }
+  const simd_json_value *root = simd_json_doc_root(doc);
+  const char *name = NULL;
+  size_t name_len = 0;
+  int64_t legs = 0;
+  if (simd_json_value_type(root) != SIMD_JSON_TYPE_OBJECT ||
+      !simd_json_get_string(get(root, "name"), &name, &name_len) ||
+      !simd_json_get_int64(get(root, "legs"), &legs) ||
+      simd_json_value_len(get(root, "tags")) != 1) {
+    fprintf(stderr, "unexpected document\n");
+    simd_json_doc_free(doc);
+    return 1;
+  }
+  printf("%.*s has %lld legs\n", (int)name_len, name, (long long)legs);
+  simd_json_doc_free(doc);
+
+  const char *invalid = "{\"a\": [1, 2}";
+  if (simd_json_parse((const uint8_t *)invalid, strlen(invalid), &doc,
+                      &error) != SIMD_JSON_ERROR_PARSE ||
+      doc != NULL) {
+    fprintf(stderr, "invalid input was accepted\n");
+    return 1;
+  }
+  printf("error at %zu: %s\n", error.offset, error.message);
+  return 0;
+}
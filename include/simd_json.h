@@ -0,0 +1,69 @@
+/*
+ * C API of simd-json, available when the crate is built with the `capi`
+ * feature, see `src/capi.rs` for details and `examples/capi.c` for an
+ * example.
+ *
+ * Value handles point into their document and are valid until it is
+ * freed. Strings are not NUL terminated. All functions accept NULL
+ * handles, accessors then return NULL, 0 or false.
+ */
+#ifndef SIMD_JSON_H
+#define SIMD_JSON_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define SIMD_JSON_OK 0
+#define SIMD_JSON_ERROR_PARSE 1
+#define SIMD_JSON_ERROR_INVALID_ARGUMENT 2
+#define SIMD_JSON_ERROR_PANIC 3
+
+#define SIMD_JSON_TYPE_NULL 0
+#define SIMD_JSON_TYPE_BOOL 1
+#define SIMD_JSON_TYPE_INT64 2
+#define SIMD_JSON_TYPE_UINT64 3
+#define SIMD_JSON_TYPE_DOUBLE 4
+#define SIMD_JSON_TYPE_STRING 5
+#define SIMD_JSON_TYPE_ARRAY 6
+#define SIMD_JSON_TYPE_OBJECT 7
+
+#define SIMD_JSON_ERROR_MESSAGE_LEN 128
+
+typedef struct simd_json_doc simd_json_doc;
+typedef struct simd_json_value simd_json_value;
+
+typedef struct simd_json_error {
+  int32_t code;
+  size_t offset;
+  char message[SIMD_JSON_ERROR_MESSAGE_LEN];
+} simd_json_error;
+
+int32_t simd_json_parse(const uint8_t *data, size_t len, simd_json_doc **doc,
+                        simd_json_error *error);
+void simd_json_doc_free(simd_json_doc *doc);
+const simd_json_value *simd_json_doc_root(const simd_json_doc *doc);
+
+int32_t simd_json_value_type(const simd_json_value *v);
+size_t simd_json_value_len(const simd_json_value *v);
+const simd_json_value *simd_json_array_get(const simd_json_value *v,
+                                           size_t idx);
+const simd_json_value *simd_json_object_get(const simd_json_value *v,
+                                            const char *key, size_t key_len);
+
+bool simd_json_get_bool(const simd_json_value *v, bool *out);
+bool simd_json_get_int64(const simd_json_value *v, int64_t *out);
+bool simd_json_get_uint64(const simd_json_value *v, uint64_t *out);
+bool simd_json_get_double(const simd_json_value *v, double *out);
+bool simd_json_get_string(const simd_json_value *v, const char **out,
+                          size_t *out_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
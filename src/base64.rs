@@ -0,0 +1,416 @@
+//! Base64 encoding of byte strings for serde, use it on `Vec<u8>` or
+//! `bytes::Bytes` fields with `#[serde(with = "simd_json::base64")]`.
+//!
+//! The functions in this module use the standard alphabet and write
+//! padding, the ones in [`url_safe`] use the URL and filename safe
+//! alphabet without padding. Both accept input with and without padding
+//! when deserializing, but only the characters of their own alphabet.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Blob {
+//!     #[serde(with = "simd_json::base64")]
+//!     data: Vec<u8>,
+//!     #[serde(with = "simd_json::base64::url_safe")]
+//!     token: Vec<u8>,
+//! }
+//! let blob = Blob {
+//!     data: vec![0xfb, 0xff, 0x01, 0x02],
+//!     token: vec![0xfb, 0xff, 0x01, 0x02],
+//! };
+//! let mut json = simd_json::to_vec(&blob).unwrap();
+//! assert_eq!(json, br#"{"data":"+/8BAg==","token":"-_8BAg"}"#);
+//! assert_eq!(simd_json::from_slice::<Blob>(&mut json).unwrap(), blob);
+//! ```
+//!
+//! Encoding writes to the serializer through `collect_str`, the
+//! serializers of this crate write the encoded text in pieces straight
+//! into their output. Decoding reads the string the deserializer hands
+//! out, borrowed from the input when it has no escapes, and converts
+//! four characters to three bytes at a time with a lookup table.
+
+// without serde only the standard decoder is used, by `try_coerce_to`
+#![cfg_attr(not(feature = "serde_impl"), allow(dead_code))]
+
+use std::fmt;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Marks characters that are not part of an alphabet in a decode table,
+/// valid characters decode to values below 64
+const INVALID: u8 = 0xff;
+
+#[allow(clippy::cast_possible_truncation)]
+const fn decode_table(alphabet: &[u8; 64]) -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < 64 {
+        table[alphabet[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+const STANDARD_DECODE: [u8; 256] = decode_table(STANDARD);
+const URL_SAFE_DECODE: [u8; 256] = decode_table(URL_SAFE);
+
+/// Bytes encoded at once into a stack buffer by `Encoded`, a multiple of
+/// three so only the last piece needs padding
+const ENCODE_CHUNK: usize = 768;
+
+/// Displays bytes as base64 without allocating
+struct Encoded<'bytes> {
+    bytes: &'bytes [u8],
+    alphabet: &'static [u8; 64],
+    padded: bool,
+}
+
+impl fmt::Display for Encoded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0; ENCODE_CHUNK / 3 * 4];
+        for chunk in self.bytes.chunks(ENCODE_CHUNK) {
+            let len = encode_chunk(chunk, self.alphabet, self.padded, &mut buf);
+            // the alphabets and the padding are ASCII
+            f.write_str(unsafe { std::str::from_utf8_unchecked(&buf[..len]) })?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes `input` into `out` and returns the number of characters
+/// written, `out` has to hold four characters per started three bytes
+fn encode_chunk(input: &[u8], alphabet: &[u8; 64], padded: bool, out: &mut [u8]) -> usize {
+    let sextet = |acc: u32, i: usize| alphabet[(acc >> (18 - 6 * i)) as usize & 63];
+    let mut len = 0;
+    let mut groups = input.chunks_exact(3);
+    for g in groups.by_ref() {
+        let acc = u32::from(g[0]) << 16 | u32::from(g[1]) << 8 | u32::from(g[2]);
+        out[len] = sextet(acc, 0);
+        out[len + 1] = sextet(acc, 1);
+        out[len + 2] = sextet(acc, 2);
+        out[len + 3] = sextet(acc, 3);
+        len += 4;
+    }
+    let rest = groups.remainder();
+    if let Some(first) = rest.first() {
+        let acc = u32::from(*first) << 16 | rest.get(1).map_or(0, |b| u32::from(*b) << 8);
+        // one byte takes two characters, two bytes take three
+        for i in 0..=rest.len() {
+            out[len] = sextet(acc, i);
+            len += 1;
+        }
+        if padded {
+            for _ in rest.len()..3 {
+                out[len] = b'=';
+                len += 1;
+            }
+        }
+    }
+    len
+}
+
+/// Decodes base64 with the alphabet of `table`, padding is optional but
+/// has to be complete if it is there
+pub(crate) fn decode(input: &[u8], table: &[u8; 256]) -> Option<Vec<u8>> {
+    let input = if input.len() % 4 == 0 {
+        input
+            .strip_suffix(b"==")
+            .or_else(|| input.strip_suffix(b"="))
+            .unwrap_or(input)
+    } else {
+        input
+    };
+    // a single character can't encode a full byte
+    if input.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(input.len() / 4 * 3 + 2);
+    let mut groups = input.chunks_exact(4);
+    for g in groups.by_ref() {
+        let [_, b0, b1, b2] = decode_group(g, table)?.to_be_bytes();
+        out.extend_from_slice(&[b0, b1, b2]);
+    }
+    let rest = groups.remainder();
+    if !rest.is_empty() {
+        let [_, b0, b1, _] = decode_group(rest, table)?.to_be_bytes();
+        out.extend_from_slice(&[b0, b1][..rest.len() - 1]);
+    }
+    Some(out)
+}
+
+/// Decodes up to four characters into the low 24 bits, left aligned
+fn decode_group(group: &[u8], table: &[u8; 256]) -> Option<u32> {
+    let mut acc = 0;
+    let mut invalid = 0;
+    for (i, c) in group.iter().enumerate() {
+        let sextet = table[*c as usize];
+        invalid |= sextet;
+        acc |= u32::from(sextet) << (18 - 6 * i);
+    }
+    // only `INVALID` has any of the two high bits set
+    if invalid & 0xc0 == 0 {
+        Some(acc)
+    } else {
+        None
+    }
+}
+
+/// Decodes standard base64, padding is optional
+pub(crate) fn decode_standard(input: &[u8]) -> Option<Vec<u8>> {
+    decode(input, &STANDARD_DECODE)
+}
+
+#[cfg(feature = "serde_impl")]
+mod serde_impl {
+    use super::{decode, Encoded};
+    use crate::serde_ext::{de, Deserializer, Serializer};
+    use std::fmt;
+
+    pub(super) fn serialize<T, S>(
+        bytes: &T,
+        serializer: S,
+        alphabet: &'static [u8; 64],
+        padded: bool,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + AsRef<[u8]>,
+        S: Serializer,
+    {
+        serializer.collect_str(&Encoded {
+            bytes: bytes.as_ref(),
+            alphabet,
+            padded,
+        })
+    }
+
+    pub(super) fn deserialize<'de, T, D>(
+        deserializer: D,
+        table: &'static [u8; 256],
+    ) -> Result<T, D::Error>
+    where
+        T: From<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_str(Base64Visitor(table))
+            .map(T::from)
+    }
+
+    struct Base64Visitor(&'static [u8; 256]);
+
+    impl<'de> de::Visitor<'de> for Base64Visitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a base64 encoded string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            decode(v.as_bytes(), self.0)
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+}
+
+/// Serializes bytes as a base64 string with the standard alphabet and
+/// padding
+///
+/// # Errors
+///
+/// Will return `Err` if the serializer fails to write the string.
+#[cfg(feature = "serde_impl")]
+pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + AsRef<[u8]>,
+    S: crate::serde_ext::Serializer,
+{
+    serde_impl::serialize(bytes, serializer, STANDARD, true)
+}
+
+/// Deserializes bytes from a base64 string with the standard alphabet,
+/// with or without padding
+///
+/// # Errors
+///
+/// Will return `Err` if the value isn't a string or not valid base64.
+#[cfg(feature = "serde_impl")]
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<Vec<u8>>,
+    D: crate::serde_ext::Deserializer<'de>,
+{
+    serde_impl::deserialize(deserializer, &STANDARD_DECODE)
+}
+
+/// Base64 with the URL and filename safe alphabet, which uses `-` and `_`
+/// instead of `+` and `/`, and without padding as it is usually used in
+/// URLs and tokens.
+#[cfg(feature = "serde_impl")]
+pub mod url_safe {
+    use super::{serde_impl, URL_SAFE, URL_SAFE_DECODE};
+
+    /// Serializes bytes as a base64 string with the URL safe alphabet and
+    /// no padding
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the serializer fails to write the string.
+    pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + AsRef<[u8]>,
+        S: crate::serde_ext::Serializer,
+    {
+        serde_impl::serialize(bytes, serializer, URL_SAFE, false)
+    }
+
+    /// Deserializes bytes from a base64 string with the URL safe
+    /// alphabet, with or without padding
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value isn't a string or not valid base64.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<Vec<u8>>,
+        D: crate::serde_ext::Deserializer<'de>,
+    {
+        serde_impl::deserialize(deserializer, &URL_SAFE_DECODE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, Encoded, STANDARD, STANDARD_DECODE, URL_SAFE, URL_SAFE_DECODE};
+
+    fn encode(bytes: &[u8], alphabet: &'static [u8; 64], padded: bool) -> String {
+        Encoded {
+            bytes,
+            alphabet,
+            padded,
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn rfc_4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encode(plain.as_bytes(), STANDARD, true), encoded);
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(encode(plain.as_bytes(), URL_SAFE, false), unpadded);
+            for e in [encoded, unpadded] {
+                let decoded = decode(e.as_bytes(), &STANDARD_DECODE);
+                assert_eq!(decoded.as_deref(), Some(plain.as_bytes()), "{e}");
+                let decoded = decode(e.as_bytes(), &URL_SAFE_DECODE);
+                assert_eq!(decoded.as_deref(), Some(plain.as_bytes()), "{e}");
+            }
+        }
+        assert_eq!(encode(&[0xfb, 0xff, 0xbf], STANDARD, true), "+/+/");
+        assert_eq!(encode(&[0xfb, 0xff, 0xbf], URL_SAFE, true), "-_-_");
+    }
+
+    #[test]
+    fn round_trips_across_chunks() {
+        // every byte value, longer than two encoding chunks
+        let bytes: Vec<u8> = (0..=255).cycle().step_by(7).take(2000).collect();
+        for len in (0..20).chain([767, 768, 769, 1535, 1536, 1537, 2000]) {
+            let bytes = &bytes[..len];
+            for (alphabet, table) in [(STANDARD, &STANDARD_DECODE), (URL_SAFE, &URL_SAFE_DECODE)] {
+                for padded in [true, false] {
+                    let e = encode(bytes, alphabet, padded);
+                    assert_eq!(e.len() % 4 == 0, padded || len % 3 == 0);
+                    assert_eq!(decode(e.as_bytes(), table).as_deref(), Some(bytes));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let malformed = [
+            "Z",           // a single character can't encode a byte
+            "Zm9vY",       // nor can one after complete groups
+            "Zg=",         // incomplete padding
+            "Zg===",       // too much padding
+            "====",        // only padding
+            "Z===",        // padding of a single character
+            "Zg==Zg==",    // padding in the middle
+            "Zm9v!",       // not in any alphabet
+            "Zm 9v",       // whitespace
+            "Zm9v\u{e9}A", // non ASCII
+        ];
+        for m in malformed {
+            assert_eq!(decode(m.as_bytes(), &STANDARD_DECODE), None, "{m}");
+            assert_eq!(decode(m.as_bytes(), &URL_SAFE_DECODE), None, "{m}");
+        }
+        // each alphabet rejects the characters of the other one
+        assert_eq!(decode(b"-_-_", &STANDARD_DECODE), None);
+        assert_eq!(decode(b"+/+/", &URL_SAFE_DECODE), None);
+    }
+
+    #[cfg(feature = "serde_impl")]
+    #[test]
+    fn serde() {
+        use crate::{from_slice, to_string, to_string_pretty, ErrorType};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Blobs {
+            #[serde(with = "crate::base64")]
+            standard: Vec<u8>,
+            #[serde(with = "crate::base64::url_safe")]
+            url_safe: Vec<u8>,
+            #[serde(with = "crate::base64")]
+            empty: Vec<u8>,
+        }
+        let blobs = Blobs {
+            standard: vec![0xfb, 0xff, 0xbf, 0x00],
+            url_safe: vec![0xfb, 0xff, 0xbf, 0x00],
+            empty: Vec::new(),
+        };
+        let expected = r#"{"standard":"+/+/AA==","url_safe":"-_-_AA","empty":""}"#;
+        let s = to_string(&blobs).expect("serializable");
+        assert_eq!(s, expected);
+        assert_eq!(s, serde_json::to_string(&blobs).expect("serializable"));
+        let pretty = to_string_pretty(&blobs).expect("serializable");
+        assert_eq!(
+            pretty,
+            serde_json::to_string_pretty(&blobs).expect("serializable")
+        );
+        for s in [s, pretty] {
+            let mut d = s.into_bytes();
+            assert_eq!(from_slice::<Blobs>(&mut d).expect("valid blobs"), blobs);
+        }
+
+        // padding is accepted by both, escaped strings are decoded too
+        let mut d = br#"{"standard":"+\/+\/AA","url_safe":"-_-_AA==","empty":""}"#.to_vec();
+        assert_eq!(from_slice::<Blobs>(&mut d).expect("valid blobs"), blobs);
+
+        for invalid in [
+            r#"{"standard":"-_-_AA==","url_safe":"","empty":""}"#,
+            r#"{"standard":"","url_safe":"+/+/AA","empty":""}"#,
+            r#"{"standard":"A","url_safe":"","empty":""}"#,
+            r#"{"standard":[1, 2],"url_safe":"","empty":""}"#,
+        ] {
+            let mut d = invalid.as_bytes().to_vec();
+            let e = from_slice::<Blobs>(&mut d).expect_err(invalid);
+            assert!(
+                matches!(e.error(), ErrorType::Serde(_) | ErrorType::ExpectedString),
+                "{invalid}: {e}"
+            );
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::value::owned::{to_value_with_buffers, Value as OwnedValue};
+#[cfg(feature = "serde_impl")]
+use crate::Deserializer;
+use crate::{AlignedBuf, Error, Result, SIMDJSON_PADDING};
+#[cfg(feature = "serde_impl")]
+use serde_ext::Deserialize;
+
+/// The input and string buffers of the parser, kept to parse more
+/// documents without allocating them again. They grow to fit the
+/// largest document parsed with them.
+pub(crate) struct Buffers {
+    input_buffer: AlignedBuf,
+    string_buffer: Vec<u8>,
+}
+
+impl Default for Buffers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Buffers {
+    /// Creates empty buffers, they are allocated with the first document
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        Self {
+            input_buffer: AlignedBuf::with_capacity(SIMDJSON_PADDING * 2),
+            string_buffer: Vec::new(),
+        }
+    }
+
+    /// The buffers for a document of `len` bytes, growing them if needed,
+    /// the input buffer is grown by the parser itself
+    fn get(&mut self, len: usize) -> (&mut AlignedBuf, &mut [u8]) {
+        let len = len + SIMDJSON_PADDING;
+        if self.string_buffer.len() < len {
+            self.string_buffer.resize(len, 0);
+        }
+        (&mut self.input_buffer, &mut self.string_buffer)
+    }
+}
+
+/// The result of parsing a batch of documents
+#[derive(Debug)]
+pub struct BatchResult<T> {
+    /// The parsed documents with their index in the batch, in order
+    pub values: Vec<(usize, T)>,
+    /// The errors of the documents that failed to parse with their index
+    /// in the batch, in order
+    pub errors: Vec<(usize, Error)>,
+}
+
+impl<T> BatchResult<T> {
+    /// Returns true if no document failed to parse
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Parses batches of independent documents, reusing the same buffers
+/// for every document of every batch. Errors are collected per document
+/// and by default don't stop the batch.
+///
+/// ```rust
+/// use simd_json::Batch;
+/// let mut docs = vec![br#"{"a": 1}"#.to_vec(), b"{".to_vec(), b"[2]".to_vec()];
+/// let mut batch = Batch::new();
+/// let res = batch.to_owned_values(docs.iter_mut().map(Vec::as_mut_slice));
+/// assert_eq!(res.values.len(), 2);
+/// assert_eq!(res.values[1].0, 2);
+/// assert_eq!(res.errors[0].0, 1);
+/// ```
+#[derive(Default)]
+pub struct Batch {
+    buffers: Buffers,
+    stop_on_error: bool,
+}
+
+impl Batch {
+    /// Creates a batch parser with empty buffers that doesn't stop on
+    /// errors
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets if a batch stops at the first document that fails to parse,
+    /// the documents after it are neither parsed nor reported. Defaults
+    /// to `false`.
+    #[must_use]
+    pub fn stop_on_error(mut self, stop_on_error: bool) -> Self {
+        self.stop_on_error = stop_on_error;
+        self
+    }
+
+    /// Deserializes every document of `docs` with serde
+    #[cfg(feature = "serde_impl")]
+    pub fn from_slices<'d, T, I>(&mut self, docs: I) -> BatchResult<T>
+    where
+        T: Deserialize<'d>,
+        I: IntoIterator<Item = &'d mut [u8]>,
+    {
+        self.run(docs, |doc, buffers| {
+            let (input_buffer, string_buffer) = buffers.get(doc.len());
+            let mut de = Deserializer::from_slice_with_buffers(doc, input_buffer, string_buffer)?;
+            T::deserialize(&mut de)
+        })
+    }
+
+    /// Parses every document of `docs` into an owned value
+    pub fn to_owned_values<'d, I>(&mut self, docs: I) -> BatchResult<OwnedValue>
+    where
+        I: IntoIterator<Item = &'d mut [u8]>,
+    {
+        self.run(docs, |doc, buffers| {
+            let (input_buffer, string_buffer) = buffers.get(doc.len());
+            to_value_with_buffers(doc, input_buffer, string_buffer)
+        })
+    }
+
+    fn run<'d, T, I, F>(&mut self, docs: I, mut parse: F) -> BatchResult<T>
+    where
+        I: IntoIterator<Item = &'d mut [u8]>,
+        F: FnMut(&'d mut [u8], &mut Buffers) -> Result<T>,
+    {
+        let docs = docs.into_iter();
+        let mut res = BatchResult {
+            values: Vec::with_capacity(docs.size_hint().0),
+            errors: Vec::new(),
+        };
+        for (idx, doc) in docs.enumerate() {
+            match parse(doc, &mut self.buffers) {
+                Ok(v) => res.values.push((idx, v)),
+                Err(e) => {
+                    res.errors.push((idx, e));
+                    if self.stop_on_error {
+                        break;
+                    }
+                }
+            }
+        }
+        res
+    }
+}
+
+/// Deserializes a batch of independent documents with serde, see
+/// `Batch` to keep the buffers across batches or to stop at the first
+/// error.
+///
+/// ```rust
+/// let mut docs = vec![b"[1, 2]".to_vec(), b"[3,".to_vec(), b"[]".to_vec()];
+/// let res = simd_json::from_slices::<Vec<u8>, _>(docs.iter_mut().map(Vec::as_mut_slice));
+/// assert_eq!(res.values, vec![(0, vec![1, 2]), (2, vec![])]);
+/// assert_eq!(res.errors.len(), 1);
+/// ```
+#[cfg(feature = "serde_impl")]
+#[must_use]
+pub fn from_slices<'d, T, I>(docs: I) -> BatchResult<T>
+where
+    T: Deserialize<'d>,
+    I: IntoIterator<Item = &'d mut [u8]>,
+{
+    Batch::new().from_slices(docs)
+}
+
+/// Parses a batch of independent documents into owned values, see
+/// `Batch` to keep the buffers across batches or to stop at the first
+/// error.
+#[must_use]
+pub fn to_owned_values<'d, I>(docs: I) -> BatchResult<OwnedValue>
+where
+    I: IntoIterator<Item = &'d mut [u8]>,
+{
+    Batch::new().to_owned_values(docs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_owned_values, Batch};
+    use crate::json;
+
+    #[test]
+    fn interleaved_errors() {
+        let mut docs: Vec<Vec<u8>> = vec![
+            br#"{"id": 1}"#.to_vec(),
+            br#"{"id": }"#.to_vec(),
+            br#"{"id": 3, "tags": ["a", "b"]}"#.to_vec(),
+            b"".to_vec(),
+            br#"[1, 2] 3"#.to_vec(),
+            "\"é\"".as_bytes().to_vec(),
+        ];
+        let res = to_owned_values(docs.clone().iter_mut().map(Vec::as_mut_slice));
+        assert!(!res.is_ok());
+        assert_eq!(
+            res.values,
+            vec![
+                (0, json!({"id": 1})),
+                (2, json!({"id": 3, "tags": ["a", "b"]})),
+                (5, json!("é")),
+            ]
+        );
+        let failed: Vec<usize> = res.errors.iter().map(|(i, _)| *i).collect();
+        assert_eq!(failed, [1, 3, 4]);
+        // errors are the same as when parsing the document on its own
+        let single = crate::to_owned_value(&mut docs[4].clone()).expect_err("trailing data");
+        assert_eq!(res.errors[2].1, single);
+
+        let mut batch = Batch::new().stop_on_error(true);
+        let res = batch.to_owned_values(docs.iter_mut().map(Vec::as_mut_slice));
+        assert_eq!(res.values, vec![(0, json!({"id": 1}))]);
+        assert_eq!(res.errors.len(), 1);
+        assert_eq!(res.errors[0].0, 1);
+
+        // the buffers are kept between batches
+        let mut docs = vec![b"[true]".to_vec(), b"null".to_vec()];
+        let res = batch.to_owned_values(docs.iter_mut().map(Vec::as_mut_slice));
+        assert!(res.is_ok());
+        assert_eq!(res.values, vec![(0, json!([true])), (1, json!(null))]);
+    }
+
+    #[cfg(feature = "serde_impl")]
+    #[test]
+    fn serde_batch() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Row<'a> {
+            id: u64,
+            name: &'a str,
+        }
+        let mut docs: Vec<Vec<u8>> = (0..100)
+            .map(|i| {
+                if i % 10 == 7 {
+                    format!(r#"{{"id": "{i}"}}"#).into_bytes()
+                } else {
+                    format!(r#"{{"id": {i}, "name": "row {i}"}}"#).into_bytes()
+                }
+            })
+            .collect();
+        let res = Batch::new().from_slices::<Row, _>(docs.iter_mut().map(Vec::as_mut_slice));
+        assert_eq!(res.values.len(), 90);
+        assert_eq!(res.errors.len(), 10);
+        assert!(res.errors.iter().all(|(i, _)| i % 10 == 7));
+        for (i, row) in &res.values {
+            assert_eq!(usize::try_from(row.id).ok(), Some(*i));
+            assert_eq!(row.name, format!("row {i}"));
+        }
+    }
+}
@@ -0,0 +1,508 @@
+//! A C API for parsing documents and reading the resulting DOM, enabled
+//! with the `capi` feature. `include/simd_json.h` declares it and
+//! `examples/capi.c` shows how to use it.
+//!
+//! Documents are parsed into opaque `simd_json_doc` handles owning an
+//! `OwnedValue`, their values are read through `simd_json_value` handles
+//! that point into the document and stay valid until it is freed with
+//! `simd_json_doc_free`. Strings are returned as pointer and length, they
+//! are not NUL terminated.
+//!
+//! Every function catches panics at the boundary, a panic is reported as
+//! `SIMD_JSON_ERROR_PANIC` by `simd_json_parse` and as a missing value or
+//! `false` by the accessors.
+//!
+//! To link the API into a C or C++ program build a static or dynamic
+//! library with the feature enabled:
+//!
+//! ```text
+//! cargo rustc --release --features capi --crate-type staticlib
+//! ```
+use crate::prelude::ValueAccess;
+use crate::{to_owned_value, OwnedValue, StaticNode};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+/// The call succeeded
+pub const SIMD_JSON_OK: i32 = 0;
+/// The input is not valid JSON
+pub const SIMD_JSON_ERROR_PARSE: i32 = 1;
+/// A required pointer argument was `NULL`
+pub const SIMD_JSON_ERROR_INVALID_ARGUMENT: i32 = 2;
+/// The library panicked, this is a bug
+pub const SIMD_JSON_ERROR_PANIC: i32 = 3;
+
+/// `null`, or a `NULL` value handle
+pub const SIMD_JSON_TYPE_NULL: i32 = 0;
+/// `true` or `false`
+pub const SIMD_JSON_TYPE_BOOL: i32 = 1;
+/// An integer that fits into an `int64_t`
+pub const SIMD_JSON_TYPE_INT64: i32 = 2;
+/// A positive integer that only fits into an `uint64_t`
+pub const SIMD_JSON_TYPE_UINT64: i32 = 3;
+/// A number with a fraction or exponent
+pub const SIMD_JSON_TYPE_DOUBLE: i32 = 4;
+/// A string
+pub const SIMD_JSON_TYPE_STRING: i32 = 5;
+/// An array
+pub const SIMD_JSON_TYPE_ARRAY: i32 = 6;
+/// An object
+pub const SIMD_JSON_TYPE_OBJECT: i32 = 7;
+
+/// The size of the message buffer of `ErrorInfo`
+pub const SIMD_JSON_ERROR_MESSAGE_LEN: usize = 128;
+
+/// A parsed document, `simd_json_doc` in C
+pub struct Document {
+    root: OwnedValue,
+}
+
+/// A value of a document, `simd_json_value` in C. Handles are pointers
+/// to the `OwnedValue` in the document, the type only exists to keep
+/// them opaque.
+#[repr(C)]
+pub struct ValueHandle {
+    _private: [u8; 0],
+}
+
+/// Details about a failed call, `simd_json_error` in C
+#[repr(C)]
+pub struct ErrorInfo {
+    /// One of the `SIMD_JSON_ERROR_*` codes
+    pub code: i32,
+    /// The offset in the input the error was found at
+    pub offset: usize,
+    /// A description of the error, NUL terminated and truncated to fit
+    pub message: [c_char; SIMD_JSON_ERROR_MESSAGE_LEN],
+}
+
+impl ErrorInfo {
+    #[allow(clippy::cast_possible_wrap)]
+    fn set(&mut self, code: i32, offset: usize, message: &str) {
+        self.code = code;
+        self.offset = offset;
+        let len = message.len().min(SIMD_JSON_ERROR_MESSAGE_LEN - 1);
+        for (dst, src) in self.message.iter_mut().zip(&message.as_bytes()[..len]) {
+            *dst = *src as c_char;
+        }
+        self.message[len] = 0;
+    }
+}
+
+/// Runs `f`, returning `default` if it panics
+fn guard<T>(default: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+fn handle(v: &OwnedValue) -> *const ValueHandle {
+    let v: *const OwnedValue = v;
+    v.cast()
+}
+
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet
+unsafe fn value<'v>(v: *const ValueHandle) -> Option<&'v OwnedValue> {
+    v.cast::<OwnedValue>().as_ref()
+}
+
+/// Parses the `len` bytes at `data` into a new document that is stored in
+/// `*doc`. The input is copied, it isn't modified and can be freed
+/// afterwards. Returns `SIMD_JSON_OK` or one of the `SIMD_JSON_ERROR_*`
+/// codes, on errors `*doc` is set to `NULL` and, unless it is `NULL`,
+/// `error` is filled in.
+///
+/// # Safety
+///
+/// `data` has to point to `len` readable bytes, it may only be `NULL` if
+/// `len` is 0. `doc` has to be a valid pointer to write the document to
+/// and `error` either `NULL` or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn simd_json_parse(
+    data: *const u8,
+    len: usize,
+    doc: *mut *mut Document,
+    error: *mut ErrorInfo,
+) -> i32 {
+    let error = error.as_mut();
+    let res = guard(Err((SIMD_JSON_ERROR_PANIC, None)), || {
+        if doc.is_null() || (data.is_null() && len > 0) {
+            return Err((SIMD_JSON_ERROR_INVALID_ARGUMENT, None));
+        }
+        let mut input = if len == 0 {
+            Vec::new()
+        } else {
+            slice::from_raw_parts(data, len).to_vec()
+        };
+        to_owned_value(&mut input)
+            .map(|root| Box::into_raw(Box::new(Document { root })))
+            .map_err(|e| (SIMD_JSON_ERROR_PARSE, Some(e)))
+    });
+    match res {
+        Ok(parsed) => {
+            *doc = parsed;
+            SIMD_JSON_OK
+        }
+        Err((code, e)) => {
+            if !doc.is_null() {
+                *doc = ptr::null_mut();
+            }
+            if let Some(error) = error {
+                match (code, e) {
+                    (_, Some(e)) => error.set(code, e.index(), &e.to_string()),
+                    (SIMD_JSON_ERROR_INVALID_ARGUMENT, None) => {
+                        error.set(code, 0, "invalid argument");
+                    }
+                    (_, None) => error.set(code, 0, "panic"),
+                }
+            }
+            code
+        }
+    }
+}
+
+/// Frees a document, all handles of its values become invalid.
+///
+/// # Safety
+///
+/// `doc` has to be `NULL` or a document returned by `simd_json_parse`
+/// that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn simd_json_doc_free(doc: *mut Document) {
+    if !doc.is_null() {
+        guard((), || drop(Box::from_raw(doc)));
+    }
+}
+
+/// The root value of a document, `NULL` if `doc` is `NULL`.
+///
+/// # Safety
+///
+/// `doc` has to be `NULL` or a document that wasn't freed yet.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn simd_json_doc_root(doc: *const Document) -> *const ValueHandle {
+    doc.as_ref().map_or(ptr::null(), |doc| handle(&doc.root))
+}
+
+/// The `SIMD_JSON_TYPE_*` of a value, a `NULL` handle is a JSON `null`.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn simd_json_value_type(v: *const ValueHandle) -> i32 {
+    guard(SIMD_JSON_TYPE_NULL, || match value(v) {
+        None | Some(OwnedValue::Static(StaticNode::Null)) => SIMD_JSON_TYPE_NULL,
+        Some(OwnedValue::Static(StaticNode::Bool(_))) => SIMD_JSON_TYPE_BOOL,
+        Some(OwnedValue::Static(StaticNode::I64(_))) => SIMD_JSON_TYPE_INT64,
+        Some(OwnedValue::Static(StaticNode::U64(u))) => {
+            if i64::try_from(*u).is_ok() {
+                SIMD_JSON_TYPE_INT64
+            } else {
+                SIMD_JSON_TYPE_UINT64
+            }
+        }
+        #[cfg(feature = "128bit")]
+        Some(OwnedValue::Static(StaticNode::I128(_) | StaticNode::U128(_))) => {
+            SIMD_JSON_TYPE_DOUBLE
+        }
+        Some(OwnedValue::Static(StaticNode::F64(_))) => SIMD_JSON_TYPE_DOUBLE,
+        Some(OwnedValue::String(_)) => SIMD_JSON_TYPE_STRING,
+        Some(OwnedValue::Array(_)) => SIMD_JSON_TYPE_ARRAY,
+        Some(OwnedValue::Object(_)) => SIMD_JSON_TYPE_OBJECT,
+    })
+}
+
+/// The number of elements of an array or members of an object, 0 for
+/// all other values.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn simd_json_value_len(v: *const ValueHandle) -> usize {
+    guard(0, || match value(v) {
+        Some(OwnedValue::Array(a)) => a.len(),
+        Some(OwnedValue::Object(o)) => o.len(),
+        _ => 0,
+    })
+}
+
+/// The element at `idx` of an array, `NULL` if `v` isn't an array or
+/// too short.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn simd_json_array_get(
+    v: *const ValueHandle,
+    idx: usize,
+) -> *const ValueHandle {
+    guard(ptr::null(), || match value(v) {
+        Some(OwnedValue::Array(a)) => a.get(idx).map_or(ptr::null(), handle),
+        _ => ptr::null(),
+    })
+}
+
+/// The value of the member `key` of an object, `key` is `key_len` bytes
+/// of UTF-8 and doesn't need to be NUL terminated. `NULL` if `v` isn't an
+/// object or has no such member.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet,
+/// `key` has to point to `key_len` readable bytes.
+#[no_mangle]
+#[must_use]
+pub unsafe extern "C" fn simd_json_object_get(
+    v: *const ValueHandle,
+    key: *const c_char,
+    key_len: usize,
+) -> *const ValueHandle {
+    guard(ptr::null(), || {
+        if key.is_null() {
+            return ptr::null();
+        }
+        let key = slice::from_raw_parts(key.cast::<u8>(), key_len);
+        match (value(v), std::str::from_utf8(key)) {
+            (Some(OwnedValue::Object(o)), Ok(key)) => o.get(key).map_or(ptr::null(), handle),
+            _ => ptr::null(),
+        }
+    })
+}
+
+/// Stores a bool in `*out`, returns `false` if `v` isn't a bool.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet,
+/// `out` has to be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn simd_json_get_bool(v: *const ValueHandle, out: *mut bool) -> bool {
+    guard(false, || match (value(v), out.as_mut()) {
+        (Some(OwnedValue::Static(StaticNode::Bool(b))), Some(out)) => {
+            *out = *b;
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Stores an integer in `*out`, returns `false` if `v` isn't an integer
+/// that fits into an `int64_t`.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet,
+/// `out` has to be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn simd_json_get_int64(v: *const ValueHandle, out: *mut i64) -> bool {
+    guard(false, || {
+        let i = match value(v) {
+            Some(OwnedValue::Static(StaticNode::I64(i))) => Some(*i),
+            Some(OwnedValue::Static(StaticNode::U64(u))) => i64::try_from(*u).ok(),
+            _ => None,
+        };
+        match (i, out.as_mut()) {
+            (Some(i), Some(out)) => {
+                *out = i;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Stores an integer in `*out`, returns `false` if `v` isn't an integer
+/// that fits into an `uint64_t`.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet,
+/// `out` has to be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn simd_json_get_uint64(v: *const ValueHandle, out: *mut u64) -> bool {
+    guard(false, || {
+        let u = match value(v) {
+            Some(OwnedValue::Static(StaticNode::I64(i))) => u64::try_from(*i).ok(),
+            Some(OwnedValue::Static(StaticNode::U64(u))) => Some(*u),
+            _ => None,
+        };
+        match (u, out.as_mut()) {
+            (Some(u), Some(out)) => {
+                *out = u;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Stores a number in `*out`, integers are converted, returns `false` if
+/// `v` isn't a number.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet,
+/// `out` has to be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn simd_json_get_double(v: *const ValueHandle, out: *mut f64) -> bool {
+    guard(false, || {
+        match (value(v).and_then(ValueAccess::cast_f64), out.as_mut()) {
+            (Some(f), Some(out)) => {
+                *out = f;
+                true
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Stores a pointer to the UTF-8 bytes of a string in `*out` and their
+/// number in `*out_len`, returns `false` if `v` isn't a string. The
+/// string is not NUL terminated and lives as long as the document.
+///
+/// # Safety
+///
+/// `v` has to be `NULL` or a handle of a document that wasn't freed yet,
+/// `out` and `out_len` have to be valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn simd_json_get_string(
+    v: *const ValueHandle,
+    out: *mut *const c_char,
+    out_len: *mut usize,
+) -> bool {
+    guard(false, || match (value(v), out.as_mut(), out_len.as_mut()) {
+        (Some(OwnedValue::String(s)), Some(out), Some(out_len)) => {
+            *out = s.as_ptr().cast();
+            *out_len = s.len();
+            true
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::CStr;
+
+    fn error_info() -> ErrorInfo {
+        ErrorInfo {
+            code: SIMD_JSON_OK,
+            offset: 0,
+            message: [0; SIMD_JSON_ERROR_MESSAGE_LEN],
+        }
+    }
+
+    fn parse(input: &[u8]) -> (i32, *mut Document, ErrorInfo) {
+        let mut doc = ptr::null_mut();
+        let mut error = error_info();
+        let code = unsafe { simd_json_parse(input.as_ptr(), input.len(), &mut doc, &mut error) };
+        (code, doc, error)
+    }
+
+    unsafe fn get(v: *const ValueHandle, key: &str) -> *const ValueHandle {
+        simd_json_object_get(v, key.as_ptr().cast(), key.len())
+    }
+
+    #[test]
+    fn happy_path() {
+        let (code, doc, _) = parse(
+            br#"{"name": "ferris", "age": 7, "big": 18446744073709551615, "pi": 3.5, "ok": true, "tags": ["a", null]}"#,
+        );
+        assert_eq!(code, SIMD_JSON_OK);
+        unsafe {
+            let root = simd_json_doc_root(doc);
+            assert_eq!(simd_json_value_type(root), SIMD_JSON_TYPE_OBJECT);
+            assert_eq!(simd_json_value_len(root), 6);
+
+            let mut s = ptr::null();
+            let mut len = 0;
+            assert!(simd_json_get_string(get(root, "name"), &mut s, &mut len));
+            assert_eq!(slice::from_raw_parts(s.cast::<u8>(), len), b"ferris");
+
+            let mut i = 0;
+            assert!(simd_json_get_int64(get(root, "age"), &mut i));
+            assert_eq!(i, 7);
+            let mut u = 0;
+            assert_eq!(
+                simd_json_value_type(get(root, "big")),
+                SIMD_JSON_TYPE_UINT64
+            );
+            assert!(!simd_json_get_int64(get(root, "big"), &mut i));
+            assert!(simd_json_get_uint64(get(root, "big"), &mut u));
+            assert_eq!(u, u64::MAX);
+            let mut f = 0.0;
+            assert!(simd_json_get_double(get(root, "pi"), &mut f));
+            assert!((f - 3.5).abs() < f64::EPSILON);
+            let mut b = false;
+            assert!(simd_json_get_bool(get(root, "ok"), &mut b));
+            assert!(b);
+
+            let tags = get(root, "tags");
+            assert_eq!(simd_json_value_type(tags), SIMD_JSON_TYPE_ARRAY);
+            assert_eq!(simd_json_value_len(tags), 2);
+            assert_eq!(
+                simd_json_value_type(simd_json_array_get(tags, 0)),
+                SIMD_JSON_TYPE_STRING
+            );
+            assert_eq!(
+                simd_json_value_type(simd_json_array_get(tags, 1)),
+                SIMD_JSON_TYPE_NULL
+            );
+            assert!(simd_json_array_get(tags, 2).is_null());
+            assert!(get(root, "missing").is_null());
+            assert!(get(tags, "name").is_null());
+            assert!(!simd_json_get_bool(get(root, "age"), &mut b));
+            simd_json_doc_free(doc);
+        }
+    }
+
+    #[test]
+    fn error_path() {
+        let (code, doc, error) = parse(br#"{"a": [1, 2}"#);
+        assert_eq!(code, SIMD_JSON_ERROR_PARSE);
+        assert!(doc.is_null());
+        assert_eq!(error.code, SIMD_JSON_ERROR_PARSE);
+        assert!(error.offset > 0);
+        let message = unsafe { CStr::from_ptr(error.message.as_ptr()) };
+        assert!(message
+            .to_string_lossy()
+            .contains(&format!("at character {}", error.offset)));
+
+        let (code, doc, error) = parse(b"");
+        assert_eq!(
+            (code, error.code),
+            (SIMD_JSON_ERROR_PARSE, SIMD_JSON_ERROR_PARSE)
+        );
+        assert!(doc.is_null());
+
+        let mut doc = ptr::null_mut();
+        let code = unsafe { simd_json_parse(ptr::null(), 3, &mut doc, ptr::null_mut()) };
+        assert_eq!(code, SIMD_JSON_ERROR_INVALID_ARGUMENT);
+        let code = unsafe { simd_json_parse(b"1".as_ptr(), 1, ptr::null_mut(), ptr::null_mut()) };
+        assert_eq!(code, SIMD_JSON_ERROR_INVALID_ARGUMENT);
+
+        unsafe {
+            assert_eq!(simd_json_value_type(ptr::null()), SIMD_JSON_TYPE_NULL);
+            assert!(simd_json_doc_root(ptr::null()).is_null());
+            simd_json_doc_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn long_messages_are_truncated() {
+        let mut info = error_info();
+        info.set(SIMD_JSON_ERROR_PARSE, 0, &"x".repeat(1000));
+        let message = unsafe { CStr::from_ptr(info.message.as_ptr()) };
+        assert_eq!(message.to_bytes().len(), SIMD_JSON_ERROR_MESSAGE_LEN - 1);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::tape::skip;
+use crate::{to_tape, Node, Result};
+use std::collections::HashMap;
+
+/// Objects with more entries than this look up keys in a map instead of
+/// scanning the other object
+const LINEAR_LOOKUP: usize = 16;
+
+/// Compares two JSON documents for semantic equality, ignoring
+/// whitespace and the order of object keys. Values are compared the same
+/// way `PartialEq` compares values: integers are equal if their values
+/// are, floats are compared approximately and an integer never equals a
+/// float, so `1` and `1.0` are different.
+///
+/// Both documents are only parsed into tapes, no values are built. Like
+/// all parsing functions this rewrites the slices to unescape strings.
+///
+/// ```rust
+/// let mut a = br#"{"a": [1, 2.5e0], "b": "c"}"#.to_vec();
+/// let mut b = br#"{ "b" : "c", "a" : [1, 2.5] }"#.to_vec();
+/// assert!(simd_json::json_eq(&mut a, &mut b).unwrap());
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if either document is invalid JSON.
+pub fn json_eq(a: &mut [u8], b: &mut [u8]) -> Result<bool> {
+    let a = to_tape(a)?;
+    let b = to_tape(b)?;
+    // the root of a tape is at index 1
+    Ok(node_eq(&a, 1, &b, 1))
+}
+
+fn node_eq(a: &[Node], i: usize, b: &[Node], j: usize) -> bool {
+    match (a[i], b[j]) {
+        (Node::Static(x), Node::Static(y)) => x == y,
+        (Node::String(x), Node::String(y)) => x == y,
+        (Node::Array(len, _), Node::Array(other_len, _)) => {
+            if len != other_len {
+                return false;
+            }
+            let (mut i, mut j) = (i + 1, j + 1);
+            for _ in 0..len {
+                if !node_eq(a, i, b, j) {
+                    return false;
+                }
+                i = skip(a, i);
+                j = skip(b, j);
+            }
+            true
+        }
+        (Node::Object(len, _), Node::Object(other_len, _)) => {
+            len == other_len && object_eq(a, i, b, j, len)
+        }
+        _ => false,
+    }
+}
+
+/// Compares two objects of the same length, for keys that are present
+/// more than once the first occurrence is used
+fn object_eq(a: &[Node], i: usize, b: &[Node], j: usize, len: usize) -> bool {
+    // the index of each value in `b` by its key
+    let mut b_entries = Vec::with_capacity(len);
+    let mut k = j + 1;
+    for _ in 0..len {
+        if let Node::String(key) = b[k] {
+            b_entries.push((key, k + 1));
+        }
+        k = skip(b, k + 1);
+    }
+    let index: HashMap<&str, usize> = if len > LINEAR_LOOKUP {
+        b_entries.iter().rev().copied().collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut k = i + 1;
+    for _ in 0..len {
+        let value = match a[k] {
+            Node::String(key) if len > LINEAR_LOOKUP => index.get(key).copied(),
+            Node::String(key) => b_entries.iter().find(|(k, _)| *k == key).map(|(_, v)| *v),
+            _ => None,
+        };
+        match value {
+            Some(v) if node_eq(a, k + 1, b, v) => (),
+            _ => return false,
+        }
+        k = skip(a, k + 1);
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::json_eq;
+
+    fn eq(a: &str, b: &str) -> bool {
+        let mut a = a.as_bytes().to_vec();
+        let mut b = b.as_bytes().to_vec();
+        json_eq(&mut a, &mut b).expect("valid json")
+    }
+
+    #[test]
+    fn equal() {
+        assert!(eq("1", " 1 "));
+        assert!(eq("1e2", "100.0"));
+        assert!(eq(r#""a\nb""#, r#""a\u000ab""#));
+        assert!(eq("[1, [2, {}], null]", "[1,[2,{}],null]"));
+        assert!(eq(
+            r#"{"a": {"x": 1, "y": [true]}, "b": null}"#,
+            r#"{"b": null, "a": {"y": [true], "x": 1}}"#
+        ));
+    }
+
+    #[test]
+    fn different() {
+        assert!(!eq("1", "1.0"));
+        assert!(!eq("1", "\"1\""));
+        assert!(!eq("[1, 2]", "[2, 1]"));
+        assert!(!eq("[1, 2]", "[1, 2, 3]"));
+        assert!(!eq(r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#));
+        assert!(!eq(r#"{"a": 1, "b": 2}"#, r#"{"a": 1, "c": 2}"#));
+        assert!(!eq(r#"{"a": [1]}"#, r#"{"a": 1}"#));
+        assert!(!eq("{}", "[]"));
+    }
+
+    #[test]
+    fn large_objects() {
+        let object = |keys: &mut dyn Iterator<Item = usize>| {
+            let entries: Vec<_> = keys.map(|i| format!(r#""k{i}": [{i}]"#)).collect();
+            format!("{{{}}}", entries.join(","))
+        };
+        let a = object(&mut (0..100));
+        assert!(eq(&a, &object(&mut (0..100).rev())));
+        assert!(!eq(&a, &object(&mut (1..101))));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut a = b"[1,".to_vec();
+        let mut b = b"[1]".to_vec();
+        assert!(json_eq(&mut a, &mut b).is_err());
+    }
+}
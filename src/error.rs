@@ -1,4 +1,5 @@
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 use value_trait::ValueType;
 
@@ -7,8 +8,8 @@ use value_trait::ValueType;
 pub enum ErrorType {
     /// A specific type was expected but another one encountered.
     Unexpected(Option<ValueType>, Option<ValueType>),
-    /// Simd-json only supports inputs of up to
-    /// 4GB in size.
+    /// The input is larger than `MAX_INPUT_LEN`, one byte short of 4 GiB,
+    /// or than the limit that was set for it
     InputTooLarge,
     /// The key of a map isn't a string
     BadKeyType,
@@ -82,6 +83,77 @@ pub enum ErrorType {
     ExpectedObjectKey,
     /// Overflow of a limited buffer
     Overflow,
+    /// Arrays and objects are nested deeper than `ParseOptions::max_depth`
+    DepthLimit {
+        /// The configured limit
+        limit: usize,
+        /// The depth of the array or object that exceeded the limit
+        actual: usize,
+    },
+    /// The input is longer than `ParseOptions::max_input_len`
+    InputLengthLimit {
+        /// The configured limit
+        limit: usize,
+        /// The length of the input
+        actual: usize,
+    },
+    /// The strings of the document, including object keys, are longer
+    /// than `ParseOptions::max_string_bytes` once unescaped
+    StringBytesLimit {
+        /// The configured limit
+        limit: usize,
+        /// The total length of the strings parsed up to and including
+        /// the one that exceeded the limit
+        actual: usize,
+    },
+    /// A string, or object key, is longer than
+    /// `ParseOptions::max_string_len` once unescaped
+    StringLengthLimit {
+        /// The configured limit
+        limit: usize,
+        /// The length of the string
+        actual: usize,
+    },
+    /// The document has more tape nodes than `ParseOptions::max_tape_nodes`
+    TapeNodesLimit {
+        /// The configured limit
+        limit: usize,
+        /// The number of nodes when the limit was exceeded
+        actual: usize,
+    },
+    /// An object has more entries than `ParseOptions::max_object_entries`
+    ObjectEntriesLimit {
+        /// The configured limit
+        limit: usize,
+        /// The number of entries when the limit was exceeded
+        actual: usize,
+    },
+    /// An array has more elements than `ParseOptions::max_array_len`
+    ArrayLengthLimit {
+        /// The configured limit
+        limit: usize,
+        /// The number of elements when the limit was exceeded
+        actual: usize,
+    },
+    /// A JSON pointer is not valid according to RFC 6901
+    InvalidPointer,
+    /// There is no array or object at the parent of a JSON pointer, or
+    /// the index of the pointer is out of range
+    NoValueAtPointer,
+    /// Two edits of a document replace the same value, or one of them
+    /// replaces a value inside the other
+    OverlappingEdits,
+    /// A tape or array index is out of range
+    IndexOutOfBounds {
+        /// The requested index
+        index: usize,
+        /// The number of tape nodes or array elements
+        len: usize,
+    },
+    /// A key appears more than once in the same object
+    DuplicateKey(String),
+    /// An allocation of the given number of bytes failed
+    AllocationFailed(usize),
     /// IO error
     Io(std::io::Error),
 }
@@ -97,44 +169,88 @@ impl PartialEq for ErrorType {
     #[must_use]
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::Io(_), Self::Io(_))
-            | (Self::BadKeyType, Self::BadKeyType)
-            | (Self::ExpectedArray, Self::ExpectedArray)
-            | (Self::ExpectedArrayComma, Self::ExpectedArrayComma)
-            | (Self::ExpectedBoolean, Self::ExpectedBoolean)
-            | (Self::ExpectedEnum, Self::ExpectedEnum)
-            | (Self::ExpectedFloat, Self::ExpectedFloat)
-            | (Self::ExpectedInteger, Self::ExpectedInteger)
-            | (Self::ExpectedMap, Self::ExpectedMap)
-            | (Self::ExpectedObjectColon, Self::ExpectedObjectColon)
-            | (Self::ExpectedMapComma, Self::ExpectedMapComma)
-            | (Self::ExpectedMapEnd, Self::ExpectedMapEnd)
-            | (Self::ExpectedNull, Self::ExpectedNull)
-            | (Self::ExpectedNumber, Self::ExpectedNumber)
-            | (Self::ExpectedSigned, Self::ExpectedSigned)
-            | (Self::ExpectedString, Self::ExpectedString)
-            | (Self::ExpectedUnsigned, Self::ExpectedUnsigned)
-            | (Self::InternalError, Self::InternalError)
-            | (Self::InvalidEscape, Self::InvalidEscape)
-            | (Self::InvalidExponent, Self::InvalidExponent)
-            | (Self::InvalidNumber, Self::InvalidNumber)
-            | (Self::InvalidUtf8, Self::InvalidUtf8)
-            | (Self::InvalidUnicodeEscape, Self::InvalidUnicodeEscape)
-            | (Self::InvalidUnicodeCodepoint, Self::InvalidUnicodeCodepoint)
-            | (Self::KeyMustBeAString, Self::KeyMustBeAString)
-            | (Self::NoStructure, Self::NoStructure)
-            | (Self::Parser, Self::Parser)
-            | (Self::Eof, Self::Eof)
-            | (Self::Syntax, Self::Syntax)
-            | (Self::TrailingData, Self::TrailingData)
-            | (Self::UnexpectedCharacter, Self::UnexpectedCharacter)
-            | (Self::UnterminatedString, Self::UnterminatedString)
-            | (Self::ExpectedArrayContent, Self::ExpectedArrayContent)
-            | (Self::ExpectedObjectContent, Self::ExpectedObjectContent)
-            | (Self::ExpectedObjectKey, Self::ExpectedObjectKey)
-            | (Self::Overflow, Self::Overflow) => true,
-            (Self::Serde(s1), Self::Serde(s2)) => s1 == s2,
-            _ => false,
+            (Self::Unexpected(e1, f1), Self::Unexpected(e2, f2)) => e1 == e2 && f1 == f2,
+            (
+                Self::DepthLimit {
+                    limit: l1,
+                    actual: a1,
+                },
+                Self::DepthLimit {
+                    limit: l2,
+                    actual: a2,
+                },
+            )
+            | (
+                Self::InputLengthLimit {
+                    limit: l1,
+                    actual: a1,
+                },
+                Self::InputLengthLimit {
+                    limit: l2,
+                    actual: a2,
+                },
+            )
+            | (
+                Self::StringBytesLimit {
+                    limit: l1,
+                    actual: a1,
+                },
+                Self::StringBytesLimit {
+                    limit: l2,
+                    actual: a2,
+                },
+            )
+            | (
+                Self::StringLengthLimit {
+                    limit: l1,
+                    actual: a1,
+                },
+                Self::StringLengthLimit {
+                    limit: l2,
+                    actual: a2,
+                },
+            )
+            | (
+                Self::TapeNodesLimit {
+                    limit: l1,
+                    actual: a1,
+                },
+                Self::TapeNodesLimit {
+                    limit: l2,
+                    actual: a2,
+                },
+            )
+            | (
+                Self::ObjectEntriesLimit {
+                    limit: l1,
+                    actual: a1,
+                },
+                Self::ObjectEntriesLimit {
+                    limit: l2,
+                    actual: a2,
+                },
+            )
+            | (
+                Self::ArrayLengthLimit {
+                    limit: l1,
+                    actual: a1,
+                },
+                Self::ArrayLengthLimit {
+                    limit: l2,
+                    actual: a2,
+                },
+            ) => l1 == l2 && a1 == a2,
+            (
+                Self::IndexOutOfBounds { index: i1, len: l1 },
+                Self::IndexOutOfBounds { index: i2, len: l2 },
+            ) => i1 == i2 && l1 == l2,
+            (Self::Serde(s1), Self::Serde(s2))
+            | (Self::DuplicateKey(s1), Self::DuplicateKey(s2)) => s1 == s2,
+            (Self::AllocationFailed(b1), Self::AllocationFailed(b2)) => b1 == b2,
+            (Self::Io(e1), Self::Io(e2)) => e1.kind() == e2.kind(),
+            // every variant with a payload has an arm above, so variants
+            // that get here are equal if they are the same unit variant
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
         }
     }
 }
@@ -147,6 +263,8 @@ pub struct Error {
     character: Option<char>,
     /// Type of error
     error: ErrorType,
+    /// The file that was parsed, boxed to keep errors small
+    path: Option<Box<PathBuf>>,
 }
 
 impl Error {
@@ -155,6 +273,7 @@ impl Error {
             index,
             character,
             error,
+            path: None,
         }
     }
     pub(crate) fn new_c(index: usize, character: char, error: ErrorType) -> Self {
@@ -168,14 +287,65 @@ impl Error {
             index: 0,
             character: None,
             error: t,
+            path: None,
+        }
+    }
+
+    /// Attaches the path of the file the error happened in
+    #[must_use]
+    pub(crate) fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(Box::new(path.to_path_buf()));
+        self
+    }
+
+    /// Byte index the error was encountered at. For IO errors this is
+    /// the number of bytes that were successfully read before the failure.
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Type of the error
+    #[must_use]
+    pub fn error(&self) -> &ErrorType {
+        &self.error
+    }
+
+    /// The path of the file the error happened in, if the input was
+    /// read from or named as a file
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref().map(PathBuf::as_path)
+    }
+
+    /// The kind of the underlying IO error, if this error was caused
+    /// by a failing reader or writer.
+    #[must_use]
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        if let ErrorType::Io(e) = &self.error {
+            Some(e.kind())
+        } else {
+            None
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        if let ErrorType::Io(e) = &self.error {
+            Some(e)
+        } else {
+            None
         }
     }
 }
-impl std::error::Error for Error {}
 
 #[cfg(not(tarpaulin_include))]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path.display())?;
+        }
         if let Some(c) = self.character {
             write!(f, "{:?} at character {} ('{c}')", self.error, self.index)
         } else {
@@ -199,4 +369,45 @@ mod test {
         let e = Error::generic(ErrorType::InternalError);
         assert_eq!(e.to_string(), "InternalError at character 0");
     }
+
+    #[test]
+    fn io_kind() {
+        use std::error::Error as _;
+        use std::io;
+        let e = Error::from(io::Error::new(io::ErrorKind::NotFound, "nope"));
+        assert_eq!(e.io_kind(), Some(io::ErrorKind::NotFound));
+        assert!(e.source().is_some());
+        assert_eq!(
+            e,
+            Error::from(io::Error::new(io::ErrorKind::NotFound, "other"))
+        );
+        assert_ne!(
+            e,
+            Error::from(io::Error::new(io::ErrorKind::ConnectionReset, "nope"))
+        );
+        let e = Error::generic(ErrorType::InternalError);
+        assert_eq!(e.io_kind(), None);
+        assert!(e.source().is_none());
+    }
+
+    #[test]
+    fn eq() {
+        use value_trait::ValueType;
+        assert_eq!(ErrorType::InputTooLarge, ErrorType::InputTooLarge);
+        assert_eq!(ErrorType::OverlappingEdits, ErrorType::OverlappingEdits);
+        assert_ne!(ErrorType::InputTooLarge, ErrorType::Overflow);
+        assert_eq!(
+            ErrorType::Unexpected(Some(ValueType::String), None),
+            ErrorType::Unexpected(Some(ValueType::String), None)
+        );
+        assert_ne!(
+            ErrorType::Unexpected(Some(ValueType::String), None),
+            ErrorType::Unexpected(Some(ValueType::Bool), None)
+        );
+        assert_ne!(
+            ErrorType::AllocationFailed(1),
+            ErrorType::AllocationFailed(2)
+        );
+        assert_ne!(ErrorType::AllocationFailed(1), ErrorType::Overflow);
+    }
 }
@@ -0,0 +1,378 @@
+//! Schema inference over many documents.
+//!
+//! `SchemaInference` collects the key paths, value types, optionality and
+//! numeric ranges of the documents it observes, the result is a JSON
+//! Schema like description of the documents as an `OwnedValue`:
+//!
+//! ```rust
+//! use simd_json::infer::SchemaInference;
+//! use simd_json::json;
+//! let mut inference = SchemaInference::default();
+//! let mut d = br#"{"id": 1, "name": "ferris"}"#.to_vec();
+//! inference.observe_slice(&mut d).unwrap();
+//! let mut d = br#"{"id": 2, "tags": ["a"]}"#.to_vec();
+//! inference.observe_slice(&mut d).unwrap();
+//! let schema = inference.finish();
+//! assert_eq!(schema.documents(), 2);
+//! assert_eq!(
+//!     schema.value(),
+//!     &json!({
+//!         "type": "object",
+//!         "properties": {
+//!             "id": {"type": "integer", "minimum": 1, "maximum": 2},
+//!             "name": {"type": "string"},
+//!             "tags": {"type": "array", "items": {"type": "string"}}
+//!         },
+//!         "required": ["id"]
+//!     })
+//! );
+//! ```
+//!
+//! The schema of every value has a `type` that is either a single type
+//! name or a list of them, in the order `null`, `boolean`, `integer` or
+//! `number`, `string`, `array` and `object`. Integers are reported as
+//! `number` once a float was observed at the same path. Numbers have
+//! `minimum` and `maximum`, arrays fold the schemas of all their elements
+//! into `items` and objects list their keys in `properties` and the keys
+//! present in every observed object in `required`.
+
+use crate::value::owned::{Object, Value as OwnedValue};
+use crate::value::ObjectHasher;
+use crate::{BorrowedValue, Deserializer, Node, Result, StaticNode};
+use std::collections::BTreeMap;
+
+/// Collects the schema of the documents it observes, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct SchemaInference {
+    root: Schema,
+    documents: usize,
+}
+
+impl SchemaInference {
+    /// Adds a document to the inferred schema
+    pub fn observe(&mut self, v: &BorrowedValue) {
+        self.documents += 1;
+        self.root.observe(v);
+    }
+
+    /// Parses a document and adds it to the inferred schema, this works
+    /// on the tape and never builds a DOM.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON, in that case the
+    /// document is not added.
+    pub fn observe_slice(&mut self, s: &mut [u8]) -> Result<()> {
+        let tape = Deserializer::from_slice(s)?.into_tape();
+        self.documents += 1;
+        // the first node of the tape is the root node
+        self.root.observe_tape(&tape, 1);
+        Ok(())
+    }
+
+    /// Finishes the inference and returns the schema
+    #[must_use]
+    pub fn finish(self) -> InferredSchema {
+        InferredSchema {
+            schema: self.root.to_value(),
+            documents: self.documents,
+        }
+    }
+}
+
+/// The result of a `SchemaInference`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferredSchema {
+    schema: OwnedValue,
+    documents: usize,
+}
+
+impl InferredSchema {
+    /// The number of documents the schema was inferred from
+    #[must_use]
+    pub fn documents(&self) -> usize {
+        self.documents
+    }
+
+    /// The schema as a value
+    #[must_use]
+    pub fn value(&self) -> &OwnedValue {
+        &self.schema
+    }
+
+    /// Turns the schema into a value
+    #[must_use]
+    pub fn into_value(self) -> OwnedValue {
+        self.schema
+    }
+}
+
+impl From<InferredSchema> for OwnedValue {
+    fn from(s: InferredSchema) -> Self {
+        s.schema
+    }
+}
+
+/// What was observed at a single path
+#[derive(Debug, Clone, Default)]
+struct Schema {
+    null: bool,
+    boolean: bool,
+    string: bool,
+    array: bool,
+    int_range: Option<(i128, i128)>,
+    float_range: Option<(f64, f64)>,
+    items: Option<Box<Schema>>,
+    objects: usize,
+    /// The schema of each key and the number of objects it was seen in
+    properties: BTreeMap<String, (usize, Schema)>,
+}
+
+impl Schema {
+    fn observe_static(&mut self, s: StaticNode) {
+        match s {
+            StaticNode::Null => self.null = true,
+            StaticNode::Bool(_) => self.boolean = true,
+            StaticNode::I64(v) => self.observe_int(i128::from(v)),
+            StaticNode::U64(v) => self.observe_int(i128::from(v)),
+            #[cfg(feature = "128bit")]
+            StaticNode::I128(v) => self.observe_int(v),
+            #[cfg(feature = "128bit")]
+            StaticNode::U128(v) => self.observe_int(i128::try_from(v).unwrap_or(i128::MAX)),
+            StaticNode::F64(v) => {
+                self.float_range = Some(match self.float_range {
+                    Some((min, max)) => (min.min(v), max.max(v)),
+                    None => (v, v),
+                });
+            }
+        }
+    }
+
+    fn observe_int(&mut self, v: i128) {
+        self.int_range = Some(match self.int_range {
+            Some((min, max)) => (min.min(v), max.max(v)),
+            None => (v, v),
+        });
+    }
+
+    fn items(&mut self) -> &mut Self {
+        self.items.get_or_insert_with(Box::default)
+    }
+
+    fn property(&mut self, key: &str) -> &mut Self {
+        let (count, schema) = self.properties.entry(key.to_string()).or_default();
+        *count += 1;
+        schema
+    }
+
+    fn observe(&mut self, v: &BorrowedValue) {
+        match v {
+            BorrowedValue::Static(s) => self.observe_static(*s),
+            BorrowedValue::String(_) => self.string = true,
+            BorrowedValue::Array(a) => {
+                self.array = true;
+                for v in a {
+                    self.items().observe(v);
+                }
+            }
+            BorrowedValue::Object(o) => {
+                self.objects += 1;
+                for (k, v) in o.iter() {
+                    self.property(k).observe(v);
+                }
+            }
+        }
+    }
+
+    /// Observes the value starting at `idx` and returns the index after it
+    fn observe_tape(&mut self, tape: &[Node], idx: usize) -> usize {
+        match tape[idx] {
+            Node::Static(s) => {
+                self.observe_static(s);
+                idx + 1
+            }
+            Node::String(_) => {
+                self.string = true;
+                idx + 1
+            }
+            Node::Array(len, _) => {
+                self.array = true;
+                let mut idx = idx + 1;
+                for _ in 0..len {
+                    idx = self.items().observe_tape(tape, idx);
+                }
+                idx
+            }
+            Node::Object(len, _) => {
+                self.objects += 1;
+                let mut idx = idx + 1;
+                for _ in 0..len {
+                    if let Node::String(key) = tape[idx] {
+                        idx = self.property(key).observe_tape(tape, idx + 1);
+                    } else {
+                        // keys are always strings
+                        unreachable!();
+                    }
+                }
+                idx
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn to_value(&self) -> OwnedValue {
+        let mut types = Vec::new();
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if self.float_range.is_some() {
+            types.push("number");
+        } else if self.int_range.is_some() {
+            types.push("integer");
+        }
+        if self.string {
+            types.push("string");
+        }
+        if self.array {
+            types.push("array");
+        }
+        if self.objects > 0 {
+            types.push("object");
+        }
+
+        let mut schema = Object::with_capacity_and_hasher(6, ObjectHasher::default());
+        match types.as_slice() {
+            [] => (),
+            [t] => {
+                schema.insert("type".into(), OwnedValue::from(*t));
+            }
+            _ => {
+                schema.insert("type".into(), OwnedValue::from(types));
+            }
+        }
+        match (self.int_range, self.float_range) {
+            (Some((min, max)), None) => {
+                schema.insert("minimum".into(), int_value(min));
+                schema.insert("maximum".into(), int_value(max));
+            }
+            (int_range, Some((mut min, mut max))) => {
+                if let Some((int_min, int_max)) = int_range {
+                    min = min.min(int_min as f64);
+                    max = max.max(int_max as f64);
+                }
+                schema.insert("minimum".into(), OwnedValue::from(min));
+                schema.insert("maximum".into(), OwnedValue::from(max));
+            }
+            (None, None) => (),
+        }
+        if let Some(items) = &self.items {
+            schema.insert("items".into(), items.to_value());
+        }
+        if self.objects > 0 {
+            let properties: Object = self
+                .properties
+                .iter()
+                .map(|(k, (_, v))| (k.clone(), v.to_value()))
+                .collect();
+            let required: Vec<OwnedValue> = self
+                .properties
+                .iter()
+                .filter(|(_, (count, _))| *count >= self.objects)
+                .map(|(k, _)| OwnedValue::from(k.as_str()))
+                .collect();
+            schema.insert("properties".into(), OwnedValue::from(properties));
+            schema.insert("required".into(), OwnedValue::from(required));
+        }
+        OwnedValue::from(schema)
+    }
+}
+
+/// Integers observed in 64 bit always fit into either an `i64` or a `u64`
+#[allow(clippy::cast_precision_loss)]
+fn int_value(v: i128) -> OwnedValue {
+    if let Ok(v) = i64::try_from(v) {
+        OwnedValue::from(v)
+    } else if let Ok(v) = u64::try_from(v) {
+        OwnedValue::from(v)
+    } else {
+        OwnedValue::from(v as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SchemaInference;
+    use crate::{json, to_borrowed_value, OwnedValue};
+
+    const DOCS: [&str; 4] = [
+        r#"{"id": 1, "name": "a", "tags": ["x"], "score": 1.5}"#,
+        r#"{"id": 7, "name": null, "tags": [], "extra": {"flag": true}}"#,
+        r#"{"id": 3, "name": "c", "tags": ["y", 2], "score": 2}"#,
+        r#"{"id": 18446744073709551615, "name": "d", "tags": [[]]}"#,
+    ];
+
+    fn expected() -> OwnedValue {
+        json!({
+            "type": "object",
+            "properties": {
+                "extra": {
+                    "type": "object",
+                    "properties": {"flag": {"type": "boolean"}},
+                    "required": ["flag"]
+                },
+                "id": {"type": "integer", "minimum": 1, "maximum": 18_446_744_073_709_551_615_u64},
+                "name": {"type": ["null", "string"]},
+                "score": {"type": "number", "minimum": 1.5, "maximum": 2.0},
+                "tags": {
+                    "type": "array",
+                    "items": {"type": ["integer", "string", "array"], "minimum": 2, "maximum": 2}
+                }
+            },
+            "required": ["id", "name", "tags"]
+        })
+    }
+
+    #[test]
+    fn observe() {
+        let mut inference = SchemaInference::default();
+        for d in DOCS {
+            let mut d = d.as_bytes().to_vec();
+            inference.observe(&to_borrowed_value(&mut d).expect("valid json"));
+        }
+        let schema = inference.finish();
+        assert_eq!(schema.documents(), 4);
+        assert_eq!(OwnedValue::from(schema), expected());
+    }
+
+    #[test]
+    fn observe_slice() {
+        let mut inference = SchemaInference::default();
+        for d in DOCS {
+            let mut d = d.as_bytes().to_vec();
+            inference.observe_slice(&mut d).expect("valid json");
+        }
+        let mut d = br#"{"id": "#.to_vec();
+        assert!(inference.observe_slice(&mut d).is_err());
+        let schema = inference.finish();
+        assert_eq!(schema.documents(), 4);
+        assert_eq!(schema.into_value(), expected());
+    }
+
+    #[test]
+    fn scalars() {
+        let mut inference = SchemaInference::default();
+        assert_eq!(inference.clone().finish().value(), &json!({}));
+        for d in ["1", "-2.5", "true", "null"] {
+            let mut d = d.as_bytes().to_vec();
+            inference.observe_slice(&mut d).expect("valid json");
+        }
+        assert_eq!(
+            inference.finish().into_value(),
+            json!({"type": ["null", "boolean", "number"], "minimum": -2.5, "maximum": 1.0})
+        );
+    }
+}
@@ -291,10 +291,52 @@ impl<'key> KnownKey<'key> {
         'value: 'target,
         F: FnOnce() -> Value<'value>,
     {
-        map.raw_entry_mut()
+        // we use `insert_hashed_nocheck` so the key isn't hashed a second time
+        match map
+            .raw_entry_mut()
             .from_key_hashed_nocheck(self.hash, &self.key)
-            .or_insert_with(|| (self.key.clone(), with()))
-            .1
+        {
+            RawEntryMut::Occupied(e) => e.into_mut(),
+            RawEntryMut::Vacant(e) => {
+                e.insert_hashed_nocheck(self.hash, self.key.clone(), with())
+                    .1
+            }
+        }
+    }
+
+    /// Looks up this key in a `Value`, inserts the result of `default`
+    /// when the key wasn't present. The same as `lookup_or_insert_mut`,
+    /// named like `Entry::or_insert_with`.
+    /// # Errors
+    /// * if target is not a record
+    ///
+    /// ```rust
+    /// use simd_json::*;
+    /// let mut object: BorrowedValue = json!({"answer": 23}).into();
+    /// let known_key = KnownKey::from("question");
+    ///
+    /// let question = known_key
+    ///     .lookup_or_insert_with(&mut object, || "unknown".into())
+    ///     .unwrap();
+    /// assert_eq!(*question, "unknown");
+    ///
+    /// let mut not_an_object = BorrowedValue::from(42);
+    /// assert!(known_key
+    ///     .lookup_or_insert_with(&mut not_an_object, || "unknown".into())
+    ///     .is_err());
+    /// ```
+    #[inline]
+    pub fn lookup_or_insert_with<'target, 'value, F>(
+        &self,
+        target: &'target mut Value<'value>,
+        default: F,
+    ) -> Result<&'target mut Value<'value>, Error>
+    where
+        'key: 'value,
+        'value: 'target,
+        F: FnOnce() -> Value<'value>,
+    {
+        self.lookup_or_insert_mut(target, default)
     }
 
     /// Inserts a value key into  `Value`, returns None if the
@@ -388,6 +430,73 @@ impl<'key> KnownKey<'key> {
             }
         }
     }
+
+    /// Removes this key from a `Value`, returns None if the
+    /// key wasn't present otherwise Some(`old value`).
+    /// # Errors
+    ///   * if `target` isn't an object
+    ///
+    /// ```rust
+    /// use simd_json::*;
+    /// let mut object: BorrowedValue = json!({
+    ///   "answer": 42,
+    ///   "key": 7
+    /// }).into();
+    /// let known_key = KnownKey::from("answer");
+    ///
+    /// assert_eq!(known_key.remove(&mut object).unwrap(), Some(42.into()));
+    /// assert_eq!(known_key.remove(&mut object).unwrap(), None);
+    /// assert!(object.get("answer").is_none());
+    /// ```
+    #[inline]
+    pub fn remove<'target, 'value>(
+        &self,
+        target: &'target mut Value<'value>,
+    ) -> Result<Option<Value<'value>>, Error>
+    where
+        'key: 'value,
+        'value: 'target,
+    {
+        target
+            .as_object_mut()
+            .map(|m| self.map_remove(m))
+            .ok_or_else(|| Error::NotAnObject(target.value_type()))
+    }
+
+    /// Removes this key from `map`, returns None if the
+    /// key wasn't present otherwise Some(`old value`).
+    ///
+    /// ```rust
+    /// use simd_json::*;
+    ///
+    /// let mut object: BorrowedValue = json!({
+    ///   "answer": 42,
+    ///   "key": 7
+    /// }).into();
+    /// let known_key = KnownKey::from("answer");
+    ///
+    /// if let Some(inner) = object.as_object_mut() {
+    ///   assert_eq!(known_key.map_remove(inner), Some(42.into()));
+    ///   assert_eq!(known_key.map_remove(inner), None);
+    /// }
+    /// ```
+    #[inline]
+    pub fn map_remove<'target, 'value>(
+        &self,
+        map: &'target mut super::borrowed::Object<'value>,
+    ) -> Option<Value<'value>>
+    where
+        'key: 'value,
+        'value: 'target,
+    {
+        match map
+            .raw_entry_mut()
+            .from_key_hashed_nocheck(self.hash, &self.key)
+        {
+            RawEntryMut::Occupied(e) => Some(e.remove()),
+            RawEntryMut::Vacant(_e) => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -459,6 +568,96 @@ mod tests {
             assert_eq!(r2.as_u8(), Some(3));
         }
     }
+    #[test]
+    fn lookup_or_insert_with() {
+        use crate::cow::Cow;
+        let key1 = KnownKey::from(Cow::from("key"));
+        let key2 = KnownKey::from(Cow::from("cake"));
+
+        let mut v1 = Value::null();
+        assert_eq!(
+            key1.lookup_or_insert_with(&mut v1, || 2.into()),
+            Err(Error::NotAnObject(ValueType::Null))
+        );
+
+        // small objects are backed by a vector, large ones by a hash map
+        for size in [1, 64] {
+            let mut v = Value::object();
+            for i in 1..size {
+                v.insert(format!("key{i}"), i).expect("failed to insert");
+            }
+            v.insert("key", 1).expect("failed to insert");
+            let r1 = key1
+                .lookup_or_insert_with(&mut v, || unreachable!())
+                .expect("failed to look up");
+            assert_eq!(r1.as_u8(), Some(1));
+            let r2 = key2
+                .lookup_or_insert_with(&mut v, || 3.into())
+                .expect("failed to insert");
+            assert_eq!(r2.as_u8(), Some(3));
+            assert_eq!(v["cake"], 3);
+            assert_eq!(v.as_object().expect("not an object").len(), size + 1);
+        }
+    }
+
+    #[test]
+    fn known_key_remove() {
+        use crate::cow::Cow;
+        let key1 = KnownKey::from(Cow::from("key"));
+        let key2 = KnownKey::from(Cow::from("cake"));
+
+        let mut v1 = Value::null();
+        assert_eq!(
+            key1.remove(&mut v1),
+            Err(Error::NotAnObject(ValueType::Null))
+        );
+
+        // small objects are backed by a vector, large ones by a hash map
+        for size in [1, 64] {
+            let mut v = Value::object();
+            for i in 1..size {
+                v.insert(format!("key{i}"), i).expect("failed to insert");
+            }
+            v.insert("key", 1).expect("failed to insert");
+            assert_eq!(key2.remove(&mut v).expect("failed to remove"), None);
+            assert_eq!(
+                key1.remove(&mut v).expect("failed to remove"),
+                Some(1.into())
+            );
+            assert_eq!(key1.remove(&mut v).expect("failed to remove"), None);
+            assert!(v.get("key").is_none());
+            assert_eq!(v.as_object().expect("not an object").len(), size - 1);
+        }
+    }
+
+    #[test]
+    fn lookup_or_insert_mut_map() {
+        use crate::cow::Cow;
+        let key1 = KnownKey::from(Cow::from("key"));
+        let key2 = KnownKey::from(Cow::from("cake"));
+
+        let mut v = Value::object_with_capacity(128);
+        for i in 0..64 {
+            v.insert(format!("key{i}"), i).expect("failed to insert");
+        }
+        v.insert("key", 1).expect("failed to insert");
+        {
+            let r1 = key1
+                .lookup_or_insert_mut(&mut v, || 2.into())
+                .expect("failed to insert");
+            assert_eq!(r1.as_u8(), Some(1));
+        }
+        {
+            let r2 = key2
+                .lookup_or_insert_mut(&mut v, || 3.into())
+                .expect("failed to insert");
+            assert_eq!(r2.as_u8(), Some(3));
+        }
+        // the inserted key is found through the regular lookup as well
+        assert_eq!(v["cake"], 3);
+        assert_eq!(key2.lookup(&v), Some(&Value::from(3)));
+    }
+
     #[test]
     fn known_key_map() {
         use crate::cow::Cow;
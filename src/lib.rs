@@ -47,6 +47,14 @@
 //! quite a few test cases along with property based testing, please use
 //! this library with caution.
 //!
+//! ## Input size
+//!
+//! Documents are limited to [`MAX_INPUT_LEN`] bytes, one byte short of
+//! 4 GiB, as structural positions are stored as `u32`. Every parsing
+//! function checks the length up front and fails with
+//! `ErrorType::InputTooLarge` for larger inputs, split very large inputs
+//! into several documents, for example with `Documents`.
+//!
 //!
 //! ## Features
 //!
@@ -74,6 +82,18 @@
 //! and parsing them comes as a performance penalty due to extra logic
 //! and a changed memory layout.
 //!
+//! ### `128bit-string-fallback`
+//!
+//! Implies `128bit`. When serializing values through serde, 128 bit
+//! integers that fit into 64 bit are emitted as 64 bit integers and
+//! larger ones are emitted as strings. This helps with serializers
+//! such as MessagePack or CBOR that do not support 128 bit integers.
+//! The fallback applies to every serde serializer, so with it enabled
+//! serde_json and the serde based writers in this crate produce
+//! `"340282366920938463463374607431768211455"` instead of a number for
+//! values that don't fit into 64 bit. The `Writable` encoders of the value
+//! types are not affected.
+//!
 //! ### `known-key`
 //!
 //! The known-key feature changes hasher for the objects, from ahash
@@ -84,6 +104,20 @@
 //! time on lookups. In workloads that are heavy at accessing some well
 //! known keys this can be a performance advantage.
 //!
+//! ### `paranoid-checks`
+//!
+//! A debugging aid for soundness reports: turns the unchecked slice
+//! accesses used in the hot loops into bounds checked ones that panic
+//! instead of reading out of bounds. Debug builds always do this, the
+//! feature enables it for release builds. When disabled the checks are
+//! compiled out entirely, `codegen.sh` verifies that the release assembly
+//! is identical to plain `get_unchecked` calls.
+//!
+//! ### `capi`
+//!
+//! Exposes `extern "C"` functions to parse documents and read them from
+//! C or C++, see the `capi` module and `include/simd_json.h`.
+//!
 //! ## Usage
 //!
 //! simd-json offers two main entry points for usage:
@@ -112,13 +146,23 @@
 //!
 //! ### Serde Compatible API
 //!
-//! ```ignore
+//! ```
+//! # #[cfg(feature = "serde_impl")]
+//! # {
 //! use simd_json;
 //! use serde_json::Value;
 //!
 //! let mut d = br#"{"some": ["key", "value", 2]}"#.to_vec();
-//! let v: Value = simd_json::serde::from_slice(&mut d).unwrap();
+//! let v: Value = simd_json::from_slice(&mut d).unwrap();
+//! # }
 //! ```
+//!
+//! The serde functions are available at the crate root under the same
+//! names as in `serde_json`: `from_slice`, `from_str`, `from_reader`,
+//! `to_vec`, `to_string`, `to_writer` and their `_pretty` variants. When
+//! migrating keep in mind that `from_slice` takes a mutable slice, which
+//! is rewritten to unescape strings, and that `from_str` is `unsafe` as
+//! the `str` might not be valid UTF-8 afterwards.
 
 #[cfg(feature = "serde_impl")]
 extern crate serde as serde_ext;
@@ -129,10 +173,17 @@ pub mod serde;
 
 #[cfg(feature = "serde_impl")]
 pub use crate::serde::{
-    from_reader, from_slice, from_str, to_string, to_string_pretty, to_vec, to_vec_pretty,
-    to_writer, to_writer_pretty,
+    from_file, from_reader, from_reader_size_limited, from_slice, from_slice_seed,
+    from_slice_streaming, from_slice_with_opts, from_slice_with_path, from_str, lazy,
+    parse_array_of, to_string, to_string_pretty, to_string_pretty_with_opts, to_string_with_opts,
+    to_vec, to_vec_pretty, to_vec_pretty_with_opts, to_vec_with_opts, to_writer, to_writer_pretty,
+    to_writer_pretty_with_opts, to_writer_with_opts, try_from_slice, KeyOffset, LazyValue,
+    RawString, ValidNumber,
 };
 
+#[cfg(all(feature = "serde_impl", feature = "bytes"))]
+pub use crate::serde::{from_bytes, owned_value_from_bytes, to_bytes, to_bytes_mut};
+
 /// Default trait imports;
 pub mod prelude;
 
@@ -141,6 +192,7 @@ mod charutils;
 mod macros;
 mod error;
 mod numberparse;
+mod options;
 mod safer_unchecked;
 mod stringparse;
 
@@ -228,14 +280,113 @@ use simdutf8::basic::imp::x86::sse42::ChunkedUtf8ValidatorImp;
 const _: () =
     compile_error!("Please compile with a simd compatible cpu setting, read the simdjson README.");
 
+/// The largest input in bytes that can be parsed, `u32::MAX`, one byte
+/// short of 4 GiB. Like simdjson the parser stores the positions of
+/// structural characters as `u32`, which keeps the index at four bytes
+/// per structural character. Larger inputs are rejected with
+/// `ErrorType::InputTooLarge` before any buffer is allocated for them.
+pub const MAX_INPUT_LEN: usize = u32::MAX as usize;
+
+/// Returns the name of the SIMD backend simd-json was compiled with, one of
+/// `"avx2"`, `"sse4.2"`, `"neon"`, `"wasm-simd128"` or `"fallback"`.
+///
+/// There is no runtime dispatch, the backend is selected at compile time
+/// through the enabled target features (for example with
+/// `RUSTFLAGS="-C target-cpu=native"`) and this reports that selection,
+/// not what the cpu running the program supports. `"fallback"` means the
+/// crate was built without a simd compatible cpu setting (see the
+/// `allow-non-simd` feature).
+///
+/// ```rust
+/// let backend = simd_json::compiled_simd_backend();
+/// assert_eq!(backend == "avx2", cfg!(target_feature = "avx2"));
+/// ```
+#[must_use]
+pub const fn compiled_simd_backend() -> &'static str {
+    if cfg!(target_feature = "avx2") {
+        "avx2"
+    } else if cfg!(target_feature = "sse4.2") {
+        "sse4.2"
+    } else if cfg!(target_feature = "neon") {
+        "neon"
+    } else if cfg!(target_feature = "simd128") {
+        "wasm-simd128"
+    } else {
+        "fallback"
+    }
+}
+
+/// The same as [`compiled_simd_backend`], the backend is selected at
+/// compile time and not detected at runtime.
+#[deprecated(note = "the backend is selected at compile time, use `compiled_simd_backend`")]
+#[must_use]
+pub const fn active_simd_backend() -> &'static str {
+    compiled_simd_backend()
+}
+
+/// Writes `s` to `out` as a quoted JSON string, escaping it with the
+/// same SIMD accelerated escaper the serializers use. This is a building
+/// block for custom serializers.
+///
+/// ```rust
+/// let mut out = Vec::new();
+/// simd_json::encode_string_to_json("say \"hi\"\n", &mut out);
+/// assert_eq!(out, br#""say \"hi\"\n""#);
+/// ```
+pub fn encode_string_to_json(s: &str, out: &mut Vec<u8>) {
+    struct StringGenerator<'out>(&'out mut Vec<u8>);
+    impl<'out> value_trait::generator::BaseGenerator for StringGenerator<'out> {
+        type T = Vec<u8>;
+        #[inline]
+        fn get_writer(&mut self) -> &mut Vec<u8> {
+            self.0
+        }
+        #[inline]
+        fn write_min(&mut self, _slice: &[u8], min: u8) -> std::io::Result<()> {
+            self.0.push(min);
+            Ok(())
+        }
+    }
+    // writing to a `Vec` can't fail
+    let _r = value_trait::generator::BaseGenerator::write_string(&mut StringGenerator(out), s);
+}
+
+pub mod base64;
+mod batch;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod compare;
+pub mod infer;
+pub mod low_level;
+pub mod pointer;
+mod secure;
+mod splice;
+mod split;
 mod stage2;
+mod stream;
+#[cfg(feature = "unescape-stats")]
+mod unescape_stats;
+mod unique;
 /// simd-json JSON-DOM value
 pub mod value;
 
 use std::{alloc::dealloc, mem};
 pub use value_trait::StaticNode;
 
+#[cfg(feature = "serde_impl")]
+pub use crate::batch::from_slices;
+pub use crate::batch::{to_owned_values, Batch, BatchResult};
+pub use crate::compare::json_eq;
 pub use crate::error::{Error, ErrorType};
+pub use crate::options::{DuplicateKeys, FieldMatching, NumberMode, ParseOptions};
+pub use crate::secure::SecureDeserializer;
+pub use crate::splice::splice;
+pub use crate::split::{split_array, split_object};
+pub use crate::stream::{iter_lines_borrowed, BorrowedLines, DocumentError, Documents};
+pub use crate::stringparse::decode_unicode_escapes;
+#[cfg(feature = "unescape-stats")]
+pub use crate::unescape_stats::UnescapeStats;
+pub use crate::unique::{check_unique_field, Duplicate, PathSeg, UniqueReport};
 pub use crate::value::*;
 pub use value_trait::ValueType;
 
@@ -262,6 +413,210 @@ pub fn to_tape(s: &mut [u8]) -> Result<Vec<Node>> {
     Deserializer::from_slice(s).map(Deserializer::into_tape)
 }
 
+/// Allocates an empty `Vec` for `capacity` elements, failing with
+/// `ErrorType::AllocationFailed` instead of aborting
+pub(crate) fn try_vec<T>(capacity: usize) -> Result<Vec<T>> {
+    let mut v = Vec::new();
+    match v.try_reserve_exact(capacity) {
+        Ok(()) => Ok(v),
+        Err(_) => Err(Error::generic(ErrorType::AllocationFailed(
+            capacity.saturating_mul(mem::size_of::<T>()),
+        ))),
+    }
+}
+
+/// Estimates the number of tape nodes `to_tape` produces for `json`
+/// with a single byte scan, without validating or parsing it. For valid
+/// JSON the estimate is exact, for invalid JSON it is a best guess.
+///
+/// This is useful to size storage for tapes or values up front.
+///
+/// ```rust
+/// let mut d = br#"{"a": [1, true, "b"]}"#.to_vec();
+/// let estimate = simd_json::estimate_tape_size(&d);
+/// assert_eq!(estimate, simd_json::to_tape(&mut d).unwrap().len());
+/// ```
+#[must_use]
+pub fn estimate_tape_size(json: &[u8]) -> usize {
+    // the tape starts with a root node
+    let mut nodes = 1;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut in_atom = false;
+    for &c in json {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == b'\\' {
+                escaped = true;
+            } else if c == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            b'"' => {
+                in_string = true;
+                in_atom = false;
+                nodes += 1;
+            }
+            b'{' | b'[' => {
+                in_atom = false;
+                nodes += 1;
+            }
+            b'}' | b']' | b',' | b':' | b' ' | b'\t' | b'\n' | b'\r' => in_atom = false,
+            _ => {
+                if !in_atom {
+                    in_atom = true;
+                    nodes += 1;
+                }
+            }
+        }
+    }
+    nodes
+}
+
+/// Counts the tape nodes `to_tape` produces for `json` by running only
+/// the SIMD structural scan, the tape itself is not built and the input
+/// is not modified. Unlike `estimate_tape_size` this validates UTF-8 and
+/// strings, but not the structure of the document, for valid JSON the
+/// count is exact.
+///
+/// ```rust
+/// let mut d = br#"{"a": [1, true, "b"]}"#.to_vec();
+/// let count = simd_json::tape_node_count(&d).unwrap();
+/// assert_eq!(count, simd_json::to_tape(&mut d).unwrap().len());
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the structural scan fails, for example on
+/// invalid UTF-8, an unterminated string or empty input.
+pub fn tape_node_count(json: &[u8]) -> Result<usize> {
+    if json.len() > MAX_INPUT_LEN {
+        return Err(Error::generic(ErrorType::InputTooLarge));
+    }
+    let structural_indexes =
+        unsafe { Deserializer::find_structural_bits(json) }.map_err(Error::generic)?;
+    // the first index is the root, every other index that doesn't close a
+    // container or separate values starts a node
+    let nodes = structural_indexes
+        .iter()
+        .skip(1)
+        .filter(|&&idx| !matches!(json[idx as usize], b'}' | b']' | b',' | b':'))
+        .count();
+    Ok(nodes + 1)
+}
+
+/// Finds the end of the first JSON value in `json` by running only the
+/// SIMD structural scan, for framing concatenated or length prefixed
+/// streams. Returns the offset right after the first balanced object or
+/// array, or after the first scalar, leading whitespace is skipped.
+///
+/// The content of the value is not validated, parsing the returned
+/// range can still fail. The scan covers all of `json`, so it has to be
+/// valid UTF-8 and can't end inside a string.
+///
+/// ```rust
+/// let d = br#" {"a": ["}"]} [1] 2"#;
+/// let end = simd_json::first_document_end(d).unwrap();
+/// assert_eq!(&d[..end], br#" {"a": ["}"]}"#);
+/// assert_eq!(simd_json::first_document_end(b"42 true").unwrap(), 2);
+/// assert!(simd_json::first_document_end(b"[1, [2]").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the structural scan fails, the input is empty,
+/// the first object or array isn't closed or the input starts with a
+/// closing bracket or separator.
+pub fn first_document_end(json: &[u8]) -> Result<usize> {
+    if json.len() > MAX_INPUT_LEN {
+        return Err(Error::generic(ErrorType::InputTooLarge));
+    }
+    let structural_indexes =
+        unsafe { Deserializer::find_structural_bits(json) }.map_err(Error::generic)?;
+    // the first index is the root, the scan fails if there are no others
+    let mut indexes = structural_indexes.iter().skip(1).map(|idx| *idx as usize);
+    let start = indexes
+        .next()
+        .ok_or_else(|| Error::generic(ErrorType::Eof))?;
+    let first = json[start];
+    match first {
+        b'{' | b'[' => {
+            let mut depth: usize = 0;
+            for idx in std::iter::once(start).chain(indexes) {
+                match json[idx] {
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(idx + 1);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            Err(Error::new_c(start, char::from(first), ErrorType::Eof))
+        }
+        b'}' | b']' | b',' | b':' => Err(Error::new_c(
+            start,
+            char::from(first),
+            ErrorType::UnexpectedCharacter,
+        )),
+        // scalars end before the whitespace in front of the next
+        // structural character, the closing quote of a string isn't one
+        _ => {
+            let next = indexes.next().unwrap_or(json.len());
+            Ok(json[start..next]
+                .iter()
+                .rposition(|c| !matches!(c, b' ' | b'\t' | b'\n' | b'\r'))
+                .map_or(next, |p| start + p + 1))
+        }
+    }
+}
+
+/// Parses a single JSON number from a string, without the surrounding
+/// JSON parser. Leading and trailing whitespace is ignored.
+///
+/// ```rust
+/// use simd_json::{parse_number_str, StaticNode};
+/// assert_eq!(parse_number_str("42").unwrap(), StaticNode::I64(42));
+/// assert_eq!(parse_number_str(" -1.5 ").unwrap(), StaticNode::F64(-1.5));
+/// assert!(parse_number_str("42abc").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is not a valid JSON number.
+pub fn parse_number_str(s: &str) -> Result<StaticNode> {
+    let s = s.trim_matches(|c| matches!(c, ' ' | '\t' | '\n' | '\r'));
+    let bytes = s.as_bytes();
+    // The number parser stops at the first character that isn't part of a
+    // number, so anything outside of the number alphabet is trailing data.
+    if let Some(idx) = bytes
+        .iter()
+        .position(|c| !matches!(c, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'))
+    {
+        return Err(Error::new_c(
+            idx,
+            char::from(bytes[idx]),
+            ErrorType::InvalidNumber,
+        ));
+    }
+    let negative = match bytes.first() {
+        Some(b'-') => true,
+        Some(b'0'..=b'9') => false,
+        Some(c) => return Err(Error::new_c(0, char::from(*c), ErrorType::InvalidNumber)),
+        None => return Err(Error::generic(ErrorType::Eof)),
+    };
+    // the number parser reads ahead so we need a padded, zero terminated buffer
+    let mut buf = Vec::with_capacity(bytes.len() + SIMDJSON_PADDING);
+    buf.extend_from_slice(bytes);
+    buf.resize(bytes.len() + SIMDJSON_PADDING, 0);
+    Deserializer::parse_number(0, &buf, negative)
+}
+
 pub(crate) trait Stage1Parse<T> {
     fn compute_quote_mask(quote_bits: u64) -> u64;
 
@@ -407,6 +762,20 @@ pub struct Deserializer<'de> {
     // used (array / object use len) everything else uses idx
     pub(crate) tape: Vec<Node<'de>>,
     idx: usize,
+    #[cfg(feature = "serde_impl")]
+    field_matching: FieldMatching,
+    duplicate_keys: DuplicateKeys,
+    #[cfg(feature = "serde_impl")]
+    null_as_default: bool,
+    /// The address of the input, strings on the tape point into it
+    #[cfg(feature = "serde_impl")]
+    input_start: usize,
+    /// The tape index of the key of the map value being deserialized,
+    /// 0 if there is none
+    #[cfg(feature = "serde_impl")]
+    last_key: usize,
+    #[cfg(feature = "unescape-stats")]
+    unescape_stats: UnescapeStats,
 }
 
 impl<'de> Deserializer<'de> {
@@ -416,6 +785,77 @@ impl<'de> Deserializer<'de> {
         self.tape
     }
 
+    /// Builds an owned value from the document, consuming the
+    /// deserializer. The value always holds the whole document, even if
+    /// parts of it were deserialized with serde before.
+    ///
+    /// ```rust
+    /// use simd_json::Deserializer;
+    /// let mut d = br#"{"a": [1, 2]}"#.to_vec();
+    /// let v = Deserializer::from_slice(&mut d).unwrap().into_owned_value();
+    /// assert_eq!(v["a"][1], 2);
+    /// ```
+    #[must_use]
+    pub fn into_owned_value(mut self) -> OwnedValue {
+        self.restart();
+        value::owned::OwnedDeserializer::from_deserializer(self).parse()
+    }
+
+    /// Returns the length in bytes of the unescaped string at tape index
+    /// `idx`, without copying or decoding it. Tape indexes are the ones of
+    /// `into_tape`, the root value is at index 1. Returns `None` if the
+    /// node at `idx` is not a string.
+    ///
+    /// ```rust
+    /// use simd_json::Deserializer;
+    /// let mut d = br#"["caf\u00e9", 1]"#.to_vec();
+    /// let de = Deserializer::from_slice(&mut d).unwrap();
+    /// assert_eq!(de.json_string_byte_len(2), Some(5));
+    /// assert_eq!(de.json_string_byte_len(3), None);
+    /// ```
+    #[must_use]
+    pub fn json_string_byte_len(&self, idx: usize) -> Option<usize> {
+        match self.tape.get(idx) {
+            Some(Node::String(s)) => Some(s.len()),
+            _ => None,
+        }
+    }
+
+    /// Returns how the strings of the document were unescaped
+    #[cfg(feature = "unescape-stats")]
+    #[must_use]
+    pub fn unescape_stats(&self) -> &UnescapeStats {
+        &self.unescape_stats
+    }
+
+    /// Resets the deserializer to the start of the tape. This allows
+    /// deserializing the same parsed document multiple times, for example
+    /// into different types, without parsing it again.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "serde_impl")] {
+    /// use serde::Deserialize;
+    /// use simd_json::{BorrowedValue, Deserializer};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Header<'a> {
+    ///     kind: &'a str,
+    /// }
+    ///
+    /// let mut d = br#"{"kind": "ping", "payload": [1, 2, 3]}"#.to_vec();
+    /// let mut de = Deserializer::from_slice(&mut d).unwrap();
+    /// let header = Header::deserialize(&mut de).unwrap();
+    /// de.restart();
+    /// let full = BorrowedValue::deserialize(&mut de).unwrap();
+    /// assert_eq!(header.kind, "ping");
+    /// assert_eq!(full["payload"][2], 3);
+    /// # }
+    /// ```
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub fn restart(&mut self) {
+        self.idx = 0;
+    }
+
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     fn error(error: ErrorType) -> Error {
         Error::new(0, None, error)
@@ -431,16 +871,68 @@ impl<'de> Deserializer<'de> {
     /// # Errors
     ///
     /// Will return `Err` if `s` is invalid JSON.
-    #[allow(clippy::uninit_vec)]
     pub fn from_slice(input: &'de mut [u8]) -> Result<Self> {
-        let len = input.len();
+        Self::from_slice_with_opts(input, ParseOptions::default())
+    }
 
-        let mut string_buffer: Vec<u8> = Vec::with_capacity(len + SIMDJSON_PADDING);
-        unsafe {
-            string_buffer.set_len(len + SIMDJSON_PADDING);
-        };
+    /// Creates a deserializer from input that arrives in several
+    /// segments, such as the chunks of a HTTP body. The segments are
+    /// copied into `scratch` once, after reserving their total length,
+    /// and strings of the document point into it. The parser pads its
+    /// input itself so `scratch` needs no room for padding, reusing it
+    /// between documents avoids allocating it again.
+    ///
+    /// ```rust
+    /// use simd_json::Deserializer;
+    /// let mut scratch = Vec::new();
+    /// let segments: [&[u8]; 2] = [br#"{"a": [1,"#, br#" 2]}"#];
+    /// let de = Deserializer::from_segments(&segments, &mut scratch).unwrap();
+    /// assert_eq!(de.into_owned_value()["a"][1], 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the concatenated segments are invalid JSON.
+    pub fn from_segments(segments: &[&[u8]], scratch: &'de mut Vec<u8>) -> Result<Self> {
+        let len: usize = segments.iter().map(|s| s.len()).sum();
+        if len > MAX_INPUT_LEN {
+            return Err(Self::error(ErrorType::InputTooLarge));
+        }
+        scratch.clear();
+        scratch.reserve(len);
+        for segment in segments {
+            scratch.extend_from_slice(segment);
+        }
+        Self::from_slice(scratch)
+    }
 
-        Self::from_slice_with_buffer(input, &mut string_buffer)
+    /// Like `from_segments`, but if at most one segment isn't empty it
+    /// is parsed in place without copying it, it is then taken out of
+    /// `segments` and `scratch` is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the concatenated segments are invalid JSON.
+    pub fn from_segments_mut(
+        segments: &mut [&'de mut [u8]],
+        scratch: &'de mut Vec<u8>,
+    ) -> Result<Self> {
+        if segments.iter().filter(|s| !s.is_empty()).count() <= 1 {
+            return match segments.iter_mut().find(|s| !s.is_empty()) {
+                Some(segment) => Self::from_slice(std::mem::take(segment)),
+                None => Self::from_slice(&mut []),
+            };
+        }
+        let len: usize = segments.iter().map(|s| s.len()).sum();
+        if len > MAX_INPUT_LEN {
+            return Err(Self::error(ErrorType::InputTooLarge));
+        }
+        scratch.clear();
+        scratch.reserve(len);
+        for segment in segments.iter() {
+            scratch.extend_from_slice(segment);
+        }
+        Self::from_slice(scratch)
     }
 
     /// Creates a serializer from a mutable slice of bytes using a temporary
@@ -455,6 +947,9 @@ impl<'de> Deserializer<'de> {
         // data and limit re-allocation frequency.
 
         let len = input.len();
+        if len > MAX_INPUT_LEN {
+            return Err(Self::error(ErrorType::InputTooLarge));
+        }
 
         // let buf_start: usize = input.as_ptr() as *const () as usize;
         // let needs_relocation = (buf_start + input.len()) % page_size::get() < SIMDJSON_PADDING;
@@ -463,6 +958,31 @@ impl<'de> Deserializer<'de> {
         Self::from_slice_with_buffers(input, &mut buffer, string_buffer)
     }
 
+    /// Creates a serializer from a mutable slice of bytes using the
+    /// given parse options
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON or violates the limits
+    /// set in `opts`.
+    #[allow(clippy::uninit_vec)]
+    pub fn from_slice_with_opts(input: &'de mut [u8], opts: ParseOptions) -> Result<Self> {
+        let len = input.len();
+        // checked before allocating the buffers for the input
+        if len > MAX_INPUT_LEN {
+            return Err(Self::error(ErrorType::InputTooLarge));
+        }
+        stry!(opts.check_input_len(len));
+
+        let mut string_buffer: Vec<u8> = Vec::with_capacity(len + SIMDJSON_PADDING);
+        unsafe {
+            string_buffer.set_len(len + SIMDJSON_PADDING);
+        };
+        let mut buffer = AlignedBuf::with_capacity(len + SIMDJSON_PADDING * 2);
+
+        Self::from_slice_with_buffers_and_opts(input, &mut buffer, &mut string_buffer, opts)
+    }
+
     /// Creates a serializer from a mutable slice of bytes using a temporary
     /// buffer for strings for them to be copied in and out if needed
     ///
@@ -473,17 +993,101 @@ impl<'de> Deserializer<'de> {
         input: &'de mut [u8],
         input_buffer: &mut AlignedBuf,
         string_buffer: &mut [u8],
+    ) -> Result<Self> {
+        Self::from_slice_with_buffers_and_opts(
+            input,
+            input_buffer,
+            string_buffer,
+            ParseOptions::default(),
+        )
+    }
+
+    /// Creates a serializer from a mutable slice of bytes using a temporary
+    /// buffer for strings for them to be copied in and out if needed and
+    /// the given parse options
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON or violates the limits
+    /// set in `opts`.
+    pub fn from_slice_with_buffers_and_opts(
+        input: &'de mut [u8],
+        input_buffer: &mut AlignedBuf,
+        string_buffer: &mut [u8],
+        opts: ParseOptions,
     ) -> Result<Self> {
         let len = input.len();
 
-        if len > std::u32::MAX as usize {
+        if len > MAX_INPUT_LEN {
             return Err(Self::error(ErrorType::InputTooLarge));
         }
+        stry!(opts.check_input_len(len));
 
         if input_buffer.capacity() < len + SIMDJSON_PADDING * 2 {
             *input_buffer = AlignedBuf::with_capacity(len + SIMDJSON_PADDING * 2);
         }
 
+        // 6 is a heuristic number to estimate it turns out a rate of 1/6 structural characters
+        // leads almost never to relocations.
+        let structural_indexes = Vec::with_capacity(len / 6);
+        Self::from_buffers::<false>(input, input_buffer, string_buffer, structural_indexes, opts)
+    }
+
+    /// Creates a deserializer like `from_slice_with_opts`, but fails with
+    /// `ErrorType::AllocationFailed` instead of aborting if one of the
+    /// parser's buffers can't be allocated or grown. The buffers are
+    /// sized like for `from_slice_with_opts`.
+    ///
+    /// ```rust
+    /// use simd_json::{Deserializer, ParseOptions};
+    /// let mut d = br#"{"a": [1, 2]}"#.to_vec();
+    /// let de = Deserializer::try_from_slice_with_opts(&mut d, ParseOptions::default()).unwrap();
+    /// assert_eq!(de.into_tape().len(), 6);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON, violates the limits
+    /// set in `opts` or a buffer can't be allocated.
+    #[allow(clippy::uninit_vec)]
+    pub fn try_from_slice_with_opts(input: &'de mut [u8], opts: ParseOptions) -> Result<Self> {
+        let len = input.len();
+
+        if len > MAX_INPUT_LEN {
+            return Err(Self::error(ErrorType::InputTooLarge));
+        }
+        stry!(opts.check_input_len(len));
+
+        let mut string_buffer: Vec<u8> = stry!(try_vec(len + SIMDJSON_PADDING));
+        unsafe {
+            string_buffer.set_len(len + SIMDJSON_PADDING);
+        };
+        let mut input_buffer = stry!(AlignedBuf::try_with_capacity(len + SIMDJSON_PADDING * 2));
+        // 6 is a heuristic number to estimate it turns out a rate of 1/6 structural characters
+        // leads almost never to relocations.
+        let structural_indexes = stry!(try_vec(len / 6));
+
+        Self::from_buffers::<true>(
+            input,
+            &mut input_buffer,
+            &mut string_buffer,
+            structural_indexes,
+            opts,
+        )
+    }
+
+    /// Parses `input` using buffers that are large enough, with `FALLIBLE`
+    /// the buffers that are allocated or grown while parsing fail with
+    /// `ErrorType::AllocationFailed` instead of aborting
+    fn from_buffers<const FALLIBLE: bool>(
+        input: &'de mut [u8],
+        input_buffer: &mut AlignedBuf,
+        string_buffer: &mut [u8],
+        structural_indexes: Vec<u32>,
+        opts: ParseOptions,
+    ) -> Result<Self> {
+        let len = input.len();
+
         unsafe {
             std::ptr::copy_nonoverlapping(input.as_ptr(), input_buffer.as_mut_ptr(), len);
 
@@ -494,7 +1098,7 @@ impl<'de> Deserializer<'de> {
         };
 
         let s1_result: std::result::Result<Vec<u32>, ErrorType> =
-            unsafe { Self::find_structural_bits(input) };
+            unsafe { Self::find_structural_bits_into::<FALLIBLE>(input, structural_indexes) };
 
         let structural_indexes = match s1_result {
             Ok(i) => i,
@@ -503,10 +1107,32 @@ impl<'de> Deserializer<'de> {
             }
         };
 
-        let tape: Vec<Node> =
-            Self::build_tape(input, input_buffer, string_buffer, &structural_indexes)?;
-
-        Ok(Self { tape, idx: 0 })
+        #[cfg(any(feature = "unescape-stats", feature = "serde_impl"))]
+        let input_start = input.as_ptr() as usize;
+
+        let tape: Vec<Node> = Self::build_tape::<FALLIBLE>(
+            input,
+            input_buffer,
+            string_buffer,
+            &structural_indexes,
+            opts,
+        )?;
+
+        Ok(Self {
+            #[cfg(feature = "unescape-stats")]
+            unescape_stats: UnescapeStats::collect(&tape, input_start, input_buffer),
+            tape,
+            idx: 0,
+            #[cfg(feature = "serde_impl")]
+            field_matching: opts.field_matching,
+            duplicate_keys: opts.duplicate_keys,
+            #[cfg(feature = "serde_impl")]
+            null_as_default: opts.null_as_default,
+            #[cfg(feature = "serde_impl")]
+            input_start,
+            #[cfg(feature = "serde_impl")]
+            last_key: 0,
+        })
     }
 
     #[cfg(feature = "serde_impl")]
@@ -529,14 +1155,37 @@ impl<'de> Deserializer<'de> {
     }
 
     //#[inline(never)]
-    #[allow(clippy::cast_possible_truncation)]
     pub(crate) unsafe fn find_structural_bits(
         input: &[u8],
     ) -> std::result::Result<Vec<u32>, ErrorType> {
-        let len = input.len();
         // 6 is a heuristic number to estimate it turns out a rate of 1/6 structural characters
         // leads almost never to relocations.
-        let mut structural_indexes = Vec::with_capacity(len / 6);
+        Self::find_structural_bits_into::<false>(input, Vec::with_capacity(input.len() / 6))
+    }
+
+    /// Finds the structural bits, appending them to an empty `Vec`. With
+    /// `FALLIBLE` the `Vec` is grown with `try_reserve` before every chunk
+    /// is flattened, so `flatten_bits` never has to grow it.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) unsafe fn find_structural_bits_into<const FALLIBLE: bool>(
+        input: &[u8],
+        mut structural_indexes: Vec<u32>,
+    ) -> std::result::Result<Vec<u32>, ErrorType> {
+        /// Makes room for the indexes of a chunk, as many as `flatten_bits`
+        /// reserves
+        fn try_reserve_chunk(
+            structural_indexes: &mut Vec<u32>,
+        ) -> std::result::Result<(), ErrorType> {
+            structural_indexes.try_reserve(64).map_err(|_| {
+                let len = structural_indexes.len() + 64;
+                ErrorType::AllocationFailed(len.saturating_mul(mem::size_of::<u32>()))
+            })
+        }
+
+        let len = input.len();
+        if FALLIBLE {
+            try_reserve_chunk(&mut structural_indexes)?;
+        }
         structural_indexes.push(0); // push extra root element
 
         let mut utf8_validator = ChunkedUtf8ValidatorImp::new();
@@ -595,6 +1244,9 @@ impl<'de> Deserializer<'de> {
 
             // take the previous iterations structural bits, not our current iteration,
             // and flatten
+            if FALLIBLE {
+                try_reserve_chunk(&mut structural_indexes)?;
+            }
             #[allow(clippy::cast_possible_truncation)]
             SimdInput::flatten_bits(&mut structural_indexes, idx as u32, structurals);
 
@@ -640,6 +1292,9 @@ impl<'de> Deserializer<'de> {
 
             // take the previous iterations structural bits, not our current iteration,
             // and flatten
+            if FALLIBLE {
+                try_reserve_chunk(&mut structural_indexes)?;
+            }
             SimdInput::flatten_bits(&mut structural_indexes, idx as u32, structurals);
 
             let mut whitespace: u64 = 0;
@@ -660,6 +1315,9 @@ impl<'de> Deserializer<'de> {
             return Err(ErrorType::Syntax);
         }
         // finally, flatten out the remaining structurals from the last iteration
+        if FALLIBLE {
+            try_reserve_chunk(&mut structural_indexes)?;
+        }
         SimdInput::flatten_bits(&mut structural_indexes, idx as u32, structurals);
 
         // a valid JSON file cannot have zero structural indexes - we should have
@@ -722,6 +1380,27 @@ impl AlignedBuf {
         }
     }
 
+    /// Creates a new aligned buffer like `with_capacity`, but fails with
+    /// `ErrorType::AllocationFailed` instead of aborting if the memory
+    /// can't be allocated
+    pub(crate) fn try_with_capacity(capacity: usize) -> Result<Self> {
+        let failed = || Error::generic(ErrorType::AllocationFailed(capacity));
+        let layout = match Layout::from_size_align(capacity, SIMDJSON_PADDING / 2) {
+            Ok(layout) => layout,
+            Err(_) => return Err(failed()),
+        };
+        if mem::size_of::<usize>() < 8 && capacity > isize::MAX as usize {
+            return Err(failed());
+        }
+        let inner = stry!(NonNull::new(unsafe { alloc(layout) }).ok_or_else(failed));
+        Ok(Self {
+            layout,
+            capacity,
+            len: 0,
+            inner,
+        })
+    }
+
     fn as_mut_ptr(&mut self) -> *mut u8 {
         self.inner.as_ptr()
     }
@@ -796,6 +1475,197 @@ mod tests {
         let _: TestStruct<_> = TestStruct(super::AlignedBuf::with_capacity(0));
     }
 
+    #[test]
+    fn estimate_tape_size() {
+        use super::{estimate_tape_size, to_tape};
+        for d in [
+            &br#"1"#[..],
+            br#"  "a"  "#,
+            br#"[]"#,
+            br#"{}"#,
+            br#"[1,-2.5e3,true,false,null]"#,
+            br#"{"a": {"b": [1, {"c": "d"}]}, "e": []}"#,
+            br#"["\"", "{[,:]}", "\\"]"#,
+        ] {
+            let mut d = d.to_vec();
+            let estimate = estimate_tape_size(&d);
+            assert_eq!(estimate, to_tape(&mut d).expect("valid json").len());
+        }
+    }
+
+    #[test]
+    fn from_segments() {
+        use super::Deserializer;
+        use crate::{json, ErrorType};
+        let mut scratch = Vec::with_capacity(4);
+        // a segment boundary in the middle of a `\u` escape
+        let segments: [&[u8]; 5] = [b"", br#"{"k\u00"#, b"", br#"e9": ["x\u00"#, br#"e9"]}"#];
+        let de = Deserializer::from_segments(&segments, &mut scratch).expect("valid json");
+        assert_eq!(de.into_owned_value(), json!({"k\u{e9}": ["x\u{e9}"]}));
+        assert!(scratch.capacity() >= segments.iter().map(|s| s.len()).sum());
+
+        let err = |segments: &[&[u8]]| match Deserializer::from_segments(segments, &mut Vec::new())
+        {
+            Ok(_) => panic!("invalid json"),
+            Err(e) => e,
+        };
+        assert_eq!(err(&[b"", b""]).error(), &ErrorType::Eof);
+        assert_ne!(err(&[b"[1", b"2"]).error(), &ErrorType::Eof);
+
+        let mut a = br#"[1, "\u00"#.to_vec();
+        let mut b = br#"e9"]"#.to_vec();
+        let mut segments = [&mut a[..], &mut b[..]];
+        let de = Deserializer::from_segments_mut(&mut segments, &mut scratch).expect("valid json");
+        assert_eq!(de.into_owned_value(), json!([1, "\u{e9}"]));
+    }
+
+    #[test]
+    fn from_segments_mut_in_place() {
+        use super::{Deserializer, Node};
+        let mut input = br#"["abc"]"#.to_vec();
+        let range = input.as_ptr_range();
+        let mut empty: [u8; 0] = [];
+        let mut scratch = Vec::new();
+        let mut segments = [&mut empty[..], &mut input[..], &mut []];
+        let de = Deserializer::from_segments_mut(&mut segments, &mut scratch).expect("valid json");
+        assert!(segments.iter().all(|s| s.is_empty()));
+        match de.into_tape()[2] {
+            Node::String(s) => {
+                assert_eq!(s, "abc");
+                assert!(range.contains(&s.as_ptr()));
+            }
+            n => panic!("expected a string, got {n:?}"),
+        }
+        assert_eq!(scratch.capacity(), 0);
+    }
+
+    #[test]
+    fn first_document_end() {
+        use super::{first_document_end, ErrorType};
+        let cases: [(&[u8], usize); 9] = [
+            (b"1", 1),
+            (b"  -1.5e3 2", 8),
+            (b"true,", 4),
+            (br#""a\"b" "c""#, 6),
+            (br#""a]" "#, 4),
+            (b"[]", 2),
+            (br#"{"a": {"b": [1, "]"]}}{}"#, 22),
+            (b"\n[[],[]] ]", 8),
+            ("[\"é\"]".as_bytes(), 6),
+        ];
+        for (d, end) in cases {
+            assert_eq!(
+                first_document_end(d).expect("a document"),
+                end,
+                "{}",
+                String::from_utf8_lossy(d)
+            );
+        }
+        let error = |d: &[u8]| first_document_end(d).expect_err("no document");
+        assert_eq!(
+            first_document_end(b"{\"a\": [1}").map_err(|e| e.index()),
+            Err(0)
+        );
+        assert!(matches!(error(b"").error(), ErrorType::Eof));
+        assert!(matches!(error(b"  ").error(), ErrorType::Eof));
+        assert!(matches!(error(b"[[]").error(), ErrorType::Eof));
+        assert!(matches!(
+            error(b"]").error(),
+            ErrorType::UnexpectedCharacter
+        ));
+        assert!(matches!(
+            error(b", 1").error(),
+            ErrorType::UnexpectedCharacter
+        ));
+        assert!(matches!(error(br#"[1] "open"#).error(), ErrorType::Syntax));
+        assert!(matches!(error(b"[\xff]").error(), ErrorType::InvalidUtf8));
+    }
+
+    #[test]
+    fn tape_node_count() {
+        use super::{tape_node_count, to_tape, ErrorType};
+        for d in [
+            &br#"1"#[..],
+            br#"  "a"  "#,
+            br#"[]"#,
+            br#"{}"#,
+            br#"[1,-2.5e3,true,false,null]"#,
+            br#"{"a": {"b": [1, {"c": "d"}]}, "e": []}"#,
+            br#"["\"", "{[,:]}", "\\"]"#,
+        ] {
+            let mut d = d.to_vec();
+            let count = tape_node_count(&d).expect("valid json");
+            assert_eq!(count, to_tape(&mut d).expect("valid json").len());
+        }
+        let large = format!("[{}]", vec![r#"{"a": [1, "b"]}"#; 1000].join(","));
+        let count = tape_node_count(large.as_bytes()).expect("valid json");
+        assert_eq!(count, 1 + 1 + 1000 * 5);
+        assert!(tape_node_count(b"").is_err());
+        assert!(tape_node_count(br#"["abc]"#).is_err());
+        assert_eq!(
+            tape_node_count(b"[\xff]")
+                .expect_err("invalid utf8")
+                .error(),
+            &ErrorType::InvalidUtf8
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn parse_number_str() {
+        use super::{parse_number_str, StaticNode};
+        assert_eq!(parse_number_str("0"), Ok(StaticNode::I64(0)));
+        assert_eq!(parse_number_str("-42"), Ok(StaticNode::I64(-42)));
+        assert_eq!(
+            parse_number_str("18446744073709551615"),
+            Ok(StaticNode::U64(u64::MAX))
+        );
+        assert_eq!(parse_number_str("\t1e3\n"), Ok(StaticNode::F64(1000.0)));
+        assert_eq!(parse_number_str("0.25"), Ok(StaticNode::F64(0.25)));
+        assert!(parse_number_str("").is_err());
+        assert!(parse_number_str("-").is_err());
+        assert!(parse_number_str("01").is_err());
+        assert!(parse_number_str("1 2").is_err());
+        assert!(parse_number_str("1,2").is_err());
+        assert!(parse_number_str("+1").is_err());
+        assert!(parse_number_str("1.").is_err());
+    }
+
+    #[test]
+    fn encode_string_to_json() {
+        let encode = |s: &str| {
+            let mut out = b"prefix ".to_vec();
+            super::encode_string_to_json(s, &mut out);
+            String::from_utf8(out).expect("valid utf8")
+        };
+        assert_eq!(encode(""), r#"prefix """#);
+        assert_eq!(encode("plain"), r#"prefix "plain""#);
+        assert_eq!(encode("a\"b\\c/"), r#"prefix "a\"b\\c/""#);
+        assert_eq!(
+            encode("\n\r\t\u{8}\u{c}\u{1}"),
+            r#"prefix "\n\r\t\b\f\u0001""#
+        );
+        assert_eq!(
+            encode("caf\u{e9} \u{1f980}"),
+            "prefix \"caf\u{e9} \u{1f980}\""
+        );
+        let long = "0123456789abcdef\"".repeat(8);
+        let mut d = encode(&long).into_bytes().split_off(7);
+        assert_eq!(to_owned_value(&mut d).expect("valid json"), long.as_str());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn compiled_simd_backend() {
+        let backend = super::compiled_simd_backend();
+        assert_eq!(backend, super::active_simd_backend());
+        #[cfg(target_feature = "avx2")]
+        assert_eq!(backend, "avx2");
+        #[cfg(all(target_feature = "sse4.2", not(target_feature = "avx2")))]
+        assert_eq!(backend, "sse4.2");
+        assert!(["avx2", "sse4.2", "neon", "wasm-simd128", "fallback"].contains(&backend));
+    }
+
     #[test]
     fn count1() {
         let mut d = String::from("[]");
@@ -838,6 +1708,32 @@ mod tests {
         assert_eq!(simd.tape[2], Node::Array(0, 3));
     }
 
+    #[test]
+    fn into_owned_value() {
+        let mut d = br#"{"a": [1, "b"], "c": null}"#.to_vec();
+        let mut simd = Deserializer::from_slice(&mut d).expect("");
+        // the value is built from the start even after reading from the tape
+        unsafe {
+            simd.next_();
+            simd.next_();
+        }
+        assert_eq!(
+            simd.into_owned_value(),
+            crate::json!({"a": [1, "b"], "c": null})
+        );
+    }
+
+    #[test]
+    fn json_string_byte_len() {
+        let mut d = br#"{"key": "a\tb", "e": ""}"#.to_vec();
+        let simd = Deserializer::from_slice(&mut d).expect("");
+        assert_eq!(simd.json_string_byte_len(1), None);
+        assert_eq!(simd.json_string_byte_len(2), Some(3));
+        assert_eq!(simd.json_string_byte_len(3), Some(3));
+        assert_eq!(simd.json_string_byte_len(5), Some(0));
+        assert_eq!(simd.json_string_byte_len(6), None);
+    }
+
     #[cfg(feature = "128bit")]
     #[test]
     fn odd_nuber() {
@@ -999,6 +1895,24 @@ mod tests_serde {
 
     use value_trait::{Builder, Mutable, StaticNode};
 
+    #[test]
+    fn root_reexports() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            id: u64,
+        }
+        let mut d = br#"{"id": 1}"#.to_vec();
+        let row: Row = crate::from_slice(&mut d).expect("valid json");
+        assert_eq!(row, Row { id: 1 });
+        let mut s = String::from(r#"{"id": 2}"#);
+        let row: Row = unsafe { crate::from_str(&mut s) }.expect("valid json");
+        assert_eq!(row, Row { id: 2 });
+        let row: Row = crate::from_reader(&br#"{"id": 3}"#[..]).expect("valid json");
+        assert_eq!(row, Row { id: 3 });
+        assert_eq!(crate::to_string(&[1, 2]).expect("serializable"), "[1,2]");
+        assert_eq!(crate::to_vec(&"a").expect("serializable"), b"\"a\"");
+    }
+
     #[test]
     fn empty() {
         let mut d = String::new();
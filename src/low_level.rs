@@ -0,0 +1,136 @@
+//! Character classification and encoding helpers used by the parser, for
+//! building tokenizers for JSON-like formats.
+//!
+//! The functions in this module are a stable subset of the parser
+//! internals: their results for every input are covered by semver, so
+//! they don't change in minor releases even if the parser's own tables
+//! do. They are wrappers around the lookup tables the parser uses and are
+//! inlined.
+//!
+//! ```rust
+//! use simd_json::low_level::{codepoint_to_utf8, hex_to_u32, is_structural_or_whitespace};
+//! assert!(is_structural_or_whitespace(b','));
+//! assert!(!is_structural_or_whitespace(b'a'));
+//! let cp = hex_to_u32(b"00e9").unwrap();
+//! let mut buf = [0; 4];
+//! let len = codepoint_to_utf8(cp, &mut buf).unwrap();
+//! assert_eq!(&buf[..len], "é".as_bytes());
+//! ```
+
+use crate::charutils;
+
+/// Returns true for the bytes that end an atom such as a number or a
+/// literal: the structural characters `{`, `}`, `[`, `]`, `:` and `,`,
+/// the JSON whitespace ` `, `\t`, `\n` and `\r`, and `\0`, which the
+/// parser uses as padding after the input.
+#[inline]
+#[must_use]
+pub fn is_structural_or_whitespace(c: u8) -> bool {
+    charutils::is_structural_or_whitespace(c) != 0
+}
+
+/// The negation of `is_structural_or_whitespace`.
+#[inline]
+#[must_use]
+pub fn is_not_structural_or_whitespace(c: u8) -> bool {
+    charutils::is_not_structural_or_whitespace(c) != 0
+}
+
+/// Returns true for the structural characters `{`, `}`, `[`, `]`, `:`
+/// and `,`.
+#[inline]
+#[must_use]
+pub fn is_structural(c: u8) -> bool {
+    matches!(c, b'{' | b'}' | b'[' | b']' | b':' | b',')
+}
+
+/// Returns true for JSON whitespace: ` `, `\t`, `\n` and `\r`.
+#[inline]
+#[must_use]
+pub fn is_whitespace(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Decodes the four hex digits of a `\u` escape, upper and lower case
+/// digits are accepted. Returns `None` if any of the bytes is not a hex
+/// digit.
+#[inline]
+#[must_use]
+pub fn hex_to_u32(digits: &[u8; 4]) -> Option<u32> {
+    let v = charutils::hex_to_u32_nocheck(digits);
+    // an invalid digit sets the high 16 bits
+    if v > 0xFFFF {
+        None
+    } else {
+        Some(v)
+    }
+}
+
+/// Writes the UTF-8 encoding of a code point to `out` and returns its
+/// length. Returns `None` for surrogates, which have to be combined into
+/// a code point first, and for values above `0x10FFFF`.
+#[inline]
+#[must_use]
+pub fn codepoint_to_utf8(cp: u32, out: &mut [u8; 4]) -> Option<usize> {
+    if (0xD800..=0xDFFF).contains(&cp) {
+        return None;
+    }
+    match charutils::codepoint_to_utf8(cp, out) {
+        0 => None,
+        len => Some(len),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        codepoint_to_utf8, hex_to_u32, is_not_structural_or_whitespace, is_structural,
+        is_structural_or_whitespace, is_whitespace,
+    };
+
+    #[test]
+    fn classification() {
+        for c in 0..=u8::MAX {
+            let expected = b"{}[]:, \t\n\r\0".contains(&c);
+            assert_eq!(is_structural_or_whitespace(c), expected, "{c}");
+            assert_eq!(is_not_structural_or_whitespace(c), !expected, "{c}");
+            assert_eq!(is_structural(c), b"{}[]:,".contains(&c), "{c}");
+            assert_eq!(is_whitespace(c), b" \t\n\r".contains(&c), "{c}");
+        }
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(hex_to_u32(b"0000"), Some(0));
+        assert_eq!(hex_to_u32(b"ffff"), Some(0xFFFF));
+        assert_eq!(hex_to_u32(b"FfFf"), Some(0xFFFF));
+        assert_eq!(hex_to_u32(b"12aB"), Some(0x12AB));
+        for i in 0..=0xFFFF_u32 {
+            let digits = format!("{i:04x}");
+            let digits: &[u8; 4] = digits.as_bytes().try_into().expect("four digits");
+            assert_eq!(hex_to_u32(digits), Some(i));
+        }
+        for c in (0..=u8::MAX).filter(|c| !c.is_ascii_hexdigit()) {
+            for pos in 0..4 {
+                let mut digits = *b"0000";
+                digits[pos] = c;
+                assert_eq!(hex_to_u32(&digits), None, "{c} at {pos}");
+            }
+        }
+    }
+
+    #[test]
+    fn utf8() {
+        let mut out = [0; 4];
+        for c in (0..=u32::from(char::MAX)).filter_map(char::from_u32) {
+            let mut expected = [0; 4];
+            let expected = c.encode_utf8(&mut expected).as_bytes();
+            let len = codepoint_to_utf8(u32::from(c), &mut out).expect("a valid code point");
+            assert_eq!(&out[..len], expected);
+        }
+        assert_eq!(codepoint_to_utf8(0xD800, &mut out), None);
+        assert_eq!(codepoint_to_utf8(0xDFFF, &mut out), None);
+        assert_eq!(codepoint_to_utf8(0x11_0000, &mut out), None);
+        assert_eq!(codepoint_to_utf8(u32::MAX, &mut out), None);
+    }
+}
@@ -141,6 +141,11 @@ impl<'de> Deserializer<'de> {
             }
             _ => {}
         }
+        // numbers too long for the fast paths are parsed from the slice up
+        // to `idx`, so what follows has to be checked first
+        if is_structural_or_whitespace(get!(buf, idx)) == 0 {
+            err!(idx, get!(buf, idx))
+        }
         if is_float {
             if unlikely!(digit_count >= 19) {
                 let start_digits = get!(buf, start) as usize;
@@ -155,9 +160,6 @@ impl<'de> Deserializer<'de> {
                     );
                 }
             }
-            if is_structural_or_whitespace(get!(buf, idx)) == 0 {
-                err!(idx, get!(buf, idx))
-            }
             f64_from_parts(
                 !negative,
                 num,
@@ -167,8 +169,6 @@ impl<'de> Deserializer<'de> {
             )
         } else if unlikely!(digit_count >= 18) {
             parse_large_integer(start_idx, buf, negative)
-        } else if is_structural_or_whitespace(get!(buf, idx)) == 0 {
-            err!(idx, get!(buf, idx))
         } else {
             Ok(StaticNode::I64(if negative {
                 unsafe { static_cast_i64!(num.wrapping_neg()) } // -(num as i64)
@@ -528,6 +528,19 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn long_numbers_with_trailing_garbage() {
+        for n in [
+            "1234567890123456789x",
+            "-1234567890123456789x",
+            "12345678901234567890123.5x",
+            "1.2345678901234567890123e5x",
+            "[1234567890123456789x]",
+        ] {
+            assert!(to_value_from_str(n).is_err(), "{n}");
+        }
+    }
+
     #[test]
     fn minus_309() -> Result<(), crate::Error> {
         assert_eq!(
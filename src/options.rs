@@ -0,0 +1,715 @@
+use crate::{Error, ErrorType, Result};
+use value_trait::StaticNode;
+
+/// Options to configure the parser.
+///
+/// The default options are strict and match the behaviour of the plain
+/// parsing functions such as `to_owned_value` or `from_slice`, options
+/// can be adjusted using the builder methods:
+///
+/// ```rust
+/// use simd_json::ParseOptions;
+/// let opts = ParseOptions::strict().max_depth(64);
+/// let mut d = br#"[[1, 2], [3]]"#.to_vec();
+/// let v = simd_json::to_owned_value_with_opts(&mut d, opts).unwrap();
+/// assert_eq!(v[0][1], 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// The maximum nesting depth of arrays and objects, `None` means
+    /// the depth is not limited.
+    pub max_depth: Option<usize>,
+    /// The maximum length of the input in bytes.
+    pub max_input_len: Option<usize>,
+    /// The maximum total length of all strings, including object keys,
+    /// after unescaping.
+    pub max_string_bytes: Option<usize>,
+    /// The maximum length of a single string, including object keys,
+    /// after unescaping.
+    pub max_string_len: Option<usize>,
+    /// The maximum number of nodes on the tape, one per string, number,
+    /// literal, array and object.
+    pub max_tape_nodes: Option<usize>,
+    /// The maximum number of entries of a single object.
+    pub max_object_entries: Option<usize>,
+    /// The maximum number of elements of a single array.
+    pub max_array_len: Option<usize>,
+    /// How object keys are matched to struct fields when deserializing
+    /// with serde.
+    pub field_matching: FieldMatching,
+    /// How the DOM builders handle duplicate keys in objects.
+    pub duplicate_keys: DuplicateKeys,
+    /// If `null` is deserialized as the default value of non `Option`
+    /// types when deserializing with serde, see
+    /// [`null_as_default`](Self::null_as_default).
+    pub null_as_default: bool,
+    /// Which numeric types numbers are parsed into.
+    pub number_mode: NumberMode,
+}
+
+/// How object keys are matched to struct fields when deserializing
+/// structs with serde. Keys that match a field exactly always win, the
+/// lenient modes only apply to keys that don't.
+///
+/// With a lenient mode, structs with two fields that are the same after
+/// normalization fail to deserialize.
+///
+/// ```rust
+/// use simd_json::{FieldMatching, ParseOptions};
+/// #[derive(serde::Deserialize)]
+/// struct User {
+///     user_name: String,
+/// }
+/// let opts = ParseOptions::strict().field_matching(FieldMatching::SnakeCamelAgnostic);
+/// let mut d = br#"{"UserName": "ferris"}"#.to_vec();
+/// let u: User = simd_json::from_slice_with_opts(&mut d, opts).unwrap();
+/// assert_eq!(u.user_name, "ferris");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldMatching {
+    /// Keys have to match field names exactly, this is the default.
+    Exact,
+    /// Keys and field names are compared ignoring ASCII case.
+    CaseInsensitive,
+    /// Keys and field names are compared ignoring ASCII case and
+    /// underscores, so `user_name`, `userName` and `UserName` match.
+    SnakeCamelAgnostic,
+}
+
+impl Default for FieldMatching {
+    fn default() -> Self {
+        Self::Exact
+    }
+}
+
+/// How `to_owned_value_with_opts` and `to_borrowed_value_with_opts`
+/// handle keys that appear more than once in an object. Deserializing
+/// with serde is not affected.
+///
+/// ```rust
+/// use simd_json::{json, DuplicateKeys, ParseOptions};
+/// let opts = ParseOptions::strict().duplicate_keys(DuplicateKeys::MergeObjects);
+/// let mut d = br#"{"a": {"x": 1}, "a": {"y": 2}}"#.to_vec();
+/// let v = simd_json::to_owned_value_with_opts(&mut d, opts).unwrap();
+/// assert_eq!(v, json!({"a": {"x": 1, "y": 2}}));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keys are not checked, this is the default and the fastest. Objects
+    /// with duplicate keys hold all of them, with the `value-no-dup-keys`
+    /// feature the last value wins.
+    Unchecked,
+    /// The first value of a key is kept.
+    FirstWins,
+    /// The last value of a key is kept, at the position of the first one.
+    LastWins,
+    /// Object values of a duplicate key are merged recursively, for any
+    /// other values, including objects within the merged objects, the
+    /// last value wins. Merged keys keep the position of their first
+    /// occurrence.
+    MergeObjects,
+}
+
+impl Default for DuplicateKeys {
+    fn default() -> Self {
+        Self::Unchecked
+    }
+}
+
+/// Which numeric types numbers are parsed into. This applies to every
+/// way of parsing, the DOM values as well as serde.
+///
+/// There is no arbitrary precision mode, values have no representation
+/// for the raw text of a number. Integers are limited to 64 bits, or 128
+/// bits with the `128bit` feature, and larger integers fail to parse in
+/// every mode.
+///
+/// For the same reason numbers are always converted while parsing, even
+/// if they are never read. Deferring the conversion to the first access
+/// would need a variant holding the unconverted number in both value
+/// types, which every match on a value would have to handle.
+///
+/// ```rust
+/// use simd_json::{NumberMode, ParseOptions};
+/// use simd_json::prelude::*;
+/// let opts = ParseOptions::strict().number_mode(NumberMode::AlwaysFloat);
+/// let mut d = br#"[1, -2, 3.5]"#.to_vec();
+/// let v = simd_json::to_owned_value_with_opts(&mut d, opts).unwrap();
+/// assert_eq!(v[0].as_f64(), Some(1.0));
+/// assert!(v[1].is_f64());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberMode {
+    /// Integers are parsed into the smallest fitting integer type and
+    /// numbers with a fraction or an exponent into `f64`, this is the
+    /// default.
+    Detect,
+    /// Every number is parsed into an `f64`, like JavaScript does.
+    /// Integers beyond 2^53 are rounded to the nearest `f64`.
+    AlwaysFloat,
+}
+
+impl Default for NumberMode {
+    fn default() -> Self {
+        Self::Detect
+    }
+}
+
+impl ParseOptions {
+    /// Checks the input length against `max_input_len`
+    pub(crate) fn check_input_len(&self, len: usize) -> Result<()> {
+        match self.max_input_len {
+            Some(limit) if len > limit => Err(Error::generic(ErrorType::InputLengthLimit {
+                limit,
+                actual: len,
+            })),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl NumberMode {
+    /// Converts a parsed number to the type of this mode
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn convert(self, n: StaticNode) -> StaticNode {
+        match (self, n) {
+            (Self::AlwaysFloat, StaticNode::I64(i)) => StaticNode::F64(i as f64),
+            (Self::AlwaysFloat, StaticNode::U64(u)) => StaticNode::F64(u as f64),
+            #[cfg(feature = "128bit")]
+            (Self::AlwaysFloat, StaticNode::I128(i)) => StaticNode::F64(i as f64),
+            #[cfg(feature = "128bit")]
+            (Self::AlwaysFloat, StaticNode::U128(u)) => StaticNode::F64(u as f64),
+            (_, n) => n,
+        }
+    }
+}
+
+#[cfg(feature = "serde_impl")]
+impl FieldMatching {
+    /// Tests if `key` matches `field` in this mode, that is if both have
+    /// the same `normalize`d form
+    pub(crate) fn matches(self, key: &str, field: &str) -> bool {
+        match self {
+            Self::Exact => key == field,
+            Self::CaseInsensitive => key.eq_ignore_ascii_case(field),
+            Self::SnakeCamelAgnostic => {
+                let mut key = key.bytes().filter(|c| *c != b'_');
+                let mut field = field.bytes().filter(|c| *c != b'_');
+                loop {
+                    match (key.next(), field.next()) {
+                        (None, None) => return true,
+                        (Some(k), Some(f)) if k.eq_ignore_ascii_case(&f) => (),
+                        _ => return false,
+                    }
+                }
+            }
+        }
+    }
+
+    /// The form of a name that is compared in this mode
+    pub(crate) fn normalize(self, name: &str) -> String {
+        match self {
+            Self::Exact => name.to_string(),
+            Self::CaseInsensitive => name.to_ascii_lowercase(),
+            Self::SnakeCamelAgnostic => name
+                .chars()
+                .filter(|c| *c != '_')
+                .map(|c| c.to_ascii_lowercase())
+                .collect(),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Strict RFC 8259 parsing without any limits, this is the default.
+    #[must_use]
+    pub const fn strict() -> Self {
+        Self {
+            max_depth: None,
+            max_input_len: None,
+            max_string_bytes: None,
+            max_string_len: None,
+            max_tape_nodes: None,
+            max_object_entries: None,
+            max_array_len: None,
+            field_matching: FieldMatching::Exact,
+            duplicate_keys: DuplicateKeys::Unchecked,
+            null_as_default: false,
+            number_mode: NumberMode::Detect,
+        }
+    }
+
+    /// A profile for untrusted input that bounds every resource a
+    /// document can consume, with limits derived from the largest
+    /// accepted input:
+    ///
+    /// | limit                | value                     | error                |
+    /// |----------------------|---------------------------|----------------------|
+    /// | `max_input_len`      | `max_input_len`           | `InputLengthLimit`   |
+    /// | `max_depth`          | 64                        | `DepthLimit`         |
+    /// | `max_string_bytes`   | `max_input_len / 2`       | `StringBytesLimit`   |
+    /// | `max_tape_nodes`     | `max_input_len / 8`       | `TapeNodesLimit`     |
+    /// | `max_object_entries` | `max_input_len / 64`      | `ObjectEntriesLimit` |
+    /// | `max_array_len`      | `max_input_len / 16`      | `ArrayLengthLimit`   |
+    ///
+    /// The node, entry and length limits are at least 16. Inputs that
+    /// are over the length limit are rejected before anything is
+    /// allocated, all other limits are checked while the tape is built
+    /// and parsing stops at the first violation. Each limit can be
+    /// adjusted with its builder afterwards.
+    ///
+    /// ```rust
+    /// use simd_json::{ErrorType, ParseOptions};
+    /// let opts = ParseOptions::hardened(1024);
+    /// let mut d = format!("[{}]", "[".repeat(100)).into_bytes();
+    /// let e = simd_json::to_owned_value_with_opts(&mut d, opts).unwrap_err();
+    /// assert_eq!(e.error(), &ErrorType::DepthLimit { limit: 64, actual: 65 });
+    ///
+    /// let mut d = vec![b' '; 2048];
+    /// let e = simd_json::to_owned_value_with_opts(&mut d, opts).unwrap_err();
+    /// assert_eq!(e.error(), &ErrorType::InputLengthLimit { limit: 1024, actual: 2048 });
+    /// ```
+    #[must_use]
+    pub const fn hardened(max_input_len: usize) -> Self {
+        const fn at_least_16(n: usize) -> usize {
+            if n < 16 {
+                16
+            } else {
+                n
+            }
+        }
+        Self::strict()
+            .max_depth(64)
+            .max_input_len(max_input_len)
+            .max_string_bytes(max_input_len / 2)
+            .max_tape_nodes(at_least_16(max_input_len / 8))
+            .max_object_entries(at_least_16(max_input_len / 64))
+            .max_array_len(at_least_16(max_input_len / 16))
+    }
+
+    /// Limits the nesting depth of arrays and objects, documents that
+    /// nest deeper fail with `ErrorType::DepthLimit`.
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Limits the length of the input in bytes, longer inputs fail with
+    /// `ErrorType::InputLengthLimit` before anything is allocated.
+    #[must_use]
+    pub const fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.max_input_len = Some(max_input_len);
+        self
+    }
+
+    /// Limits the total length of all strings after unescaping, documents
+    /// with more string data fail with `ErrorType::StringBytesLimit`.
+    #[must_use]
+    pub const fn max_string_bytes(mut self, max_string_bytes: usize) -> Self {
+        self.max_string_bytes = Some(max_string_bytes);
+        self
+    }
+
+    /// Limits the length of every single string after unescaping, longer
+    /// strings fail with `ErrorType::StringLengthLimit` at the offset of
+    /// the string. Strings are unescaped in place, so the limit fires
+    /// before a value for the string is allocated.
+    #[must_use]
+    pub const fn max_string_len(mut self, max_string_len: usize) -> Self {
+        self.max_string_len = Some(max_string_len);
+        self
+    }
+
+    /// Limits the number of tape nodes, documents with more values fail
+    /// with `ErrorType::TapeNodesLimit`. This bounds the size of the tape
+    /// and of the values built from it.
+    #[must_use]
+    pub const fn max_tape_nodes(mut self, max_tape_nodes: usize) -> Self {
+        self.max_tape_nodes = Some(max_tape_nodes);
+        self
+    }
+
+    /// Limits the number of entries of each object, larger objects fail
+    /// with `ErrorType::ObjectEntriesLimit`.
+    #[must_use]
+    pub const fn max_object_entries(mut self, max_object_entries: usize) -> Self {
+        self.max_object_entries = Some(max_object_entries);
+        self
+    }
+
+    /// Limits the number of elements of each array, longer arrays fail
+    /// with `ErrorType::ArrayLengthLimit`.
+    #[must_use]
+    pub const fn max_array_len(mut self, max_array_len: usize) -> Self {
+        self.max_array_len = Some(max_array_len);
+        self
+    }
+
+    /// Sets how object keys are matched to struct fields when
+    /// deserializing with serde.
+    #[must_use]
+    pub const fn field_matching(mut self, field_matching: FieldMatching) -> Self {
+        self.field_matching = field_matching;
+        self
+    }
+
+    /// Sets how the DOM builders handle duplicate object keys.
+    #[must_use]
+    pub const fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Deserializes `null` as the default value when deserializing
+    /// booleans, numbers, strings, sequences, maps and structs with serde:
+    /// `false`, `0`, `""` and empty sequences and maps. `Option`s still
+    /// deserialize `null` as `None`. Defaults to `false`.
+    ///
+    /// Types that don't say what they expect, like halfbrown's
+    /// `HashMap` or `serde_json::Value`, get an empty map for `null`.
+    ///
+    /// This is lossy, a `null` can no longer be told apart from an
+    /// actual zero or empty value.
+    ///
+    /// ```rust
+    /// use simd_json::ParseOptions;
+    /// #[derive(serde::Deserialize)]
+    /// struct Stats {
+    ///     count: u32,
+    ///     label: Option<String>,
+    /// }
+    /// let opts = ParseOptions::strict().null_as_default(true);
+    /// let mut d = br#"{"count": null, "label": null}"#.to_vec();
+    /// let s: Stats = simd_json::from_slice_with_opts(&mut d, opts).unwrap();
+    /// assert_eq!(s.count, 0);
+    /// assert_eq!(s.label, None);
+    /// ```
+    #[must_use]
+    pub const fn null_as_default(mut self, null_as_default: bool) -> Self {
+        self.null_as_default = null_as_default;
+        self
+    }
+
+    /// Sets which numeric types numbers are parsed into.
+    #[must_use]
+    pub const fn number_mode(mut self, number_mode: NumberMode) -> Self {
+        self.number_mode = number_mode;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ParseOptions;
+    use crate::{to_borrowed_value_with_opts, to_owned_value_with_opts, ErrorType};
+    use value_trait::ValueAccess;
+
+    #[test]
+    fn defaults_are_strict() {
+        assert_eq!(ParseOptions::default(), ParseOptions::strict());
+        assert_eq!(ParseOptions::default().max_depth, None);
+    }
+
+    #[test]
+    fn default_matches_plain_parsing() {
+        let inputs: [&[u8]; 6] = [
+            br#"{"a": [1, 2.5, "three", null, true]}"#,
+            br#"[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[[1]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]]"#,
+            b"[1,]",
+            b"{\"a\" 1}",
+            b"01",
+            b"[1, 2] 3",
+        ];
+        for input in inputs {
+            let mut d1 = input.to_vec();
+            let mut d2 = input.to_vec();
+            assert_eq!(
+                crate::to_owned_value(&mut d1),
+                to_owned_value_with_opts(&mut d2, ParseOptions::default())
+            );
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let limited = |opts: ParseOptions| {
+            move |input: &str| {
+                let mut d = input.as_bytes().to_vec();
+                match to_owned_value_with_opts(&mut d, opts) {
+                    Ok(_) => None,
+                    Err(e) => Some(format!("{:?}", e.error())),
+                }
+            }
+        };
+
+        let arrays = limited(ParseOptions::strict().max_array_len(2));
+        assert_eq!(arrays("[1, 2]"), None);
+        assert_eq!(arrays(r#"{"a": 1, "b": 2, "c": 3}"#), None);
+        assert_eq!(
+            arrays("[[1, 2], [1, 2, 3]]"),
+            Some("ArrayLengthLimit { limit: 2, actual: 3 }".to_string())
+        );
+
+        let objects = limited(ParseOptions::strict().max_object_entries(1));
+        assert_eq!(objects(r#"[{"a": 1}, 2, 3]"#), None);
+        assert_eq!(
+            objects(r#"{"a": {}, "b": 2}"#),
+            Some("ObjectEntriesLimit { limit: 1, actual: 2 }".to_string())
+        );
+
+        let empty = limited(
+            ParseOptions::strict()
+                .max_array_len(0)
+                .max_object_entries(0),
+        );
+        assert_eq!(empty("[]"), None);
+        assert_eq!(empty("{}"), None);
+        assert_eq!(
+            empty("[1]"),
+            Some("ArrayLengthLimit { limit: 0, actual: 1 }".to_string())
+        );
+        assert_eq!(
+            empty(r#"{"a": 1}"#),
+            Some("ObjectEntriesLimit { limit: 0, actual: 1 }".to_string())
+        );
+
+        let nodes = limited(ParseOptions::strict().max_tape_nodes(3));
+        assert_eq!(nodes("[1, 2]"), None);
+        assert_eq!(nodes("\"s\""), None);
+        assert_eq!(
+            nodes("[1, [2]]"),
+            Some("TapeNodesLimit { limit: 3, actual: 4 }".to_string())
+        );
+
+        // counted after unescaping, keys included
+        let strings = limited(ParseOptions::strict().max_string_bytes(4));
+        assert_eq!(strings(r#"{"a": "\u00e9\t"}"#), None);
+        assert_eq!(
+            strings(r#"{"ab": "cde"}"#),
+            Some("StringBytesLimit { limit: 4, actual: 5 }".to_string())
+        );
+
+        let string = limited(ParseOptions::strict().max_string_len(2));
+        assert_eq!(string(r#"{"ab": ["\u00e9", "cd"]}"#), None);
+        assert_eq!(
+            string(r#"{"ab": ["cd", "\\\\\\"]}"#),
+            Some("StringLengthLimit { limit: 2, actual: 3 }".to_string())
+        );
+        assert_eq!(
+            string(r#"{"abc": 1}"#),
+            Some("StringLengthLimit { limit: 2, actual: 3 }".to_string())
+        );
+        let mut d = br#"[1, "a", "abc"]"#.to_vec();
+        let e = to_owned_value_with_opts(&mut d, ParseOptions::strict().max_string_len(2))
+            .expect_err("too long");
+        assert_eq!(e.index(), 9);
+
+        let input = limited(ParseOptions::strict().max_input_len(4));
+        assert_eq!(input("[1] "), None);
+        assert_eq!(
+            input("[1]  "),
+            Some("InputLengthLimit { limit: 4, actual: 5 }".to_string())
+        );
+    }
+
+    #[test]
+    fn hardened() {
+        let opts = ParseOptions::hardened(1 << 20);
+        assert_eq!(opts.max_input_len, Some(1 << 20));
+        assert_eq!(opts.max_depth, Some(64));
+        assert_eq!(opts.max_string_bytes, Some(1 << 19));
+        assert_eq!(opts.max_tape_nodes, Some(1 << 17));
+        assert_eq!(opts.max_object_entries, Some(1 << 14));
+        assert_eq!(opts.max_array_len, Some(1 << 16));
+        let small = ParseOptions::hardened(100);
+        assert_eq!(small.max_tape_nodes, Some(16));
+        assert_eq!(small.max_object_entries, Some(16));
+        assert_eq!(small.max_array_len, Some(16));
+
+        let rows: Vec<_> = (0..1000)
+            .map(|i| format!(r#"{{"id": {i}, "name": "row {i}", "tags": ["a", "b"]}}"#))
+            .collect();
+        let mut d = format!("[{}]", rows.join(",")).into_bytes();
+        let v = to_owned_value_with_opts(&mut d, opts).expect("valid json");
+        assert_eq!(v[999]["name"], "row 999");
+    }
+
+    #[test]
+    fn field_matching() {
+        use super::FieldMatching;
+        assert!(FieldMatching::Exact.matches("user_name", "user_name"));
+        assert!(!FieldMatching::Exact.matches("User_Name", "user_name"));
+        assert!(FieldMatching::CaseInsensitive.matches("User_Name", "user_name"));
+        assert!(!FieldMatching::CaseInsensitive.matches("UserName", "user_name"));
+        assert!(FieldMatching::SnakeCamelAgnostic.matches("UserName", "user_name"));
+        assert!(FieldMatching::SnakeCamelAgnostic.matches("userName", "user_name"));
+        assert!(FieldMatching::SnakeCamelAgnostic.matches("_user__name_", "username"));
+        assert!(!FieldMatching::SnakeCamelAgnostic.matches("user_names", "user_name"));
+        assert!(!FieldMatching::SnakeCamelAgnostic.matches("user", "user_name"));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        use super::DuplicateKeys;
+        use crate::json;
+        let input =
+            br#"{"a": {"x": 1, "n": {"p": 1}}, "b": 2, "a": {"y": 2, "x": 3, "n": {"q": 2}}}"#;
+        let parse = |duplicate_keys| {
+            let mut d = input.to_vec();
+            to_owned_value_with_opts(
+                &mut d,
+                ParseOptions::default().duplicate_keys(duplicate_keys),
+            )
+            .expect("valid json")
+        };
+        assert_eq!(
+            parse(DuplicateKeys::FirstWins),
+            json!({"a": {"x": 1, "n": {"p": 1}}, "b": 2})
+        );
+        assert_eq!(
+            parse(DuplicateKeys::LastWins),
+            json!({"a": {"y": 2, "x": 3, "n": {"q": 2}}, "b": 2})
+        );
+        let merged = parse(DuplicateKeys::MergeObjects);
+        assert_eq!(
+            merged,
+            json!({"a": {"x": 3, "n": {"p": 1, "q": 2}, "y": 2}, "b": 2})
+        );
+        // merged keys keep the position of their first occurrence
+        let keys: Vec<_> = merged["a"]
+            .as_object()
+            .expect("object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys, ["x", "n", "y"]);
+        let keys: Vec<_> = merged
+            .as_object()
+            .expect("object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys, ["a", "b"]);
+
+        // non object collisions and duplicates inside objects
+        let mut d =
+            br#"{"a": {"x": 1, "x": {"y": 1}}, "a": [1], "c": {"d": 1, "d": {"e": 1}}}"#.to_vec();
+        let opts = ParseOptions::default().duplicate_keys(DuplicateKeys::MergeObjects);
+        let v = to_borrowed_value_with_opts(&mut d, opts).expect("valid json");
+        assert_eq!(v, json!({"a": [1], "c": {"d": {"e": 1}}}));
+    }
+
+    #[test]
+    fn max_depth() {
+        let opts = ParseOptions::default().max_depth(2);
+        let mut d = br#"[{"a": 1}]"#.to_vec();
+        assert!(to_owned_value_with_opts(&mut d, opts).is_ok());
+        let mut d = br#"[{"a": [1]}]"#.to_vec();
+        let e = to_borrowed_value_with_opts(&mut d, opts).expect_err("depth limit");
+        assert_eq!(
+            e.error(),
+            &ErrorType::DepthLimit {
+                limit: 2,
+                actual: 3
+            }
+        );
+        let mut d = br#"[]"#.to_vec();
+        let e = to_owned_value_with_opts(&mut d, ParseOptions::default().max_depth(0))
+            .expect_err("depth limit");
+        assert_eq!(
+            e.error(),
+            &ErrorType::DepthLimit {
+                limit: 0,
+                actual: 1
+            }
+        );
+        let mut d = br#"42"#.to_vec();
+        assert!(to_owned_value_with_opts(&mut d, ParseOptions::default().max_depth(0)).is_ok());
+    }
+
+    #[test]
+    fn number_mode() {
+        use super::NumberMode;
+        use crate::OwnedValue;
+        use value_trait::StaticNode;
+        let parse = |input: &str, number_mode| {
+            let mut d = input.as_bytes().to_vec();
+            to_owned_value_with_opts(&mut d, ParseOptions::strict().number_mode(number_mode)).map(
+                |v| match v {
+                    OwnedValue::Array(a) => a
+                        .into_iter()
+                        .map(|v| match v {
+                            OwnedValue::Static(s) => s,
+                            _ => unreachable!(),
+                        })
+                        .collect::<Vec<_>>(),
+                    _ => unreachable!(),
+                },
+            )
+        };
+        let input = "[0, -1, 18446744073709551615, 2.5, 1e2, -0.0]";
+        assert_eq!(
+            parse(input, NumberMode::Detect).expect("valid json"),
+            [
+                StaticNode::I64(0),
+                StaticNode::I64(-1),
+                StaticNode::U64(u64::MAX),
+                StaticNode::F64(2.5),
+                StaticNode::F64(100.0),
+                StaticNode::F64(-0.0),
+            ]
+        );
+        assert_eq!(
+            parse(input, NumberMode::AlwaysFloat).expect("valid json"),
+            [
+                StaticNode::F64(0.0),
+                StaticNode::F64(-1.0),
+                StaticNode::F64(18_446_744_073_709_551_615.0),
+                StaticNode::F64(2.5),
+                StaticNode::F64(100.0),
+                StaticNode::F64(-0.0),
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde_impl")]
+    #[test]
+    fn field_matching_normalize() {
+        use super::FieldMatching;
+        let names = [
+            "user_name",
+            "userName",
+            "UserName",
+            "USER__NAME",
+            "user",
+            "\u{e9}_a",
+        ];
+        for mode in [
+            FieldMatching::Exact,
+            FieldMatching::CaseInsensitive,
+            FieldMatching::SnakeCamelAgnostic,
+        ] {
+            for a in names {
+                for b in names {
+                    assert_eq!(
+                        mode.matches(a, b),
+                        mode.normalize(a) == mode.normalize(b),
+                        "{mode:?} {a} {b}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde_impl")]
+    #[test]
+    fn number_mode_serde() {
+        use super::NumberMode;
+        let mut d = br#"[1, 2.0]"#.to_vec();
+        let opts = ParseOptions::strict().number_mode(NumberMode::AlwaysFloat);
+        let v: Vec<f32> = crate::from_slice_with_opts(&mut d, opts).expect("valid json");
+        assert_eq!(v, [1.0, 2.0]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Borrowed JSON pointers and their segments.
+//!
+//! A [`Pointer`] is a validated [RFC 6901](https://tools.ietf.org/html/rfc6901)
+//! pointer borrowing its string, it splits into typed [`Segment`]s so code
+//! working with pointers doesn't have to unescape and parse reference
+//! tokens itself. [`JsonPointer`] is the owned counterpart used by the
+//! value lookup functions, it can be borrowed as a `Pointer`.
+//!
+//! ```rust
+//! use simd_json::pointer::{Pointer, Segment};
+//! let p = Pointer::new("/users/0/a~1b").unwrap();
+//! let segments: Vec<Segment> = p.segments().collect();
+//! assert_eq!(
+//!     segments,
+//!     vec![Segment::Key("users".into()), Segment::Index(0), Segment::Key("a/b".into())]
+//! );
+//! ```
+
+pub use crate::value::JsonPointer;
+use crate::{Error, ErrorType, Result};
+use std::borrow::Cow;
+use std::fmt;
+
+/// A reference token of a pointer
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Segment<'a> {
+    /// A token that is not an array index, unescaped
+    Key(Cow<'a, str>),
+    /// A token that is an array index as defined by RFC 6901, a decimal
+    /// number without leading zeros. On objects it refers to the key with
+    /// the same digits.
+    Index(usize),
+}
+
+impl<'a> Segment<'a> {
+    fn from_token(token: &'a str) -> Self {
+        if let Some(idx) = crate::value::pointer::array_index(token) {
+            Self::Index(idx)
+        } else if token.contains('~') {
+            // `~1` has to be replaced first so `~01` becomes `~1`
+            Self::Key(Cow::Owned(token.replace("~1", "/").replace("~0", "~")))
+        } else {
+            Self::Key(Cow::Borrowed(token))
+        }
+    }
+
+    /// The segment as an object key, indexes are turned back into their
+    /// digits
+    #[must_use]
+    pub fn to_key(&self) -> Cow<'a, str> {
+        match self {
+            Self::Key(key) => key.clone(),
+            Self::Index(idx) => Cow::Owned(idx.to_string()),
+        }
+    }
+}
+
+/// A JSON pointer borrowing its string, the syntax is validated when it
+/// is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pointer<'a>(&'a str);
+
+impl<'a> Pointer<'a> {
+    /// Creates a pointer, validating its syntax. The empty string points
+    /// to the whole document.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the pointer doesn't start with `/` or
+    /// contains a `~` that is not followed by `0` or `1`.
+    pub fn new(s: &'a str) -> Result<Self> {
+        validate(s)?;
+        Ok(Self(s))
+    }
+
+    /// The pointer as a string, as it was passed to `new`
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// Iterates over the segments of the pointer, the pointer to the whole
+    /// document has none
+    pub fn segments(&self) -> impl Iterator<Item = Segment<'a>> {
+        self.0.split('/').skip(1).map(Segment::from_token)
+    }
+}
+
+impl<'a> From<&'a JsonPointer> for Pointer<'a> {
+    fn from(p: &'a JsonPointer) -> Self {
+        Self(p.as_str())
+    }
+}
+
+impl fmt::Display for Pointer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Checks the syntax of a pointer
+pub(crate) fn validate(s: &str) -> Result<()> {
+    let bytes = s.as_bytes();
+    if let Some(c) = bytes.first() {
+        if *c != b'/' {
+            return Err(Error::new_c(0, *c as char, ErrorType::InvalidPointer));
+        }
+    }
+    for (i, c) in bytes.iter().enumerate() {
+        if *c == b'~' && !matches!(bytes.get(i + 1), Some(b'0' | b'1')) {
+            return Err(Error::new_c(i, '~', ErrorType::InvalidPointer));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{JsonPointer, Pointer, Segment};
+    use crate::ErrorType;
+
+    fn segments(s: &str) -> Vec<Segment> {
+        Pointer::new(s).expect("valid pointer").segments().collect()
+    }
+
+    #[test]
+    fn segments_of_pointers() {
+        assert!(segments("").is_empty());
+        assert_eq!(segments("/"), vec![Segment::Key("".into())]);
+        assert_eq!(
+            segments("/a/0/10/01/-/~01"),
+            vec![
+                Segment::Key("a".into()),
+                Segment::Index(0),
+                Segment::Index(10),
+                Segment::Key("01".into()),
+                Segment::Key("-".into()),
+                Segment::Key("~1".into()),
+            ]
+        );
+        assert_eq!(
+            segments("/m~0n/a~1b"),
+            vec![Segment::Key("m~n".into()), Segment::Key("a/b".into())]
+        );
+        assert_eq!(Segment::Index(10).to_key(), "10");
+        assert_eq!(Segment::Key("a/b".into()).to_key(), "a/b");
+    }
+
+    #[test]
+    fn validation() {
+        let e = Pointer::new("a").expect_err("no leading slash");
+        assert_eq!((e.error(), e.index()), (&ErrorType::InvalidPointer, 0));
+        let e = Pointer::new("/a~2").expect_err("bad escape");
+        assert_eq!((e.error(), e.index()), (&ErrorType::InvalidPointer, 2));
+        let owned = JsonPointer::new("/a/1").expect("valid pointer");
+        let p = Pointer::from(&owned);
+        assert_eq!(p, Pointer::new("/a/1").expect("valid pointer"));
+        assert_eq!(p.to_string(), "/a/1");
+    }
+}
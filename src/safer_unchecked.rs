@@ -1,5 +1,8 @@
 use core::slice::SliceIndex;
 
+/// All unchecked slice accesses in the crate go through this trait so
+/// they can be turned into checked, panicking, accesses in debug builds
+/// or with the `paranoid-checks` feature.
 pub trait GetSaferUnchecked<T> {
     unsafe fn get_kinda_unchecked<I>(&self, index: I) -> &<I as SliceIndex<[T]>>::Output
     where
@@ -19,7 +22,7 @@ impl<T> GetSaferUnchecked<T> for [T] {
     where
         I: SliceIndex<[T]>,
     {
-        if cfg!(debug_assertions) {
+        if cfg!(any(debug_assertions, feature = "paranoid-checks")) {
             &self[index]
         } else {
             self.get_unchecked(index)
@@ -31,7 +34,7 @@ impl<T> GetSaferUnchecked<T> for [T] {
     where
         I: SliceIndex<[T]>,
     {
-        if cfg!(debug_assertions) {
+        if cfg!(any(debug_assertions, feature = "paranoid-checks")) {
             &mut self[index]
         } else {
             self.get_unchecked_mut(index)
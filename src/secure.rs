@@ -0,0 +1,232 @@
+use crate::{BorrowedValue, Deserializer, OwnedValue, ParseOptions, Result};
+
+/// A parser for untrusted input with every resource limit set, so server
+/// code can't forget one. The defaults are:
+///
+/// | limit              | default          |
+/// |--------------------|------------------|
+/// | `max_depth`        | 128              |
+/// | `max_input_len`    | 10 MiB           |
+/// | `max_string_bytes` | 1 MiB            |
+/// | `max_tape_nodes`   | 100 000          |
+///
+/// The string limit applies to the total length of all strings in a
+/// document, including object keys. Each limit can be adjusted with its
+/// builder, options that are not limits are the defaults of
+/// `ParseOptions`.
+///
+/// ```rust
+/// use simd_json::{ErrorType, SecureDeserializer};
+/// let de = SecureDeserializer::new().max_depth(2);
+/// let mut d = br#"{"a": [1, 2]}"#.to_vec();
+/// let v = de.to_owned_value(&mut d).unwrap();
+/// assert_eq!(v["a"][1], 2);
+/// let mut d = br#"{"a": [[1]]}"#.to_vec();
+/// let e = de.to_owned_value(&mut d).unwrap_err();
+/// assert_eq!(e.error(), &ErrorType::DepthLimit { limit: 2, actual: 3 });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecureDeserializer {
+    opts: ParseOptions,
+}
+
+impl Default for SecureDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecureDeserializer {
+    /// A parser with the default limits
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            opts: ParseOptions::strict()
+                .max_depth(128)
+                .max_input_len(10 << 20)
+                .max_string_bytes(1 << 20)
+                .max_tape_nodes(100_000),
+        }
+    }
+
+    /// Sets the maximum nesting depth of arrays and objects
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.opts = self.opts.max_depth(max_depth);
+        self
+    }
+
+    /// Sets the maximum length of the input in bytes
+    #[must_use]
+    pub const fn max_input_len(mut self, max_input_len: usize) -> Self {
+        self.opts = self.opts.max_input_len(max_input_len);
+        self
+    }
+
+    /// Sets the maximum total length of all strings after unescaping
+    #[must_use]
+    pub const fn max_string_bytes(mut self, max_string_bytes: usize) -> Self {
+        self.opts = self.opts.max_string_bytes(max_string_bytes);
+        self
+    }
+
+    /// Sets the maximum number of tape nodes
+    #[must_use]
+    pub const fn max_tape_nodes(mut self, max_tape_nodes: usize) -> Self {
+        self.opts = self.opts.max_tape_nodes(max_tape_nodes);
+        self
+    }
+
+    /// The options used for parsing
+    #[must_use]
+    pub const fn options(&self) -> ParseOptions {
+        self.opts
+    }
+
+    /// Parses a slice into a tape deserializer, the slice is rewritten
+    /// to unescape strings.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON or exceeds a limit.
+    pub fn deserializer<'de>(&self, s: &'de mut [u8]) -> Result<Deserializer<'de>> {
+        Deserializer::from_slice_with_opts(s, self.opts)
+    }
+
+    /// Parses a slice into an owned value, the slice is rewritten to
+    /// unescape strings.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON or exceeds a limit.
+    pub fn to_owned_value(&self, s: &mut [u8]) -> Result<OwnedValue> {
+        crate::to_owned_value_with_opts(s, self.opts)
+    }
+
+    /// Parses a slice into a borrowed value, the slice is rewritten to
+    /// unescape strings.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON or exceeds a limit.
+    pub fn to_borrowed_value<'value>(&self, s: &'value mut [u8]) -> Result<BorrowedValue<'value>> {
+        crate::to_borrowed_value_with_opts(s, self.opts)
+    }
+
+    /// Parses a slice using a serde deserializer, the slice is rewritten
+    /// to unescape strings.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON, exceeds a limit or
+    /// can't be deserialized into `T`.
+    #[cfg(feature = "serde_impl")]
+    pub fn from_slice<'a, T>(&self, s: &'a mut [u8]) -> Result<T>
+    where
+        T: serde_ext::Deserialize<'a>,
+    {
+        crate::from_slice_with_opts(s, self.opts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SecureDeserializer;
+    use crate::{Error, ErrorType, ParseOptions};
+
+    fn parse(de: SecureDeserializer, input: &str) -> Error {
+        let mut input = input.as_bytes().to_vec();
+        de.to_owned_value(&mut input)
+            .expect_err("a limit is exceeded")
+    }
+
+    #[test]
+    fn defaults() {
+        let opts = SecureDeserializer::default().options();
+        assert_eq!(
+            opts,
+            ParseOptions::strict()
+                .max_depth(128)
+                .max_input_len(10 * 1024 * 1024)
+                .max_string_bytes(1024 * 1024)
+                .max_tape_nodes(100_000)
+        );
+
+        let de = SecureDeserializer::new();
+        let deep = format!("{}{}", "[".repeat(129), "]".repeat(129));
+        assert_eq!(
+            parse(de, &deep).error(),
+            &ErrorType::DepthLimit {
+                limit: 128,
+                actual: 129
+            }
+        );
+        let nested = format!("{}{}", "[".repeat(128), "]".repeat(128));
+        assert!(de.to_borrowed_value(&mut nested.into_bytes()).is_ok());
+
+        let strings = format!(
+            r#"["{}", "{}"]"#,
+            "x".repeat(1 << 19),
+            "y".repeat((1 << 19) + 1)
+        );
+        assert_eq!(
+            parse(de, &strings).error(),
+            &ErrorType::StringBytesLimit {
+                limit: 1 << 20,
+                actual: (1 << 20) + 1
+            }
+        );
+
+        let values = format!("[{}]", vec!["0"; 100_000].join(","));
+        assert!(matches!(
+            parse(de, &values).error(),
+            ErrorType::TapeNodesLimit { limit: 100_000, .. }
+        ));
+    }
+
+    #[test]
+    fn adjusted() {
+        let de = SecureDeserializer::new()
+            .max_depth(1)
+            .max_input_len(16)
+            .max_string_bytes(4)
+            .max_tape_nodes(8);
+        assert_eq!(
+            parse(de, "[[]]").error(),
+            &ErrorType::DepthLimit {
+                limit: 1,
+                actual: 2
+            }
+        );
+        assert_eq!(
+            parse(de, &" ".repeat(17)).error(),
+            &ErrorType::InputLengthLimit {
+                limit: 16,
+                actual: 17
+            }
+        );
+        assert!(matches!(
+            parse(de, r#"["abcde"]"#).error(),
+            ErrorType::StringBytesLimit { limit: 4, .. }
+        ));
+        assert!(matches!(
+            // an array of eight numbers is longer than 16 bytes
+            parse(de.max_input_len(32), "[1,2,3,4,5,6,7,8]").error(),
+            ErrorType::TapeNodesLimit { limit: 8, .. }
+        ));
+        let mut d = br#"["abcd"]"#.to_vec();
+        assert!(de.deserializer(&mut d).is_ok());
+    }
+
+    #[cfg(feature = "serde_impl")]
+    #[test]
+    fn serde() {
+        let de = SecureDeserializer::new();
+        let mut d = br#"{"a": [1, 2]}"#.to_vec();
+        let v: std::collections::HashMap<&str, Vec<u8>> =
+            de.from_slice(&mut d).expect("valid json");
+        assert_eq!(v["a"], vec![1, 2]);
+        let mut d = format!("{}{}", "[".repeat(200), "]".repeat(200)).into_bytes();
+        assert!(de.from_slice::<crate::OwnedValue>(&mut d).is_err());
+    }
+}
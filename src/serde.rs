@@ -1,3 +1,5 @@
+#[cfg(feature = "bytes")]
+mod bytes;
 /// simd-json integrates with serde, this module holds this integration.
 /// note that when parsing to a dom you should use the functions in
 /// `to_owned_value` or `to_borrowed_value` as they provide much
@@ -6,19 +8,46 @@
 /// However if have to use serde for other reasons or are parsing
 /// directly to structs this is the place to go.
 ///
+/// ## Key order in `serde_json` conversions
+///
+/// The `TryFrom` / `TryInto` conversions between our values and
+/// `serde_json::Value` insert object entries in the iteration order of
+/// the source object, so order is kept as far as the target map allows:
+///
+/// * Our objects keep insertion order for up to 32 keys, larger objects
+///   are hash maps and iterate in an unspecified order.
+/// * `serde_json` objects are sorted by key unless its `preserve_order`
+///   feature is enabled, the `preserve-order` feature of this crate turns
+///   it on.
+///
 mod de;
+mod intern;
+mod key_offset;
+mod lazy;
 mod se;
+mod shared_str;
+pub mod tryserde;
 mod value;
+#[cfg(feature = "bytes")]
+pub use self::bytes::{from_bytes, owned_value_from_bytes, to_bytes, to_bytes_mut};
+pub use self::de::{SimdMapAccess, SimdSeqAccess};
+pub use self::intern::{Interned, Interner};
+pub use self::key_offset::KeyOffset;
+pub use self::lazy::{lazy, LazyValue};
 pub use self::se::*;
+pub use self::shared_str::{deserialize_arc_str, deserialize_rc_str};
 pub use self::value::*;
-use crate::{stry, Deserializer, Error, ErrorType, Result};
+use crate::value::{borrowed, owned, ObjectHasher};
+use crate::{stry, Deserializer, Error, ErrorType, ParseOptions, Result};
 use crate::{BorrowedValue, OwnedValue};
 use crate::{Node, StaticNode};
 use serde::de::DeserializeOwned;
+use serde_ext::de::DeserializeSeed;
 use serde_ext::Deserialize;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::io;
+use std::path::Path;
 use value_trait::prelude::*;
 type ConvertResult<T> = std::result::Result<T, SerdeConversionError>;
 
@@ -29,15 +58,18 @@ pub enum SerdeConversionError {
     NanOrInfinity,
     /// The number is out of the 64 bit bound
     NumberOutOfBounds,
+    /// The value was expected to be an object
+    NotAnObject,
     /// Something horrible went wrong, please open a ticket at <https://simd-json.rs>
     Oops,
 }
 impl std::fmt::Display for SerdeConversionError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use SerdeConversionError::{NanOrInfinity, NumberOutOfBounds, Oops};
+        use SerdeConversionError::{NanOrInfinity, NotAnObject, NumberOutOfBounds, Oops};
         match self {
             NanOrInfinity => write!(f, "JSON can not represent NAN or Infinity values"),
             NumberOutOfBounds => write!(f, "Serde can not represent 128 bit values"),
+            NotAnObject => write!(f, "The value is not an object"),
             Oops => write!(
                 f,
                 "Unreachable code is reachable, oops - please open a bug with simd-json"
@@ -62,6 +94,134 @@ where
     let mut deserializer = stry!(Deserializer::from_slice(s));
     T::deserialize(&mut deserializer)
 }
+/// parses a byte slice using a serde deserializer and the given
+/// parse options.
+/// note that the slice will be rewritten in the process.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or violates the limits
+/// set in `opts`.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub fn from_slice_with_opts<'a, T>(s: &'a mut [u8], opts: ParseOptions) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = stry!(Deserializer::from_slice_with_opts(s, opts));
+    T::deserialize(&mut deserializer)
+}
+
+/// parses a byte slice using a serde deserializer and a
+/// `DeserializeSeed`, for example `Interned`.
+/// note that the slice will be rewritten in the process.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or can't be deserialized
+/// with `seed`.
+pub fn from_slice_seed<'a, S>(s: &'a mut [u8], seed: S) -> Result<S::Value>
+where
+    S: DeserializeSeed<'a>,
+{
+    let mut deserializer = stry!(Deserializer::from_slice(s));
+    seed.deserialize(&mut deserializer)
+}
+
+/// parses a byte slice using a serde deserializer, failing with
+/// `ErrorType::AllocationFailed` instead of aborting if the parser's
+/// buffers or the tape can't be allocated. Memory allocated by `T`'s
+/// `Deserialize` implementation is not covered.
+/// note that the slice will be rewritten in the process.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or an allocation fails.
+pub fn try_from_slice<'a, T>(s: &'a mut [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = stry!(Deserializer::try_from_slice_with_opts(
+        s,
+        ParseOptions::default()
+    ));
+    T::deserialize(&mut deserializer)
+}
+
+/// parses a byte slice holding a top level array into a `Vec`, the
+/// `Vec` is allocated up front using the array length from the tape.
+/// note that the slice will be rewritten in the process.
+///
+/// ```rust
+/// let mut d = br#"[{"id": 1}, {"id": 2}]"#.to_vec();
+/// #[derive(serde::Deserialize)]
+/// struct Row {
+///     id: u64,
+/// }
+/// let rows: Vec<Row> = simd_json::parse_array_of(&mut d).unwrap();
+/// assert_eq!(rows.len(), 2);
+/// assert_eq!(rows[1].id, 2);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON, is not an array or an
+/// element can't be deserialized into `T`.
+pub fn parse_array_of<T>(s: &mut [u8]) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = stry!(Deserializer::from_slice(s));
+    // A parsed tape always holds at least the root element
+    if let Node::Array(len, _) = unsafe { deserializer.next_() } {
+        let mut res = Vec::with_capacity(len);
+        for _ in 0..len {
+            res.push(stry!(T::deserialize(&mut deserializer)));
+        }
+        Ok(res)
+    } else {
+        Err(Deserializer::error(ErrorType::ExpectedArray))
+    }
+}
+
+/// parses a byte slice holding a top level array, calling `callback`
+/// with each element as soon as it is deserialized instead of collecting
+/// them. Elements can borrow from the slice, as with `from_slice`.
+/// note that the slice will be rewritten in the process.
+///
+/// ```rust
+/// let mut d = br#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]"#.to_vec();
+/// #[derive(serde::Deserialize)]
+/// struct Row<'a> {
+///     id: u64,
+///     name: &'a str,
+/// }
+/// let mut names = String::new();
+/// simd_json::from_slice_streaming(&mut d, |row: Row| names.push_str(row.name)).unwrap();
+/// assert_eq!(names, "ab");
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON, is not an array or an
+/// element can't be deserialized into `T`, `callback` is not called for
+/// that element or any after it.
+pub fn from_slice_streaming<'a, T, F>(s: &'a mut [u8], mut callback: F) -> Result<()>
+where
+    T: Deserialize<'a>,
+    F: FnMut(T),
+{
+    let mut deserializer = stry!(Deserializer::from_slice(s));
+    // A parsed tape always holds at least the root element
+    if let Node::Array(len, _) = unsafe { deserializer.next_() } {
+        for _ in 0..len {
+            callback(stry!(T::deserialize(&mut deserializer)));
+        }
+        Ok(())
+    } else {
+        Err(Deserializer::error(ErrorType::ExpectedArray))
+    }
+}
+
 /// parses a str  using a serde deserializer.
 /// note that the slice will be rewritten in the process and
 /// might not remain a valid utf8 string in its entirety.
@@ -90,7 +250,9 @@ where
 /// # Errors
 ///
 /// Will return `Err` if an IO error is encountered while reading
-/// rdr or if the readers content is invalid JSON.
+/// rdr or if the readers content is invalid JSON. For IO errors the
+/// index of the error is the number of bytes read before the failure,
+/// so `0` means the reader failed before producing any data.
 #[cfg_attr(not(feature = "no-inline"), inline(always))]
 pub fn from_reader<R, T>(mut rdr: R) -> Result<T>
 where
@@ -99,12 +261,85 @@ where
 {
     let mut data = Vec::new();
     if let Err(e) = rdr.read_to_end(&mut data) {
-        return Err(Error::generic(ErrorType::Io(e)));
+        // `read_to_end` keeps the bytes read so far, so the length tells
+        // us how far we got before the reader failed.
+        return Err(Error::new(data.len(), None, ErrorType::Io(e)));
     };
     let mut deserializer = stry!(Deserializer::from_slice(&mut data));
     T::deserialize(&mut deserializer)
 }
 
+/// parses a Reader using a serde deserializer, reading at most
+/// `max_bytes` bytes. Use this instead of `from_reader` for untrusted
+/// input, such as request bodies, to bound the memory used.
+///
+/// # Errors
+///
+/// Will return `Err` with `ErrorType::InputTooLarge` if the reader
+/// produces more than `max_bytes` bytes, otherwise the same errors as
+/// `from_reader`.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub fn from_reader_size_limited<R, T>(rdr: R, max_bytes: usize) -> Result<T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    let mut data = Vec::new();
+    // read one byte past the limit so we can tell if it was exceeded
+    let limit = (max_bytes as u64).saturating_add(1);
+    if let Err(e) = io::Read::read_to_end(&mut rdr.take(limit), &mut data) {
+        return Err(Error::new(data.len(), None, ErrorType::Io(e)));
+    };
+    if data.len() > max_bytes {
+        return Err(Error::new(max_bytes, None, ErrorType::InputTooLarge));
+    }
+    let mut deserializer = stry!(Deserializer::from_slice(&mut data));
+    T::deserialize(&mut deserializer)
+}
+
+/// parses a byte slice read from the file at `path` using a serde
+/// deserializer, errors carry the path so they can be reported as
+/// `path: error`. Note that the slice will be rewritten in the process.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn from_slice_with_path<'a, T, P>(s: &'a mut [u8], path: P) -> Result<T>
+where
+    T: Deserialize<'a>,
+    P: AsRef<Path>,
+{
+    from_slice(s).map_err(|e| e.with_path(path.as_ref()))
+}
+
+/// reads and parses the file at `path` using a serde deserializer, errors
+/// carry the path so they can be reported as `path: error`.
+///
+/// ```rust,no_run
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     name: String,
+/// }
+/// match simd_json::from_file::<Config, _>("config.json") {
+///     Ok(config) => println!("hello {}", config.name),
+///     // prints `config.json: ...`
+///     Err(e) => eprintln!("{e}"),
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the file can't be read or is invalid JSON.
+pub fn from_file<T, P>(path: P) -> Result<T>
+where
+    T: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let mut data = std::fs::read(path).map_err(|e| Error::from(e).with_path(path))?;
+    from_slice_with_path(&mut data, path)
+}
+
 impl serde::de::Error for Error {
     fn custom<T: fmt::Display>(msg: T) -> Self {
         Self::generic(ErrorType::Serde(msg.to_string()))
@@ -117,6 +352,19 @@ impl serde_ext::ser::Error for Error {
     }
 }
 
+/// Wraps `serde_json` errors, to allow using `?` on them in functions
+/// returning simd-json errors. IO errors are kept as `ErrorType::Io`, all
+/// other errors become `ErrorType::Serde` with the error message.
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        if e.is_io() {
+            Self::generic(ErrorType::Io(e.into()))
+        } else {
+            Self::generic(ErrorType::Serde(e.to_string()))
+        }
+    }
+}
+
 // Functions purely used by serde
 impl<'de> Deserializer<'de> {
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
@@ -136,6 +384,23 @@ impl<'de> Deserializer<'de> {
             .ok_or_else(|| Self::error(ErrorType::Eof))
     }
 
+    /// Skips a `null` if `null_as_default` is set, the caller visits the
+    /// default value of its type instead.
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn skip_null_as_default(&mut self) -> bool {
+        if self.null_as_default
+            && matches!(
+                self.tape.get(self.idx + 1),
+                Some(Node::Static(StaticNode::Null))
+            )
+        {
+            self.skip();
+            true
+        } else {
+            false
+        }
+    }
+
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     #[allow(clippy::cast_sign_loss)]
     fn parse_u8(&mut self) -> Result<u8> {
@@ -277,14 +542,22 @@ impl TryFrom<serde_json::Value> for OwnedValue {
                 }
             }
             Value::String(b) => Self::String(b),
-            Value::Array(a) => a
-                .into_iter()
-                .map(Self::try_from)
-                .collect::<ConvertResult<Self>>()?,
-            Value::Object(o) => o
-                .into_iter()
-                .map(|(k, v)| Ok((k, Self::try_from(v)?)))
-                .collect::<ConvertResult<Self>>()?,
+            Value::Array(a) => {
+                let mut res = Vec::with_capacity(a.len());
+                for v in a {
+                    res.push(Self::try_from(v)?);
+                }
+                Self::Array(res)
+            }
+            Value::Object(o) => {
+                let mut res =
+                    owned::Object::with_capacity_and_hasher(o.len(), ObjectHasher::default());
+                // serde_json maps never contain duplicate keys
+                for (k, v) in o {
+                    res.insert_nocheck(k, Self::try_from(v)?);
+                }
+                Self::Object(Box::new(res))
+            }
         })
     }
 }
@@ -318,20 +591,95 @@ impl TryInto<serde_json::Value> for OwnedValue {
                 }
             }
             Self::String(b) => Value::String(b),
-            Self::Array(a) => Value::Array(
-                a.into_iter()
-                    .map(TryInto::try_into)
-                    .collect::<ConvertResult<Vec<Value>>>()?,
-            ),
-            Self::Object(o) => Value::Object(
-                o.into_iter()
-                    .map(|(k, v)| Ok((k, v.try_into()?)))
-                    .collect::<ConvertResult<serde_json::map::Map<String, Value>>>()?,
-            ),
+            Self::Array(a) => {
+                let mut res = Vec::with_capacity(a.len());
+                for v in a {
+                    res.push(v.try_into()?);
+                }
+                Value::Array(res)
+            }
+            Self::Object(o) => Value::Object(owned_object_to_map(*o)?),
         })
     }
 }
 
+impl TryFrom<OwnedValue> for serde_json::Map<String, serde_json::Value> {
+    type Error = SerdeConversionError;
+    fn try_from(item: OwnedValue) -> ConvertResult<Self> {
+        if let OwnedValue::Object(o) = item {
+            owned_object_to_map(*o)
+        } else {
+            Err(SerdeConversionError::NotAnObject)
+        }
+    }
+}
+
+fn owned_object_to_map(
+    o: owned::Object,
+) -> ConvertResult<serde_json::Map<String, serde_json::Value>> {
+    let mut res = serde_json::Map::with_capacity(o.len());
+    for (k, v) in o {
+        res.insert(k, v.try_into()?);
+    }
+    Ok(res)
+}
+
+/// Compares with a `serde_json` value without converting either side.
+///
+/// Integers are equal if they have the same value, independent of their
+/// type, 128 bit integers outside of the 64 bit range are never equal as
+/// `serde_json` can't hold them. Floats are only equal to floats and
+/// compared exactly, like `serde_json` does, so `1` and `1.0` differ.
+/// Objects are equal if they have the same keys with equal values, in
+/// any order.
+///
+/// ```rust
+/// use simd_json::json;
+/// let v = json!({"a": [1, -2, 2.5, null], "b": "x"});
+/// assert_eq!(v, serde_json::json!({"b": "x", "a": [1, -2, 2.5, null]}));
+/// assert_ne!(v, serde_json::json!({"a": [1.0, -2, 2.5, null], "b": "x"}));
+/// ```
+impl PartialEq<serde_json::Value> for OwnedValue {
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        use serde_json::Value;
+        match (self, other) {
+            (Self::Static(StaticNode::Null), Value::Null) => true,
+            (Self::Static(StaticNode::Bool(b1)), Value::Bool(b2)) => b1 == b2,
+            (Self::Static(s), Value::Number(n)) => static_eq_number(s, n),
+            (Self::String(s1), Value::String(s2)) => s1 == s2,
+            (Self::Array(a1), Value::Array(a2)) => a1 == a2,
+            (Self::Object(o1), Value::Object(o2)) => {
+                o1.len() == o2.len()
+                    && o1
+                        .iter()
+                        .all(|(k, v)| o2.get(k).map_or(false, |v2| v == v2))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<OwnedValue> for serde_json::Value {
+    fn eq(&self, other: &OwnedValue) -> bool {
+        other == self
+    }
+}
+
+/// Compares a number node with a `serde_json` number
+#[allow(clippy::float_cmp)]
+fn static_eq_number(s: &StaticNode, n: &serde_json::Number) -> bool {
+    match s {
+        StaticNode::I64(i) => n.as_i64() == Some(*i),
+        StaticNode::U64(u) => n.as_u64() == Some(*u),
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(i) => i64::try_from(*i).map_or(false, |i| n.as_i64() == Some(i)),
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(u) => u64::try_from(*u).map_or(false, |u| n.as_u64() == Some(u)),
+        StaticNode::F64(f) => n.is_f64() && n.as_f64() == Some(*f),
+        StaticNode::Null | StaticNode::Bool(_) => false,
+    }
+}
+
 impl<'value> TryFrom<serde_json::Value> for BorrowedValue<'value> {
     type Error = SerdeConversionError;
     fn try_from(item: serde_json::Value) -> ConvertResult<Self> {
@@ -346,11 +694,22 @@ impl<'value> TryFrom<serde_json::Value> for BorrowedValue<'value> {
                 _ => Err(SerdeConversionError::Oops),
             },
             Value::String(b) => Ok(Self::String(b.into())),
-            Value::Array(a) => a.into_iter().map(Self::try_from).collect(),
-            Value::Object(o) => o
-                .into_iter()
-                .map(|(k, v)| Ok((k, Self::try_from(v)?)))
-                .collect(),
+            Value::Array(a) => {
+                let mut res = Vec::with_capacity(a.len());
+                for v in a {
+                    res.push(Self::try_from(v)?);
+                }
+                Ok(Self::Array(res))
+            }
+            Value::Object(o) => {
+                let mut res =
+                    borrowed::Object::with_capacity_and_hasher(o.len(), ObjectHasher::default());
+                // serde_json maps never contain duplicate keys
+                for (k, v) in o {
+                    res.insert_nocheck(k.into(), Self::try_from(v)?);
+                }
+                Ok(Self::Object(Box::new(res)))
+            }
         }
     }
 }
@@ -384,16 +743,20 @@ impl<'value> TryInto<serde_json::Value> for BorrowedValue<'value> {
                 }
             }
             BorrowedValue::String(b) => Value::String(b.to_string()),
-            BorrowedValue::Array(a) => Value::Array(
-                a.into_iter()
-                    .map(TryInto::try_into)
-                    .collect::<ConvertResult<Vec<Value>>>()?,
-            ),
-            BorrowedValue::Object(o) => Value::Object(
-                o.into_iter()
-                    .map(|(k, v)| Ok((k.to_string(), v.try_into()?)))
-                    .collect::<ConvertResult<serde_json::map::Map<String, Value>>>()?,
-            ),
+            BorrowedValue::Array(a) => {
+                let mut res = Vec::with_capacity(a.len());
+                for v in a {
+                    res.push(v.try_into()?);
+                }
+                Value::Array(res)
+            }
+            BorrowedValue::Object(o) => {
+                let mut res = serde_json::Map::with_capacity(o.len());
+                for (k, v) in o.into_iter() {
+                    res.insert(k.to_string(), v.try_into()?);
+                }
+                Value::Object(res)
+            }
         })
     }
 }
@@ -485,6 +848,8 @@ mod test {
         });
         let s_c: SerdeValue = v.clone().try_into().unwrap();
         assert_eq!(s, s_c);
+        assert_eq!(v, s);
+        assert_eq!(s, v);
         let v_c: OwnedValue = s.try_into().unwrap();
         assert_eq!(v, v_c);
 
@@ -499,6 +864,30 @@ mod test {
         assert_eq!(v, v_deser);
     }
 
+    #[test]
+    fn eq_serde_json() {
+        assert_eq!(json!(i64::MAX as u64 + 1), sjson!(i64::MAX as u64 + 1));
+        assert_eq!(json!(-1), sjson!(-1));
+        assert_eq!(json!(0.1), sjson!(0.1));
+        assert_ne!(json!(1), sjson!(1.0));
+        assert_ne!(json!(1.0), sjson!(1));
+        assert_ne!(json!(-1), sjson!(u64::MAX));
+        assert_ne!(json!(null), sjson!(false));
+        assert_ne!(json!("1"), sjson!(1));
+        assert_ne!(json!([1, 2]), sjson!([1]));
+        assert_ne!(json!([1, 2]), sjson!([2, 1]));
+        assert_eq!(json!({"a": 1, "b": 2}), sjson!({"b": 2, "a": 1}));
+        assert_ne!(json!({"a": 1}), sjson!({"a": 1, "b": 2}));
+        assert_ne!(json!({"a": 1, "c": 2}), sjson!({"a": 1, "b": 2}));
+        assert_ne!(json!({"a": 1}), sjson!([1]));
+        #[cfg(feature = "128bit")]
+        {
+            assert_eq!(OwnedValue::from(5_u128), sjson!(5));
+            assert_eq!(OwnedValue::from(-5_i128), sjson!(-5));
+            assert_ne!(OwnedValue::from(u128::MAX), sjson!(u64::MAX));
+        }
+    }
+
     #[test]
     fn convert_borrowed_value() {
         let v: BorrowedValue = json!({
@@ -555,6 +944,508 @@ mod test {
         assert_eq!(v, v_c);
     }
 
+    struct FailingReader {
+        data: &'static [u8],
+        kind: std::io::ErrorKind,
+    }
+
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.data.is_empty() {
+                return Err(std::io::Error::new(self.kind, "mock failure"));
+            }
+            let n = buf.len().min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn from_reader_size_limited() {
+        let input = br#"{"key": [1, 2, 3]}"#;
+        let v: OwnedValue =
+            crate::from_reader_size_limited(&input[..], input.len()).expect("within limit");
+        assert_eq!(v, json!({"key": [1, 2, 3]}));
+        let e = crate::from_reader_size_limited::<_, OwnedValue>(&input[..], input.len() - 1)
+            .unwrap_err();
+        assert_eq!(e.error(), &ErrorType::InputTooLarge);
+        assert_eq!(e.index(), input.len() - 1);
+        let e = crate::from_reader_size_limited::<_, OwnedValue>(&b""[..], 0).unwrap_err();
+        assert_eq!(e.error(), &ErrorType::Eof);
+
+        let rdr = FailingReader {
+            data: br#"{"key": "#,
+            kind: std::io::ErrorKind::ConnectionReset,
+        };
+        let e = crate::from_reader_size_limited::<_, OwnedValue>(rdr, 64).unwrap_err();
+        assert_eq!(e.io_kind(), Some(std::io::ErrorKind::ConnectionReset));
+    }
+
+    #[test]
+    fn from_serde_json_error() {
+        fn parse(s: &str) -> crate::Result<SerdeValue> {
+            Ok(serde_json::from_str(s)?)
+        }
+        let e = parse("[1, ").unwrap_err();
+        assert!(matches!(e.error(), ErrorType::Serde(msg) if msg.contains("EOF")));
+
+        let rdr = FailingReader {
+            data: b"[",
+            kind: std::io::ErrorKind::ConnectionReset,
+        };
+        let e = Error::from(serde_json::from_reader::<_, SerdeValue>(rdr).unwrap_err());
+        assert_eq!(e.io_kind(), Some(std::io::ErrorKind::ConnectionReset));
+    }
+
+    #[test]
+    fn from_reader_io_errors() {
+        use std::io::ErrorKind;
+        let rdr = FailingReader {
+            data: b"",
+            kind: ErrorKind::NotFound,
+        };
+        let e = crate::from_reader::<_, OwnedValue>(rdr).unwrap_err();
+        assert_eq!(e.io_kind(), Some(ErrorKind::NotFound));
+        assert_eq!(e.index(), 0);
+
+        let rdr = FailingReader {
+            data: br#"{"key": "#,
+            kind: ErrorKind::ConnectionReset,
+        };
+        let e = crate::from_reader::<_, OwnedValue>(rdr).unwrap_err();
+        assert_eq!(e.io_kind(), Some(ErrorKind::ConnectionReset));
+        assert_eq!(e.index(), 8);
+        assert_eq!(
+            e,
+            Error::new(8, None, ErrorType::Io(ErrorKind::ConnectionReset.into()))
+        );
+
+        let e = crate::from_reader::<_, OwnedValue>(&b"[1,"[..]).unwrap_err();
+        assert_eq!(e.io_kind(), None);
+    }
+
+    #[test]
+    fn from_file() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Config {
+            name: String,
+            port: u16,
+        }
+        let dir = std::env::temp_dir();
+        let valid = dir.join(format!("simd-json-valid-{}.json", std::process::id()));
+        let invalid = dir.join(format!("simd-json-invalid-{}.json", std::process::id()));
+        std::fs::write(&valid, br#"{"name": "server", "port": 8080}"#).unwrap();
+        std::fs::write(&invalid, br#"{"name": "server", "port": 80800}"#).unwrap();
+
+        let config: Config = crate::from_file(&valid).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                name: "server".to_string(),
+                port: 8080
+            }
+        );
+
+        let e = crate::from_file::<Config, _>(&invalid).unwrap_err();
+        assert_eq!(e.path(), Some(invalid.as_path()));
+        let msg = e.to_string();
+        assert!(
+            msg.starts_with(&format!("{}: ", invalid.display())),
+            "{msg}"
+        );
+        assert!(msg.len() > invalid.display().to_string().len() + 2);
+
+        let missing = dir.join("simd-json-does-not-exist.json");
+        let e = crate::from_file::<Config, _>(&missing).unwrap_err();
+        assert_eq!(e.io_kind(), Some(std::io::ErrorKind::NotFound));
+        assert_eq!(e.path(), Some(missing.as_path()));
+
+        let mut d = b"[1, 2".to_vec();
+        let e = crate::from_slice_with_path::<Vec<u8>, _>(&mut d, "inline.json").unwrap_err();
+        assert!(e.to_string().starts_with("inline.json: "));
+        let mut d = b"[1, 2".to_vec();
+        assert_eq!(
+            crate::from_slice::<Vec<u8>>(&mut d).unwrap_err().path(),
+            None
+        );
+        std::fs::remove_file(valid).unwrap();
+        std::fs::remove_file(invalid).unwrap();
+    }
+
+    #[test]
+    fn restart_deserializer() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Header<'a> {
+            kind: &'a str,
+        }
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Payload {
+            kind: String,
+            payload: Vec<u8>,
+        }
+        let mut d = br#"{"kind": "ping", "payload": [1, 2, 3]}"#.to_vec();
+        let mut de = SimdDeserializer::from_slice(&mut d).unwrap();
+
+        let header = Header::deserialize(&mut de).unwrap();
+        assert_eq!(header, Header { kind: "ping" });
+
+        de.restart();
+        let payload = Payload::deserialize(&mut de).unwrap();
+        assert_eq!(
+            payload,
+            Payload {
+                kind: "ping".to_string(),
+                payload: vec![1, 2, 3]
+            }
+        );
+
+        de.restart();
+        let value = BorrowedValue::deserialize(&mut de).unwrap();
+        assert_eq!(value, json!({"kind": "ping", "payload": [1, 2, 3]}));
+    }
+
+    #[test]
+    fn convert_owned_object_to_map() {
+        use std::convert::TryFrom;
+        let v: OwnedValue = json!({"int": 42, "array": [1, "two"], "object": {"null": null}});
+        let m = serde_json::Map::<String, SerdeValue>::try_from(v).unwrap();
+        let s = sjson!({"int": 42, "array": [1, "two"], "object": {"null": null}});
+        assert_eq!(Some(&m), s.as_object());
+
+        let v: OwnedValue = json!([1, 2]);
+        assert!(matches!(
+            serde_json::Map::<String, SerdeValue>::try_from(v),
+            Err(super::SerdeConversionError::NotAnObject)
+        ));
+    }
+
+    #[test]
+    fn convert_keeps_source_order() {
+        use crate::prelude::*;
+        use std::convert::TryFrom;
+        let s = sjson!({"z": 1, "a": 2, "m": {"y": 3, "b": 4}});
+        let serde_keys: Vec<String> = s.as_object().unwrap().keys().cloned().collect();
+
+        // our objects keep insertion order for small objects so the result
+        // mirrors serde_json's iteration order
+        let v = OwnedValue::try_from(s.clone()).unwrap();
+        let keys: Vec<String> = v.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys, serde_keys);
+        let v = BorrowedValue::try_from(s).unwrap();
+        let keys: Vec<String> = v
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.to_string())
+            .collect();
+        assert_eq!(keys, serde_keys);
+
+        // large objects round trip, even if the order isn't kept
+        let large: serde_json::Map<String, SerdeValue> = (0..100)
+            .map(|i| (format!("key{i}"), SerdeValue::from(i)))
+            .collect();
+        let large = SerdeValue::Object(large);
+        let v = OwnedValue::try_from(large.clone()).unwrap();
+        assert_eq!(v.as_object().unwrap().len(), 100);
+        let back: SerdeValue = v.try_into().unwrap();
+        assert_eq!(back, large);
+        let v = BorrowedValue::try_from(large.clone()).unwrap();
+        let back: SerdeValue = v.try_into().unwrap();
+        assert_eq!(back, large);
+    }
+
+    #[cfg(feature = "preserve-order")]
+    #[test]
+    fn merged_duplicate_keys_preserve_order() {
+        use crate::{DuplicateKeys, ParseOptions};
+        let opts = ParseOptions::default().duplicate_keys(DuplicateKeys::MergeObjects);
+        let mut d = br#"{"z": {"y": 1}, "a": 2, "z": {"x": 2}}"#.to_vec();
+        let v = crate::to_owned_value_with_opts(&mut d, opts).unwrap();
+        let s: SerdeValue = v.try_into().unwrap();
+        assert_eq!(sto_string(&s).unwrap(), r#"{"z":{"y":1,"x":2},"a":2}"#);
+    }
+
+    #[cfg(feature = "preserve-order")]
+    #[test]
+    fn convert_preserve_order() {
+        use crate::prelude::*;
+        use std::convert::TryFrom;
+        let expected = vec!["z", "a", "m"];
+
+        let v: OwnedValue = json!({"z": 1, "a": 2, "m": 3});
+        let s: SerdeValue = v.try_into().unwrap();
+        let keys: Vec<&str> = s.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, expected);
+        let m = serde_json::Map::<String, SerdeValue>::try_from(json!({"z": 1, "a": 2, "m": 3}))
+            .unwrap();
+        let keys: Vec<&str> = m.keys().map(String::as_str).collect();
+        assert_eq!(keys, expected);
+
+        let v: BorrowedValue = json!({"z": 1, "a": 2, "m": 3}).into();
+        let s: SerdeValue = v.try_into().unwrap();
+        let keys: Vec<&str> = s.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, expected);
+
+        let s = sjson!({"z": 1, "a": 2, "m": 3});
+        let v = OwnedValue::try_from(s.clone()).unwrap();
+        let keys: Vec<&str> = v.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, expected);
+        let v = BorrowedValue::try_from(s).unwrap();
+        let keys: Vec<&str> = v.as_object().unwrap().keys().map(|k| &**k).collect();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn parse_array_of() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row {
+            id: u64,
+            name: String,
+        }
+        let mut d = br#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b", "extra": [1]}]"#.to_vec();
+        let rows: Vec<Row> = crate::parse_array_of(&mut d).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row {
+                    id: 1,
+                    name: "a".to_string()
+                },
+                Row {
+                    id: 2,
+                    name: "b".to_string()
+                }
+            ]
+        );
+        assert_eq!(rows.capacity(), 2);
+
+        let mut d = b"[]".to_vec();
+        assert!(crate::parse_array_of::<Row>(&mut d).unwrap().is_empty());
+
+        let mut d = br#"{"id": 1, "name": "a"}"#.to_vec();
+        let e = crate::parse_array_of::<Row>(&mut d).unwrap_err();
+        assert_eq!(e.error(), &ErrorType::ExpectedArray);
+
+        let mut d = br#"[{"id": 1, "name": "a"}, {"id": "2", "name": "b"}]"#.to_vec();
+        assert!(crate::parse_array_of::<Row>(&mut d).is_err());
+    }
+
+    #[test]
+    fn from_slice_streaming() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Row<'a> {
+            id: u64,
+            name: &'a str,
+        }
+        let mut d = br#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b", "extra": [1]}]"#.to_vec();
+        let mut rows = Vec::new();
+        crate::from_slice_streaming(&mut d, |row: Row| rows.push((row.id, row.name))).unwrap();
+        assert_eq!(rows, vec![(1, "a"), (2, "b")]);
+
+        let mut d = b"[]".to_vec();
+        let mut calls = 0;
+        crate::from_slice_streaming(&mut d, |_: Row| calls += 1).unwrap();
+        assert_eq!(calls, 0);
+
+        let mut d = br#"{"id": 1, "name": "a"}"#.to_vec();
+        let e = crate::from_slice_streaming(&mut d, |_: Row| ()).unwrap_err();
+        assert_eq!(e.error(), &ErrorType::ExpectedArray);
+
+        // elements before the bad one were already handed out
+        let mut d = br#"[1, 2, "3", 4]"#.to_vec();
+        let mut seen = Vec::new();
+        assert!(crate::from_slice_streaming(&mut d, |n: u8| seen.push(n)).is_err());
+        assert_eq!(seen, vec![1, 2]);
+    }
+
+    #[test]
+    fn access() {
+        use serde::de::{MapAccess, SeqAccess};
+        let mut d = br#"[{"a": [1, 2]}, {}, 3]"#.to_vec();
+        let mut de = SimdDeserializer::from_slice(&mut d).unwrap();
+        let mut seq = de.array_access().unwrap();
+        let first: HashMap<String, Vec<u8>> = seq.next_element().unwrap().unwrap();
+        assert_eq!(first["a"], [1, 2]);
+        assert_eq!(SeqAccess::size_hint(&seq), Some(2));
+        let second: HashMap<String, u8> = seq.next_element().unwrap().unwrap();
+        assert!(second.is_empty());
+        assert_eq!(seq.next_element::<u8>().unwrap(), Some(3));
+        assert_eq!(seq.next_element::<u8>().unwrap(), None);
+
+        let mut d = br#"{"k": "v"}"#.to_vec();
+        let mut de = SimdDeserializer::from_slice(&mut d).unwrap();
+        let e = de.array_access().err().expect("not an array");
+        assert_eq!(e.error(), &ErrorType::ExpectedArray);
+        let mut de = SimdDeserializer::from_slice(&mut d).unwrap();
+        let mut map = de.object_access().unwrap();
+        assert_eq!(MapAccess::size_hint(&map), Some(1));
+        assert_eq!(map.next_entry().unwrap(), Some(("k", "v")));
+        assert_eq!(map.next_key::<&str>().unwrap(), None);
+        let mut d = b"[]".to_vec();
+        let mut de = SimdDeserializer::from_slice(&mut d).unwrap();
+        assert!(de.object_access().is_err());
+    }
+
+    #[cfg(not(feature = "approx-number-parsing"))]
+    #[test]
+    fn f32_rounding() {
+        let parse = |s: &str| crate::from_slice::<f32>(&mut s.as_bytes().to_vec()).unwrap();
+        for s in [
+            "16777216",
+            "16777217",
+            "16777219",
+            "0.1",
+            "0.2",
+            "0.3",
+            "0.7",
+            "1.1",
+            "3.4028235e38",
+            "3.4028236e38",
+            "1e39",
+            "1.17549435e-38",
+            "1.1754942e-38",
+            "1e-45",
+            "1.4e-45",
+            "7e-46",
+            "1e-46",
+            "-0.0",
+            "-1.5e-40",
+        ] {
+            let expected: f32 = s.parse().unwrap();
+            assert_eq!(parse(s).to_bits(), expected.to_bits(), "{s}");
+        }
+        // just below the midpoint of 1 + 2^-23 and 1 + 2^-22, the nearest
+        // `f64` is the midpoint itself which then rounds to even
+        let s = "1.00000017881393432617187499";
+        let direct: f32 = s.parse().unwrap();
+        let twice = s.parse::<f64>().unwrap() as f32;
+        assert_ne!(direct, twice);
+        assert_eq!(parse(s), twice);
+    }
+
+    #[test]
+    fn null_as_default() {
+        use crate::ParseOptions;
+        #[derive(Deserialize, Debug, PartialEq, Default)]
+        struct Inner {
+            n: i8,
+            f: f32,
+            tag: Option<String>,
+        }
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Outer {
+            count: u32,
+            ratio: f64,
+            flag: bool,
+            name: String,
+            list: Vec<u64>,
+            map: HashMap<String, i32>,
+            inner: Option<Inner>,
+            inners: Vec<Inner>,
+            nested: Inner,
+            maybe: Option<u32>,
+        }
+        let input = br#"{
+            "count": null, "ratio": null, "flag": null, "name": null,
+            "list": [1, null], "map": {"a": null}, "inner": null,
+            "inners": [{"n": null, "f": null, "tag": null}, {"n": 1, "f": 0.5, "tag": "t"}],
+            "nested": {"n": null, "f": 2, "tag": null}, "maybe": null
+        }"#;
+        assert!(crate::from_slice::<Outer>(&mut input.to_vec()).is_err());
+
+        let opts = ParseOptions::default().null_as_default(true);
+        let v: Outer = crate::from_slice_with_opts(&mut input.to_vec(), opts).unwrap();
+        assert_eq!(
+            v,
+            Outer {
+                count: 0,
+                ratio: 0.0,
+                flag: false,
+                name: String::new(),
+                list: vec![1, 0],
+                map: hashmap! {"a".to_string() => 0},
+                inner: None,
+                inners: vec![
+                    Inner::default(),
+                    Inner {
+                        n: 1,
+                        f: 0.5,
+                        tag: Some("t".to_string())
+                    }
+                ],
+                nested: Inner {
+                    n: 0,
+                    f: 2.0,
+                    tag: None
+                },
+                maybe: None,
+            }
+        );
+
+        // null containers become empty ones, `nested` has to be present
+        let mut d = br#"{"count": 1, "ratio": 1, "flag": true, "name": "x", "list": null,
+            "map": null, "inner": {"n": 1, "f": 1, "tag": null}, "inners": null,
+            "nested": {"n": 2, "f": null, "tag": "t"}, "maybe": 3}"#
+            .to_vec();
+        let v: Outer = crate::from_slice_with_opts(&mut d, opts).unwrap();
+        assert!(v.list.is_empty() && v.map.is_empty() && v.inners.is_empty());
+        assert_eq!(v.inner.map(|i| i.tag), Some(None));
+        assert_eq!(v.maybe, Some(3));
+    }
+
+    #[test]
+    fn field_matching() {
+        use crate::{FieldMatching, ParseOptions};
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        struct User {
+            user_name: String,
+            id: u64,
+            #[serde(default)]
+            is_admin: bool,
+        }
+        let exact = ParseOptions::default();
+        let case = ParseOptions::default().field_matching(FieldMatching::CaseInsensitive);
+        let agnostic = ParseOptions::default().field_matching(FieldMatching::SnakeCamelAgnostic);
+        let expected = User {
+            user_name: "ferris".to_string(),
+            id: 1,
+            is_admin: true,
+        };
+
+        let mut d = br#"{"user_name": "ferris", "Id": 1, "IS_ADMIN": true}"#.to_vec();
+        assert!(crate::from_slice_with_opts::<User>(&mut d.clone(), exact).is_err());
+        assert_eq!(
+            crate::from_slice_with_opts::<User>(&mut d, case).unwrap(),
+            expected
+        );
+
+        let mut d = br#"{"UserName": "ferris", "id": 1, "isAdmin": true}"#.to_vec();
+        assert!(crate::from_slice_with_opts::<User>(&mut d.clone(), case).is_err());
+        assert_eq!(
+            crate::from_slice_with_opts::<User>(&mut d, agnostic).unwrap(),
+            expected
+        );
+
+        // unknown fields are still rejected
+        let mut d = br#"{"UserName": "ferris", "ID": 1, "Admin": true}"#.to_vec();
+        assert!(crate::from_slice_with_opts::<User>(&mut d, agnostic).is_err());
+
+        #[derive(Deserialize, Debug)]
+        struct Ambiguous {
+            #[allow(dead_code)]
+            user_name: u8,
+            #[allow(dead_code)]
+            username: u8,
+        }
+        let mut d = br#"{"user_name": 1, "username": 2}"#.to_vec();
+        assert!(crate::from_slice_with_opts::<Ambiguous>(&mut d.clone(), exact).is_ok());
+        assert!(crate::from_slice_with_opts::<Ambiguous>(&mut d.clone(), case).is_ok());
+        let e = crate::from_slice_with_opts::<Ambiguous>(&mut d, agnostic).unwrap_err();
+        assert!(e.to_string().contains("ambiguous"), "{e}");
+    }
+
     #[test]
     fn option_field_absent() {
         #[derive(serde::Deserialize, Debug)]
@@ -817,7 +1708,10 @@ mod test {
     fn maps() {
         let key_error = Err(Error::generic(ErrorType::KeyMustBeAString));
         assert_eq!(crate::to_string(&hashmap! {b"1234" => 3_i8}), key_error);
-        assert_eq!(crate::to_string(&hashmap! {true => 3_i8}), key_error);
+        assert_eq!(
+            crate::to_string(&hashmap! {true => 3_i8}),
+            Ok(String::from(r#"{"true":3}"#))
+        );
         assert_eq!(
             crate::to_string(&hashmap! {[3_u8, 4_u8] => 3_i8}),
             key_error
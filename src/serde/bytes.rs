@@ -0,0 +1,135 @@
+use crate::{OwnedValue, Result};
+use ::bytes::{BufMut, Bytes, BytesMut};
+use serde::de::DeserializeOwned;
+use serde_ext::ser;
+
+/// The initial capacity reserved for serializing, the same as `to_vec`
+const INITIAL_CAPACITY: usize = 512;
+
+/// parses a `BytesMut` using a serde deserializer, the buffer is
+/// rewritten in place the same way `from_slice` rewrites a slice.
+///
+/// The parser copies the input into its own padded buffer, so the
+/// `BytesMut` does not need any spare capacity after the data and is
+/// never reallocated or copied into a `Vec`.
+///
+/// ```rust
+/// use bytes::BytesMut;
+/// let mut b = BytesMut::from(&br#"{"id": 1}"#[..]);
+/// let v: std::collections::HashMap<String, u64> = simd_json::from_bytes(&mut b).unwrap();
+/// assert_eq!(v["id"], 1);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `b` is invalid JSON.
+pub fn from_bytes<T>(b: &mut BytesMut) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    crate::from_slice(b)
+}
+
+/// Parses a `BytesMut` into an owned value, the buffer is rewritten in
+/// place, see `from_bytes`.
+///
+/// # Errors
+///
+/// Will return `Err` if `b` is invalid JSON.
+pub fn owned_value_from_bytes(b: &mut BytesMut) -> Result<OwnedValue> {
+    crate::to_owned_value(b)
+}
+
+/// Serializes a value into `Bytes`.
+///
+/// ```rust
+/// let b = simd_json::to_bytes(&vec![1, 2, 3]).unwrap();
+/// assert_eq!(&b[..], b"[1,2,3]");
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the value can't be serialized.
+pub fn to_bytes<T>(to: &T) -> Result<Bytes>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut b = BytesMut::with_capacity(INITIAL_CAPACITY);
+    to_bytes_mut(to, &mut b).map(|_| b.freeze())
+}
+
+/// Serializes a value, appending it to a `BytesMut`. There is no way to
+/// know the length of the output up front, if `b` has less than 512
+/// bytes of spare capacity that much is reserved before writing.
+///
+/// # Errors
+///
+/// Will return `Err` if the value can't be serialized.
+pub fn to_bytes_mut<T>(to: &T, b: &mut BytesMut) -> Result<()>
+where
+    T: ser::Serialize + ?Sized,
+{
+    b.reserve(INITIAL_CAPACITY);
+    crate::to_writer(b.writer(), to)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_bytes, owned_value_from_bytes, to_bytes, to_bytes_mut};
+    use crate::json;
+    use ::bytes::BytesMut;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Row {
+        id: u64,
+        name: String,
+    }
+
+    #[test]
+    fn parse_in_place() {
+        let input = br#"{"id": 7, "name": "tab\tbed"}"#;
+        let mut b = BytesMut::from(&input[..]);
+        let (ptr, capacity) = (b.as_ptr(), b.capacity());
+        let row: Row = from_bytes(&mut b).expect("valid json");
+        assert_eq!(
+            row,
+            Row {
+                id: 7,
+                name: "tab\tbed".to_string()
+            }
+        );
+        // no tail padding was reserved and the buffer was not replaced
+        assert_eq!((b.as_ptr(), b.capacity()), (ptr, capacity));
+
+        let mut b = BytesMut::from(&input[..]);
+        let ptr = b.as_ptr();
+        let v = owned_value_from_bytes(&mut b).expect("valid json");
+        assert_eq!(v, json!({"id": 7, "name": "tab\tbed"}));
+        assert_eq!(b.as_ptr(), ptr);
+
+        let mut b = BytesMut::from(&b"[1,"[..]);
+        assert!(from_bytes::<Vec<u8>>(&mut b).is_err());
+    }
+
+    #[test]
+    fn serialize() {
+        let v = json!({"id": 7, "names": ["a", "b"]});
+        let b = to_bytes(&v).expect("serializable");
+        assert_eq!(&b[..], crate::to_vec(&v).expect("serializable").as_slice());
+
+        // appends, and only reserves if there is too little room
+        let mut b = BytesMut::with_capacity(1024);
+        b.extend_from_slice(b"x");
+        let ptr = b.as_ptr();
+        to_bytes_mut(&v, &mut b).expect("serializable");
+        to_bytes_mut(&1, &mut b).expect("serializable");
+        assert_eq!(b.as_ptr(), ptr);
+        assert_eq!(&b[..2], b"x{");
+        assert!(b.ends_with(b"]}1"));
+
+        // the frozen bytes share the buffer
+        let ptr = b.as_ptr();
+        assert_eq!(b.freeze().as_ptr(), ptr);
+    }
+}
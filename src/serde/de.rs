@@ -1,7 +1,10 @@
 use crate::serde_ext::de::IntoDeserializer;
-use crate::{serde_ext, stry, Deserializer, Error, ErrorType, Node, Result, StaticNode};
+use crate::{
+    serde_ext, stry, Deserializer, Error, ErrorType, FieldMatching, Node, Result, StaticNode,
+};
 use serde_ext::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
 use serde_ext::forward_to_deserialize_any;
+use std::collections::HashMap;
 use std::str;
 
 impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de>
@@ -18,6 +21,10 @@ where
     where
         V: Visitor<'de>,
     {
+        // maps like halfbrown's don't ask for a map
+        if self.skip_null_as_default() {
+            return visitor.visit_map(CommaSeparated::new(self, 0));
+        }
         match stry!(self.next()) {
             Node::String(s) => visitor.visit_borrowed_str(s),
             Node::Static(StaticNode::Null) => visitor.visit_unit(),
@@ -53,6 +60,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_bool(false);
+        }
         match stry!(self.next()) {
             Node::Static(StaticNode::Bool(b)) => visitor.visit_bool(b),
             _c => Err(Deserializer::error(ErrorType::ExpectedBoolean)),
@@ -66,6 +76,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_borrowed_str("");
+        }
         if let Ok(Node::String(s)) = self.next() {
             visitor.visit_borrowed_str(s)
         } else {
@@ -78,6 +91,11 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_str("");
+        }
+        // strings are already unescaped into the input, owned targets
+        // copy them once into an allocation of the exact size
         if let Ok(Node::String(s)) = self.next() {
             visitor.visit_str(s)
         } else {
@@ -93,6 +111,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_i8(0);
+        }
         visitor.visit_i8(stry!(self.parse_i8()))
     }
 
@@ -102,6 +123,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_i16(0);
+        }
         visitor.visit_i16(stry!(self.parse_i16()))
     }
 
@@ -111,6 +135,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_i32(0);
+        }
         visitor.visit_i32(stry!(self.parse_i32()))
     }
 
@@ -119,6 +146,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_i64(0);
+        }
         visitor.visit_i64(stry!(self.parse_i64()))
     }
 
@@ -127,6 +157,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_i128(0);
+        }
         visitor.visit_i128(stry!(self.parse_i128()))
     }
 
@@ -136,6 +169,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_u8(0);
+        }
         visitor.visit_u8(stry!(self.parse_u8()))
     }
 
@@ -145,6 +181,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_u16(0);
+        }
         visitor.visit_u16(stry!(self.parse_u16()))
     }
 
@@ -154,6 +193,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_u32(0);
+        }
         visitor.visit_u32(stry!(self.parse_u32()))
     }
 
@@ -162,6 +204,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_u64(0);
+        }
         visitor.visit_u64(stry!(self.parse_u64()))
     }
 
@@ -170,6 +215,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_u128(0);
+        }
         visitor.visit_u128(stry!(self.parse_u128()))
     }
 
@@ -179,6 +227,14 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_f32(0.0);
+        }
+        // The tape only holds the `f64` parsed from the input, so this
+        // rounds twice, first to `f64` and then to `f32`. Like serde_json
+        // this is accepted: it only differs from rounding the decimal to
+        // `f32` directly when the `f64` lands exactly between two `f32`s,
+        // which takes more than 17 significant digits.
         let v: f64 = stry!(self.parse_double());
         visitor.visit_f32(v as f32)
     }
@@ -188,6 +244,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_f64(0.0);
+        }
         visitor.visit_f64(stry!(self.parse_double()))
     }
 
@@ -233,6 +292,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_seq(CommaSeparated::new(self, 0));
+        }
         // Parse the opening bracket of the sequence.
         if let Ok(Node::Array(len, _)) = self.next() {
             // Give the visitor access to each element of the sequence.
@@ -284,10 +346,16 @@ where
     // As is done here, serializers are encouraged to treat newtype structs as
     // insignificant wrappers around the data they contain. That means not
     // parsing anything other than the contained value.
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == super::lazy::TOKEN {
+            return self.deserialize_lazy(visitor);
+        }
+        if name == super::key_offset::TOKEN {
+            return self.deserialize_key_offset(visitor);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -296,6 +364,9 @@ where
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_map(CommaSeparated::new(self, 0));
+        }
         // Parse the opening bracket of the sequence.
         if let Ok(Node::Object(len, _)) = self.next() {
             // Give the visitor access to each element of the sequence.
@@ -309,15 +380,24 @@ where
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.skip_null_as_default() {
+            return visitor.visit_map(CommaSeparated::new(self, 0));
+        }
         match self.next() {
             // Give the visitor access to each element of the sequence.
-            Ok(Node::Object(len, _)) => visitor.visit_map(CommaSeparated::new(self, len)),
+            Ok(Node::Object(len, _)) if self.field_matching == FieldMatching::Exact => {
+                visitor.visit_map(CommaSeparated::new(self, len))
+            }
+            Ok(Node::Object(len, _)) => {
+                stry!(check_ambiguous_fields(self.field_matching, fields));
+                visitor.visit_map(CommaSeparated::with_fields(self, len, fields))
+            }
             Ok(Node::Array(len, _)) => visitor.visit_seq(CommaSeparated::new(self, len)),
             _ => Err(Deserializer::error(ErrorType::ExpectedMap)),
         }
@@ -411,12 +491,59 @@ impl<'de, 'a> de::VariantAccess<'de> for VariantAccess<'a, 'de> {
 struct CommaSeparated<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
     len: usize,
+    // struct fields keys are matched against when not using exact matching
+    fields: &'static [&'static str],
 }
 impl<'a, 'de> CommaSeparated<'a, 'de> {
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn new(de: &'a mut Deserializer<'de>, len: usize) -> Self {
-        CommaSeparated { de, len }
+        CommaSeparated {
+            de,
+            len,
+            fields: &[],
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn with_fields(
+        de: &'a mut Deserializer<'de>,
+        len: usize,
+        fields: &'static [&'static str],
+    ) -> Self {
+        CommaSeparated { de, len, fields }
+    }
+
+    /// Finds the field a key matches when it doesn't match one exactly
+    fn lenient_field(&self) -> Result<Option<&'static str>> {
+        if let Node::String(key) = stry!(self.de.peek()) {
+            if !self.fields.iter().any(|f| *f == key) {
+                let field_matching = self.de.field_matching;
+                return Ok(self
+                    .fields
+                    .iter()
+                    .find(|f| field_matching.matches(key, f))
+                    .copied());
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Fails if two fields of a struct can't be told apart with the given
+/// field matching mode.
+fn check_ambiguous_fields(
+    field_matching: FieldMatching,
+    fields: &'static [&'static str],
+) -> Result<()> {
+    let mut seen = HashMap::with_capacity(fields.len());
+    for b in fields {
+        if let Some(a) = seen.insert(field_matching.normalize(b), b) {
+            return Err(de::Error::custom(format!(
+                "fields `{a}` and `{b}` are ambiguous with {field_matching:?} field matching"
+            )));
+        }
     }
+    Ok(())
 }
 
 // `SeqAccess` is provided to the `Visitor` to give it the ability to iterate
@@ -456,6 +583,12 @@ impl<'de, 'a> MapAccess<'de> for CommaSeparated<'a, 'de> {
             Ok(None)
         } else {
             self.len -= 1;
+            if !self.fields.is_empty() {
+                if let Some(field) = stry!(self.lenient_field()) {
+                    self.de.skip();
+                    return seed.deserialize(field.into_deserializer()).map(Some);
+                }
+            }
             seed.deserialize(MapKey { de: &mut *self.de }).map(Some)
         }
     }
@@ -465,6 +598,8 @@ impl<'de, 'a> MapAccess<'de> for CommaSeparated<'a, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
+        // the key was the last node read, `KeyOffset` looks it up
+        self.de.last_key = self.de.idx;
         // read the value
         seed.deserialize(&mut *self.de)
     }
@@ -475,6 +610,115 @@ impl<'de, 'a> MapAccess<'de> for CommaSeparated<'a, 'de> {
     }
 }
 
+/// The elements of an array, as a standalone `SeqAccess` for custom
+/// deserializers, see `Deserializer::array_access`.
+///
+/// Elements are read in order, elements that are not read stay on the
+/// tape and have to be read before the deserializer is used for anything
+/// else.
+pub struct SimdSeqAccess<'a, 'de: 'a>(CommaSeparated<'a, 'de>);
+
+impl<'de, 'a> SeqAccess<'de> for SimdSeqAccess<'a, 'de> {
+    type Error = Error;
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.next_element_seed(seed)
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn size_hint(&self) -> Option<usize> {
+        SeqAccess::size_hint(&self.0)
+    }
+}
+
+/// The entries of an object, as a standalone `MapAccess` for custom
+/// deserializers, see `Deserializer::object_access`.
+///
+/// Keys and values are read in order, entries that are not read stay on
+/// the tape and have to be read before the deserializer is used for
+/// anything else.
+pub struct SimdMapAccess<'a, 'de: 'a>(CommaSeparated<'a, 'de>);
+
+impl<'de, 'a> MapAccess<'de> for SimdMapAccess<'a, 'de> {
+    type Error = Error;
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.0.next_key_seed(seed)
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.0.next_value_seed(seed)
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn size_hint(&self) -> Option<usize> {
+        MapAccess::size_hint(&self.0)
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// Reads the next value, which has to be an array, and returns a
+    /// `SeqAccess` over its elements.
+    ///
+    /// ```rust
+    /// use serde::de::SeqAccess;
+    /// let mut d = br#"[1, "two", [3]]"#.to_vec();
+    /// let mut de = simd_json::Deserializer::from_slice(&mut d).unwrap();
+    /// let mut seq = de.array_access().unwrap();
+    /// assert_eq!(seq.size_hint(), Some(3));
+    /// assert_eq!(seq.next_element::<u8>().unwrap(), Some(1));
+    /// assert_eq!(seq.next_element::<String>().unwrap(), Some("two".to_string()));
+    /// assert_eq!(seq.next_element::<Vec<u8>>().unwrap(), Some(vec![3]));
+    /// assert_eq!(seq.next_element::<u8>().unwrap(), None);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the next value is not an array.
+    pub fn array_access(&mut self) -> Result<SimdSeqAccess<'_, 'de>> {
+        match stry!(self.next()) {
+            Node::Array(len, _) => Ok(SimdSeqAccess(CommaSeparated::new(self, len))),
+            _ => Err(Self::error(ErrorType::ExpectedArray)),
+        }
+    }
+
+    /// Reads the next value, which has to be an object, and returns a
+    /// `MapAccess` over its entries.
+    ///
+    /// ```rust
+    /// use serde::de::MapAccess;
+    /// let mut d = br#"{"a": 1, "b": [2]}"#.to_vec();
+    /// let mut de = simd_json::Deserializer::from_slice(&mut d).unwrap();
+    /// let mut map = de.object_access().unwrap();
+    /// assert_eq!(map.next_entry::<String, u8>().unwrap(), Some(("a".to_string(), 1)));
+    /// assert_eq!(map.next_key::<String>().unwrap(), Some("b".to_string()));
+    /// assert_eq!(map.next_value::<Vec<u8>>().unwrap(), vec![2]);
+    /// assert_eq!(map.next_key::<String>().unwrap(), None);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the next value is not an object.
+    pub fn object_access(&mut self) -> Result<SimdMapAccess<'_, 'de>> {
+        match stry!(self.next()) {
+            Node::Object(len, _) => Ok(SimdMapAccess(CommaSeparated::new(self, len))),
+            _ => Err(Self::error(ErrorType::ExpectedMap)),
+        }
+    }
+}
+
 // `MapKey` is provided to the `Visitor` to give it the ability to parse integers
 // from string as JSON keys are always string
 struct MapKey<'de: 'a, 'a> {
@@ -0,0 +1,281 @@
+use serde_ext::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::BuildHasher;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A pool of strings that repeated string values are looked up in, so
+/// every distinct string is allocated once.
+pub trait Interner {
+    /// Returns the pooled string equal to `s`, `s` is added to the pool
+    /// if it isn't in it yet.
+    fn intern(&mut self, s: &str) -> Arc<str>;
+}
+
+impl<S> Interner for HashSet<Arc<str>, S>
+where
+    S: BuildHasher,
+{
+    fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(pooled) = self.get(s) {
+            return pooled.clone();
+        }
+        let pooled = Arc::<str>::from(s);
+        self.insert(pooled.clone());
+        pooled
+    }
+}
+
+/// A `DeserializeSeed` for a `T` whose strings are looked up in an
+/// `Interner` before they are allocated, `T` is one of `Arc<str>`,
+/// `Option<T>`, `Vec<T>` or `HashMap<Arc<str>, T>` with the keys interned
+/// as well.
+///
+/// Strings reach the seed as slices of the input, so with a pool that
+/// already holds a string deserializing it doesn't allocate. For structs
+/// the seed can be used for single fields from a hand written
+/// `DeserializeSeed` with `MapAccess::next_value_seed`.
+///
+/// ```rust
+/// use simd_json::serde::{from_slice_seed, Interned};
+/// use std::collections::HashSet;
+/// use std::sync::Arc;
+///
+/// let mut pool: HashSet<Arc<str>> = HashSet::new();
+/// let mut d = br#"["ACTIVE", "EU-WEST-1", "ACTIVE", null]"#.to_vec();
+/// let seed = Interned::<Vec<Option<Arc<str>>>>::new(&mut pool);
+/// let v = from_slice_seed(&mut d, seed).unwrap();
+/// assert_eq!(v[0].as_deref(), Some("ACTIVE"));
+/// assert!(Arc::ptr_eq(v[0].as_ref().unwrap(), v[2].as_ref().unwrap()));
+/// assert_eq!(pool.len(), 2);
+/// ```
+pub struct Interned<'pool, T> {
+    pool: &'pool mut dyn Interner,
+    marker: PhantomData<T>,
+}
+
+impl<'pool, T> Interned<'pool, T> {
+    /// A seed looking up strings in `pool`
+    pub fn new(pool: &'pool mut dyn Interner) -> Self {
+        Self {
+            pool,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Interned<'_, Arc<str>> {
+    type Value = Arc<str>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StrVisitor(self.pool))
+    }
+}
+
+impl<'de, T> DeserializeSeed<'de> for Interned<'_, Option<T>>
+where
+    for<'p> Interned<'p, T>: DeserializeSeed<'de, Value = T>,
+{
+    type Value = Option<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_option(OptionVisitor::<T>(Interned::new(self.pool)))
+    }
+}
+
+impl<'de, T> DeserializeSeed<'de> for Interned<'_, Vec<T>>
+where
+    for<'p> Interned<'p, T>: DeserializeSeed<'de, Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SeqVisitor::<T>(Interned::new(self.pool)))
+    }
+}
+
+impl<'de, T, S> DeserializeSeed<'de> for Interned<'_, HashMap<Arc<str>, T, S>>
+where
+    for<'p> Interned<'p, T>: DeserializeSeed<'de, Value = T>,
+    S: BuildHasher + Default,
+{
+    type Value = HashMap<Arc<str>, T, S>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapVisitor::<T, S>(Interned::new(self.pool)))
+    }
+}
+
+/// Don't preallocate more than this many elements based on a size hint
+const MAX_PREALLOCATED: usize = 4096;
+
+struct StrVisitor<'pool>(&'pool mut dyn Interner);
+
+impl<'de> Visitor<'de> for StrVisitor<'_> {
+    type Value = Arc<str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(self.0.intern(v))
+    }
+}
+
+struct OptionVisitor<'pool, T>(Interned<'pool, Option<T>>);
+
+impl<'de, T> Visitor<'de> for OptionVisitor<'_, T>
+where
+    for<'p> Interned<'p, T>: DeserializeSeed<'de, Value = T>,
+{
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an optional value")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Interned::<T>::new(self.0.pool)
+            .deserialize(deserializer)
+            .map(Some)
+    }
+}
+
+struct SeqVisitor<'pool, T>(Interned<'pool, Vec<T>>);
+
+impl<'de, T> Visitor<'de> for SeqVisitor<'_, T>
+where
+    for<'p> Interned<'p, T>: DeserializeSeed<'de, Value = T>,
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let pool = self.0.pool;
+        let mut v = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(MAX_PREALLOCATED));
+        while let Some(e) = seq.next_element_seed(Interned::<T>::new(&mut *pool))? {
+            v.push(e);
+        }
+        Ok(v)
+    }
+}
+
+struct MapVisitor<'pool, T, S>(Interned<'pool, HashMap<Arc<str>, T, S>>);
+
+impl<'de, T, S> Visitor<'de> for MapVisitor<'_, T, S>
+where
+    for<'p> Interned<'p, T>: DeserializeSeed<'de, Value = T>,
+    S: BuildHasher + Default,
+{
+    type Value = HashMap<Arc<str>, T, S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let pool = self.0.pool;
+        let mut m = HashMap::with_capacity_and_hasher(
+            map.size_hint().unwrap_or(0).min(MAX_PREALLOCATED),
+            S::default(),
+        );
+        while let Some(k) = map.next_key_seed(Interned::<Arc<str>>::new(&mut *pool))? {
+            let v = map.next_value_seed(Interned::<T>::new(&mut *pool))?;
+            m.insert(k, v);
+        }
+        Ok(m)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Interned, Interner};
+    use crate::serde::from_slice_seed;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Arc;
+
+    #[test]
+    fn pointer_equality() {
+        let mut pool: HashSet<Arc<str>> = HashSet::new();
+        let eu = pool.intern("EU-WEST-1");
+        let mut d = br#"{
+            "a": ["ACTIVE", "EU-WEST-1", "ACT\u0049VE", null],
+            "ACTIVE": [],
+            "b": [null, "EU-WEST-1", "a"]
+        }"#
+        .to_vec();
+        let seed = Interned::<HashMap<Arc<str>, Vec<Option<Arc<str>>>>>::new(&mut pool);
+        let m = from_slice_seed(&mut d, seed).expect("valid json");
+        assert_eq!(m.len(), 3);
+        let a = &m["a"];
+        let b = &m["b"];
+        let active = a[0].as_ref().expect("a string");
+        assert_eq!(&**active, "ACTIVE");
+        // escaped strings are interned after unescaping
+        assert!(Arc::ptr_eq(active, a[2].as_ref().expect("a string")));
+        // values that were already pooled are reused
+        assert!(Arc::ptr_eq(&eu, a[1].as_ref().expect("a string")));
+        assert!(Arc::ptr_eq(&eu, b[1].as_ref().expect("a string")));
+        // keys and values share the pool
+        let (key, _) = m.iter().find(|(k, _)| &***k == "ACTIVE").expect("a key");
+        assert!(Arc::ptr_eq(key, active));
+        let (key, _) = m.iter().find(|(k, _)| &***k == "a").expect("a key");
+        assert!(Arc::ptr_eq(key, b[2].as_ref().expect("a string")));
+        assert_eq!(a[3], None);
+        assert_eq!(pool.len(), 4);
+    }
+
+    #[test]
+    fn errors() {
+        let mut pool: HashSet<Arc<str>> = HashSet::new();
+        let mut d = br#"["a", 1]"#.to_vec();
+        let r = from_slice_seed(&mut d, Interned::<Vec<Arc<str>>>::new(&mut pool));
+        assert!(r.is_err());
+        let mut d = br#"{"a": 1}"#.to_vec();
+        let r = from_slice_seed(&mut d, Interned::<Vec<Arc<str>>>::new(&mut pool));
+        assert!(r.is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{Deserializer, Error, Node, Result};
+use serde_ext::de::value::UsizeDeserializer;
+use serde_ext::de::{self, Deserialize, DeserializeSeed, SeqAccess, Visitor};
+use serde_ext::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The newtype name `KeyOffset` asks for, our deserializer answers it
+/// with the offset of the key followed by the value.
+pub(crate) const TOKEN: &str = "$simd_json::private::KeyOffset";
+
+/// A value of an object together with the byte offset of its key in the
+/// input, the offset is the one of the key's opening quote.
+///
+/// The offset is only known when deserializing the value of a map or
+/// struct field with a `Deserializer`, it is `None` for other values,
+/// for flattened fields and with other deserializers. Serializing a
+/// `KeyOffset` serializes the value.
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use simd_json::KeyOffset;
+///
+/// #[derive(Deserialize)]
+/// struct Payment {
+///     account: KeyOffset<String>,
+///     amount: u64,
+/// }
+///
+/// let mut d = br#"{"amount": 10, "account": "12-34"}"#.to_vec();
+/// let payment: Payment = simd_json::from_slice(&mut d).unwrap();
+/// assert_eq!(payment.account.value, "12-34");
+/// assert_eq!(payment.account.offset, Some(15));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KeyOffset<T> {
+    /// The offset of the key in the input
+    pub offset: Option<usize>,
+    /// The value
+    pub value: T,
+}
+
+impl<'de, T> Deserialize<'de> for KeyOffset<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, KeyOffsetVisitor(PhantomData))
+    }
+}
+
+impl<T> Serialize for KeyOffset<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+struct KeyOffsetVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for KeyOffsetVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = KeyOffset<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    // only sent by our deserializer, see `Deserializer::deserialize_key_offset`
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let offset: usize = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value: T = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(KeyOffset {
+            offset: Some(offset),
+            value,
+        })
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(|value| KeyOffset {
+            offset: None,
+            value,
+        })
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// Hands the offset of the current key and the next value to a
+    /// `KeyOffset` visitor
+    pub(crate) fn deserialize_key_offset<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // the key is the last node that was read if we are deserializing
+        // the value of a map
+        let offset = match self.tape.get(self.idx) {
+            Some(Node::String(key)) if self.idx != 0 && self.idx == self.last_key => {
+                key.as_ptr() as usize - self.input_start - 1
+            }
+            _ => return visitor.visit_newtype_struct(self),
+        };
+        visitor.visit_seq(KeyOffsetAccess {
+            de: self,
+            offset: Some(offset),
+            done: false,
+        })
+    }
+}
+
+/// The offset followed by the value
+struct KeyOffsetAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    offset: Option<usize>,
+    done: bool,
+}
+
+impl<'de, 'a> SeqAccess<'de> for KeyOffsetAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(offset) = self.offset.take() {
+            seed.deserialize(UsizeDeserializer::new(offset)).map(Some)
+        } else if self.done {
+            Ok(None)
+        } else {
+            self.done = true;
+            seed.deserialize(&mut *self.de).map(Some)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.offset.is_some()) + usize::from(!self.done))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyOffset;
+    use crate::{from_slice, to_string, OwnedValue};
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize)]
+    struct Record {
+        id: KeyOffset<u64>,
+        tags: Vec<KeyOffset<String>>,
+        nested: Option<KeyOffset<HashMap<String, KeyOffset<OwnedValue>>>>,
+    }
+
+    #[test]
+    fn offsets() {
+        let input =
+            r#"{"tags": ["a"], "nésted": 1, "nested": {"x\"y": [1], "z": {"k": 2}}, "id": 7}"#;
+        let mut d = input.as_bytes().to_vec();
+        let r: Record = from_slice(&mut d).expect("valid json");
+        assert_eq!(r.id.value, 7);
+        assert_eq!(r.id.offset, input.find(r#""id""#));
+        // array elements don't have a key
+        assert_eq!(r.tags[0].offset, None);
+        let nested = r.nested.expect("nested is set");
+        assert_eq!(nested.offset, input.find(r#""nested""#));
+        assert_eq!(nested.value["x\"y"].offset, input.find(r#""x\"y""#));
+        assert_eq!(nested.value["z"].offset, input.find(r#""z""#));
+
+        let r: Record = from_slice(&mut br#"{"id": 1, "tags": [], "nested": null}"#.to_vec())
+            .expect("valid json");
+        assert_eq!(r.id.offset, Some(1));
+        assert!(r.nested.is_none());
+    }
+
+    #[test]
+    fn other_deserializers() {
+        let v: KeyOffset<Vec<u8>> = from_slice(&mut b"[1, 2]".to_vec()).expect("valid json");
+        assert_eq!(v.offset, None);
+        assert_eq!(v.value, vec![1, 2]);
+        let v: KeyOffset<u8> = serde_json::from_str("1").expect("valid json");
+        assert_eq!(v.offset, None);
+        let map: HashMap<String, KeyOffset<u8>> =
+            serde_json::from_str(r#"{"a": 1}"#).expect("valid json");
+        assert_eq!(map["a"].offset, None);
+        assert_eq!(to_string(&map["a"]).expect("serializable"), "1");
+    }
+}
@@ -0,0 +1,379 @@
+use crate::value::borrowed::{Object, Value};
+use crate::value::ObjectHasher;
+use crate::{stry, Deserializer, Error, ErrorType, Node, OwnedValue, Result, StaticNode};
+use serde_ext::de::value::{MapDeserializer, SeqDeserializer};
+use serde_ext::de::{self, Deserialize, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde_ext::forward_to_deserialize_any;
+use std::fmt;
+use std::iter;
+
+/// The newtype name `LazyValue` asks for, our deserializer answers it with
+/// the nodes of the value instead of the value itself.
+pub(crate) const TOKEN: &str = "$simd_json::private::LazyValue";
+
+/// A value that is kept as a range of the tape until it is needed.
+///
+/// Deserializing a `LazyValue` from a `Deserializer` only copies the tape
+/// nodes of the value, strings keep pointing into the input. The
+/// `BorrowedValue` is built by `materialize`, so nested objects that are
+/// never looked at are never allocated. With other deserializers the value
+/// is built right away and its strings are copied.
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use simd_json::{json, LazyValue};
+///
+/// #[derive(Deserialize)]
+/// struct Event<'a> {
+///     kind: String,
+///     #[serde(borrow)]
+///     payload: LazyValue<'a>,
+/// }
+///
+/// let mut d = br#"{"kind": "click", "payload": {"x": 1, "y": [2, 3]}}"#.to_vec();
+/// let event: Event = simd_json::from_slice(&mut d).unwrap();
+/// assert_eq!(event.kind, "click");
+/// assert_eq!(event.payload.materialize(), json!({"x": 1, "y": [2, 3]}));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyValue<'input> {
+    repr: Repr<'input>,
+}
+
+#[derive(Debug, Clone)]
+enum Repr<'input> {
+    Tape(Vec<Node<'input>>),
+    Value(Value<'input>),
+}
+
+impl<'input> LazyValue<'input> {
+    /// Builds the value, this allocates the arrays and objects it
+    /// contains every time it is called.
+    #[must_use]
+    pub fn materialize(&self) -> Value<'input> {
+        match &self.repr {
+            Repr::Tape(nodes) => build(&mut nodes.iter()),
+            Repr::Value(v) => v.clone(),
+        }
+    }
+
+    /// Builds the value, consuming the lazy value.
+    #[must_use]
+    pub fn into_value(self) -> Value<'input> {
+        match self.repr {
+            Repr::Tape(nodes) => build(&mut nodes.iter()),
+            Repr::Value(v) => v,
+        }
+    }
+}
+
+fn build<'input>(nodes: &mut std::slice::Iter<Node<'input>>) -> Value<'input> {
+    match nodes.next() {
+        Some(Node::Static(s)) => Value::Static(*s),
+        Some(Node::String(s)) => Value::from(*s),
+        Some(Node::Array(len, _)) => Value::Array((0..*len).map(|_| build(nodes)).collect()),
+        Some(Node::Object(len, _)) => {
+            let mut res = Object::with_capacity_and_hasher(*len, ObjectHasher::default());
+            for _ in 0..*len {
+                if let Some(Node::String(key)) = nodes.next() {
+                    let value = build(nodes);
+                    res.insert((*key).into(), value);
+                }
+            }
+            Value::from(res)
+        }
+        // the nodes always hold a complete value
+        None => Value::Static(StaticNode::Null),
+    }
+}
+
+/// Deserializes a `LazyValue`, for use with
+/// `#[serde(borrow, deserialize_with = "simd_json::lazy")]`.
+///
+/// # Errors
+///
+/// Will return `Err` if the input is not a JSON value.
+pub fn lazy<'de: 'input, 'input, D>(
+    deserializer: D,
+) -> std::result::Result<LazyValue<'input>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    LazyValue::deserialize(deserializer)
+}
+
+impl<'de: 'input, 'input> Deserialize<'de> for LazyValue<'input> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, LazyVisitor)
+    }
+}
+
+struct LazyVisitor;
+
+impl<'de> Visitor<'de> for LazyVisitor {
+    type Value = LazyValue<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    // only sent by our deserializer, see `Deserializer::deserialize_lazy`
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut nodes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(TapeNode(node)) = seq.next_element()? {
+            nodes.push(node);
+        }
+        Ok(LazyValue {
+            repr: Repr::Tape(nodes),
+        })
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // other deserializers don't have to hand out borrowed strings
+        OwnedValue::deserialize(deserializer).map(|v| LazyValue {
+            repr: Repr::Value(Value::from(v)),
+        })
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// Hands the tape nodes of the next value to a `LazyValue` visitor
+    pub(crate) fn deserialize_lazy<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let start = self.idx + 1;
+        let end = match stry!(self.peek()) {
+            Node::Array(_, end) | Node::Object(_, end) => end,
+            Node::String(_) | Node::Static(_) => start + 1,
+        };
+        let nodes = stry!(self
+            .tape
+            .get(start..end)
+            .ok_or_else(|| Self::error(ErrorType::Eof)));
+        self.idx = end - 1;
+        visitor.visit_seq(TapeNodes(nodes.iter()))
+    }
+}
+
+struct TapeNodes<'a, 'de>(std::slice::Iter<'a, Node<'de>>);
+
+impl<'de, 'a> SeqAccess<'de> for TapeNodes<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.0.next() {
+            Some(node) => seed.deserialize(NodeDeserializer(*node)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+/// Sends a single tape node through serde, arrays are a sequence and
+/// objects a map holding their length.
+struct NodeDeserializer<'de>(Node<'de>);
+
+impl<'de> de::Deserializer<'de> for NodeDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Node::String(s) => visitor.visit_borrowed_str(s),
+            Node::Static(StaticNode::Null) => visitor.visit_unit(),
+            Node::Static(StaticNode::Bool(b)) => visitor.visit_bool(b),
+            Node::Static(StaticNode::F64(n)) => visitor.visit_f64(n),
+            Node::Static(StaticNode::I64(n)) => visitor.visit_i64(n),
+            #[cfg(feature = "128bit")]
+            Node::Static(StaticNode::I128(n)) => visitor.visit_i128(n),
+            Node::Static(StaticNode::U64(n)) => visitor.visit_u64(n),
+            #[cfg(feature = "128bit")]
+            Node::Static(StaticNode::U128(n)) => visitor.visit_u128(n),
+            Node::Array(len, _) => visitor.visit_seq(SeqDeserializer::new(iter::once(len))),
+            Node::Object(len, _) => visitor.visit_map(MapDeserializer::new(iter::once((len, ())))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// A tape node read back from a `NodeDeserializer`
+struct TapeNode<'de>(Node<'de>);
+
+impl<'de> Deserialize<'de> for TapeNode<'de> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(TapeNodeVisitor)
+    }
+}
+
+struct TapeNodeVisitor;
+
+impl<'de> Visitor<'de> for TapeNodeVisitor {
+    type Value = TapeNode<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a tape node")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::String(v)))
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::Static(StaticNode::Null)))
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::Static(StaticNode::Bool(v))))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::Static(StaticNode::F64(v))))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::Static(StaticNode::I64(v))))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::Static(StaticNode::U64(v))))
+    }
+
+    #[cfg(feature = "128bit")]
+    fn visit_i128<E>(self, v: i128) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::Static(StaticNode::I128(v))))
+    }
+
+    #[cfg(feature = "128bit")]
+    fn visit_u128<E>(self, v: u128) -> std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(TapeNode(Node::Static(StaticNode::U128(v))))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let len = seq.next_element()?.unwrap_or_default();
+        Ok(TapeNode(Node::Array(len, 0)))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let len = map.next_key()?.unwrap_or_default();
+        map.next_value::<()>()?;
+        Ok(TapeNode(Node::Object(len, 0)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::LazyValue;
+    use crate::json;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Wide<'a> {
+        id: u64,
+        #[serde(borrow)]
+        profile: LazyValue<'a>,
+        #[serde(borrow, deserialize_with = "crate::lazy")]
+        tags: LazyValue<'a>,
+        name: LazyValue<'a>,
+        last: bool,
+    }
+
+    #[test]
+    fn lazy() {
+        let mut d = br#"{
+            "id": 7,
+            "profile": {"nested": {"a": [1, -2, 3.5, null]}, "s": "esc\"aped"},
+            "ignored": {"deep": [[[{}]]]},
+            "tags": ["x", "y"],
+            "name": "ferris",
+            "last": true
+        }"#
+        .to_vec();
+        let w: Wide = crate::from_slice(&mut d).unwrap();
+        assert_eq!(w.id, 7);
+        assert!(w.last);
+        let expected = json!({"nested": {"a": [1, -2, 3.5, null]}, "s": "esc\"aped"});
+        assert_eq!(w.profile.materialize(), expected);
+        assert_eq!(w.profile.into_value(), expected);
+        assert_eq!(w.tags.materialize(), json!(["x", "y"]));
+        assert_eq!(w.name.materialize(), "ferris");
+    }
+
+    #[test]
+    fn lazy_from_value() {
+        // other deserializers build the value right away
+        let v = crate::value::borrowed::Value::from(json!({
+            "id": 1, "profile": {"a": {}}, "tags": [], "name": null, "last": false
+        }));
+        let w: Wide = crate::serde::from_borrowed_value(v).unwrap();
+        assert_eq!(w.profile.materialize(), json!({"a": {}}));
+        assert_eq!(w.tags.materialize(), json!([]));
+        assert_eq!(w.name.materialize(), ());
+    }
+
+    #[test]
+    fn lazy_values() {
+        for input in ["1", "\"s\"", "[]", "{}", "[[1], {\"a\": []}]"] {
+            let mut d = input.as_bytes().to_vec();
+            let l: LazyValue = crate::from_slice(&mut d).unwrap();
+            let mut d = input.as_bytes().to_vec();
+            assert_eq!(l.materialize(), crate::to_borrowed_value(&mut d).unwrap());
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use super::to_writer;
+use crate::{serde_ext, Error, ErrorType};
+use serde_ext::ser;
+use std::io::{BufWriter, Write};
+
+/// The record separator that starts every record in RFC 7464 JSON text
+/// sequences
+const RS: u8 = 0x1E;
+
+/// How a `LineWriter` frames the documents it writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineFraming {
+    /// Newline delimited JSON (NDJSON / JSON lines), every document is
+    /// followed by `\n`
+    Lines,
+    /// JSON text sequences as defined in RFC 7464, every document is
+    /// preceded by the record separator `0x1E` and followed by `\n`
+    JsonSeq,
+}
+
+/// When a `LineWriter` flushes the records it wrote to the underlying
+/// writer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Records are buffered and written once the buffer is full, on
+    /// `flush`, `into_inner` or when the writer is dropped.
+    Buffered,
+    /// The underlying writer is flushed after every record, so readers
+    /// on the other end of a pipe or socket see every record right away.
+    EveryRecord,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        Self::Buffered
+    }
+}
+
+/// Writes a stream of documents, one compact document per record, as
+/// NDJSON or as RFC 7464 JSON text sequences.
+///
+/// The serialization buffer and the output buffer are kept across
+/// writes, a document that fails to serialize is not written at all.
+///
+/// ```rust
+/// use simd_json::serde::LineWriter;
+/// let mut w = LineWriter::ndjson(Vec::new());
+/// w.write(&[1, 2]).unwrap();
+/// w.write("three").unwrap();
+/// assert_eq!(w.into_inner().unwrap(), b"[1,2]\n\"three\"\n");
+/// ```
+#[derive(Debug)]
+pub struct LineWriter<W: Write> {
+    writer: BufWriter<W>,
+    buf: Vec<u8>,
+    framing: LineFraming,
+    flush: FlushPolicy,
+}
+
+impl<W: Write> LineWriter<W> {
+    /// Creates a writer with the given framing
+    #[must_use]
+    pub fn new(writer: W, framing: LineFraming) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            buf: Vec::with_capacity(512),
+            framing,
+            flush: FlushPolicy::default(),
+        }
+    }
+
+    /// Creates a writer for newline delimited JSON
+    #[must_use]
+    pub fn ndjson(writer: W) -> Self {
+        Self::new(writer, LineFraming::Lines)
+    }
+
+    /// Creates a writer for RFC 7464 JSON text sequences
+    #[must_use]
+    pub fn json_seq(writer: W) -> Self {
+        Self::new(writer, LineFraming::JsonSeq)
+    }
+
+    /// Sets when records are flushed, defaults to
+    /// `FlushPolicy::Buffered`.
+    #[must_use]
+    pub fn flush_policy(mut self, flush: FlushPolicy) -> Self {
+        self.flush = flush;
+        self
+    }
+
+    /// Writes a document as a single record
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the document can't be serialized or writing
+    /// to the underlying writer fails.
+    pub fn write<T>(&mut self, to: &T) -> crate::Result<()>
+    where
+        T: ser::Serialize + ?Sized,
+    {
+        self.buf.clear();
+        if self.framing == LineFraming::JsonSeq {
+            self.buf.push(RS);
+        }
+        to_writer(&mut self.buf, to)?;
+        // compact output escapes newlines in strings and never breaks
+        // lines, so a newline can only be the record terminator
+        debug_assert!(!self.buf.contains(&b'\n'));
+        self.buf.push(b'\n');
+        self.writer
+            .write_all(&self.buf)
+            .map_err(|err| Error::generic(ErrorType::Io(err)))?;
+        if self.flush == FlushPolicy::EveryRecord {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes all buffered records and flushes the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to or flushing the underlying writer
+    /// fails.
+    pub fn flush(&mut self) -> crate::Result<()> {
+        self.writer
+            .flush()
+            .map_err(|err| Error::generic(ErrorType::Io(err)))
+    }
+
+    /// The underlying writer
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    /// Writes all buffered records and returns the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing the buffered records fails.
+    pub fn into_inner(self) -> crate::Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|err| Error::generic(ErrorType::Io(err.into_error())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FlushPolicy, LineWriter};
+    use crate::Documents;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: usize,
+        name: String,
+    }
+
+    const RECORDS: usize = 1_000_000;
+
+    fn record(id: usize) -> Record {
+        Record {
+            id,
+            name: format!("line\n{id}"),
+        }
+    }
+
+    #[test]
+    fn ndjson_round_trip() {
+        let mut w = LineWriter::ndjson(Vec::new());
+        for id in 0..RECORDS {
+            w.write(&record(id)).expect("write");
+        }
+        let out = w.into_inner().expect("into_inner");
+        assert_eq!(out.iter().filter(|c| **c == b'\n').count(), RECORDS);
+        let mut n = 0;
+        for (id, doc) in Documents::ndjson(&out).enumerate() {
+            let v = doc.expect("valid json");
+            assert_eq!(v["id"], id);
+            assert_eq!(v["name"], format!("line\n{id}"));
+            n += 1;
+        }
+        assert_eq!(n, RECORDS);
+    }
+
+    #[test]
+    fn json_seq_round_trip() {
+        let mut w = LineWriter::json_seq(Vec::new());
+        for id in 0..RECORDS {
+            w.write(&record(id)).expect("write");
+        }
+        let out = w.into_inner().expect("into_inner");
+        assert_eq!(out.first(), Some(&0x1E));
+        let mut n = 0;
+        for (id, rec) in out.split(|c| *c == 0x1E).skip(1).enumerate() {
+            assert_eq!(rec.last(), Some(&b'\n'));
+            let mut rec = rec.to_vec();
+            let r: Record = crate::from_slice(&mut rec).expect("valid json");
+            assert_eq!(r, record(id));
+            n += 1;
+        }
+        assert_eq!(n, RECORDS);
+    }
+
+    #[test]
+    fn flush_every_record() {
+        let mut w = LineWriter::ndjson(Vec::new()).flush_policy(FlushPolicy::EveryRecord);
+        w.write(&1).expect("write");
+        assert_eq!(w.get_ref(), b"1\n");
+        w.write(&[true]).expect("write");
+        assert_eq!(w.get_ref(), b"1\n[true]\n");
+
+        let mut w = LineWriter::ndjson(Vec::new());
+        w.write(&1).expect("write");
+        assert!(w.get_ref().is_empty());
+        w.flush().expect("flush");
+        assert_eq!(w.get_ref(), b"1\n");
+    }
+}
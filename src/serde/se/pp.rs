@@ -1,5 +1,8 @@
-use crate::{serde_ext, stry, Error, ErrorType};
+use super::{sorted_key, write_display_string, MapKeySerializer};
+use crate::value::float::write_float;
+use crate::{serde_ext, stry, Error, ErrorType, SerializeOptions};
 use serde_ext::ser;
+use std::fmt;
 use std::io::Write;
 use std::result::Result;
 use std::str;
@@ -19,9 +22,7 @@ pub fn to_vec_pretty<T>(to: &T) -> crate::Result<Vec<u8>>
 where
     T: ser::Serialize + ?Sized,
 {
-    let v = Vec::with_capacity(512);
-    let mut s = PrettySerializer::new(v);
-    to.serialize(&mut s).map(|_| s.writer)
+    to_vec_pretty_with_opts(to, SerializeOptions::default())
 }
 
 /// Write a value to a string
@@ -36,6 +37,44 @@ where
     to_vec_pretty(to).map(|v| unsafe { String::from_utf8_unchecked(v) })
 }
 
+/// Write a value to a vector using the given options
+/// # Errors
+/// when the data can not be written
+#[inline]
+pub fn to_vec_pretty_with_opts<T>(to: &T, opts: SerializeOptions) -> crate::Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let v = Vec::with_capacity(512);
+    let mut s = PrettySerializer::new(v, opts);
+    to.serialize(&mut s).map(|_| s.writer)
+}
+
+/// Write a value to a string using the given options
+///
+/// ```rust
+/// use simd_json::{to_string_pretty_with_opts, ExponentStyle, SerializeOptions};
+/// use std::collections::HashMap;
+/// let v: HashMap<&str, f64> = [("b", 1e-7), ("a", 1e16)].into_iter().collect();
+/// let opts = SerializeOptions::default()
+///     .stable_key_order(true)
+///     .exponent_style(ExponentStyle::AlwaysPlus);
+/// assert_eq!(
+///     to_string_pretty_with_opts(&v, opts).unwrap(),
+///     "{\n  \"a\": 1e+16,\n  \"b\": 1e-7\n}"
+/// );
+/// ```
+///
+/// # Errors
+/// when the data can not be written
+#[inline]
+pub fn to_string_pretty_with_opts<T>(to: &T, opts: SerializeOptions) -> crate::Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec_pretty_with_opts(to, opts).map(|v| unsafe { String::from_utf8_unchecked(v) })
+}
+
 /// Write a value to a string
 /// # Errors
 /// when the data can not be written
@@ -45,19 +84,65 @@ where
     T: ser::Serialize + ?Sized,
     W: Write,
 {
-    let mut s = PrettySerializer::new(writer);
+    to_writer_pretty_with_opts(writer, to, SerializeOptions::default())
+}
+
+/// Write a value to a writer using the given options
+/// # Errors
+/// when the data can not be written
+#[inline]
+pub fn to_writer_pretty_with_opts<T, W>(
+    writer: W,
+    to: &T,
+    opts: SerializeOptions,
+) -> crate::Result<()>
+where
+    T: ser::Serialize + ?Sized,
+    W: Write,
+{
+    let mut s = PrettySerializer::new(writer, opts);
     to.serialize(&mut s)
 }
 struct PrettySerializer<W: Write> {
     writer: W,
     dent: u32,
+    opts: SerializeOptions,
 }
 impl<W: Write> PrettySerializer<W> {
-    fn new(writer: W) -> Self {
-        Self { writer, dent: 0 }
+    fn new(writer: W, opts: SerializeOptions) -> Self {
+        Self {
+            writer,
+            dent: 0,
+            opts,
+        }
+    }
+
+    /// A serializer for an entry of an object written with
+    /// `stable_key_order`, at the same indentation
+    fn entry(&self, entry: Vec<u8>) -> PrettySerializer<Vec<u8>> {
+        PrettySerializer {
+            writer: entry,
+            dent: self.dent,
+            opts: self.opts,
+        }
     }
 }
 
+/// Writes the buffered entries of an object in the order of their keys
+fn write_sorted<W: Write>(
+    s: &mut PrettySerializer<W>,
+    entries: &mut [(String, Vec<u8>)],
+) -> Result<(), Error> {
+    entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+    for (i, (_, entry)) in entries.iter().enumerate() {
+        if i > 0 {
+            iomap!(s.write(b","))?;
+        }
+        iomap!(s.new_line().and_then(|_| s.write(entry)))?;
+    }
+    Ok(())
+}
+
 impl<W> BaseGenerator for PrettySerializer<W>
 where
     W: Write,
@@ -72,6 +157,10 @@ where
         self.writer.write_all(&[min])
     }
     #[inline]
+    fn write_float(&mut self, num: f64) -> std::io::Result<()> {
+        write_float(&mut self.writer, num, self.opts.exponent_style)
+    }
+    #[inline]
     fn new_line(&mut self) -> std::io::Result<()> {
         self.write_char(b'\n').and_then(|_| match self.dent {
             0 => Ok(()),
@@ -241,6 +330,9 @@ where
 struct SerializeMap<'serializer, W: Write + 'serializer> {
     s: &'serializer mut PrettySerializer<W>,
     first: bool,
+    /// The unescaped keys and serialized entries of an object written
+    /// with `stable_key_order`
+    sorted: Option<Vec<(String, Vec<u8>)>>,
 }
 
 impl<'serializer, W> ser::SerializeMap for SerializeMap<'serializer, W>
@@ -257,17 +349,24 @@ where
         let SerializeMap {
             ref mut s,
             ref mut first,
+            ref mut sorted,
             ..
         } = *self;
 
-        if *first {
+        if let Some(sorted) = sorted {
+            *first = false;
+            let (key, mut entry) = sorted_key(key, s.opts)?;
+            entry.extend_from_slice(b": ");
+            sorted.push((key, entry));
+            Ok(())
+        } else if *first {
             *first = false;
             iomap!(s.new_line())
-                .and_then(|_| key.serialize(&mut **s))
+                .and_then(|_| key.serialize(MapKeySerializer { s: &mut **s }))
                 .and_then(|_| iomap!(s.write(b": ")))
         } else {
             iomap!(s.write(b",").and_then(|_| s.new_line()))
-                .and_then(|_| key.serialize(&mut **s))
+                .and_then(|_| key.serialize(MapKeySerializer { s: &mut **s }))
                 .and_then(|_| iomap!(s.write(b": ")))
         }
     }
@@ -276,16 +375,31 @@ where
     where
         T: serde_ext::Serialize,
     {
-        let SerializeMap { ref mut s, .. } = *self;
-        value.serialize(&mut **s)
+        let SerializeMap {
+            ref mut s,
+            ref mut sorted,
+            ..
+        } = *self;
+        if let Some((_, entry)) = sorted.as_mut().and_then(|sorted| sorted.last_mut()) {
+            let mut entry_s = s.entry(std::mem::take(entry));
+            value.serialize(&mut entry_s)?;
+            *entry = entry_s.writer;
+            Ok(())
+        } else {
+            value.serialize(&mut **s)
+        }
     }
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        if self.first {
+        let SerializeMap { s, first, sorted } = self;
+        if let Some(mut entries) = sorted {
+            write_sorted(s, &mut entries)?;
+        }
+        if first {
             Ok(())
         } else {
-            self.s.dedent();
-            iomap!(self.s.new_line().and_then(|_| self.s.write(b"}")))
+            s.dedent();
+            iomap!(s.new_line().and_then(|_| s.write(b"}")))
         }
     }
 }
@@ -308,9 +422,18 @@ where
         let SerializeMap {
             ref mut s,
             ref mut first,
-            ..
+            ref mut sorted,
         } = *self;
-        if *first {
+        if let Some(sorted) = sorted {
+            *first = false;
+            let mut entry = s.entry(Vec::new());
+            iomap!(entry
+                .write_simple_string(key)
+                .and_then(|_| entry.write(b": ")))?;
+            value.serialize(&mut entry)?;
+            sorted.push((key.to_string(), entry.writer));
+            Ok(())
+        } else if *first {
             *first = false;
             iomap!(s
                 .new_line()
@@ -320,6 +443,7 @@ where
         } else {
             iomap!(s
                 .write(b",")
+                .and_then(|_| s.new_line())
                 .and_then(|_| s.write_simple_string(key))
                 .and_then(|_| s.write(b": ")))
             .and_then(|_| value.serialize(&mut **s))
@@ -327,11 +451,15 @@ where
     }
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        if self.first {
+        let SerializeMap { s, first, sorted } = self;
+        if let Some(mut entries) = sorted {
+            write_sorted(s, &mut entries)?;
+        }
+        if first {
             Ok(())
         } else {
-            self.s.dedent();
-            iomap!(self.s.new_line().and_then(|_| self.s.write(b"}")))
+            s.dedent();
+            iomap!(s.new_line().and_then(|_| s.write(b"}")))
         }
     }
 }
@@ -372,6 +500,7 @@ where
         } else {
             iomap!(s
                 .write(b",")
+                .and_then(|_| s.new_line())
                 .and_then(|_| s.write_simple_string(key))
                 .and_then(|_| s.write(b": ")))
             .and_then(|_| value.serialize(&mut **s))
@@ -474,6 +603,13 @@ where
         iomap!(self.write_string(v))
     }
     #[inline]
+    fn collect_str<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + fmt::Display,
+    {
+        write_display_string(self, value)
+    }
+    #[inline]
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
         iomap!(self.write(b"[").and_then(|_| {
             if let Some((first, rest)) = v.split_first() {
@@ -524,12 +660,15 @@ where
     #[inline]
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: serde_ext::Serialize,
     {
+        if let Some(quoted) = super::raw::raw_token(name) {
+            return value.serialize(super::raw::RawWriter::new(&mut self.writer, quoted));
+        }
         value.serialize(self)
     }
 
@@ -605,6 +744,7 @@ where
             iomap!(self.write(b"{"))
         }
         .map(move |_| SerializeMap {
+            sorted: self.opts.stable_key_order.then(Vec::new),
             s: self,
             first: true,
         })
@@ -655,7 +795,9 @@ mod test {
     #[cfg(not(target_arch = "wasm32"))]
     use proptest::prelude::*;
 
-    #[cfg(not(feature = "128bit"))]
+    // With the string fallback 128 bit integers outside of the 64 bit range
+    // are written as strings, so they can't round trip as numbers
+    #[cfg(any(not(feature = "128bit"), feature = "128bit-string-fallback"))]
     #[cfg(not(target_arch = "wasm32"))]
     fn arb_json_value() -> BoxedStrategy<Value> {
         let leaf = prop_oneof![
@@ -687,7 +829,7 @@ mod test {
         .boxed()
     }
 
-    #[cfg(feature = "128bit")]
+    #[cfg(all(feature = "128bit", not(feature = "128bit-string-fallback")))]
     #[cfg(not(target_arch = "wasm32"))]
     fn arb_json_value() -> BoxedStrategy<Value> {
         let leaf = prop_oneof![
@@ -753,4 +895,66 @@ mod test {
         let v2: Value = from_slice(&mut res).expect("generated bad json");
         assert_eq!(v, v2);
     }
+
+    #[test]
+    fn with_opts() {
+        use crate::{ExponentStyle, SerializeOptions};
+        use std::collections::HashMap;
+        #[derive(serde::Serialize)]
+        struct Doc {
+            z: u8,
+            map: HashMap<String, Vec<HashMap<String, f64>>>,
+            a: Option<u8>,
+        }
+        let doc = Doc {
+            z: 1,
+            map: (0..3)
+                .rev()
+                .map(|i| {
+                    let inner = (0..2)
+                        .rev()
+                        .map(|j| (format!("n{j}"), f64::from(i + j) * 1e20));
+                    (format!("k{i}"), vec![inner.collect()])
+                })
+                .collect(),
+            a: None,
+        };
+        let expected = r#"{
+  "a": null,
+  "map": {
+    "k0": [
+      {
+        "n0": 0.0,
+        "n1": 1e20
+      }
+    ],
+    "k1": [
+      {
+        "n0": 1e20,
+        "n1": 2e20
+      }
+    ],
+    "k2": [
+      {
+        "n0": 2e20,
+        "n1": 3e20
+      }
+    ]
+  },
+  "z": 1
+}"#;
+        let opts = SerializeOptions::default().stable_key_order(true);
+        let s = crate::to_string_pretty_with_opts(&doc, opts).expect("to_string_pretty_with_opts");
+        assert_eq!(s, expected);
+        let mut w = Vec::new();
+        crate::to_writer_pretty_with_opts(&mut w, &doc, opts).expect("to_writer_pretty_with_opts");
+        assert_eq!(w, s.as_bytes());
+
+        let opts = opts.exponent_style(ExponentStyle::Uppercase);
+        assert_eq!(
+            crate::to_string_pretty_with_opts(&[1e20, -2.5e-9], opts)
+                .expect("to_string_pretty_with_opts"),
+            "[\n  1E20,\n  -2.5E-9\n]"
+        );
+    }
 }
@@ -0,0 +1,354 @@
+use crate::{serde_ext, Deserializer, Error, ErrorType, Node, Result};
+use serde_ext::ser::{self, Impossible, Serialize};
+use std::io::Write;
+
+/// The newtype name `ValidNumber` serializes with, our serializers write
+/// its content as is.
+pub(crate) const NUMBER_TOKEN: &str = "$simd_json::private::ValidNumber";
+/// The newtype name `RawString` serializes with, our serializers write
+/// its content in quotes.
+pub(crate) const STRING_TOKEN: &str = "$simd_json::private::RawString";
+
+/// Returns if a newtype is written verbatim and if it needs quotes
+pub(crate) fn raw_token(name: &str) -> Option<bool> {
+    if name == NUMBER_TOKEN {
+        Some(false)
+    } else if name == STRING_TOKEN {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// A JSON number that was validated once and is written verbatim by the
+/// serializers of this crate, so `1.50` stays `1.50`.
+///
+/// With other serializers the number is written as a string.
+///
+/// ```rust
+/// use simd_json::serde::ValidNumber;
+/// let n = ValidNumber::new("1.50").unwrap();
+/// assert_eq!(simd_json::to_string(&vec![n]).unwrap(), "[1.50]");
+/// assert!(ValidNumber::new("01").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidNumber(String);
+
+impl ValidNumber {
+    /// Validates `s` as a JSON number, whitespace around it is not
+    /// accepted.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is not a valid JSON number.
+    pub fn new(s: &str) -> Result<Self> {
+        if let Some((idx, c)) = s
+            .char_indices()
+            .find(|(_, c)| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            return Err(Error::new_c(idx, c, ErrorType::InvalidNumber));
+        }
+        crate::parse_number_str(s)?;
+        Ok(Self(s.to_string()))
+    }
+
+    /// The number as it will be written
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Serialize for ValidNumber {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(NUMBER_TOKEN, self.0.as_str())
+    }
+}
+
+/// The content of a JSON string, without the quotes, that is already
+/// escaped. It is validated once and written verbatim in quotes by the
+/// serializers of this crate, without escaping it again.
+///
+/// With other serializers the escaped content is written as a string, so
+/// it is escaped a second time.
+///
+/// ```rust
+/// use simd_json::serde::RawString;
+/// let s = RawString::new_escaped(r#"say \"hi\"\n"#).unwrap();
+/// assert_eq!(simd_json::to_string(&s).unwrap(), r#""say \"hi\"\n""#);
+/// assert!(RawString::new_escaped(r#"say "hi""#).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawString(String);
+
+impl RawString {
+    /// Validates `s` as the escaped content of a JSON string: it may not
+    /// contain unescaped quotes or control characters and all escapes
+    /// have to be valid.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is not the content of a valid JSON string.
+    pub fn new_escaped(s: &str) -> Result<Self> {
+        if let Some((idx, c)) = s.char_indices().find(|(_, c)| *c < ' ') {
+            return Err(Error::new_c(idx, c, ErrorType::UnexpectedCharacter));
+        }
+        let mut quoted = Vec::with_capacity(s.len() + 2);
+        quoted.push(b'"');
+        quoted.extend_from_slice(s.as_bytes());
+        quoted.push(b'"');
+        // an unescaped quote ends the string early and leaves trailing data
+        let de = Deserializer::from_slice(&mut quoted)?;
+        match de.tape.get(1) {
+            Some(Node::String(_)) if de.tape.len() == 2 => Ok(Self(s.to_string())),
+            _ => Err(Error::generic(ErrorType::ExpectedString)),
+        }
+    }
+
+    /// The escaped content as it will be written, without quotes
+    #[must_use]
+    pub fn as_escaped(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Serialize for RawString {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(STRING_TOKEN, self.0.as_str())
+    }
+}
+
+fn not_raw() -> Error {
+    Error::generic(ErrorType::Serde(
+        "raw JSON can only be written from a string".into(),
+    ))
+}
+
+/// Writes the string content of a `ValidNumber` or `RawString` without
+/// escaping it
+pub(crate) struct RawWriter<'w, W: Write> {
+    writer: &'w mut W,
+    quoted: bool,
+}
+
+impl<'w, W: Write> RawWriter<'w, W> {
+    pub(crate) fn new(writer: &'w mut W, quoted: bool) -> Self {
+        Self { writer, quoted }
+    }
+}
+
+macro_rules! reject {
+    ($($name:ident($t:ty)),*) => {
+        $(
+            fn $name(self, _v: $t) -> Result<()> {
+                Err(not_raw())
+            }
+        )*
+    };
+}
+
+impl<W: Write> ser::Serializer for RawWriter<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        if self.quoted {
+            self.writer.write_all(b"\"")?;
+            self.writer.write_all(v.as_bytes())?;
+            self.writer.write_all(b"\"")?;
+        } else {
+            self.writer.write_all(v.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    reject!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str)
+    );
+
+    fn serialize_none(self) -> Result<()> {
+        Err(not_raw())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<()> {
+        Err(not_raw())
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(not_raw())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        Err(not_raw())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()> {
+        Err(not_raw())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(not_raw())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(not_raw())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(not_raw())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(not_raw())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(not_raw())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(not_raw())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(not_raw())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RawString, ValidNumber};
+    use crate::{from_slice, json, to_owned_value, to_string, to_string_pretty, OwnedValue};
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Record {
+        amount: ValidNumber,
+        label: RawString,
+        extra: Vec<ValidNumber>,
+        by_name: BTreeMap<RawString, ValidNumber>,
+    }
+
+    #[test]
+    fn invalid() {
+        for n in [
+            "", "01", "1.", "+1", "-", ".5", "1e", "abc", " 1", "1 ", "1\n", "1,2",
+        ] {
+            assert!(ValidNumber::new(n).is_err(), "{n:?}");
+        }
+        for s in [
+            "\"",
+            r#"a"b"#,
+            r#"a", "b"#,
+            "\\",
+            r"a\",
+            r"\x",
+            r"\u12",
+            r"\ud800",
+            "tab\there",
+            "\n",
+        ] {
+            assert!(RawString::new_escaped(s).is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn verbatim() {
+        let mut by_name = BTreeMap::new();
+        by_name.insert(
+            RawString::new_escaped(r#"k\"ey"#).expect("valid string"),
+            ValidNumber::new("-0.0").expect("valid number"),
+        );
+        let record = Record {
+            amount: ValidNumber::new("1.50").expect("valid number"),
+            label: RawString::new_escaped(r"café \/ \n").expect("valid string"),
+            extra: ["0", "18446744073709551615", "2.5E-1"]
+                .iter()
+                .map(|n| ValidNumber::new(n).expect("valid number"))
+                .collect(),
+            by_name,
+        };
+        assert_eq!(record.label.as_escaped(), r"café \/ \n");
+        assert_eq!(record.amount.as_str(), "1.50");
+
+        let expected = json!({
+            "amount": 1.5,
+            "label": "café / \n",
+            "extra": [0, u64::MAX, 0.25],
+            "by_name": {"k\"ey": -0.0}
+        });
+        let s = to_string(&record).expect("serializable");
+        assert_eq!(
+            s,
+            r#"{"amount":1.50,"label":"café \/ \n","extra":[0,18446744073709551615,2.5E-1],"by_name":{"k\"ey":-0.0}}"#
+        );
+        assert_eq!(to_owned_value(&mut s.into_bytes()), Ok(expected.clone()));
+
+        let s = to_string_pretty(&record).expect("serializable");
+        assert!(s.contains("1.50"), "{s}");
+        let v: OwnedValue = from_slice(&mut s.into_bytes()).expect("valid json");
+        assert_eq!(v, expected);
+    }
+}
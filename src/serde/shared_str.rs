@@ -0,0 +1,72 @@
+use serde_ext::de::{self, Deserializer, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Strings are unescaped in place into the input, so every string,
+/// escaped or not, reaches the visitor as a `&str` and is copied exactly
+/// once into the target.
+struct StrVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for StrVisitor<T>
+where
+    T: for<'s> From<&'s str>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        Ok(T::from(v))
+    }
+}
+
+/// Deserializes an `Arc<str>` with a single allocation, for use with
+/// `#[serde(deserialize_with = "simd_json::serde::deserialize_arc_str")]`.
+///
+/// serde's own implementation (behind its `rc` feature) deserializes a
+/// `Box<str>` first and copies it into the `Arc`, which allocates twice.
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use std::sync::Arc;
+///
+/// #[derive(Deserialize)]
+/// struct Tag {
+///     #[serde(deserialize_with = "simd_json::serde::deserialize_arc_str")]
+///     name: Arc<str>,
+/// }
+///
+/// let mut d = br#"{"name": "crab"}"#.to_vec();
+/// let tag: Tag = simd_json::from_slice(&mut d).unwrap();
+/// assert_eq!(&*tag.name, "crab");
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the value is not a string.
+pub fn deserialize_arc_str<'de, D>(deserializer: D) -> Result<Arc<str>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_str(StrVisitor(PhantomData))
+}
+
+/// Deserializes an `Rc<str>` with a single allocation, the same as
+/// `deserialize_arc_str`.
+///
+/// # Errors
+///
+/// Will return `Err` if the value is not a string.
+pub fn deserialize_rc_str<'de, D>(deserializer: D) -> Result<Rc<str>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_str(StrVisitor(PhantomData))
+}
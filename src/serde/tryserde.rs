@@ -0,0 +1,83 @@
+//! Fallible typed deserialization that falls back to a DOM value, for
+//! pipelines that want a typed value when possible but must not lose
+//! documents that don't fit the type.
+
+use crate::{stry, Deserializer, Error, OwnedValue, Result};
+use serde::de::DeserializeOwned;
+
+/// Tries to deserialize a byte slice into `T`, if the JSON is valid but
+/// doesn't match `T` the already parsed document is returned as an
+/// `OwnedValue` along with the error, so it doesn't have to be parsed
+/// again. Note that the slice will be rewritten in the process.
+///
+/// ```rust
+/// use simd_json::serde::tryserde::try_from_slice_or_value;
+/// #[derive(serde::Deserialize)]
+/// struct Point {
+///     x: i64,
+///     y: i64,
+/// }
+/// let mut d = br#"{"x": 1, "y": 2}"#.to_vec();
+/// let p = try_from_slice_or_value::<Point>(&mut d).unwrap().ok().unwrap();
+/// assert_eq!((p.x, p.y), (1, 2));
+///
+/// let mut d = br#"{"x": 1}"#.to_vec();
+/// let (_e, v) = try_from_slice_or_value::<Point>(&mut d).unwrap().err().unwrap();
+/// assert_eq!(v, simd_json::json!({"x": 1}));
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON, the inner result holds the
+/// error and value if the JSON could not be deserialized into `T`.
+pub fn try_from_slice_or_value<T>(
+    s: &mut [u8],
+) -> Result<std::result::Result<T, (Error, OwnedValue)>>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = stry!(Deserializer::from_slice(s));
+    match T::deserialize(&mut deserializer) {
+        Ok(t) => Ok(Ok(t)),
+        Err(e) => Ok(Err((e, deserializer.into_owned_value()))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::try_from_slice_or_value;
+    use crate::json;
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    #[test]
+    fn typed() {
+        let mut d = br#"{"x": 1, "y": -2}"#.to_vec();
+        let r = try_from_slice_or_value::<Point>(&mut d).expect("valid json");
+        assert_eq!(r.ok(), Some(Point { x: 1, y: -2 }));
+    }
+
+    #[test]
+    fn fallback() {
+        let mut d = br#"{"x": 1, "y": "two", "z": [null]}"#.to_vec();
+        let r = try_from_slice_or_value::<Point>(&mut d).expect("valid json");
+        let (_, v) = r.err().expect("not a point");
+        assert_eq!(v, json!({"x": 1, "y": "two", "z": [null]}));
+
+        // arrays can be points as well, but not arrays of strings
+        let mut d = br#"["1", "2"]"#.to_vec();
+        let r = try_from_slice_or_value::<Point>(&mut d).expect("valid json");
+        let (_, v) = r.err().expect("not a point");
+        assert_eq!(v, json!(["1", "2"]));
+    }
+
+    #[test]
+    fn invalid_json() {
+        let mut d = br#"{"x": 1"#.to_vec();
+        assert!(try_from_slice_or_value::<Point>(&mut d).is_err());
+    }
+}
@@ -23,11 +23,29 @@ impl<'value> Serialize for Value<'value> {
             Value::Static(StaticNode::Bool(b)) => serializer.serialize_bool(*b),
             Value::Static(StaticNode::F64(f)) => serializer.serialize_f64(*f),
             Value::Static(StaticNode::U64(i)) => serializer.serialize_u64(*i),
-            #[cfg(feature = "128bit")]
+            #[cfg(all(feature = "128bit", not(feature = "128bit-string-fallback")))]
             Value::Static(StaticNode::U128(i)) => serializer.serialize_u128(*i),
+            #[cfg(feature = "128bit-string-fallback")]
+            Value::Static(StaticNode::U128(i)) => {
+                // Not every serializer supports 128 bit integers, so we use the
+                // 64 bit calls where possible and fall back to strings otherwise
+                if let Ok(i) = u64::try_from(*i) {
+                    serializer.serialize_u64(i)
+                } else {
+                    serializer.collect_str(i)
+                }
+            }
             Value::Static(StaticNode::I64(i)) => serializer.serialize_i64(*i),
-            #[cfg(feature = "128bit")]
+            #[cfg(all(feature = "128bit", not(feature = "128bit-string-fallback")))]
             Value::Static(StaticNode::I128(i)) => serializer.serialize_i128(*i),
+            #[cfg(feature = "128bit-string-fallback")]
+            Value::Static(StaticNode::I128(i)) => {
+                if let Ok(i) = i64::try_from(*i) {
+                    serializer.serialize_i64(i)
+                } else {
+                    serializer.collect_str(i)
+                }
+            }
             Value::String(s) => serializer.serialize_str(s),
             Value::Array(v) => {
                 let mut seq = serializer.serialize_seq(Some(v.len()))?;
@@ -427,8 +445,8 @@ impl<'se> serde_ext::Serializer for MapKeySerializer<'se> {
         value.serialize(self)
     }
 
-    fn serialize_bool(self, _value: bool) -> Result<Self::Ok> {
-        Err(key_must_be_a_string())
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok> {
+        Ok(Cow::from(if value { "true" } else { "false" }))
     }
 
     fn serialize_i8(self, value: i8) -> Result<Self::Ok> {
@@ -631,6 +649,69 @@ mod test {
         assert_eq!(s, "false");
     }
 
+    #[cfg(feature = "128bit-string-fallback")]
+    #[test]
+    fn int_128_fallback() {
+        let v = Value::Static(StaticNode::U128(42));
+        let s = serde_json::to_string(&v).expect("Failed to serialize");
+        assert_eq!(s, "42");
+        let v = Value::Static(StaticNode::U128(u128::MAX));
+        let s = serde_json::to_string(&v).expect("Failed to serialize");
+        assert_eq!(s, "\"340282366920938463463374607431768211455\"");
+        let v = Value::Static(StaticNode::I128(-42));
+        let s = serde_json::to_string(&v).expect("Failed to serialize");
+        assert_eq!(s, "-42");
+        let v = Value::Static(StaticNode::I128(i128::MIN));
+        let s = serde_json::to_string(&v).expect("Failed to serialize");
+        assert_eq!(s, "\"-170141183460469231731687303715884105728\"");
+    }
+
+    #[cfg(feature = "128bit-string-fallback")]
+    #[test]
+    fn int_128_fallback_msgpack() {
+        let v = Value::Array(vec![
+            Value::Static(StaticNode::U128(42)),
+            Value::Static(StaticNode::U128(u128::MAX)),
+            Value::Static(StaticNode::I128(-42)),
+            Value::Static(StaticNode::I128(i128::MIN)),
+        ]);
+        let b = rmp_serde::to_vec(&v).expect("Failed to serialize");
+        let r: crate::OwnedValue = rmp_serde::from_slice(&b).expect("Failed to deserialize");
+        assert_eq!(
+            r,
+            crate::OwnedValue::from(vec![
+                crate::OwnedValue::from(42_u64),
+                crate::OwnedValue::from(u128::MAX.to_string()),
+                crate::OwnedValue::from(-42_i64),
+                crate::OwnedValue::from(i128::MIN.to_string()),
+            ])
+        );
+    }
+
+    #[cfg(feature = "128bit-string-fallback")]
+    #[test]
+    fn int_128_fallback_cbor() {
+        let v = Value::Array(vec![
+            Value::Static(StaticNode::U128(42)),
+            Value::Static(StaticNode::U128(u128::MAX)),
+            Value::Static(StaticNode::I128(-42)),
+            Value::Static(StaticNode::I128(i128::MIN)),
+        ]);
+        let mut b = Vec::new();
+        ciborium::ser::into_writer(&v, &mut b).expect("Failed to serialize");
+        let r: crate::OwnedValue =
+            ciborium::de::from_reader(b.as_slice()).expect("Failed to deserialize");
+        assert_eq!(
+            r,
+            crate::OwnedValue::from(vec![
+                crate::OwnedValue::from(42_u64),
+                crate::OwnedValue::from(u128::MAX.to_string()),
+                crate::OwnedValue::from(-42_i64),
+                crate::OwnedValue::from(i128::MIN.to_string()),
+            ])
+        );
+    }
+
     #[test]
     fn float() {
         let v = Value::Static(StaticNode::F64(1.0));
@@ -20,11 +20,29 @@ impl Serialize for Value {
             Value::Static(StaticNode::Bool(b)) => serializer.serialize_bool(*b),
             Value::Static(StaticNode::F64(f)) => serializer.serialize_f64(*f),
             Value::Static(StaticNode::U64(i)) => serializer.serialize_u64(*i),
-            #[cfg(feature = "128bit")]
+            #[cfg(all(feature = "128bit", not(feature = "128bit-string-fallback")))]
             Value::Static(StaticNode::U128(i)) => serializer.serialize_u128(*i),
+            #[cfg(feature = "128bit-string-fallback")]
+            Value::Static(StaticNode::U128(i)) => {
+                // Not every serializer supports 128 bit integers, so we use the
+                // 64 bit calls where possible and fall back to strings otherwise
+                if let Ok(i) = u64::try_from(*i) {
+                    serializer.serialize_u64(i)
+                } else {
+                    serializer.collect_str(i)
+                }
+            }
             Value::Static(StaticNode::I64(i)) => serializer.serialize_i64(*i),
-            #[cfg(feature = "128bit")]
+            #[cfg(all(feature = "128bit", not(feature = "128bit-string-fallback")))]
             Value::Static(StaticNode::I128(i)) => serializer.serialize_i128(*i),
+            #[cfg(feature = "128bit-string-fallback")]
+            Value::Static(StaticNode::I128(i)) => {
+                if let Ok(i) = i64::try_from(*i) {
+                    serializer.serialize_i64(i)
+                } else {
+                    serializer.collect_str(i)
+                }
+            }
             Value::String(s) => serializer.serialize_str(s),
             Value::Array(v) => {
                 let mut seq = serializer.serialize_seq(Some(v.len()))?;
@@ -404,8 +422,8 @@ impl serde_ext::Serializer for MapKeySerializer {
         value.serialize(self)
     }
 
-    fn serialize_bool(self, _value: bool) -> Result<Self::Ok> {
-        Err(key_must_be_a_string())
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok> {
+        Ok(value.to_string())
     }
 
     fn serialize_i8(self, value: i8) -> Result<Self::Ok> {
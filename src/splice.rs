@@ -0,0 +1,252 @@
+use crate::pointer::{Pointer, Segment};
+use crate::prelude::*;
+use crate::{to_owned_value, Deserializer, Error, ErrorType, OwnedValue, Result};
+
+/// The structural indexes of a document, used to find the byte ranges
+/// of values without building a DOM.
+struct Spans<'input> {
+    input: &'input [u8],
+    structural_indexes: Vec<u32>,
+}
+
+impl Spans<'_> {
+    /// The byte offset of the `i`th structural character
+    fn pos(&self, i: usize) -> usize {
+        self.structural_indexes
+            .get(i)
+            .map_or(self.input.len(), |p| *p as usize)
+    }
+
+    /// The structural index following the value starting at `i`
+    fn skip(&self, i: usize) -> usize {
+        if !matches!(self.input[self.pos(i)], b'[' | b'{') {
+            return i + 1;
+        }
+        let mut depth = 0_usize;
+        let mut j = i;
+        loop {
+            match self.input[self.pos(j)] {
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' => depth -= 1,
+                _ => (),
+            }
+            j += 1;
+            if depth == 0 {
+                return j;
+            }
+        }
+    }
+
+    /// The byte range of the value starting at `i`, without the
+    /// whitespace following it
+    fn range(&self, i: usize) -> (usize, usize) {
+        let start = self.pos(i);
+        let next = self.pos(self.skip(i));
+        let end = self.input[start..next]
+            .iter()
+            .rposition(|c| !matches!(c, b' ' | b'\t' | b'\n' | b'\r'))
+            .map_or(start, |e| start + e + 1);
+        (start, end)
+    }
+
+    /// Checks if the key starting at `i` equals `key` once unescaped
+    fn key_eq(&self, i: usize, key: &str) -> Result<bool> {
+        let (start, end) = self.range(i);
+        let raw = &self.input[start + 1..end - 1];
+        if !raw.contains(&b'\\') {
+            return Ok(raw == key.as_bytes());
+        }
+        let mut quoted = self.input[start..end].to_vec();
+        let unescaped = to_owned_value(&mut quoted)?;
+        Ok(unescaped.as_str() == Some(key))
+    }
+
+    /// Follows `pointer` from the root and returns the byte range of the
+    /// value it points to
+    fn locate(&self, pointer: Pointer) -> Result<(usize, usize)> {
+        // the first structural index is the root pseudo element
+        let mut i = 1;
+        for segment in pointer.segments() {
+            let at = self.pos(i);
+            let no_value = || Error::new(at, None, ErrorType::NoValueAtPointer);
+            match (self.input[at], segment) {
+                (b'[', Segment::Index(idx)) => {
+                    let mut j = i + 1;
+                    if self.input[self.pos(j)] == b']' {
+                        return Err(no_value());
+                    }
+                    for _ in 0..idx {
+                        // the element is followed by a comma or the
+                        // closing bracket
+                        j = self.skip(j);
+                        if self.input[self.pos(j)] == b']' {
+                            return Err(no_value());
+                        }
+                        j += 1;
+                    }
+                    i = j;
+                }
+                (b'{', segment) => {
+                    let key = segment.to_key();
+                    let mut found = None;
+                    let mut j = i + 1;
+                    while self.input[self.pos(j)] != b'}' {
+                        // key, colon, value and a comma or the closing brace
+                        let value = j + 2;
+                        if self.key_eq(j, &key)? {
+                            // like a DOM the last duplicate key wins
+                            found = Some(value);
+                        }
+                        j = self.skip(value);
+                        if self.input[self.pos(j)] == b',' {
+                            j += 1;
+                        }
+                    }
+                    i = found.ok_or_else(no_value)?;
+                }
+                _ => return Err(no_value()),
+            }
+        }
+        Ok(self.range(i))
+    }
+}
+
+/// Replaces values in a document without re-serializing all of it, each
+/// edit is a pointer to a value and the value to put in its place.
+///
+/// Only the replacement values are serialized, everything else is copied
+/// from `original` byte by byte so formatting, whitespace and number
+/// representations outside of the edited values are kept. The pointers
+/// are resolved against the original document, so edits can't add or
+/// remove keys or elements.
+///
+/// `original` is validated by parsing a copy of it to a tape, so this
+/// needs memory for a copy of the document and its tape, but no values
+/// are built.
+///
+/// ```rust
+/// use simd_json::{json, pointer::Pointer, splice};
+/// let original = br#"{ "name": "simd",  "version": 1.0e0, "tags": [ "fast" ] }"#;
+/// let edits = [
+///     (Pointer::new("/version").unwrap(), json!(2)),
+///     (Pointer::new("/tags/0").unwrap(), json!("faster")),
+/// ];
+/// let patched = splice(original, &edits).unwrap();
+/// assert_eq!(
+///     patched,
+///     br#"{ "name": "simd",  "version": 2, "tags": [ "faster" ] }"#
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `original` is invalid JSON, a pointer doesn't
+/// point to a value or two edits overlap, that is they point to the
+/// same value or one of them points into the value of the other.
+pub fn splice(original: &[u8], edits: &[(Pointer<'_>, OwnedValue)]) -> Result<Vec<u8>> {
+    if original.len() > crate::MAX_INPUT_LEN {
+        return Err(Error::generic(ErrorType::InputTooLarge));
+    }
+    // the structural pass alone doesn't validate atoms, stage 2 does
+    crate::to_tape(&mut original.to_vec())?;
+    let structural_indexes =
+        unsafe { Deserializer::find_structural_bits(original) }.map_err(Error::generic)?;
+    let spans = Spans {
+        input: original,
+        structural_indexes,
+    };
+
+    let mut ranges = Vec::with_capacity(edits.len());
+    for (pointer, value) in edits {
+        let (start, end) = spans.locate(*pointer)?;
+        ranges.push((start, end, value));
+    }
+    ranges.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut res = Vec::with_capacity(original.len());
+    let mut copied = 0;
+    for (start, end, value) in ranges {
+        if start < copied {
+            return Err(Error::new(start, None, ErrorType::OverlappingEdits));
+        }
+        res.extend_from_slice(&original[copied..start]);
+        res.extend_from_slice(value.encode().as_bytes());
+        copied = end;
+    }
+    res.extend_from_slice(&original[copied..]);
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::splice;
+    use crate::pointer::Pointer;
+    use crate::{json, to_owned_value, ErrorType, OwnedValue};
+
+    fn edit(p: &str, v: OwnedValue) -> (Pointer, OwnedValue) {
+        (Pointer::new(p).expect("valid pointer"), v)
+    }
+
+    #[test]
+    fn untouched_bytes_are_kept() {
+        let original = br#"{
+    "id" : 1.50E+2,
+    "a\/b": "old",
+    "list": [ 0 , 1, [true], {"x": null}  ],
+    "small": 1.000e-3
+}
+"#;
+        let edits = [
+            edit("/list/3/x", json!({"new": [1, 2]})),
+            edit("/a~1b", json!("new \"value\"")),
+            edit("/list/1", json!(-7)),
+        ];
+        let patched = splice(original, &edits).expect("valid edits");
+
+        let old_str = br#""old""#;
+        let old_start = original
+            .windows(old_str.len())
+            .position(|w| w == old_str)
+            .expect("old value");
+        // everything before the first edit is copied verbatim
+        assert_eq!(patched[..old_start], original[..old_start]);
+        let tail = b"}  ],\n    \"small\": 1.000e-3\n}\n";
+        assert!(patched.ends_with(tail));
+        assert!(patched
+            .windows(b"[ 0 , -7, [true], {\"x\": {".len())
+            .any(|w| w == b"[ 0 , -7, [true], {\"x\": {"));
+
+        let mut expected = to_owned_value(&mut original.to_vec()).expect("valid json");
+        expected["a/b"] = json!("new \"value\"");
+        expected["list"][1] = json!(-7);
+        expected["list"][3]["x"] = json!({"new": [1, 2]});
+        let mut patched = patched;
+        assert_eq!(to_owned_value(&mut patched).expect("valid json"), expected);
+    }
+
+    #[test]
+    fn whole_document_and_no_edits() {
+        let original = b"  [1, 2]  ";
+        let patched = splice(original, &[edit("", json!({"a": 1}))]).expect("valid edit");
+        assert_eq!(patched, br#"  {"a":1}  "#);
+        assert_eq!(splice(original, &[]).expect("no edits"), original);
+        let patched = splice(b"[1,2,3]", &[edit("/2", json!(null))]).expect("valid edit");
+        assert_eq!(patched, b"[1,2,null]");
+    }
+
+    #[test]
+    fn errors() {
+        let original = br#"{"a": [1, {"b": 2}], "c": "d"}"#;
+        let e = splice(original, &[edit("/a", json!(1)), edit("/a/1/b", json!(3))])
+            .expect_err("nested edits");
+        assert_eq!(e.error(), &ErrorType::OverlappingEdits);
+        let e = splice(original, &[edit("/c", json!(1)), edit("/c", json!(2))])
+            .expect_err("same value twice");
+        assert_eq!(e.error(), &ErrorType::OverlappingEdits);
+        for p in ["/x", "/a/2", "/a/0/b", "/c/0", "/a/b"] {
+            let e = splice(original, &[edit(p, json!(1))]).expect_err(p);
+            assert_eq!(e.error(), &ErrorType::NoValueAtPointer, "{p}");
+        }
+        assert!(splice(b"[1, tru]", &[edit("/0", json!(2))]).is_err());
+    }
+}
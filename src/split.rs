@@ -0,0 +1,314 @@
+use crate::{Deserializer, Error, ErrorType, Result};
+
+/// A direct child of the top level container, for objects `colon` holds
+/// the position of the `:` separating key and value.
+struct Member {
+    start: usize,
+    colon: Option<usize>,
+    end: usize,
+}
+
+#[cfg_attr(not(feature = "no-inline"), inline)]
+fn trim_start(s: &[u8]) -> &[u8] {
+    let start = s
+        .iter()
+        .position(|c| !matches!(c, b' ' | b'\t' | b'\n' | b'\r'))
+        .unwrap_or(s.len());
+    &s[start..]
+}
+
+#[cfg_attr(not(feature = "no-inline"), inline)]
+fn trim_end(s: &[u8]) -> &[u8] {
+    let end = s
+        .iter()
+        .rposition(|c| !matches!(c, b' ' | b'\t' | b'\n' | b'\r'))
+        .map_or(0, |i| i + 1);
+    &s[..end]
+}
+
+/// Uses the structural pass to find the byte ranges of the direct children
+/// of the top level array or object. Strings and nesting are validated,
+/// atoms such as numbers are not.
+fn split_container(input: &[u8], open: u8, close: u8) -> Result<Vec<Member>> {
+    let is_object = open == b'{';
+    let content_error = || {
+        if is_object {
+            ErrorType::ExpectedObjectContent
+        } else {
+            ErrorType::ExpectedArrayContent
+        }
+    };
+
+    if input.len() > crate::MAX_INPUT_LEN {
+        return Err(Error::generic(ErrorType::InputTooLarge));
+    }
+    let structural_indexes =
+        unsafe { Deserializer::find_structural_bits(input) }.map_err(Error::generic)?;
+
+    let mut members = Vec::new();
+    let mut stack: Vec<u8> = Vec::new();
+    // state of the member we are currently looking at
+    let mut start: Option<usize> = None;
+    let mut colon: Option<usize> = None;
+    let mut values: usize = 0;
+    let mut done = false;
+
+    // skip the root element
+    for &idx in structural_indexes.iter().skip(1) {
+        let idx = idx as usize;
+        let c = input[idx];
+        if done {
+            return Err(Error::new_c(idx, c as char, ErrorType::TrailingData));
+        }
+        if stack.is_empty() {
+            if c != open {
+                let t = if is_object {
+                    ErrorType::ExpectedMap
+                } else {
+                    ErrorType::ExpectedArray
+                };
+                return Err(Error::new_c(idx, c as char, t));
+            }
+            stack.push(c);
+            continue;
+        }
+        if stack.len() == 1 {
+            if c == b',' || c == close {
+                if let Some(member_start) = start {
+                    let complete = if is_object {
+                        colon.is_some() && values == 2
+                    } else {
+                        values == 1
+                    };
+                    if !complete {
+                        return Err(Error::new_c(idx, c as char, content_error()));
+                    }
+                    members.push(Member {
+                        start: member_start,
+                        colon,
+                        end: idx,
+                    });
+                } else if c == b',' || !members.is_empty() {
+                    // leading, double or trailing commas
+                    return Err(Error::new_c(idx, c as char, content_error()));
+                }
+                start = None;
+                colon = None;
+                values = 0;
+                if c == close {
+                    stack.pop();
+                    done = true;
+                }
+                continue;
+            }
+            if c == b':' {
+                if !is_object || colon.is_some() || values != 1 {
+                    return Err(Error::new_c(idx, ':', ErrorType::UnexpectedCharacter));
+                }
+                colon = Some(idx);
+                continue;
+            }
+            if is_object && start.is_none() && c != b'"' {
+                return Err(Error::new_c(idx, c as char, ErrorType::ExpectedObjectKey));
+            }
+            let allowed = if colon.is_some() { 2 } else { 1 };
+            if values == allowed {
+                let t = match (is_object, colon) {
+                    (false, _) => ErrorType::ExpectedArrayComma,
+                    (true, None) => ErrorType::ExpectedObjectColon,
+                    (true, Some(_)) => ErrorType::ExpectedMapComma,
+                };
+                return Err(Error::new_c(idx, c as char, t));
+            }
+            values += 1;
+            start.get_or_insert(idx);
+        }
+        match c {
+            b'[' | b'{' => stack.push(c),
+            b']' | b'}' => {
+                let expected = if c == b']' { b'[' } else { b'{' };
+                if stack.pop() != Some(expected) {
+                    return Err(Error::new_c(idx, c as char, ErrorType::Syntax));
+                }
+            }
+            _ => (),
+        }
+    }
+    if done {
+        Ok(members)
+    } else {
+        Err(Error::new(input.len(), None, ErrorType::Eof))
+    }
+}
+
+/// Splits a top level JSON array into the raw bytes of its elements
+/// without unescaping strings or building a DOM. Each element is itself
+/// a JSON document that can be handed to any of the parsing functions.
+///
+/// Only the structure (strings, nesting and separators) is validated,
+/// so elements may still be invalid JSON, for example misspelled atoms.
+///
+/// ```rust
+/// let input = br#"[1, "a,]", {"b": [2, 3]}]"#;
+/// let elements: Vec<&[u8]> = simd_json::split_array(input).unwrap().collect();
+/// assert_eq!(elements, vec![&b"1"[..], br#""a,]""#, br#"{"b": [2, 3]}"#]);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `input` is not a structurally valid JSON array.
+pub fn split_array(input: &[u8]) -> Result<impl Iterator<Item = &[u8]>> {
+    let members = split_container(input, b'[', b']')?;
+    Ok(members
+        .into_iter()
+        .map(move |m| trim_end(&input[m.start..m.end])))
+}
+
+/// Splits a top level JSON object into the raw bytes of its keys and
+/// values without unescaping strings or building a DOM. Keys include
+/// their quotes, so both keys and values are JSON documents on their own.
+///
+/// Only the structure (strings, nesting and separators) is validated,
+/// so values may still be invalid JSON, for example misspelled atoms.
+///
+/// ```rust
+/// let input = br#"{"a": 1, "b\"": [2, "}"]}"#;
+/// let members: Vec<(&[u8], &[u8])> = simd_json::split_object(input).unwrap().collect();
+/// assert_eq!(members, vec![(&br#""a""#[..], &b"1"[..]), (br#""b\"""#, br#"[2, "}"]"#)]);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `input` is not a structurally valid JSON object.
+pub fn split_object(input: &[u8]) -> Result<impl Iterator<Item = (&[u8], &[u8])>> {
+    let members = split_container(input, b'{', b'}')?;
+    Ok(members.into_iter().map(move |m| {
+        // objects always have a colon, see `split_container`
+        let colon = m.colon.unwrap_or(m.start);
+        (
+            trim_end(&input[m.start..colon]),
+            trim_start(trim_end(&input[colon + 1..m.end])),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_array, split_object};
+    use crate::{to_owned_value, Error, ErrorType};
+
+    fn array(input: &[u8]) -> Vec<&[u8]> {
+        split_array(input).expect("valid array").collect()
+    }
+
+    fn error(r: crate::Result<impl Iterator>) -> Error {
+        match r {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert!(array(b"[]").is_empty());
+        assert!(array(b" [ ] ").is_empty());
+        assert_eq!(split_object(b"{}").expect("valid object").count(), 0);
+    }
+
+    #[test]
+    fn elements() {
+        let input = br#" [ 1 , "a]b", "c,d" , "e\"]f\\", [1, [2]], {"x": ",]"}, null ] "#;
+        let elements = array(input);
+        assert_eq!(
+            elements,
+            vec![
+                &b"1"[..],
+                br#""a]b""#,
+                br#""c,d""#,
+                br#""e\"]f\\""#,
+                b"[1, [2]]",
+                br#"{"x": ",]"}"#,
+                b"null"
+            ]
+        );
+        for e in elements {
+            let mut e = e.to_vec();
+            assert!(to_owned_value(&mut e).is_ok());
+        }
+    }
+
+    #[test]
+    fn members() {
+        let input = br#"{"a" : 1, "b]\"": [",", "}"], "c": {"d": "e"} }"#;
+        let members: Vec<(&[u8], &[u8])> = split_object(input).expect("valid object").collect();
+        assert_eq!(
+            members,
+            vec![
+                (&br#""a""#[..], &b"1"[..]),
+                (br#""b]\"""#, br#"[",", "}"]"#),
+                (br#""c""#, br#"{"d": "e"}"#),
+            ]
+        );
+        for (k, v) in members {
+            let mut k = k.to_vec();
+            let mut v = v.to_vec();
+            assert!(to_owned_value(&mut k).is_ok());
+            assert!(to_owned_value(&mut v).is_ok());
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error(split_array(b"")).error(), &ErrorType::Eof);
+        assert_eq!(error(split_array(b"[1, 2")).error(), &ErrorType::Eof);
+        assert_eq!(error(split_array(b"{}")).error(), &ErrorType::ExpectedArray);
+        assert_eq!(
+            error(split_array(b"[1] 2")).error(),
+            &ErrorType::TrailingData
+        );
+        assert_eq!(
+            error(split_array(b"[1 2]")).error(),
+            &ErrorType::ExpectedArrayComma
+        );
+        assert_eq!(
+            error(split_array(b"[1,]")).error(),
+            &ErrorType::ExpectedArrayContent
+        );
+        assert_eq!(
+            error(split_array(b"[,1]")).error(),
+            &ErrorType::ExpectedArrayContent
+        );
+        assert_eq!(
+            error(split_array(b"[1,,2]")).error(),
+            &ErrorType::ExpectedArrayContent
+        );
+        assert_eq!(error(split_array(b"[[1}]")).error(), &ErrorType::Syntax);
+        assert_eq!(
+            error(split_array(b"[1: 2]")).error(),
+            &ErrorType::UnexpectedCharacter
+        );
+        assert_eq!(error(split_array(br#"["a]"#)).error(), &ErrorType::Syntax);
+
+        assert_eq!(error(split_object(b"[]")).error(), &ErrorType::ExpectedMap);
+        assert_eq!(
+            error(split_object(b"{1: 2}")).error(),
+            &ErrorType::ExpectedObjectKey
+        );
+        assert_eq!(
+            error(split_object(br#"{"a" "b": 2}"#)).error(),
+            &ErrorType::ExpectedObjectColon
+        );
+        assert_eq!(
+            error(split_object(br#"{"a": 1 2}"#)).error(),
+            &ErrorType::ExpectedMapComma
+        );
+        assert_eq!(
+            error(split_object(br#"{"a"}"#)).error(),
+            &ErrorType::ExpectedObjectContent
+        );
+        assert_eq!(
+            error(split_object(br#"{"a":}"#)).error(),
+            &ErrorType::ExpectedObjectContent
+        );
+    }
+}
@@ -2,7 +2,7 @@
 use crate::charutils::is_not_structural_or_whitespace;
 use crate::safer_unchecked::GetSaferUnchecked;
 use crate::value::tape::Node;
-use crate::{Deserializer, Error, ErrorType, Result};
+use crate::{try_vec, Deserializer, Error, ErrorType, ParseOptions, Result};
 use value_trait::StaticNode;
 
 #[cfg_attr(not(feature = "no-inline"), inline(always))]
@@ -101,18 +101,36 @@ impl<'de> Deserializer<'de> {
         unused_unsafe,
         clippy::uninit_vec
     )]
-    pub(crate) fn build_tape(
+    pub(crate) fn build_tape<const FALLIBLE: bool>(
         input: &'de mut [u8],
         input2: &[u8],
         buffer: &mut [u8],
         structural_indexes: &[u32],
+        opts: ParseOptions,
     ) -> Result<Vec<Node<'de>>> {
         // While a valid json can have at max len/2 (`[[[]]]`)elements that are relevant
         // a invalid json might exceed this `[[[[[[` and we need to protect against that.
-        let mut res: Vec<Node<'de>> = Vec::with_capacity(structural_indexes.len());
-        let mut stack = Vec::with_capacity(structural_indexes.len());
+        let (mut res, mut stack): (Vec<Node<'de>>, Vec<(StackState, usize, usize)>) = if FALLIBLE {
+            (
+                try_vec(structural_indexes.len())?,
+                try_vec(structural_indexes.len())?,
+            )
+        } else {
+            (
+                Vec::with_capacity(structural_indexes.len()),
+                Vec::with_capacity(structural_indexes.len()),
+            )
+        };
 
         let mut depth: usize = 0;
+        let max_depth = opts.max_depth.unwrap_or(usize::MAX);
+        let max_string_bytes = opts.max_string_bytes.unwrap_or(usize::MAX);
+        let max_string_len = opts.max_string_len.unwrap_or(usize::MAX);
+        let max_tape_nodes = opts.max_tape_nodes.unwrap_or(usize::MAX);
+        let max_object_entries = opts.max_object_entries.unwrap_or(usize::MAX);
+        let max_array_len = opts.max_array_len.unwrap_or(usize::MAX);
+        let number_mode = opts.number_mode;
+        let mut string_bytes: usize = 0;
         let mut last_start = 1;
         let mut cnt: usize = 0;
         let mut r_i = 0;
@@ -146,6 +164,13 @@ impl<'de> Deserializer<'de> {
 
         macro_rules! insert_res {
             ($t:expr) => {
+                // the dummy root node at index 0 is not counted
+                if unlikely!(r_i > max_tape_nodes) {
+                    fail!(ErrorType::TapeNodesLimit {
+                        limit: max_tape_nodes,
+                        actual: r_i,
+                    });
+                }
                 unsafe {
                     res.as_mut_ptr().add(r_i).write($t);
                     r_i += 1;
@@ -179,13 +204,42 @@ impl<'de> Deserializer<'de> {
             }};
         }
 
-        insert_res!(Node::Static(StaticNode::Null));
-
         macro_rules! insert_str {
             () => {
-                insert_res!(Node::String(s2try!(Self::parse_str_(
-                    input, &input2, buffer, idx
-                ))));
+                let s = s2try!(Self::parse_str_(input, &input2, buffer, idx));
+                if unlikely!(s.len() > max_string_len) {
+                    fail!(ErrorType::StringLengthLimit {
+                        limit: max_string_len,
+                        actual: s.len(),
+                    });
+                }
+                string_bytes += s.len();
+                if unlikely!(string_bytes > max_string_bytes) {
+                    fail!(ErrorType::StringBytesLimit {
+                        limit: max_string_bytes,
+                        actual: string_bytes,
+                    });
+                }
+                insert_res!(Node::String(s));
+            };
+        }
+
+        macro_rules! insert_number {
+            ($negative:expr) => {
+                let n = s2try!(Self::parse_number(idx, input2, $negative));
+                insert_res!(Node::Static(number_mode.convert(n)));
+            };
+        }
+
+        // checks the element count of the current array or object
+        macro_rules! check_len {
+            ($max:expr, $limit:ident) => {
+                if unlikely!(cnt > $max) {
+                    fail!(ErrorType::$limit {
+                        limit: $max,
+                        actual: cnt,
+                    });
+                }
             };
         }
 
@@ -198,6 +252,7 @@ impl<'de> Deserializer<'de> {
                 match c {
                     b',' => {
                         cnt += 1;
+                        check_len!(max_array_len, ArrayLengthLimit);
                         update_char!();
                         goto!(MainArraySwitch);
                     }
@@ -217,6 +272,7 @@ impl<'de> Deserializer<'de> {
                 match c {
                     b',' => {
                         cnt += 1;
+                        check_len!(max_object_entries, ObjectEntriesLimit);
                         update_char!();
                         if c == b'"' {
                             insert_str!();
@@ -241,6 +297,7 @@ impl<'de> Deserializer<'de> {
                     cnt = 0;
                     goto!(ScopeEnd);
                 }
+                check_len!(max_array_len, ArrayLengthLimit);
                 goto!(MainArraySwitch);
             };
         }
@@ -250,6 +307,7 @@ impl<'de> Deserializer<'de> {
                 update_char!();
                 match c {
                     b'"' => {
+                        check_len!(max_object_entries, ObjectEntriesLimit);
                         insert_str!();
                         goto!(ObjectKey)
                     }
@@ -284,6 +342,8 @@ impl<'de> Deserializer<'de> {
                 return Err(Error::new_c(idx, c as char, $t));
             };
         }
+        insert_res!(Node::Static(StaticNode::Null));
+
         // State start, we pull this outside of the
         // loop to reduce the number of required checks
         update_char!();
@@ -297,12 +357,19 @@ impl<'de> Deserializer<'de> {
                 last_start = r_i;
                 insert_res!(Node::Object(0, 0));
 
+                if unlikely!(depth >= max_depth) {
+                    fail!(ErrorType::DepthLimit {
+                        limit: max_depth,
+                        actual: depth + 1
+                    });
+                }
                 depth += 1;
                 cnt = 1;
 
                 update_char!();
                 match c {
                     b'"' => {
+                        check_len!(max_object_entries, ObjectEntriesLimit);
                         insert_str!();
                         state = State::ObjectKey;
                     }
@@ -324,6 +391,12 @@ impl<'de> Deserializer<'de> {
                 last_start = r_i;
                 insert_res!(Node::Array(0, 0));
 
+                if unlikely!(depth >= max_depth) {
+                    fail!(ErrorType::DepthLimit {
+                        limit: max_depth,
+                        actual: depth + 1
+                    });
+                }
                 depth += 1;
                 cnt = 1;
 
@@ -332,6 +405,7 @@ impl<'de> Deserializer<'de> {
                     cnt = 0;
                     state = State::ScopeEnd;
                 } else {
+                    check_len!(max_array_len, ArrayLengthLimit);
                     state = State::MainArraySwitch;
                 }
             }
@@ -379,7 +453,7 @@ impl<'de> Deserializer<'de> {
                 fail!(ErrorType::TrailingData);
             }
             b'-' => {
-                insert_res!(Node::Static(s2try!(Self::parse_number(idx, input2, true))));
+                insert_number!(true);
 
                 if i == structural_indexes.len() {
                     success!();
@@ -387,7 +461,7 @@ impl<'de> Deserializer<'de> {
                 fail!(ErrorType::TrailingData);
             }
             b'0'..=b'9' => {
-                insert_res!(Node::Static(s2try!(Self::parse_number(idx, input2, false))));
+                insert_number!(false);
 
                 if i == structural_indexes.len() {
                     success!();
@@ -436,16 +510,12 @@ impl<'de> Deserializer<'de> {
                             object_continue!();
                         }
                         b'-' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number(
-                                idx, input2, true
-                            ))));
+                            insert_number!(true);
 
                             object_continue!();
                         }
                         b'0'..=b'9' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number(
-                                idx, input2, false
-                            ))));
+                            insert_number!(false);
 
                             object_continue!();
                         }
@@ -456,6 +526,12 @@ impl<'de> Deserializer<'de> {
                             }
                             last_start = r_i;
                             insert_res!(Node::Object(0, 0));
+                            if unlikely!(depth >= max_depth) {
+                                fail!(ErrorType::DepthLimit {
+                                    limit: max_depth,
+                                    actual: depth + 1
+                                });
+                            }
                             depth += 1;
                             cnt = 1;
                             object_begin!();
@@ -467,6 +543,12 @@ impl<'de> Deserializer<'de> {
                             }
                             last_start = r_i;
                             insert_res!(Node::Array(0, 0));
+                            if unlikely!(depth >= max_depth) {
+                                fail!(ErrorType::DepthLimit {
+                                    limit: max_depth,
+                                    actual: depth + 1
+                                });
+                            }
                             depth += 1;
                             cnt = 1;
                             array_begin!();
@@ -542,16 +624,12 @@ impl<'de> Deserializer<'de> {
                             array_continue!();
                         }
                         b'-' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number(
-                                idx, input2, true
-                            ))));
+                            insert_number!(true);
 
                             array_continue!();
                         }
                         b'0'..=b'9' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number(
-                                idx, input2, false
-                            ))));
+                            insert_number!(false);
 
                             array_continue!();
                         }
@@ -562,6 +640,12 @@ impl<'de> Deserializer<'de> {
                             }
                             last_start = r_i;
                             insert_res!(Node::Object(0, 0));
+                            if unlikely!(depth >= max_depth) {
+                                fail!(ErrorType::DepthLimit {
+                                    limit: max_depth,
+                                    actual: depth + 1
+                                });
+                            }
                             depth += 1;
                             cnt = 1;
                             object_begin!();
@@ -573,6 +657,12 @@ impl<'de> Deserializer<'de> {
                             }
                             last_start = r_i;
                             insert_res!(Node::Array(0, 0));
+                            if unlikely!(depth >= max_depth) {
+                                fail!(ErrorType::DepthLimit {
+                                    limit: max_depth,
+                                    actual: depth + 1
+                                });
+                            }
                             depth += 1;
                             cnt = 1;
                             array_begin!();
@@ -0,0 +1,447 @@
+use crate::value::borrowed::{self, Value as BorrowedValue};
+use crate::value::owned::{to_value_with_buffers, Value};
+use crate::{AlignedBuf, Error, SIMDJSON_PADDING};
+use std::fmt;
+use std::ops::Range;
+
+/// How documents are separated in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Framing {
+    /// One document per line (NDJSON / JSON lines)
+    Lines,
+    /// Documents follow each other, optionally separated by whitespace
+    Concatenated,
+}
+
+/// An error for a single document in a multi document input
+#[derive(Debug, PartialEq)]
+pub struct DocumentError {
+    /// The error encountered while parsing the document
+    pub error: Error,
+    /// The bytes of the input that were skipped because of the error
+    pub skipped: Range<usize>,
+}
+
+impl fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in document at bytes {}..{}",
+            self.error, self.skipped.start, self.skipped.end
+        )
+    }
+}
+
+impl std::error::Error for DocumentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Iterates over the documents of an input holding multiple JSON
+/// documents, either one per line or concatenated. The parser buffers
+/// are reused between documents.
+///
+/// By default iteration stops after the first error, with
+/// `recoverable(true)` the iterator skips to the next plausible
+/// document and keeps going: the next line for NDJSON, the end of the
+/// broken document or the next `{` / `[` for concatenated documents.
+///
+/// ```rust
+/// use simd_json::Documents;
+/// let input = b"{\"a\": 1}\n{\"a\": \n[2]\n";
+/// let docs: Vec<_> = Documents::ndjson(input).recoverable(true).collect();
+/// assert_eq!(docs.len(), 3);
+/// assert!(docs[0].is_ok());
+/// assert_eq!(docs[1].as_ref().map_err(|e| e.skipped.clone()), Err(9..14));
+/// assert!(docs[2].is_ok());
+/// ```
+pub struct Documents<'input> {
+    input: &'input [u8],
+    pos: usize,
+    framing: Framing,
+    recoverable: bool,
+    done: bool,
+    buffer: Vec<u8>,
+    input_buffer: AlignedBuf,
+    string_buffer: Vec<u8>,
+}
+
+impl<'input> Documents<'input> {
+    fn new(input: &'input [u8], framing: Framing) -> Self {
+        Self {
+            input,
+            pos: 0,
+            framing,
+            recoverable: false,
+            done: false,
+            buffer: Vec::new(),
+            input_buffer: AlignedBuf::with_capacity(SIMDJSON_PADDING * 2),
+            string_buffer: Vec::new(),
+        }
+    }
+
+    /// Iterates over newline delimited documents (NDJSON / JSON lines),
+    /// empty lines are skipped.
+    #[must_use]
+    pub fn ndjson(input: &'input [u8]) -> Self {
+        Self::new(input, Framing::Lines)
+    }
+
+    /// Iterates over concatenated documents, such as `{"a":1}{"a":2} 3`.
+    /// Scalar documents have to be separated by whitespace.
+    #[must_use]
+    pub fn concatenated(input: &'input [u8]) -> Self {
+        Self::new(input, Framing::Concatenated)
+    }
+
+    /// Sets if the iterator keeps going after an invalid document,
+    /// defaults to `false`.
+    #[must_use]
+    pub fn recoverable(mut self, recoverable: bool) -> Self {
+        self.recoverable = recoverable;
+        self
+    }
+
+    fn parse(&mut self, doc: Range<usize>) -> crate::Result<Value> {
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&self.input[doc]);
+        let len = self.buffer.len() + SIMDJSON_PADDING;
+        if self.string_buffer.len() < len {
+            self.string_buffer.resize(len, 0);
+        }
+        to_value_with_buffers(
+            &mut self.buffer,
+            &mut self.input_buffer,
+            &mut self.string_buffer,
+        )
+    }
+
+    /// Finds the next document, returns its range and the position to
+    /// continue from after it.
+    fn next_document(&self) -> Option<(Range<usize>, usize)> {
+        let mut start = self.pos;
+        loop {
+            start += self.input.get(start..)?.iter().position(|c| !is_ws(*c))?;
+            match self.framing {
+                Framing::Lines => {
+                    let line_end = self.input[start..]
+                        .iter()
+                        .position(|c| *c == b'\n')
+                        .map_or(self.input.len(), |p| start + p);
+                    let end = start
+                        + self.input[start..line_end]
+                            .iter()
+                            .rposition(|c| !is_ws(*c))
+                            .map_or(0, |p| p + 1);
+                    if end > start {
+                        return Some((start..end, line_end + 1));
+                    }
+                    start = line_end + 1;
+                }
+                Framing::Concatenated => {
+                    let end = document_end(self.input, start);
+                    return Some(match end {
+                        Some(end) => (start..end, end),
+                        // unterminated documents run to the end of the input,
+                        // when recovering we restart at the next container
+                        None => (start..self.input.len(), self.input.len()),
+                    });
+                }
+            }
+        }
+    }
+
+    /// The position to continue from after a broken document at `doc`
+    fn resume_after_error(&self, doc: &Range<usize>, next: usize) -> usize {
+        if self.framing == Framing::Concatenated && next == self.input.len() {
+            self.input[doc.start + 1..]
+                .iter()
+                .position(|c| *c == b'{' || *c == b'[')
+                .map_or(self.input.len(), |p| doc.start + 1 + p)
+        } else {
+            next
+        }
+    }
+}
+
+impl<'input> Iterator for Documents<'input> {
+    type Item = std::result::Result<Value, DocumentError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (doc, next) = if let Some(d) = self.next_document() {
+            d
+        } else {
+            self.done = true;
+            return None;
+        };
+        match self.parse(doc.clone()) {
+            Ok(v) => {
+                self.pos = next;
+                Some(Ok(v))
+            }
+            Err(error) => {
+                let skipped = if self.recoverable {
+                    let resume = self.resume_after_error(&doc, next);
+                    self.pos = resume;
+                    doc.start..doc.end.min(resume)
+                } else {
+                    self.done = true;
+                    doc
+                };
+                Some(Err(DocumentError { error, skipped }))
+            }
+        }
+    }
+}
+
+/// Iterates over the lines of newline delimited JSON (NDJSON / JSON
+/// lines) and parses each line in place into a borrowed value, empty
+/// lines are skipped. The parser buffers are reused between lines.
+///
+/// Every line is its own part of `input` and strings are unescaped within
+/// the line, so values of different lines don't share any memory and can
+/// be kept while iterating further. A line that fails to parse yields an
+/// error and iteration continues with the next line.
+///
+/// ```rust
+/// let mut input = b"{\"a\": \"x\\ny\"}\n\n[1, 2]\n{broken\n3".to_vec();
+/// let lines: Vec<_> = simd_json::iter_lines_borrowed(&mut input).collect();
+/// assert_eq!(lines.len(), 4);
+/// assert_eq!(lines[0].as_ref().unwrap()["a"], "x\ny");
+/// assert_eq!(lines[1].as_ref().unwrap()[1], 2);
+/// assert!(lines[2].is_err());
+/// assert_eq!(lines[3].as_ref().unwrap(), &3);
+/// ```
+pub fn iter_lines_borrowed(input: &mut [u8]) -> BorrowedLines<'_> {
+    BorrowedLines {
+        rest: input,
+        input_buffer: AlignedBuf::with_capacity(SIMDJSON_PADDING * 2),
+        string_buffer: Vec::new(),
+    }
+}
+
+/// The iterator returned by `iter_lines_borrowed`
+pub struct BorrowedLines<'input> {
+    rest: &'input mut [u8],
+    input_buffer: AlignedBuf,
+    string_buffer: Vec<u8>,
+}
+
+impl<'input> BorrowedLines<'input> {
+    /// Splits off the next line, without its newline
+    fn next_line(&mut self) -> Option<&'input mut [u8]> {
+        let rest = std::mem::take(&mut self.rest);
+        if rest.is_empty() {
+            return None;
+        }
+        Some(match rest.iter().position(|c| *c == b'\n') {
+            Some(p) => {
+                let (line, tail) = rest.split_at_mut(p);
+                self.rest = &mut tail[1..];
+                line
+            }
+            None => rest,
+        })
+    }
+}
+
+impl<'input> Iterator for BorrowedLines<'input> {
+    type Item = crate::Result<BorrowedValue<'input>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.next_line()?;
+            let start = line.iter().position(|c| !is_ws(*c)).unwrap_or(line.len());
+            let end = line
+                .iter()
+                .rposition(|c| !is_ws(*c))
+                .map_or(start, |p| p + 1);
+            if start == end {
+                continue;
+            }
+            let line = &mut line[start..end];
+            let len = line.len() + SIMDJSON_PADDING;
+            if self.string_buffer.len() < len {
+                self.string_buffer.resize(len, 0);
+            }
+            return Some(borrowed::to_value_with_buffers(
+                line,
+                &mut self.input_buffer,
+                &mut self.string_buffer,
+            ));
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "no-inline"), inline)]
+fn is_ws(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Finds the end of the document starting at `start` by only looking at
+/// strings and nesting, the content is validated by the parser.
+fn document_end(input: &[u8], start: usize) -> Option<usize> {
+    let mut depth: usize = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, &c) in input.iter().enumerate().skip(start) {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == b'\\' {
+                escaped = true;
+            } else if c == b'"' {
+                in_string = false;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            continue;
+        }
+        match c {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                // a stray closing bracket at the top level is handed to the
+                // parser as a document of its own
+                if depth <= 1 {
+                    return Some(i + 1);
+                }
+                depth -= 1;
+            }
+            c if depth == 0 && is_ws(c) => return Some(i),
+            _ => (),
+        }
+    }
+    if depth == 0 && !in_string {
+        Some(input.len())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{iter_lines_borrowed, Documents};
+    use crate::prelude::*;
+    use crate::{json, BorrowedValue, OwnedValue};
+
+    fn collect(docs: Documents) -> Vec<Result<OwnedValue, std::ops::Range<usize>>> {
+        docs.map(|d| d.map_err(|e| e.skipped)).collect()
+    }
+
+    #[test]
+    fn ndjson() {
+        let input = b"{\"a\": 1}\n\n  [2]  \r\n\"three\"";
+        assert_eq!(
+            collect(Documents::ndjson(input)),
+            vec![Ok(json!({"a": 1})), Ok(json!([2])), Ok(json!("three"))]
+        );
+        assert!(Documents::ndjson(b"").next().is_none());
+        assert!(Documents::ndjson(b"\n \n").next().is_none());
+    }
+
+    #[test]
+    fn ndjson_fail_stop() {
+        let input = b"1\n{broken\n3\n";
+        assert_eq!(
+            collect(Documents::ndjson(input)),
+            vec![Ok(json!(1)), Err(2..9)]
+        );
+    }
+
+    #[test]
+    fn ndjson_recover() {
+        // start, middle and end
+        let input = b"{x\n1\n[2,\n3\n{\"a\": tru}";
+        assert_eq!(
+            collect(Documents::ndjson(input).recoverable(true)),
+            vec![
+                Err(0..2),
+                Ok(json!(1)),
+                Err(5..8),
+                Ok(json!(3)),
+                Err(11..21)
+            ]
+        );
+    }
+
+    #[test]
+    fn ndjson_recover_string_with_newline() {
+        let input = b"{\"a\": \"line\none\"}\n{\"b\": 2}\n";
+        assert_eq!(
+            collect(Documents::ndjson(input).recoverable(true)),
+            vec![Err(0..11), Err(12..17), Ok(json!({"b": 2}))]
+        );
+    }
+
+    #[test]
+    fn concatenated() {
+        let input = br#"{"a": "}"}[1,[2]] 3 "four"{"b":{}}"#;
+        assert_eq!(
+            collect(Documents::concatenated(input)),
+            vec![
+                Ok(json!({"a": "}"})),
+                Ok(json!([1, [2]])),
+                Ok(json!(3)),
+                Ok(json!("four")),
+                Ok(json!({"b": {}}))
+            ]
+        );
+    }
+
+    #[test]
+    fn concatenated_recover() {
+        // a broken but balanced document is skipped as a whole
+        let input = br#"{"a": tru}[1]"#;
+        assert_eq!(
+            collect(Documents::concatenated(input).recoverable(true)),
+            vec![Err(0..10), Ok(json!([1]))]
+        );
+        // an unterminated document restarts at the next container
+        let input = br#"{"a": [1 {"b": 2}"#;
+        assert_eq!(
+            collect(Documents::concatenated(input).recoverable(true)),
+            vec![Err(0..6), Err(6..9), Ok(json!({"b": 2}))]
+        );
+        // without recovery the rest of the input is reported as skipped
+        let input = br#"[1]{"a": "#;
+        assert_eq!(
+            collect(Documents::concatenated(input)),
+            vec![Ok(json!([1])), Err(3..9)]
+        );
+    }
+
+    #[test]
+    fn lines_borrowed_kept_across_lines() {
+        let mut input =
+            b"{\"a\": \"\\u00e9\\n\", \"b\": [\"x\"]}\r\n \n\"\\\"q\\\"\"\n[{\"a\": \"y\"}]\n{\"a\": }\n  null  "
+                .to_vec();
+        // all values are kept until the end, their strings point into
+        // their own lines
+        let lines: Vec<_> = iter_lines_borrowed(&mut input).collect();
+        assert_eq!(lines.len(), 5);
+        let first = lines[0].as_ref().expect("valid json");
+        assert_eq!(
+            OwnedValue::from(first.clone()),
+            json!({"a": "é\n", "b": ["x"]})
+        );
+        assert_eq!(
+            lines[1].as_ref().expect("valid json"),
+            &BorrowedValue::from("\"q\"")
+        );
+        assert_eq!(lines[2].as_ref().expect("valid json")[0]["a"], "y");
+        assert!(lines[3].is_err());
+        assert!(lines[4].as_ref().expect("valid json").is_null());
+        assert_eq!(first["b"][0], "x");
+
+        assert!(iter_lines_borrowed(&mut []).next().is_none());
+        assert!(iter_lines_borrowed(&mut b"\n \r\n".to_vec())
+            .next()
+            .is_none());
+    }
+}
@@ -3,6 +3,7 @@ use std::ops::Range;
 use crate::charutils::{codepoint_to_utf8, hex_to_u32_nocheck};
 use crate::error::ErrorType;
 use crate::safer_unchecked::GetSaferUnchecked;
+use crate::Error;
 
 /// begin copypasta
 /// These chars yield themselves: " \ /
@@ -80,3 +81,95 @@ pub(crate) fn handle_unicode_codepoint(
     let offset: usize = codepoint_to_utf8(code_point, dst_ptr);
     Ok((offset, src_offset))
 }
+
+/// Decodes the `\uXXXX` escapes in a string, including surrogate pairs,
+/// using the same decoder as the parser. Other escape sequences such as
+/// `\n` or `\\` are left as they are, so an escaped backslash is never
+/// mistaken for the start of a unicode escape.
+///
+/// ```rust
+/// use simd_json::decode_unicode_escapes;
+/// let s = decode_unicode_escapes(r"\u0048\u0065\u006C\u006C\u006F \uD83E\uDD80").unwrap();
+/// assert_eq!(s, "Hello \u{1f980}");
+/// assert_eq!(decode_unicode_escapes(r"a\nb\\u0041").unwrap(), r"a\nb\\u0041");
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if a unicode escape is truncated, contains invalid
+/// hex digits or encodes a lone surrogate.
+pub fn decode_unicode_escapes(s: &str) -> crate::Result<String> {
+    let src = s.as_bytes();
+    let mut res = Vec::with_capacity(src.len());
+    let mut i = 0;
+    while let Some(p) = src[i..].iter().position(|c| *c == b'\\') {
+        let esc = i + p;
+        res.extend_from_slice(&src[i..esc]);
+        match src.get(esc + 1) {
+            Some(b'u') => {
+                // the decoder reads up to 12 bytes to handle surrogate
+                // pairs, so we hand it a zero padded copy of the escape
+                let n = (src.len() - esc).min(12);
+                let mut escape = [0_u8; 12];
+                escape[..n].copy_from_slice(&src[esc..esc + n]);
+                let mut dst = [0_u8; 4];
+                match handle_unicode_codepoint(&escape, &mut dst) {
+                    Ok((o, consumed)) if o > 0 && consumed <= n => {
+                        res.extend_from_slice(&dst[..o]);
+                        i = esc + consumed;
+                    }
+                    _ => return Err(Error::new_c(esc, 'u', ErrorType::InvalidUnicodeCodepoint)),
+                }
+            }
+            Some(_) => {
+                res.extend_from_slice(&src[esc..esc + 2]);
+                i = esc + 2;
+            }
+            None => {
+                res.push(b'\\');
+                i = esc + 1;
+            }
+        }
+    }
+    res.extend_from_slice(&src[i..]);
+    String::from_utf8(res).map_err(|_| Error::generic(ErrorType::InvalidUtf8))
+}
+
+#[cfg(test)]
+mod test {
+    use super::decode_unicode_escapes;
+    use crate::ErrorType;
+
+    #[test]
+    fn decode() {
+        let d = |s| decode_unicode_escapes(s).expect("valid escapes");
+        assert_eq!(d(""), "");
+        assert_eq!(d("plain"), "plain");
+        assert_eq!(d(r"\u0048\u0065\u006C\u006c\u006F"), "Hello");
+        assert_eq!(
+            d(r"a\u00e4\u20AC\uD83D\uDE00b"),
+            "a\u{e4}\u{20ac}\u{1f600}b"
+        );
+        assert_eq!(d(r"\u00e4\u00e4"), "\u{e4}\u{e4}");
+        assert_eq!(d("\u{e4}\\u0041"), "\u{e4}A");
+        assert_eq!(d(r"\n\t\\\\u0041"), r"\n\t\\\\u0041");
+        assert_eq!(d(r"trailing\"), r"trailing\");
+    }
+
+    #[test]
+    fn decode_errors() {
+        for (s, idx) in [
+            (r"\u12", 0),
+            (r"ab\uZZZZ", 2),
+            (r"\uDE00", 0),
+            (r"\uD83D", 0),
+            (r"\uD83Dx", 0),
+            (r"\uD83D\u0041", 0),
+            (r"\uD83D\uDE0", 0),
+        ] {
+            let e = decode_unicode_escapes(s).expect_err(s);
+            assert_eq!(e.error(), &ErrorType::InvalidUnicodeCodepoint, "{s}");
+            assert_eq!(e.index(), idx, "{s}");
+        }
+    }
+}
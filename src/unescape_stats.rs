@@ -0,0 +1,113 @@
+use crate::Node;
+
+/// Counts of how the strings of a document, including object keys,
+/// were unescaped, available from `Deserializer::unescape_stats` with
+/// the `unescape-stats` feature. Strings with escapes are slower to parse
+/// than escape free ones, as they are copied through the unescape buffer.
+///
+/// The counts are collected after parsing, without slowing down the
+/// string parser itself.
+///
+/// ```rust
+/// use simd_json::Deserializer;
+/// let mut d = br#"{"plain": "a\tb", "emoji": "\ud83e\udd80"}"#.to_vec();
+/// let de = Deserializer::from_slice(&mut d).unwrap();
+/// let stats = de.unescape_stats();
+/// assert_eq!(stats.escape_free, 2);
+/// assert_eq!(stats.simple_escapes, 1);
+/// assert_eq!(stats.unicode_escapes, 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnescapeStats {
+    /// Strings without any escapes, they are never copied
+    pub escape_free: usize,
+    /// Strings with escapes such as `\n` or `\"` but no `\u` escapes
+    pub simple_escapes: usize,
+    /// Strings with at least one `\u` escape
+    pub unicode_escapes: usize,
+    /// Bytes written through the unescape buffer, from the first escape
+    /// of a string to its end
+    pub bytes_copied: usize,
+}
+
+impl UnescapeStats {
+    /// Collects the stats for the strings on a tape. `raw` is the input
+    /// before unescaping, strings on the tape are at the same offsets
+    /// from `input_start`.
+    pub(crate) fn collect(tape: &[Node], input_start: usize, raw: &[u8]) -> Self {
+        let mut stats = Self::default();
+        for node in tape {
+            if let Node::String(s) = node {
+                let offset = s.as_ptr() as usize - input_start;
+                stats.observe(&raw[offset..], s.len());
+            }
+        }
+        stats
+    }
+
+    /// Observes a raw string, without the opening quote, that unescaped
+    /// to `len` bytes
+    fn observe(&mut self, raw: &[u8], len: usize) {
+        let mut first_escape = None;
+        let mut unicode = false;
+        let mut i = 0;
+        while let Some(c) = raw.get(i) {
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    first_escape.get_or_insert(i);
+                    unicode |= raw.get(i + 1) == Some(&b'u');
+                    i += 2;
+                }
+                _ => i += 1,
+            }
+        }
+        match first_escape {
+            None => self.escape_free += 1,
+            Some(first) => {
+                if unicode {
+                    self.unicode_escapes += 1;
+                } else {
+                    self.simple_escapes += 1;
+                }
+                // everything before the first escape stays in place
+                self.bytes_copied += len - first;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UnescapeStats;
+    use crate::Deserializer;
+
+    #[test]
+    fn counts() {
+        let mut d = br#"{
+            "key": "plain",
+            "k\"ey": ["", "tab\there", "\\", "caf\u00e9 \n", "\ud83e\udd80"],
+            "n": 1
+        }"#
+        .to_vec();
+        let de = Deserializer::from_slice(&mut d).expect("valid json");
+        assert_eq!(
+            de.unescape_stats(),
+            &UnescapeStats {
+                // "key", "plain", "", "n"
+                escape_free: 4,
+                // "k\"ey", "tab\there", "\\"
+                simple_escapes: 3,
+                // "caf\u00e9 \n", "\ud83e\udd80"
+                unicode_escapes: 2,
+                // "\"ey" 3, "\there" 5, "\\" 1, "\u00e9 \n" 4, "\ud83e\udd80" 4
+                bytes_copied: 17,
+            }
+        );
+
+        let mut d = br#"[1, null]"#.to_vec();
+        let de = Deserializer::from_slice(&mut d).expect("valid json");
+        assert_eq!(de.unescape_stats(), &UnescapeStats::default());
+    }
+}
@@ -0,0 +1,331 @@
+use crate::tape::skip;
+use crate::{to_tape, Error, ErrorType, Node, Result, StaticNode};
+use std::collections::{HashMap, HashSet};
+use value_trait::ValueType;
+
+/// A step of the path to the array checked by `check_unique_field`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSeg<'p> {
+    /// The value of a key in an object, if the key is present more than
+    /// once the first occurrence is used
+    Key(&'p str),
+    /// The element at an index of an array
+    Index(usize),
+}
+
+/// An element whose field has the same value as an earlier element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Duplicate {
+    /// The index of the first element with the value
+    pub first_index: usize,
+    /// The byte offset of the field's key in the first element
+    pub first_offset: usize,
+    /// The index of the element repeating the value
+    pub index: usize,
+    /// The byte offset of the field's key in the repeating element
+    pub offset: usize,
+}
+
+/// The result of `check_unique_field`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UniqueReport {
+    /// The number of elements checked, checking stops at the first
+    /// duplicate
+    pub elements: usize,
+    /// Elements that are not objects or don't have the field
+    pub missing: usize,
+    /// The first duplicate, if there is one
+    pub duplicate: Option<Duplicate>,
+}
+
+/// A field value, integers are normalized so that the same number is
+/// the same key no matter how it is stored on the tape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Key<'a> {
+    String(&'a str),
+    Negative(i128),
+    Positive(u128),
+}
+
+impl<'a> Key<'a> {
+    fn signed(n: i128) -> Self {
+        u128::try_from(n).map_or(Self::Negative(n), Self::Positive)
+    }
+
+    fn from_node(node: Node<'a>) -> std::result::Result<Self, ValueType> {
+        match node {
+            Node::String(s) => Ok(Self::String(s)),
+            Node::Static(StaticNode::I64(n)) => Ok(Self::signed(i128::from(n))),
+            Node::Static(StaticNode::U64(n)) => Ok(Self::Positive(u128::from(n))),
+            #[cfg(feature = "128bit")]
+            Node::Static(StaticNode::I128(n)) => Ok(Self::signed(n)),
+            #[cfg(feature = "128bit")]
+            Node::Static(StaticNode::U128(n)) => Ok(Self::Positive(n)),
+            Node::Static(StaticNode::F64(_)) => Err(ValueType::F64),
+            Node::Static(StaticNode::Bool(_)) => Err(ValueType::Bool),
+            Node::Static(StaticNode::Null) => Err(ValueType::Null),
+            Node::Array(..) => Err(ValueType::Array),
+            Node::Object(..) => Err(ValueType::Object),
+        }
+    }
+}
+
+/// Checks that the objects in an array have unique values for `field`,
+/// for example that no two records share an `id`. The array is found by
+/// following `array_path` from the root, an empty path checks the root
+/// itself. Values can be strings or integers, a string never equals an
+/// integer.
+///
+/// The document is parsed into a tape and only the field of each element
+/// is looked at, no values are built. Beyond the tape the memory used is
+/// proportional to the number of distinct values. Like all parsing
+/// functions this rewrites the input to unescape strings, offsets are
+/// those of the opening quote of the field's key in the input.
+///
+/// ```rust
+/// use simd_json::{check_unique_field, PathSeg};
+/// let mut d = br#"{"rows": [{"id": 1}, {"id": 2}, {"id": 1}]}"#.to_vec();
+/// let report = check_unique_field(&mut d, &[PathSeg::Key("rows")], "id").unwrap();
+/// let duplicate = report.duplicate.unwrap();
+/// assert_eq!((duplicate.first_index, duplicate.index), (0, 2));
+/// assert_eq!(duplicate.offset, 33);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the document is invalid JSON, if there is no
+/// array at `array_path` or if a value of the field is neither a string
+/// nor an integer.
+pub fn check_unique_field(
+    input: &mut [u8],
+    array_path: &[PathSeg],
+    field: &str,
+) -> Result<UniqueReport> {
+    let input_start = input.as_ptr() as usize;
+    let tape = to_tape(input)?;
+    let offset = |key: &str| key.as_ptr() as usize - input_start - 1;
+
+    // the root of a tape is at index 1
+    let mut i = 1;
+    for seg in array_path {
+        i = match (seg, tape[i]) {
+            (PathSeg::Key(key), Node::Object(len, _)) => {
+                find_entry(&tape, i, len, key).map(|(_, v)| v)
+            }
+            (PathSeg::Index(idx), Node::Array(len, _)) if *idx < len => {
+                Some((0..*idx).fold(i + 1, |i, _| skip(&tape, i)))
+            }
+            _ => None,
+        }
+        .ok_or_else(|| Error::generic(ErrorType::ExpectedArray))?;
+    }
+    let len = match tape[i] {
+        Node::Array(len, _) => len,
+        _ => return Err(Error::generic(ErrorType::ExpectedArray)),
+    };
+
+    let mut report = UniqueReport::default();
+    let mut seen: HashMap<Key, (usize, usize)> = HashMap::new();
+    let mut element = i + 1;
+    for index in 0..len {
+        report.elements += 1;
+        let entry = match tape[element] {
+            Node::Object(len, _) => find_entry(&tape, element, len, field),
+            _ => None,
+        };
+        element = skip(&tape, element);
+        let (key, value) = match entry {
+            Some(entry) => entry,
+            None => {
+                report.missing += 1;
+                continue;
+            }
+        };
+        let value = Key::from_node(tape[value]).map_err(|found| {
+            Error::new(
+                offset(key),
+                None,
+                ErrorType::Unexpected(Some(ValueType::String), Some(found)),
+            )
+        })?;
+        if let Some((first_index, first_offset)) = seen.get(&value) {
+            report.duplicate = Some(Duplicate {
+                first_index: *first_index,
+                first_offset: *first_offset,
+                index,
+                offset: offset(key),
+            });
+            break;
+        }
+        seen.insert(value, (index, offset(key)));
+    }
+    Ok(report)
+}
+
+/// Checks that no object on the tape has a key more than once, the
+/// error points to the opening quote of the second occurrence of a key,
+/// objects are checked outermost first.
+/// `input_start` is the address of the input the tape was parsed from.
+pub(crate) fn check_unique_keys(tape: &[Node], input_start: usize) -> Result<()> {
+    let mut keys: HashSet<&str> = HashSet::new();
+    for (i, node) in tape.iter().enumerate() {
+        let len = match node {
+            Node::Object(len, _) if *len > 1 => *len,
+            _ => continue,
+        };
+        keys.clear();
+        let mut k = i + 1;
+        for _ in 0..len {
+            if let Node::String(key) = tape[k] {
+                if !keys.insert(key) {
+                    return Err(Error::new(
+                        key.as_ptr() as usize - input_start - 1,
+                        None,
+                        ErrorType::DuplicateKey(key.to_string()),
+                    ));
+                }
+            }
+            k = skip(tape, k + 1);
+        }
+    }
+    Ok(())
+}
+
+/// The key and the index of the value of the first entry for `field` in
+/// the object at `i`
+fn find_entry<'a>(
+    tape: &[Node<'a>],
+    i: usize,
+    len: usize,
+    field: &str,
+) -> Option<(&'a str, usize)> {
+    let mut k = i + 1;
+    for _ in 0..len {
+        match tape[k] {
+            Node::String(key) if key == field => return Some((key, k + 1)),
+            _ => k = skip(tape, k + 1),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_unique_field, Duplicate, PathSeg, UniqueReport};
+    use crate::ErrorType;
+    use value_trait::ValueType;
+
+    fn check(input: &str, path: &[PathSeg], field: &str) -> UniqueReport {
+        let mut input = input.as_bytes().to_vec();
+        check_unique_field(&mut input, path, field).expect("valid json")
+    }
+
+    fn records(ids: impl Iterator<Item = String>) -> String {
+        let rows: Vec<_> = ids
+            .map(|id| format!(r#"{{"name": "x", "id": {id}}}"#))
+            .collect();
+        format!("[{}]", rows.join(","))
+    }
+
+    #[test]
+    fn unique() {
+        let input = records((0..1000).map(|i| i.to_string()));
+        assert_eq!(
+            check(&input, &[], "id"),
+            UniqueReport {
+                elements: 1000,
+                missing: 0,
+                duplicate: None
+            }
+        );
+        // the same number as a string is a different value
+        assert!(check(r#"[{"id": 1}, {"id": "1"}]"#, &[], "id")
+            .duplicate
+            .is_none());
+        assert_eq!(check("[]", &[], "id"), UniqueReport::default());
+    }
+
+    #[test]
+    fn duplicate_early() {
+        let input = records(
+            ["\"a\"", "\"a\"", "\"b\"", "\"a\""]
+                .iter()
+                .map(ToString::to_string),
+        );
+        let report = check(&input, &[], "id");
+        assert_eq!(report.elements, 2);
+        let first_offset = input.find(r#""id""#).expect("key");
+        let offset = first_offset + 1 + input[first_offset + 1..].find(r#""id""#).expect("key");
+        assert_eq!(
+            report.duplicate,
+            Some(Duplicate {
+                first_index: 0,
+                first_offset,
+                index: 1,
+                offset
+            })
+        );
+    }
+
+    #[test]
+    fn duplicate_late() {
+        let ids = (0..10_000).chain(Some(1234)).map(|i| i.to_string());
+        let input = format!(
+            r#"{{"meta": {{}}, "data": [0, {{"rows": {}}}]}}"#,
+            records(ids)
+        );
+        let path = [
+            PathSeg::Key("data"),
+            PathSeg::Index(1),
+            PathSeg::Key("rows"),
+        ];
+        let report = check(&input, &path, "id");
+        assert_eq!(report.elements, 10_001);
+        let duplicate = report.duplicate.expect("a duplicate");
+        assert_eq!((duplicate.first_index, duplicate.index), (1234, 10_000));
+        assert_eq!(
+            &input[duplicate.first_offset..duplicate.first_offset + 4],
+            r#""id""#
+        );
+        assert_eq!(&input[duplicate.offset..], r#""id": 1234}]}]}"#);
+    }
+
+    #[test]
+    fn absent_fields() {
+        let input = r#"[{"id": 1}, {"key": 1}, 7, {"id": -1}, {}, {"id": 18446744073709551615}]"#;
+        assert_eq!(
+            check(input, &[], "id"),
+            UniqueReport {
+                elements: 6,
+                missing: 3,
+                duplicate: None
+            }
+        );
+        assert!(check(r#"[{"id": null}]"#, &[], "other").duplicate.is_none());
+    }
+
+    #[test]
+    fn errors() {
+        let err = |input: &str, path: &[PathSeg]| {
+            let mut input = input.as_bytes().to_vec();
+            check_unique_field(&mut input, path, "id").expect_err("invalid")
+        };
+        assert_eq!(err("{}", &[]).error(), &ErrorType::ExpectedArray);
+        assert_eq!(
+            err(r#"{"a": []}"#, &[PathSeg::Key("b")]).error(),
+            &ErrorType::ExpectedArray
+        );
+        assert_eq!(
+            err("[[]]", &[PathSeg::Index(1)]).error(),
+            &ErrorType::ExpectedArray
+        );
+        let e = err(r#"[{"id": 1}, {"id": 1.5}]"#, &[]);
+        assert!(matches!(
+            e.error(),
+            ErrorType::Unexpected(Some(ValueType::String), Some(ValueType::F64))
+        ));
+        assert_eq!(e.index(), 13);
+        assert_ne!(err("[1,", &[]).error(), &ErrorType::ExpectedArray);
+    }
+}
@@ -1,3 +1,4 @@
+mod any;
 /// This module holds the two dom implementations we use. We distinguish between
 /// owned and borrowed. The difference being is that the borrowed value will
 /// use `&str` as its string type, referencing the input, while owned will
@@ -52,18 +53,55 @@
 
 /// Borrowed values, using Cow's for strings using in situ parsing strategies wherever possible
 pub mod borrowed;
+#[cfg(feature = "coerce")]
+mod coerce;
+mod diff;
+pub(crate) mod float;
+mod fold;
+mod key_offsets;
+mod key_pool;
+mod kind;
+mod number;
+mod numeric;
 /// Owned, lifetimeless version of the value for times when lifetimes are to be avoided
 pub mod owned;
+pub(crate) mod pointer;
+pub(crate) mod pretty;
+mod redact;
+mod stable_hash;
 /// Tape implementation
 pub mod tape;
+mod unordered;
+pub use self::any::{AnyValue, AnyValueRef};
 pub use self::borrowed::{
-    to_value as to_borrowed_value, to_value_with_buffers as to_borrowed_value_with_buffers,
-    Value as BorrowedValue,
+    to_value as to_borrowed_value, to_value_mut as to_borrowed_value_mut,
+    to_value_with_buffers as to_borrowed_value_with_buffers,
+    to_value_with_opts as to_borrowed_value_with_opts,
+    to_value_with_pool as to_borrowed_value_with_pool, InPlaceValue, Value as BorrowedValue,
 };
+#[cfg(feature = "coerce")]
+pub use self::coerce::CoerceFromJson;
+pub use self::diff::pretty_print_with_color_diff;
+pub use self::float::ExponentStyle;
+pub use self::fold::ValueFolder;
+pub use self::key_offsets::{
+    to_borrowed_value_with_key_offsets, to_owned_value_with_key_offsets, KeyOffsets,
+};
+pub use self::key_pool::KeyPool;
+pub use self::kind::JsonKind;
+pub use self::number::Number;
+pub use self::numeric::NumericError;
+#[cfg(feature = "recycle")]
+pub use self::owned::to_owned_value_recycle;
 pub use self::owned::{
-    to_value as to_owned_value, to_value_with_buffers as to_owned_value_with_buffers,
-    Value as OwnedValue,
+    parse_strict, to_ndjson_bytes, to_value as to_owned_value,
+    to_value_with_buffers as to_owned_value_with_buffers,
+    to_value_with_opts as to_owned_value_with_opts, try_to_value as try_to_owned_value,
+    write_ndjson, DebugPretty, Value as OwnedValue,
 };
+pub use self::pointer::JsonPointer;
+pub use self::pretty::{PrettyOptions, SerializeOptions};
+pub use self::redact::KeyMatcher;
 use crate::safer_unchecked::GetSaferUnchecked;
 use crate::{Deserializer, Result};
 use halfbrown::HashMap;
@@ -0,0 +1,662 @@
+use super::{JsonKind, JsonPointer, Number};
+use crate::prelude::*;
+use crate::{BorrowedValue, OwnedValue, Result};
+use std::fmt;
+use std::io::{self, Write};
+
+/// Forwards read accessors to the value behind either variant
+macro_rules! forward {
+    ($($(#[$meta:meta])* fn $name:ident(&self) -> $ret:ty;)*) => {
+        $(
+            $(#[$meta])*
+            #[inline]
+            #[must_use]
+            pub fn $name(&self) -> $ret {
+                match *self {
+                    Self::Borrowed(v) => v.$name(),
+                    Self::Owned(v) => v.$name(),
+                }
+            }
+        )*
+    };
+}
+
+type Members<'v, 'value> = Box<dyn Iterator<Item = AnyValueRef<'v, 'value>> + 'v>;
+type Entries<'v, 'value> = Box<dyn Iterator<Item = (&'v str, AnyValueRef<'v, 'value>)> + 'v>;
+
+/// A value that borrows from its input when the caller can keep the
+/// input alive and owns its data otherwise, so code reading values only
+/// has to be written once. Reads go through `AnyValueRef`, which has the
+/// same accessors and is what lookups into arrays and objects return.
+///
+/// Both implement `Writable` and compare with the same primitive types
+/// as the values. They don't implement `ValueAccess` or `ValueTrait`,
+/// those traits hand out references to a single array, object and
+/// element type stored inside the value, while the containers of an
+/// `AnyValue` hold either borrowed or owned values. The inherent
+/// accessors cover the same lookups and return `AnyValueRef`s instead.
+///
+/// ```rust
+/// use simd_json::{AnyValue, JsonPointer};
+/// fn name(v: &AnyValue) -> Option<String> {
+///     let p = JsonPointer::new("/user/name").unwrap();
+///     v.pointer(&p)?.as_str().map(String::from)
+/// }
+/// let mut d = br#"{"user": {"name": "ferris"}}"#.to_vec();
+/// let borrowed = AnyValue::parse_borrowed(&mut d).unwrap();
+/// let owned = AnyValue::parse_owned(br#"{"user": {"name": "ferris"}}"#).unwrap();
+/// assert_eq!(name(&borrowed), name(&owned));
+/// ```
+#[derive(Debug, Clone)]
+pub enum AnyValue<'value> {
+    /// A value borrowing from its input
+    Borrowed(BorrowedValue<'value>),
+    /// A value owning all of its data
+    Owned(OwnedValue),
+}
+
+/// A reference to a value inside an `AnyValue`
+#[derive(Debug, Clone, Copy)]
+pub enum AnyValueRef<'v, 'value> {
+    /// A reference into a borrowed value
+    Borrowed(&'v BorrowedValue<'value>),
+    /// A reference into an owned value
+    Owned(&'v OwnedValue),
+}
+
+impl<'value> AnyValue<'value> {
+    /// Parses a slice into a value borrowing from it, the slice is
+    /// rewritten to unescape strings.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON.
+    pub fn parse_borrowed(s: &'value mut [u8]) -> Result<Self> {
+        crate::to_borrowed_value(s).map(Self::Borrowed)
+    }
+
+    /// Parses a slice into an owned value, the slice is copied first so
+    /// it is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON.
+    pub fn parse_owned(s: &[u8]) -> Result<Self> {
+        crate::to_owned_value(&mut s.to_vec()).map(Self::Owned)
+    }
+
+    /// Turns the value into an owned value, borrowed values are copied.
+    #[must_use]
+    pub fn into_owned(self) -> OwnedValue {
+        match self {
+            Self::Borrowed(v) => v.into(),
+            Self::Owned(v) => v,
+        }
+    }
+
+    /// A reference to the value for reading it
+    #[inline]
+    #[must_use]
+    pub fn as_value_ref(&self) -> AnyValueRef<'_, 'value> {
+        match self {
+            Self::Borrowed(v) => AnyValueRef::Borrowed(v),
+            Self::Owned(v) => AnyValueRef::Owned(v),
+        }
+    }
+
+    /// The type of the value
+    #[must_use]
+    pub fn value_type(&self) -> ValueType {
+        self.as_value_ref().value_type()
+    }
+
+    /// The kind of the value, see `JsonKind` for how numbers are mapped.
+    #[must_use]
+    pub fn kind(&self) -> JsonKind {
+        self.as_value_ref().kind()
+    }
+
+    /// Returns true if the value is `null`
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        self.as_value_ref().is_null()
+    }
+
+    /// The value as a bool
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_value_ref().as_bool()
+    }
+
+    /// The value as an `i64`
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_value_ref().as_i64()
+    }
+
+    /// The value as an `i128`
+    #[must_use]
+    pub fn as_i128(&self) -> Option<i128> {
+        self.as_value_ref().as_i128()
+    }
+
+    /// The value as an `u64`
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_value_ref().as_u64()
+    }
+
+    /// The value as an `u128`
+    #[must_use]
+    pub fn as_u128(&self) -> Option<u128> {
+        self.as_value_ref().as_u128()
+    }
+
+    /// The value as a float, integers are not converted
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_value_ref().as_f64()
+    }
+
+    /// The value as a float, integers are converted
+    #[must_use]
+    pub fn cast_f64(&self) -> Option<f64> {
+        self.as_value_ref().cast_f64()
+    }
+
+    /// The value as a `Number`, if it is a number that fits into 64 bit
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number> {
+        self.as_value_ref().as_number()
+    }
+
+    /// The value as a string
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_value_ref().as_str()
+    }
+
+    /// The number of elements or entries of an array or object
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        self.as_value_ref().len()
+    }
+
+    /// Returns true if the value is an empty array or object, `None`
+    /// if it is neither
+    #[must_use]
+    pub fn is_empty(&self) -> Option<bool> {
+        self.as_value_ref().is_empty()
+    }
+
+    /// Looks up a key of an object
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<AnyValueRef<'_, 'value>> {
+        self.as_value_ref().get(key)
+    }
+
+    /// Looks up an index of an array
+    #[must_use]
+    pub fn get_idx(&self, idx: usize) -> Option<AnyValueRef<'_, 'value>> {
+        self.as_value_ref().get_idx(idx)
+    }
+
+    /// Looks up the value at a JSON pointer
+    #[must_use]
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<AnyValueRef<'_, 'value>> {
+        self.as_value_ref().pointer(pointer)
+    }
+
+    /// The elements of an array
+    #[must_use]
+    pub fn members(&self) -> Option<Members<'_, 'value>> {
+        self.as_value_ref().members()
+    }
+
+    /// The entries of an object, in no particular order
+    #[must_use]
+    pub fn entries(&self) -> Option<Entries<'_, 'value>> {
+        self.as_value_ref().entries()
+    }
+}
+
+impl<'v, 'value> AnyValueRef<'v, 'value> {
+    forward! {
+        /// The type of the value
+        fn value_type(&self) -> ValueType;
+        /// The kind of the value, see `JsonKind` for how numbers are mapped.
+        fn kind(&self) -> JsonKind;
+        /// Returns true if the value is `null`
+        fn is_null(&self) -> bool;
+        /// The value as a bool
+        fn as_bool(&self) -> Option<bool>;
+        /// The value as an `i64`
+        fn as_i64(&self) -> Option<i64>;
+        /// The value as an `i128`
+        fn as_i128(&self) -> Option<i128>;
+        /// The value as an `u64`
+        fn as_u64(&self) -> Option<u64>;
+        /// The value as an `u128`
+        fn as_u128(&self) -> Option<u128>;
+        /// The value as a float, integers are not converted
+        fn as_f64(&self) -> Option<f64>;
+        /// The value as a float, integers are converted
+        fn cast_f64(&self) -> Option<f64>;
+        /// The value as a `Number`, if it is a number that fits into 64 bit
+        fn as_number(&self) -> Option<Number>;
+        /// The value as a string
+        fn as_str(&self) -> Option<&'v str>;
+    }
+
+    /// Copies the value into an owned value
+    #[must_use]
+    pub fn to_owned_value(&self) -> OwnedValue {
+        match *self {
+            Self::Borrowed(v) => v.clone().into(),
+            Self::Owned(v) => v.clone(),
+        }
+    }
+
+    /// The number of elements or entries of an array or object
+    #[must_use]
+    pub fn len(&self) -> Option<usize> {
+        match *self {
+            Self::Borrowed(BorrowedValue::Array(a)) => Some(a.len()),
+            Self::Borrowed(BorrowedValue::Object(o)) => Some(o.len()),
+            Self::Owned(OwnedValue::Array(a)) => Some(a.len()),
+            Self::Owned(OwnedValue::Object(o)) => Some(o.len()),
+            Self::Borrowed(_) | Self::Owned(_) => None,
+        }
+    }
+
+    /// Returns true if the value is an empty array or object, `None`
+    /// if it is neither
+    #[must_use]
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Looks up a key of an object
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<Self> {
+        match *self {
+            Self::Borrowed(BorrowedValue::Object(o)) => o.get(key).map(Self::Borrowed),
+            Self::Owned(OwnedValue::Object(o)) => o.get(key).map(Self::Owned),
+            Self::Borrowed(_) | Self::Owned(_) => None,
+        }
+    }
+
+    /// Looks up an index of an array
+    #[must_use]
+    pub fn get_idx(&self, idx: usize) -> Option<Self> {
+        match *self {
+            Self::Borrowed(BorrowedValue::Array(a)) => a.get(idx).map(Self::Borrowed),
+            Self::Owned(OwnedValue::Array(a)) => a.get(idx).map(Self::Owned),
+            Self::Borrowed(_) | Self::Owned(_) => None,
+        }
+    }
+
+    /// Looks up the value at a JSON pointer
+    #[must_use]
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<Self> {
+        match *self {
+            Self::Borrowed(v) => v.pointer(pointer).map(Self::Borrowed),
+            Self::Owned(v) => v.pointer(pointer).map(Self::Owned),
+        }
+    }
+
+    /// The elements of an array
+    #[must_use]
+    pub fn members(&self) -> Option<Members<'v, 'value>> {
+        match *self {
+            Self::Borrowed(BorrowedValue::Array(a)) => Some(Box::new(a.iter().map(Self::Borrowed))),
+            Self::Owned(OwnedValue::Array(a)) => Some(Box::new(a.iter().map(Self::Owned))),
+            Self::Borrowed(_) | Self::Owned(_) => None,
+        }
+    }
+
+    /// The entries of an object, in no particular order
+    #[must_use]
+    pub fn entries(&self) -> Option<Entries<'v, 'value>> {
+        match *self {
+            Self::Borrowed(BorrowedValue::Object(o)) => {
+                Some(Box::new(o.iter().map(|(k, v)| (&**k, Self::Borrowed(v)))))
+            }
+            Self::Owned(OwnedValue::Object(o)) => Some(Box::new(
+                o.iter().map(|(k, v)| (k.as_str(), Self::Owned(v))),
+            )),
+            Self::Borrowed(_) | Self::Owned(_) => None,
+        }
+    }
+}
+
+impl<'value> From<BorrowedValue<'value>> for AnyValue<'value> {
+    fn from(v: BorrowedValue<'value>) -> Self {
+        Self::Borrowed(v)
+    }
+}
+
+impl From<OwnedValue> for AnyValue<'_> {
+    fn from(v: OwnedValue) -> Self {
+        Self::Owned(v)
+    }
+}
+
+impl PartialEq for AnyValueRef<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (*self, *other) {
+            (Self::Borrowed(a), Self::Borrowed(b)) => a == b,
+            (Self::Owned(a), Self::Owned(b)) => a == b,
+            (Self::Borrowed(a), Self::Owned(b)) | (Self::Owned(b), Self::Borrowed(a)) => b == a,
+        }
+    }
+}
+
+/// Values are equal if their content is, no matter if they borrow
+impl PartialEq for AnyValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_value_ref() == other.as_value_ref()
+    }
+}
+
+/// Compares either value with a primitive, the same as the values do
+macro_rules! impl_eq {
+    ($($t:ty),*) => {
+        $(
+            impl PartialEq<$t> for AnyValueRef<'_, '_> {
+                #[inline]
+                fn eq(&self, other: &$t) -> bool {
+                    match *self {
+                        Self::Borrowed(v) => v.eq(other),
+                        Self::Owned(v) => v.eq(other),
+                    }
+                }
+            }
+
+            impl PartialEq<$t> for AnyValue<'_> {
+                #[inline]
+                fn eq(&self, other: &$t) -> bool {
+                    self.as_value_ref().eq(other)
+                }
+            }
+        )*
+    };
+}
+
+impl_eq!(
+    (),
+    bool,
+    str,
+    &str,
+    String,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    u128,
+    f32,
+    f64
+);
+
+impl Writable for AnyValueRef<'_, '_> {
+    #[inline]
+    fn encode(&self) -> String {
+        match *self {
+            Self::Borrowed(v) => v.encode(),
+            Self::Owned(v) => v.encode(),
+        }
+    }
+
+    #[inline]
+    fn encode_pp(&self) -> String {
+        match *self {
+            Self::Borrowed(v) => v.encode_pp(),
+            Self::Owned(v) => v.encode_pp(),
+        }
+    }
+
+    #[inline]
+    fn write<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        match *self {
+            Self::Borrowed(v) => v.write(w),
+            Self::Owned(v) => v.write(w),
+        }
+    }
+
+    #[inline]
+    fn write_pp<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        match *self {
+            Self::Borrowed(v) => v.write_pp(w),
+            Self::Owned(v) => v.write_pp(w),
+        }
+    }
+}
+
+impl Writable for AnyValue<'_> {
+    #[inline]
+    fn encode(&self) -> String {
+        self.as_value_ref().encode()
+    }
+
+    #[inline]
+    fn encode_pp(&self) -> String {
+        self.as_value_ref().encode_pp()
+    }
+
+    #[inline]
+    fn write<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        self.as_value_ref().write(w)
+    }
+
+    #[inline]
+    fn write_pp<'writer, W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: 'writer + Write,
+    {
+        self.as_value_ref().write_pp(w)
+    }
+}
+
+/// Displays the value as compact JSON
+impl fmt::Display for AnyValueRef<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+/// Displays the value as compact JSON
+impl fmt::Display for AnyValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AnyValue, AnyValueRef};
+    use crate::prelude::*;
+    use crate::{json, JsonKind, JsonPointer, Number, ValueType};
+
+    const INPUT: &str = r#"{
+        "null": null, "bool": true, "neg": -3, "big": 18446744073709551615,
+        "float": 2.5, "str": "a\tb", "arr": [1, "x", [null]], "obj": {"k": {"n": 1}}
+    }"#;
+
+    /// Runs `f` with the same document as a borrowed and an owned value
+    fn both(f: impl Fn(&AnyValue)) {
+        let mut d = INPUT.as_bytes().to_vec();
+        let borrowed = AnyValue::parse_borrowed(&mut d).expect("valid json");
+        assert!(matches!(borrowed, AnyValue::Borrowed(_)));
+        f(&borrowed);
+        let owned = AnyValue::parse_owned(INPUT.as_bytes()).expect("valid json");
+        assert!(matches!(owned, AnyValue::Owned(_)));
+        f(&owned);
+        assert_eq!(borrowed, owned);
+    }
+
+    #[test]
+    fn scalars() {
+        both(|v| {
+            let get = |k: &str| v.get(k).expect("present");
+            assert_eq!(v.value_type(), ValueType::Object);
+            assert_eq!(v.kind(), JsonKind::Object);
+            assert!(!v.is_null());
+            assert!(get("null").is_null());
+            assert_eq!(get("null").value_type(), ValueType::Null);
+            assert_eq!(get("bool").as_bool(), Some(true));
+            assert_eq!(get("neg").as_i64(), Some(-3));
+            assert_eq!(get("neg").as_i128(), Some(-3));
+            assert_eq!(get("neg").as_u64(), None);
+            assert_eq!(get("big").as_u64(), Some(u64::MAX));
+            assert_eq!(get("big").as_u128(), Some(u128::from(u64::MAX)));
+            assert_eq!(get("big").as_i64(), None);
+            assert_eq!(get("big").kind(), JsonKind::Integer);
+            assert_eq!(get("float").as_f64(), Some(2.5));
+            assert_eq!(get("neg").as_f64(), None);
+            assert_eq!(get("neg").cast_f64(), Some(-3.0));
+            assert_eq!(get("float").as_number(), Number::from_f64(2.5));
+            assert_eq!(get("str").as_str(), Some("a\tb"));
+            assert_eq!(get("str").as_bool(), None);
+            assert_eq!(v.as_str(), None);
+            assert_eq!(v.as_bool(), None);
+            assert_eq!(v.as_i64(), None);
+            assert_eq!(v.as_i128(), None);
+            assert_eq!(v.as_u64(), None);
+            assert_eq!(v.as_u128(), None);
+            assert_eq!(v.as_f64(), None);
+            assert_eq!(v.cast_f64(), None);
+            assert_eq!(v.as_number(), None);
+        });
+    }
+
+    #[test]
+    fn containers() {
+        both(|v| {
+            assert_eq!(v.len(), Some(8));
+            assert!(v.get("missing").is_none());
+            assert!(v.get_idx(0).is_none());
+            let arr = v.get("arr").expect("present");
+            assert_eq!(arr.len(), Some(3));
+            assert_eq!(arr.is_empty(), Some(false));
+            assert_eq!(v.is_empty(), Some(false));
+            assert_eq!(arr.get_idx(1).and_then(|x| x.as_str()), Some("x"));
+            assert!(arr.get_idx(3).is_none());
+            assert!(arr.get("k").is_none());
+            let members: Vec<_> = arr.members().expect("an array").collect();
+            assert_eq!(members.len(), 3);
+            assert_eq!(members[0].as_u64(), Some(1));
+            assert!(arr.entries().is_none());
+
+            let p = JsonPointer::new("/obj/k/n").expect("valid pointer");
+            assert_eq!(v.pointer(&p).and_then(|n| n.as_u64()), Some(1));
+            let p = JsonPointer::new("/arr/2/0").expect("valid pointer");
+            assert!(v.pointer(&p).expect("present").is_null());
+            assert!(v
+                .pointer(&JsonPointer::new("/arr/3").expect("valid"))
+                .is_none());
+
+            let mut keys: Vec<_> = v.entries().expect("an object").map(|(k, _)| k).collect();
+            keys.sort_unstable();
+            assert_eq!(
+                keys,
+                vec!["arr", "big", "bool", "float", "neg", "null", "obj", "str"]
+            );
+            assert!(v.members().is_none());
+            assert_eq!(v.get("str").expect("present").len(), None);
+            assert_eq!(v.get("bool").expect("present").is_empty(), None);
+        });
+    }
+
+    #[test]
+    fn conversions() {
+        both(|v| {
+            let owned = v.clone().into_owned();
+            assert_eq!(owned["arr"], json!([1, "x", [null]]));
+            assert_eq!(
+                v.get("obj").expect("present").to_owned_value(),
+                json!({"k": {"n": 1}})
+            );
+            assert_eq!(
+                v.get("arr").expect("present").to_string(),
+                r#"[1,"x",[null]]"#
+            );
+            assert_eq!(v.get("str").expect("present").to_string(), r#""a\tb""#);
+        });
+        let owned = AnyValue::from(json!([1, 2]));
+        let mut d = b"[1, 2]".to_vec();
+        let borrowed = AnyValue::parse_borrowed(&mut d).expect("valid json");
+        assert_eq!(owned, borrowed);
+        assert_eq!(owned.as_value_ref(), borrowed.as_value_ref());
+        assert!(matches!(borrowed.as_value_ref(), AnyValueRef::Borrowed(_)));
+        assert_ne!(owned, AnyValue::from(json!([2, 1])));
+        assert!(AnyValue::parse_owned(b"[1,").is_err());
+    }
+
+    #[test]
+    fn traits() {
+        fn dump<T: Writable>(v: &T) -> (String, String, Vec<u8>, Vec<u8>) {
+            let mut w = Vec::new();
+            v.write(&mut w).expect("write to vec");
+            let mut w_pp = Vec::new();
+            v.write_pp(&mut w_pp).expect("write to vec");
+            (v.encode(), v.encode_pp(), w, w_pp)
+        }
+        let expected = json!({"arr": [1, "x", [null]]});
+        let (compact, pretty, w, w_pp) = dump(&expected);
+        both(|v| {
+            let arr = v.get("arr").expect("present");
+            let (c, p, vw, vw_pp) = dump(&arr);
+            assert_eq!(c, r#"[1,"x",[null]]"#);
+            assert!(p.starts_with('['));
+            assert_eq!(vw, c.as_bytes());
+            assert_eq!(vw_pp, p.as_bytes());
+            let mut encoded = dump(v).0.into_bytes();
+            assert_eq!(
+                crate::to_owned_value(&mut encoded).expect("valid json"),
+                v.clone().into_owned()
+            );
+
+            let get = |k: &str| v.get(k).expect("present");
+            assert!(get("null") == ());
+            assert!(get("bool") == true);
+            assert!(get("str") == "a\tb");
+            assert!(get("str") == *"a\tb");
+            assert!(get("str") == String::from("a\tb"));
+            assert!(get("neg") == -3_i8);
+            assert!(get("neg") == -3_i16);
+            assert!(get("neg") == -3_i32);
+            assert!(get("neg") == -3_i64);
+            assert!(get("neg") == -3_i128);
+            assert!(arr.get_idx(0).expect("present") == 1_u8);
+            assert!(arr.get_idx(0).expect("present") == 1_u16);
+            assert!(arr.get_idx(0).expect("present") == 1_u32);
+            assert!(arr.get_idx(0).expect("present") == 1_u64);
+            assert!(arr.get_idx(0).expect("present") == 1_usize);
+            assert!(arr.get_idx(0).expect("present") == 1_u128);
+            assert!(get("float") == 2.5_f32);
+            assert!(get("float") == 2.5_f64);
+            assert!(get("float") != 2.0_f64);
+            assert!(get("str") != 1_u8);
+        });
+        let mut d = br#"{"arr": [1, "x", [null]]}"#.to_vec();
+        let borrowed = AnyValue::parse_borrowed(&mut d).expect("valid json");
+        assert_eq!(dump(&borrowed), (compact, pretty, w, w_pp));
+        assert!(AnyValue::from(json!(1)) == 1_u8);
+        assert!(AnyValue::from(json!("a")) == "a");
+    }
+}
@@ -22,13 +22,23 @@
 /// ```
 mod cmp;
 mod from;
+mod in_place;
 mod serialize;
 
-use super::ObjectHasher;
+pub use self::in_place::InPlaceValue;
+
+use super::numeric::{self, NumericError};
+use super::pointer::{array_index, push_token, JsonPointer};
+use super::redact::REDACTED;
+use super::unordered::multiset_eq;
+use super::{JsonKind, KeyMatcher, KeyPool, Number, ObjectHasher};
 use crate::cow::Cow;
 use crate::prelude::*;
 use crate::safer_unchecked::GetSaferUnchecked;
-use crate::{AlignedBuf, Deserializer, Node, Result, StaticNode};
+use crate::{
+    AlignedBuf, Deserializer, DuplicateKeys, Error, ErrorType, Node, ParseOptions, Result,
+    StaticNode,
+};
 use halfbrown::HashMap;
 use std::fmt;
 use std::ops::{Index, IndexMut};
@@ -52,6 +62,32 @@ pub fn to_value(s: &mut [u8]) -> Result<Value> {
     }
 }
 
+/// Parses a slice of bytes into a Value dom whose strings can be
+/// mutated, see `InPlaceValue`.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_value_mut(s: &mut [u8]) -> Result<InPlaceValue> {
+    InPlaceValue::parse(s)
+}
+
+/// Parses a slice of bytes into a Value dom using the given parse
+/// options. This function will rewrite the slice to de-escape strings.
+/// As we reference parts of the input slice the resulting dom
+/// has the same lifetime as the slice it was created from.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or violates the limits
+/// set in `opts`.
+pub fn to_value_with_opts(s: &mut [u8], opts: ParseOptions) -> Result<Value> {
+    match Deserializer::from_slice_with_opts(s, opts) {
+        Ok(de) => Ok(BorrowDeserializer::from_deserializer(de).parse()),
+        Err(e) => Err(e),
+    }
+}
+
 /// Parses a slice of bytes into a Value dom. This function will
 /// rewrite the slice to de-escape strings.
 /// As we reference parts of the input slice the resulting dom
@@ -71,6 +107,27 @@ pub fn to_value_with_buffers<'value>(
     }
 }
 
+/// Parses a slice of bytes into a Value dom like `to_value`, object keys
+/// are borrowed from `pool` instead of the input so equal keys of all
+/// documents parsed with the pool share their memory, see `KeyPool`.
+///
+/// Strings are unescaped into the input, so keys don't allocate without
+/// a pool either, with one documents can compare keys by pointer. To
+/// keep documents after their inputs are dropped without copying every
+/// key use `Value::into_pooled` instead.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_value_with_pool<'value>(
+    s: &'value mut [u8],
+    pool: &'value KeyPool,
+) -> Result<Value<'value>> {
+    let de = Deserializer::from_slice(s)?;
+    // the root of a tape is at index 1
+    Ok(de.value_from_tape(1, Some(pool)).0)
+}
+
 /// Borrowed JSON-DOM Value, consider using the `ValueTrait`
 /// to access its content
 #[derive(Debug, Clone)]
@@ -86,6 +143,53 @@ pub enum Value<'value> {
 }
 
 impl<'value> Value<'value> {
+    /// Returns the value as a `Number` if it is a number that fits
+    /// into 64 bit.
+    #[inline]
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Self::Static(s) => Number::from_static(*s),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind of the value, see `JsonKind` for how numbers
+    /// are mapped.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> JsonKind {
+        match self {
+            Self::Static(s) => JsonKind::of_static(*s),
+            Self::String(_) => JsonKind::String,
+            Self::Array(_) => JsonKind::Array,
+            Self::Object(_) => JsonKind::Object,
+        }
+    }
+
+    /// Returns true if the value is an integer, regardless of its
+    /// representation
+    #[inline]
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        self.kind().is_integer()
+    }
+
+    /// Returns true if the value is a floating point number
+    #[inline]
+    #[must_use]
+    pub fn is_float(&self) -> bool {
+        self.kind().is_float()
+    }
+
+    /// Returns true if the value is a number, regardless of its
+    /// representation
+    #[inline]
+    #[must_use]
+    pub fn is_number(&self) -> bool {
+        self.kind().is_number()
+    }
+
     /// Enforces static lifetime on a borrowed value, this will
     /// force all strings to become owned COW's, the same applies for
     /// Object keys.
@@ -117,6 +221,37 @@ impl<'value> Value<'value> {
         }
     }
 
+    /// Turns the value into one that no longer borrows from its input,
+    /// strings are copied like `into_static` does while object keys are
+    /// interned in `pool`. Documents that are kept alive together after
+    /// their inputs are gone share the memory of equal keys instead of
+    /// each owning a copy of every key.
+    ///
+    /// ```rust
+    /// use simd_json::{to_borrowed_value, BorrowedValue, KeyPool};
+    /// let pool = KeyPool::new();
+    /// let values: Vec<BorrowedValue> = (0..3)
+    ///     .map(|i| {
+    ///         let mut d = format!(r#"{{"id": {i}}}"#).into_bytes();
+    ///         to_borrowed_value(&mut d).unwrap().into_pooled(&pool)
+    ///     })
+    ///     .collect();
+    /// assert_eq!(values[2]["id"], 2);
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn into_pooled<'pool>(self, pool: &'pool KeyPool) -> Value<'pool> {
+        match self {
+            Self::String(s) => Value::String(Cow::from(s.into_owned())),
+            Self::Array(arr) => arr.into_iter().map(|v| v.into_pooled(pool)).collect(),
+            Self::Object(obj) => obj
+                .into_iter()
+                .map(|(k, v)| (Cow::from(pool.intern(&k)), v.into_pooled(pool)))
+                .collect(),
+            Self::Static(s) => Value::Static(s),
+        }
+    }
+
     /// Clones the current value and enforces a static lifetime, it works the same
     /// as `into_static` but includes cloning logic
     #[inline]
@@ -146,6 +281,355 @@ impl<'value> Value<'value> {
             Value::Static(s) => Value::Static(*s),
         }
     }
+
+    /// Looks up the value at a JSON pointer, returns `None` if there
+    /// is no value at the pointer.
+    #[must_use]
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<&Self> {
+        pointer.tokens().try_fold(self, |v, token| match v {
+            Self::Object(o) => o.get(&*token),
+            Self::Array(a) => a.get(array_index(&token)?),
+            Self::Static(_) | Self::String(_) => None,
+        })
+    }
+
+    /// Returns the first value of the document, starting with the value
+    /// itself, that matches `pred`.
+    ///
+    /// Values are visited in pre-order with an explicit stack, so deep
+    /// documents don't overflow the call stack: a value is visited before
+    /// its children and children in the order of their array or object.
+    /// Arrays and objects built by the parser keep document order, for
+    /// objects with many keys the order is the one of the underlying map.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::json;
+    /// use simd_json::BorrowedValue;
+    /// let v = BorrowedValue::from(json!({"items": [{"code": 1}, {"type": "error", "code": 2}], "meta": {"code": 3}}));
+    /// let error = v.find(|e| e.get_str("type") == Some("error")).unwrap();
+    /// assert_eq!(error["code"], 2);
+    /// // objects containing a key, parents before children
+    /// let with_code = v.find_all(|e| e.get("code").is_some());
+    /// assert_eq!(with_code.len(), 3);
+    /// assert_eq!(with_code[0]["code"], 1);
+    /// assert_eq!(with_code[1]["code"], 2);
+    /// ```
+    #[must_use]
+    pub fn find<'a, F>(&'a self, mut pred: F) -> Option<&'a Self>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut stack = vec![self];
+        while let Some(v) = stack.pop() {
+            if pred(v) {
+                return Some(v);
+            }
+            v.push_children(&mut stack);
+        }
+        None
+    }
+
+    /// Returns all values of the document, starting with the value
+    /// itself, that match `pred`, in the order of `find`.
+    #[must_use]
+    pub fn find_all<'a, F>(&'a self, mut pred: F) -> Vec<&'a Self>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut found = Vec::new();
+        let mut stack = vec![self];
+        while let Some(v) = stack.pop() {
+            if pred(v) {
+                found.push(v);
+            }
+            v.push_children(&mut stack);
+        }
+        found
+    }
+
+    /// Like `find`, but also returns the JSON pointer of the match
+    /// relative to this value.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// use simd_json::BorrowedValue;
+    /// let v = BorrowedValue::from(json!({"items": [{"c": 1}, {"type": "error", "c": 2}], "meta": {"c": 3}}));
+    /// let (pointer, found) = v.find_path(|e| e == &2).unwrap();
+    /// assert_eq!(pointer.as_str(), "/items/1/c");
+    /// assert_eq!(v.pointer(&pointer), Some(found));
+    /// ```
+    #[must_use]
+    pub fn find_path<'a, F>(&'a self, mut pred: F) -> Option<(JsonPointer, &'a Self)>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut stack = vec![(String::new(), self)];
+        while let Some((path, v)) = stack.pop() {
+            if pred(v) {
+                return Some((JsonPointer::from_valid(path), v));
+            }
+            v.push_children_with_paths(&path, &mut stack);
+        }
+        None
+    }
+
+    /// Like `find_all`, but also returns the JSON pointers of the matches
+    /// relative to this value.
+    #[must_use]
+    pub fn find_all_paths<'a, F>(&'a self, mut pred: F) -> Vec<(JsonPointer, &'a Self)>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut found = Vec::new();
+        let mut stack = vec![(String::new(), self)];
+        while let Some((path, v)) = stack.pop() {
+            v.push_children_with_paths(&path, &mut stack);
+            if pred(v) {
+                found.push((JsonPointer::from_valid(path), v));
+            }
+        }
+        found
+    }
+
+    /// Counts the leaves of the document, that is nulls, bools, numbers
+    /// and strings. Arrays and objects aren't counted, empty ones add
+    /// nothing.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// use simd_json::BorrowedValue;
+    /// let v = BorrowedValue::from(json!({"a": [1, "x", null], "b": {"c": true, "d": []}}));
+    /// assert_eq!(v.count_leaves(), 4);
+    /// ```
+    #[must_use]
+    pub fn count_leaves(&self) -> usize {
+        let mut leaves = 0;
+        let mut stack = vec![self];
+        while let Some(v) = stack.pop() {
+            match v {
+                Self::Static(_) | Self::String(_) => leaves += 1,
+                Self::Array(_) | Self::Object(_) => v.push_children(&mut stack),
+            }
+        }
+        leaves
+    }
+
+    /// Compares two values like `==` except that arrays are compared as
+    /// multisets: each element of one array has to be equal to a distinct
+    /// element of the other, in any order and again ignoring the order of
+    /// nested arrays. Objects, strings and numbers compare as usual, so an
+    /// integer stored signed and unsigned is equal while `1` and `1.0`
+    /// are not.
+    ///
+    /// ```rust
+    /// use simd_json::{json, BorrowedValue};
+    /// let a = BorrowedValue::from(json!({"tags": ["b", "a", "b"], "points": [[2, 1], [3]]}));
+    /// let b = BorrowedValue::from(json!({"points": [[3], [1, 2]], "tags": ["a", "b", "b"]}));
+    /// assert!(a.eq_unordered(&b));
+    /// assert!(a != b);
+    /// ```
+    ///
+    /// Arrays that are equal in order take as many comparisons as `==`.
+    /// Otherwise every element is compared with every element of the
+    /// other array, that is quadratic in the length of the array, plus
+    /// finding a matching of the equal pairs which is cubic in the worst
+    /// case, for example when many elements are equal to each other.
+    #[must_use]
+    pub fn eq_unordered(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Static(s1), Self::Static(s2)) => s1 == s2,
+            (Self::String(s1), Self::String(s2)) => s1 == s2,
+            (Self::Array(a1), Self::Array(a2)) => multiset_eq(a1, a2, Self::eq_unordered),
+            (Self::Object(o1), Self::Object(o2)) => {
+                o1.len() == o2.len()
+                    && o1
+                        .iter()
+                        .all(|(k, v1)| o2.get(k).map_or(false, |v2| v1.eq_unordered(v2)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Iterates over the members of an object in lexicographic byte order
+    /// of their keys, without changing the object. Returns `None` if the
+    /// value isn't an object.
+    ///
+    /// Only references to the members are sorted, the values are iterated
+    /// over as they are so nested objects keep their order.
+    ///
+    /// ```rust
+    /// use simd_json::{json, BorrowedValue};
+    /// let v = BorrowedValue::from(json!({"b": 1, "a": {"d": 2, "c": 3}, "B": 4}));
+    /// let keys: Vec<&str> = v.object_iter_sorted().unwrap().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, vec!["B", "a", "b"]);
+    /// assert!(BorrowedValue::from(1).object_iter_sorted().is_none());
+    /// ```
+    #[must_use]
+    pub fn object_iter_sorted(&self) -> Option<impl Iterator<Item = (&str, &Self)>> {
+        let o = self.as_object()?;
+        let mut entries: Vec<(&str, &Self)> = o.iter().map(|(k, v)| (k.as_ref(), v)).collect();
+        entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        Some(entries.into_iter())
+    }
+
+    /// Pushes the children of a value onto a traversal stack so they are
+    /// popped in order
+    fn push_children<'a>(&'a self, stack: &mut Vec<&'a Self>) {
+        let start = stack.len();
+        match self {
+            Self::Array(a) => stack.extend(a.iter()),
+            Self::Object(o) => stack.extend(o.values()),
+            Self::Static(_) | Self::String(_) => {}
+        }
+        stack[start..].reverse();
+    }
+
+    /// Like `push_children` with the pointer of each child
+    fn push_children_with_paths<'a>(&'a self, path: &str, stack: &mut Vec<(String, &'a Self)>) {
+        let start = stack.len();
+        match self {
+            Self::Array(a) => stack.extend(
+                a.iter()
+                    .enumerate()
+                    .map(|(i, v)| (format!("{path}/{i}"), v)),
+            ),
+            Self::Object(o) => stack.extend(o.iter().map(|(k, v)| {
+                let mut child = path.to_string();
+                push_token(&mut child, k);
+                (child, v)
+            })),
+            Self::Static(_) | Self::String(_) => {}
+        }
+        stack[start..].reverse();
+    }
+
+    /// Adds `by` to an integer or float in place, integers that
+    /// overflow are an error. This is the same as `incr_with(by, false)`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not a number or the result
+    /// can't be represented.
+    pub fn incr(&mut self, by: i64) -> std::result::Result<(), NumericError> {
+        self.incr_with(by, false)
+    }
+
+    /// Adds `by` to an integer or float in place. Integers stay integers
+    /// and switch between `i64` and `u64` as needed, results that fit
+    /// neither become floats if `promote_to_f64` is set.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::{json, BorrowedValue, NumericError};
+    /// let mut v = BorrowedValue::from(json!({"count": u64::MAX}));
+    /// assert_eq!(v["count"].incr(1), Err(NumericError::Overflow));
+    /// assert_eq!(v["count"].incr_with(1, true), Ok(()));
+    /// assert!(v["count"].is_f64());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not a number or the result
+    /// can't be represented.
+    pub fn incr_with(
+        &mut self,
+        by: i64,
+        promote_to_f64: bool,
+    ) -> std::result::Result<(), NumericError> {
+        match self {
+            Self::Static(s) => numeric::incr(s, by, promote_to_f64),
+            Self::String(_) | Self::Array(_) | Self::Object(_) => Err(NumericError::NotANumber),
+        }
+    }
+
+    /// Adds `by` to a number in place, the result is always a float.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not a number or the result is
+    /// not finite.
+    pub fn incr_f64(&mut self, by: f64) -> std::result::Result<(), NumericError> {
+        match self {
+            Self::Static(s) => numeric::incr_f64(s, by),
+            Self::String(_) | Self::Array(_) | Self::Object(_) => Err(NumericError::NotANumber),
+        }
+    }
+
+    /// Replaces the values of all object keys matched by `keys` with
+    /// `"[REDACTED]"`, at any depth. Matched values are not traversed
+    /// any further and strings that are not redacted stay borrowed.
+    pub fn redact_keys(&mut self, keys: &KeyMatcher) {
+        match self {
+            Self::Array(a) => {
+                for v in a {
+                    v.redact_keys(keys);
+                }
+            }
+            Self::Object(o) => {
+                for (k, v) in o.iter_mut() {
+                    if keys.is_match(k) {
+                        *v = Self::String(Cow::from(REDACTED));
+                    } else {
+                        v.redact_keys(keys);
+                    }
+                }
+            }
+            Self::Static(_) | Self::String(_) => (),
+        }
+    }
+
+    /// Replaces all integers with floats of the same value, at any depth,
+    /// for consumers that treat every number as a float. Integers that
+    /// can't be represented exactly as a `f64`, such as `2^53 + 1`, are
+    /// an error and leave the value unchanged.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::{to_borrowed_value, NumericError};
+    /// let mut d = br#"{"a": [1, 2.5], "b": -3}"#.to_vec();
+    /// let mut v = to_borrowed_value(&mut d).unwrap();
+    /// v.normalize_numbers_to_f64().unwrap();
+    /// assert!(v["a"][0].is_f64());
+    /// assert_eq!(v["b"], -3.0);
+    /// let mut d = b"[1, 9007199254740993]".to_vec();
+    /// let mut v = to_borrowed_value(&mut d).unwrap();
+    /// assert_eq!(v.normalize_numbers_to_f64(), Err(NumericError::Overflow));
+    /// assert!(v[0].is_i64());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an integer can't be represented exactly.
+    pub fn normalize_numbers_to_f64(&mut self) -> std::result::Result<(), NumericError> {
+        // check all numbers first so an error leaves the value unchanged
+        self.numbers_to_f64(false)?;
+        self.numbers_to_f64(true)
+    }
+
+    fn numbers_to_f64(&mut self, convert: bool) -> std::result::Result<(), NumericError> {
+        match self {
+            Self::Static(s) => {
+                if let Some(f) = numeric::exact_f64(*s)? {
+                    if convert {
+                        *s = StaticNode::F64(f);
+                    }
+                }
+            }
+            Self::Array(a) => {
+                for v in a {
+                    v.numbers_to_f64(convert)?;
+                }
+            }
+            Self::Object(o) => {
+                for v in o.values_mut() {
+                    v.numbers_to_f64(convert)?;
+                }
+            }
+            Self::String(_) => (),
+        }
+        Ok(())
+    }
 }
 
 impl<'value> Builder<'value> for Value<'value> {
@@ -355,6 +839,15 @@ impl<'value> Index<&str> for Value<'value> {
     }
 }
 
+impl<'value> Index<&JsonPointer> for Value<'value> {
+    type Output = Self;
+    #[inline]
+    #[must_use]
+    fn index(&self, index: &JsonPointer) -> &Self::Output {
+        self.pointer(index).expect("no value at pointer")
+    }
+}
+
 impl<'value> Index<usize> for Value<'value> {
     type Output = Value<'value>;
     #[inline]
@@ -388,13 +881,17 @@ impl<'value> Default for Value<'value> {
     }
 }
 
-struct BorrowDeserializer<'de>(Deserializer<'de>);
+pub(crate) struct BorrowDeserializer<'de>(Deserializer<'de>);
 
 impl<'de> BorrowDeserializer<'de> {
     pub fn from_deserializer(de: Deserializer<'de>) -> Self {
         Self(de)
     }
 
+    pub(crate) fn into_tape(self) -> Vec<Node<'de>> {
+        self.0.into_tape()
+    }
+
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     pub fn parse(&mut self) -> Value<'de> {
         match unsafe { self.0.next_() } {
@@ -424,15 +921,21 @@ impl<'de> BorrowDeserializer<'de> {
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     fn parse_map(&mut self, len: usize) -> Value<'de> {
         let mut res = Object::with_capacity_and_hasher(len, ObjectHasher::default());
+        let duplicate_keys = self.0.duplicate_keys;
 
         // Since we checked if it's empty we know that we at least have one
         // element so we eat this
         for _ in 0..len {
             if let Node::String(key) = unsafe { self.0.next_() } {
-                #[cfg(not(feature = "value-no-dup-keys"))]
-                res.insert_nocheck(key.into(), self.parse());
-                #[cfg(feature = "value-no-dup-keys")]
-                res.insert(key.into(), self.parse());
+                if duplicate_keys == DuplicateKeys::Unchecked {
+                    #[cfg(not(feature = "value-no-dup-keys"))]
+                    res.insert_nocheck(key.into(), self.parse());
+                    #[cfg(feature = "value-no-dup-keys")]
+                    res.insert(key.into(), self.parse());
+                } else {
+                    let value = self.parse();
+                    insert_duplicate_keys(&mut res, key.into(), value, duplicate_keys);
+                }
             } else {
                 unreachable!();
             }
@@ -441,6 +944,134 @@ impl<'de> BorrowDeserializer<'de> {
     }
 }
 
+impl<'de> Deserializer<'de> {
+    /// Builds the value at tape index `idx` without reading the nodes
+    /// before it. Tape indexes are the ones of `into_tape`, the root value
+    /// is at index 1, for the index of an object key the key is returned
+    /// as a string.
+    ///
+    /// ```rust
+    /// use simd_json::Deserializer;
+    /// let mut d = br#"{"a": [1, {"b": 2}]}"#.to_vec();
+    /// let de = Deserializer::from_slice(&mut d).unwrap();
+    /// assert_eq!(de.value_at_index(3).unwrap()[1]["b"], 2);
+    /// assert_eq!(de.value_at_index(5).unwrap()["b"], 2);
+    /// assert!(de.value_at_index(9).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `idx` is not an index on the tape.
+    pub fn value_at_index(&self, idx: usize) -> Result<Value<'de>> {
+        if idx == 0 || idx >= self.tape.len() {
+            return Err(Error::generic(ErrorType::IndexOutOfBounds {
+                index: idx,
+                len: self.tape.len(),
+            }));
+        }
+        Ok(self.value_from_tape(idx, None).0)
+    }
+
+    /// Builds the element at `element_idx` of the array at tape index
+    /// `array_idx`. Arrays of strings and scalars have one node per
+    /// element so the element is found directly, otherwise nested values
+    /// are skipped over using their stored size, without reading their
+    /// content.
+    ///
+    /// ```rust
+    /// use simd_json::Deserializer;
+    /// let mut d = br#"[[1, 2], [3, [4]], [5]]"#.to_vec();
+    /// let de = Deserializer::from_slice(&mut d).unwrap();
+    /// assert_eq!(de.nth_array_element(1, 2).unwrap()[0], 5);
+    /// assert_eq!(de.nth_array_element(5, 1).unwrap()[0], 4);
+    /// assert!(de.nth_array_element(1, 3).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if there is no array at `array_idx` or if the
+    /// array has no element at `element_idx`.
+    pub fn nth_array_element(&self, array_idx: usize, element_idx: usize) -> Result<Value<'de>> {
+        let (len, end) = match self.tape.get(array_idx) {
+            Some(Node::Array(len, end)) => (*len, *end),
+            _ => return Err(Error::generic(ErrorType::ExpectedArray)),
+        };
+        if element_idx >= len {
+            return Err(Error::generic(ErrorType::IndexOutOfBounds {
+                index: element_idx,
+                len,
+            }));
+        }
+        let mut idx = array_idx + 1;
+        if end - idx == len {
+            idx += element_idx;
+        } else {
+            for _ in 0..element_idx {
+                idx = crate::tape::skip(&self.tape, idx);
+            }
+        }
+        Ok(self.value_from_tape(idx, None).0)
+    }
+
+    /// Builds the value at tape index `idx` and returns it together with
+    /// the index of the node after it, object keys are looked up in
+    /// `pool` if there is one
+    fn value_from_tape(&self, idx: usize, pool: Option<&'de KeyPool>) -> (Value<'de>, usize) {
+        match self.tape[idx] {
+            Node::Static(s) => (Value::Static(s), idx + 1),
+            Node::String(s) => (Value::from(s), idx + 1),
+            Node::Array(len, end) => {
+                let mut res = Vec::with_capacity(len);
+                let mut i = idx + 1;
+                for _ in 0..len {
+                    let (v, next) = self.value_from_tape(i, pool);
+                    res.push(v);
+                    i = next;
+                }
+                (Value::Array(res), end)
+            }
+            Node::Object(len, end) => {
+                let mut res = Object::with_capacity_and_hasher(len, ObjectHasher::default());
+                let mut i = idx + 1;
+                for _ in 0..len {
+                    if let Node::String(key) = self.tape[i] {
+                        let key = pool.map_or(key, |pool| pool.intern(key));
+                        let (v, next) = self.value_from_tape(i + 1, pool);
+                        insert_duplicate_keys(&mut res, key.into(), v, self.duplicate_keys);
+                        i = next;
+                    } else {
+                        unreachable!();
+                    }
+                }
+                (Value::from(res), end)
+            }
+        }
+    }
+}
+
+/// Inserts into an object that is being built, resolving duplicate keys
+/// with the given policy
+fn insert_duplicate_keys<'de>(
+    res: &mut Object<'de>,
+    key: Cow<'de, str>,
+    value: Value<'de>,
+    duplicate_keys: DuplicateKeys,
+) {
+    if let Some(existing) = res.get_mut(&key) {
+        match (duplicate_keys, existing, value) {
+            (DuplicateKeys::FirstWins, _, _) => (),
+            (DuplicateKeys::MergeObjects, Value::Object(existing), Value::Object(value)) => {
+                for (k, v) in *value {
+                    insert_duplicate_keys(existing, k, v, duplicate_keys);
+                }
+            }
+            (_, existing, value) => *existing = value,
+        }
+    } else {
+        res.insert_nocheck(key, value);
+    }
+}
+
 #[cfg(test)]
 mod test {
     #![allow(clippy::cognitive_complexity)]
@@ -459,6 +1090,76 @@ mod test {
         assert_eq!(v.remove("key"), Ok(Some(Value::from(3))));
     }
 
+    #[test]
+    fn tape_random_access() {
+        let mut d = br#"{"a": [1, {"b": [2, 3]}, "x", [], 4], "c": null}"#.to_vec();
+        let de = Deserializer::from_slice(&mut d).expect("valid json");
+        let tape = de.tape.clone();
+        for idx in 1..tape.len() {
+            let v = de.value_at_index(idx).expect("a tape index");
+            if let Node::Array(len, _) = tape[idx] {
+                for n in 0..len {
+                    assert_eq!(
+                        de.nth_array_element(idx, n).expect("an element"),
+                        v[n],
+                        "{idx} {n}"
+                    );
+                }
+                let e = de.nth_array_element(idx, len).expect_err("out of range");
+                assert_eq!(e.error(), &ErrorType::IndexOutOfBounds { index: len, len });
+            } else {
+                let e = de.nth_array_element(idx, 0).expect_err("not an array");
+                assert_eq!(e.error(), &ErrorType::ExpectedArray);
+            }
+        }
+        assert_eq!(
+            crate::OwnedValue::from(de.value_at_index(1).expect("the root")),
+            crate::json!({"a": [1, {"b": [2, 3]}, "x", [], 4], "c": null})
+        );
+        assert_eq!(de.value_at_index(2).expect("a key"), "a");
+        let e = de.value_at_index(0).expect_err("the dummy root");
+        assert_eq!(
+            e.error(),
+            &ErrorType::IndexOutOfBounds {
+                index: 0,
+                len: tape.len()
+            }
+        );
+        assert!(de.value_at_index(tape.len()).is_err());
+    }
+
+    #[test]
+    fn redact_keys() {
+        let mut input =
+            br#"{"user": "ferris", "Token": {"a": 1}, "list": [{"token": "abc", "b": "c"}]}"#
+                .to_vec();
+        let input_range = input.as_ptr_range();
+        let mut v = to_value(&mut input).expect("valid json");
+        v.redact_keys(&KeyMatcher::case_insensitive(["TOKEN"]));
+        assert_eq!(v["Token"], "[REDACTED]");
+        assert_eq!(v["list"][0]["token"], "[REDACTED]");
+        assert_eq!(v["list"][0]["b"], "c");
+        // untouched strings still point into the input
+        assert!(matches!(&v["user"], Value::String(s) if input_range.contains(&s.as_ptr())));
+    }
+
+    #[test]
+    fn pointer() {
+        let mut input = br#"{"a": [{"b": "c"}, 2], "": {"m~n": 3, "a/b": 4}}"#.to_vec();
+        let v = to_value(&mut input).expect("valid json");
+        let p = |s| JsonPointer::new(s).expect("valid pointer");
+        assert_eq!(v[&p("")], v);
+        assert_eq!(v[&p("/a/0/b")], "c");
+        assert_eq!(v[&p("/a/1")], 2);
+        assert_eq!(v[&p("/")]["a/b"], 4);
+        assert_eq!(v[&p("//m~0n")], 3);
+        assert_eq!(v[&p("//a~1b")], 4);
+        assert!(v.pointer(&p("/a/2")).is_none());
+        assert!(v.pointer(&p("/a/01")).is_none());
+        assert!(v.pointer(&p("/a/-")).is_none());
+        assert!(v.pointer(&p("/a/0/b/c")).is_none());
+    }
+
     #[test]
     fn array_access() {
         let mut v = Value::null();
@@ -1046,6 +1747,34 @@ mod test {
         );
     }
 
+    #[test]
+    fn object_iter_sorted() {
+        // enough keys for the object to be stored as a hash map
+        let mut d = (0..40)
+            .rev()
+            .map(|i| format!(r#""k{i}": {i}"#))
+            .collect::<Vec<_>>()
+            .join(",");
+        d = format!("{{{d}, \"\u{e9}\": 0, \"Z\": 0, \"\": 0}}");
+        let mut d = d.into_bytes();
+        let v = crate::to_borrowed_value(&mut d).expect("valid json");
+        let sorted: Vec<(&str, &Value)> = v.object_iter_sorted().expect("object").collect();
+        assert_eq!(sorted.len(), 43);
+        assert_eq!(sorted[0], ("", &Value::from(0)));
+        assert_eq!(sorted[1], ("Z", &Value::from(0)));
+        assert_eq!(sorted[2], ("k0", &Value::from(0)));
+        assert_eq!(sorted[3], ("k1", &Value::from(1)));
+        assert_eq!(sorted[4], ("k10", &Value::from(10)));
+        // non ASCII keys sort by their UTF-8 bytes
+        assert_eq!(sorted[42].0, "\u{e9}");
+        assert!(sorted.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(Value::array().object_iter_sorted().is_none());
+        assert_eq!(
+            Value::object().object_iter_sorted().map(Iterator::count),
+            Some(0)
+        );
+    }
+
     #[test]
     fn test_option_from() {
         let v: Option<u8> = None;
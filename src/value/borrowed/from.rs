@@ -1,7 +1,7 @@
 use super::{Object, Value};
 use crate::cow::Cow;
 use crate::OwnedValue;
-use crate::StaticNode;
+use crate::{Number, StaticNode};
 use std::iter::FromIterator;
 
 impl<'value> From<OwnedValue> for Value<'value> {
@@ -25,6 +25,14 @@ impl<'value> From<StaticNode> for Value<'value> {
     }
 }
 
+impl<'value> From<Number> for Value<'value> {
+    #[inline]
+    #[must_use]
+    fn from(n: Number) -> Self {
+        Self::Static(n.into())
+    }
+}
+
 impl<'value, T> From<Option<T>> for Value<'value>
 where
     Value<'value>: From<T>,
@@ -0,0 +1,261 @@
+use super::{to_value, Value};
+use crate::cow::Cow;
+use crate::value::pointer::{array_index, JsonPointer};
+use crate::Result;
+use std::marker::PhantomData;
+
+/// A borrowed value whose strings can be mutated in the input buffer.
+/// Created with `to_borrowed_value_mut`.
+///
+/// Every string of a freshly parsed value is borrowed from the input
+/// buffer, escaped strings are unescaped into the buffer while parsing.
+/// Mutating such a string writes straight into the buffer, nothing is
+/// copied, so transforming the strings of a large document doesn't
+/// double its memory. Strings that are not borrowed from the buffer are
+/// copied into an owned string the first time they are mutated.
+///
+/// The constraints that come with this:
+///
+/// * Mutation goes through `with_str_mut` and `for_each_str_mut`, which
+///   hand out `&mut str`, so a string can't change its length.
+/// * There is no `&mut BorrowedValue` access to the value, and `value`
+///   hands out the value with a lifetime limited to the borrow of `self`,
+///   so no string can be held on to across mutations.
+/// * The input buffer stays borrowed until this is dropped or turned
+///   into a value with `into_value`, the mutations are visible in the
+///   buffer afterwards.
+///
+/// ```rust
+/// use simd_json::{to_borrowed_value_mut, JsonPointer};
+/// let mut d = br#"{"name": "ferris", "tags": ["crab", "rust"]}"#.to_vec();
+/// let mut v = to_borrowed_value_mut(&mut d).unwrap();
+/// let p = JsonPointer::new("/name").unwrap();
+/// v.with_str_mut(&p, str::make_ascii_uppercase).unwrap();
+/// assert_eq!(v.value()["name"], "FERRIS");
+/// v.for_each_str_mut(str::make_ascii_uppercase);
+/// assert_eq!(v.value()["tags"][1], "RUST");
+/// ```
+#[derive(Debug)]
+pub struct InPlaceValue<'value> {
+    value: Value<'value>,
+    buffer: Buffer<'value>,
+}
+
+impl<'value> InPlaceValue<'value> {
+    pub(crate) fn parse(input: &'value mut [u8]) -> Result<Self> {
+        let buffer = Buffer {
+            ptr: input.as_mut_ptr(),
+            len: input.len(),
+            _input: PhantomData,
+        };
+        // The value borrows from a view created from the buffer pointer,
+        // so the strings can later be written through the same pointer
+        let input = unsafe { std::slice::from_raw_parts_mut(buffer.ptr, buffer.len) };
+        to_value(input).map(|value| Self { value, buffer })
+    }
+
+    /// The value, its lifetime is limited to the borrow of `self`.
+    #[must_use]
+    pub fn value(&self) -> &Value<'_> {
+        &self.value
+    }
+
+    /// Turns this into the underlying value, ending mutation
+    #[must_use]
+    pub fn into_value(self) -> Value<'value> {
+        self.value
+    }
+
+    /// Calls `f` with a mutable view of the string at a JSON pointer and
+    /// returns its result. Returns `None` if there is no value at the
+    /// pointer or it is not a string.
+    pub fn with_str_mut<F, R>(&mut self, pointer: &JsonPointer, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut str) -> R,
+    {
+        let mut v = &mut self.value;
+        for token in pointer.tokens() {
+            v = match v {
+                Value::Object(o) => o.get_mut(&*token)?,
+                Value::Array(a) => a.get_mut(array_index(&token)?)?,
+                Value::Static(_) | Value::String(_) => return None,
+            };
+        }
+        if let Value::String(s) = v {
+            Some(self.buffer.str_mut(s, f))
+        } else {
+            None
+        }
+    }
+
+    /// Calls `f` with a mutable view of every string value, object keys
+    /// are not visited. Strings are visited in document order, except
+    /// for objects with more than 32 keys which are visited in hash
+    /// order.
+    pub fn for_each_str_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut str),
+    {
+        let mut stack = vec![&mut self.value];
+        while let Some(v) = stack.pop() {
+            match v {
+                Value::String(s) => self.buffer.str_mut(s, &mut f),
+                Value::Array(a) => stack.extend(a.iter_mut().rev()),
+                Value::Object(o) => {
+                    let start = stack.len();
+                    stack.extend(o.values_mut());
+                    stack[start..].reverse();
+                }
+                Value::Static(_) => (),
+            }
+        }
+    }
+}
+
+// The buffer pointer is only written through from `&mut InPlaceValue`,
+// so this is as thread safe as the `&mut [u8]` it was created from.
+unsafe impl Send for InPlaceValue<'_> {}
+unsafe impl Sync for InPlaceValue<'_> {}
+
+/// The input buffer a value was parsed from, borrowed mutably for
+/// `'value`.
+#[derive(Debug, Clone, Copy)]
+struct Buffer<'value> {
+    ptr: *mut u8,
+    len: usize,
+    _input: PhantomData<&'value mut [u8]>,
+}
+
+impl<'value> Buffer<'value> {
+    /// Calls `f` with a mutable view of a string, in the buffer if the
+    /// string is borrowed from it.
+    fn str_mut<F, R>(self, s: &mut Cow<'value, str>, f: F) -> R
+    where
+        F: FnOnce(&mut str) -> R,
+    {
+        let offset = match self.offset_of(s) {
+            Some(offset) => offset,
+            None => return with_owned(s, f),
+        };
+        let len = s.len();
+        unsafe {
+            // The string lies in the buffer and strings never overlap, so
+            // this is the only view of these bytes. The old borrow is
+            // replaced with one created after the mutation and never used
+            // again.
+            let ptr = self.ptr.add(offset);
+            let res = f(std::str::from_utf8_unchecked_mut(
+                std::slice::from_raw_parts_mut(ptr, len),
+            ));
+            *s = Cow::from(std::str::from_utf8_unchecked(std::slice::from_raw_parts(
+                ptr, len,
+            )));
+            res
+        }
+    }
+
+    /// The offset of a string in the buffer if it is borrowed from it.
+    #[allow(clippy::ptr_arg)] // whether it is borrowed matters
+    fn offset_of(self, s: &Cow<'_, str>) -> Option<usize> {
+        let s = borrowed(s)?;
+        let offset = (s.as_ptr() as usize).checked_sub(self.ptr as usize)?;
+        if offset + s.len() <= self.len {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "beef"))]
+#[allow(clippy::ptr_arg)]
+fn borrowed<'cow>(s: &'cow Cow<'_, str>) -> Option<&'cow str> {
+    match s {
+        Cow::Borrowed(s) => Some(s),
+        Cow::Owned(_) => None,
+    }
+}
+
+#[cfg(feature = "beef")]
+#[allow(clippy::ptr_arg)]
+fn borrowed<'cow>(s: &'cow Cow<'_, str>) -> Option<&'cow str> {
+    if s.is_borrowed() {
+        Some(s)
+    } else {
+        None
+    }
+}
+
+/// Calls `f` with a mutable view of a string, copying it into an owned
+/// string first if it is borrowed.
+#[cfg(not(feature = "beef"))]
+fn with_owned<F, R>(s: &mut Cow<'_, str>, f: F) -> R
+where
+    F: FnOnce(&mut str) -> R,
+{
+    f(s.to_mut().as_mut_str())
+}
+
+/// Calls `f` with a mutable view of a string, copying it into an owned
+/// string first if it is borrowed.
+#[cfg(feature = "beef")]
+fn with_owned<F, R>(s: &mut Cow<'_, str>, f: F) -> R
+where
+    F: FnOnce(&mut str) -> R,
+{
+    // beef has no `to_mut`, the string is moved out and back in, which
+    // doesn't copy it if it is already owned
+    let mut owned = std::mem::replace(s, Cow::from("")).into_owned();
+    let res = f(owned.as_mut_str());
+    *s = Cow::from(owned);
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::{InPlaceValue, Value};
+    use crate::{to_borrowed_value_mut, JsonPointer};
+
+    #[test]
+    fn in_place() {
+        let mut d = br#"{"a": "hello", "b": ["x\ty", "caf\u00e9"], "c": 1}"#.to_vec();
+        let mut v = to_borrowed_value_mut(&mut d).expect("valid json");
+        let p = |s| JsonPointer::new(s).expect("valid pointer");
+        assert_eq!(
+            v.with_str_mut(&p("/a"), |s| {
+                s.make_ascii_uppercase();
+                s.len()
+            }),
+            Some(5)
+        );
+        assert!(v.with_str_mut(&p("/c"), |_| ()).is_none());
+        assert!(v.with_str_mut(&p("/d"), |_| ()).is_none());
+        let mut seen = Vec::new();
+        v.for_each_str_mut(|s| {
+            seen.push(String::from(&*s));
+            s.make_ascii_uppercase();
+        });
+        assert_eq!(seen, vec!["HELLO", "x\ty", "caf\u{e9}"]);
+        let value = v.value();
+        assert_eq!(value["a"], "HELLO");
+        assert_eq!(value["b"][0], "X\tY");
+        assert_eq!(value["b"][1], "CAF\u{e9}");
+        drop(v);
+        // the strings, including the unescaped ones, were changed in the
+        // input buffer
+        assert!(d.starts_with(b"{\"a\": \"HELLO\", \"b\": [\"X\tY"));
+    }
+
+    #[test]
+    fn owned_strings() {
+        let mut d = b"[]".to_vec();
+        let mut v = InPlaceValue::parse(&mut d).expect("valid json");
+        v.value = Value::from(vec![
+            Value::from("static"),
+            Value::from(String::from("owned")),
+        ]);
+        v.for_each_str_mut(str::make_ascii_uppercase);
+        assert_eq!(v.value()[0], "STATIC");
+        assert_eq!(v.into_value()[1], "OWNED");
+    }
+}
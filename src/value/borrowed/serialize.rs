@@ -7,7 +7,8 @@
 use super::{Object, Value};
 use crate::prelude::*;
 use crate::stry;
-use crate::StaticNode;
+use crate::value::pretty::{entries, CompactGenerator, LimitedWriter, OptionsGenerator};
+use crate::{PrettyOptions, SerializeOptions, StaticNode};
 use std::io;
 use std::io::Write;
 use value_trait::generator::{
@@ -50,6 +51,188 @@ impl<'value> Writable for Value<'value> {
     }
 }
 
+impl<'value> Value<'value> {
+    /// Encodes the value as pretty printed JSON, formatted according to
+    /// `opts`.
+    #[must_use]
+    pub fn encode_pp_with(&self, opts: PrettyOptions) -> String {
+        let mut g = OptionsGenerator::new(Vec::new(), opts);
+        let _r = g.write_json_with(self);
+        // the generator only ever writes valid UTF-8
+        unsafe { String::from_utf8_unchecked(g.into_inner()) }
+    }
+
+    /// Encodes the value as pretty printed JSON indented by
+    /// `indent_width` times `indent_char` per level, a shorthand for
+    /// `encode_pp_with` with only the indentation changed.
+    ///
+    /// ```rust
+    /// use simd_json::{json, BorrowedValue};
+    /// let v = BorrowedValue::from(json!({"a": [1]}));
+    /// assert_eq!(
+    ///     v.to_pretty_json_string_with_indent('\t', 1),
+    ///     "{\n\t\"a\": [\n\t\t1\n\t]\n}"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_pretty_json_string_with_indent(
+        &self,
+        indent_char: char,
+        indent_width: usize,
+    ) -> String {
+        self.encode_pp_with(
+            PrettyOptions::default()
+                .indent_char(indent_char)
+                .indent(indent_width),
+        )
+    }
+
+    /// Writes the value as pretty printed JSON, formatted according to
+    /// `opts`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails.
+    pub fn write_pp_with<W>(&self, w: &mut W, opts: PrettyOptions) -> io::Result<()>
+    where
+        W: Write,
+    {
+        OptionsGenerator::new(w, opts).write_json_with(self)
+    }
+
+    /// Encodes the value as compact JSON, formatted according to `opts`.
+    #[must_use]
+    pub fn encode_with(&self, opts: SerializeOptions) -> String {
+        let mut g = CompactGenerator::new(Vec::new(), opts);
+        let _r = g.write_json(self);
+        // the generator only ever writes valid UTF-8
+        unsafe { String::from_utf8_unchecked(g.into_inner()) }
+    }
+
+    /// Writes the value as compact JSON, formatted according to `opts`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails.
+    pub fn write_with<W>(&self, w: &mut W, opts: SerializeOptions) -> io::Result<()>
+    where
+        W: Write,
+    {
+        CompactGenerator::new(w, opts).write_json(self)
+    }
+}
+
+trait OptionsWriter {
+    /// Writes a value, expanding containers that don't fit on a line
+    fn write_json_with(&mut self, json: &Value) -> io::Result<()>;
+    /// Writes a value on a single line
+    fn write_line(&mut self, json: &Value) -> io::Result<()>;
+    /// Returns the single line form of a container if it fits within
+    /// the compact threshold
+    fn fit_line(&self, json: &Value) -> Option<Vec<u8>>;
+}
+
+impl<W> OptionsWriter for OptionsGenerator<W>
+where
+    W: Write,
+{
+    fn write_json_with(&mut self, json: &Value) -> io::Result<()> {
+        match json {
+            Value::Array(array) if !array.is_empty() => {
+                if let Some(line) = self.fit_line(json) {
+                    return self.write(&line);
+                }
+                stry!(self.write(b"["));
+                self.indent();
+                for (i, item) in array.iter().enumerate() {
+                    if i > 0 {
+                        stry!(self.write(b","));
+                    }
+                    stry!(self.new_line());
+                    stry!(self.write_json_with(item));
+                }
+                self.dedent();
+                stry!(self.new_line());
+                self.write(b"]")
+            }
+            Value::Object(object) if !object.is_empty() => {
+                if let Some(line) = self.fit_line(json) {
+                    return self.write(&line);
+                }
+                stry!(self.write(b"{"));
+                self.indent();
+                for (i, (key, value)) in
+                    entries(object.iter(), self.opts.stable_key_order).enumerate()
+                {
+                    if i > 0 {
+                        stry!(self.write(b","));
+                    }
+                    stry!(self.new_line());
+                    stry!(self.write_simple_string(key));
+                    stry!(self.write(b": "));
+                    stry!(self.write_json_with(value));
+                }
+                self.dedent();
+                stry!(self.new_line());
+                self.write(b"}")
+            }
+            _ => self.write_line(json),
+        }
+    }
+
+    fn write_line(&mut self, json: &Value) -> io::Result<()> {
+        match *json {
+            Value::Static(StaticNode::Null) => self.write(b"null"),
+            Value::Static(StaticNode::I64(number)) => self.write_int(number),
+            #[cfg(feature = "128bit")]
+            Value::Static(StaticNode::I128(number)) => self.write_int(number),
+            Value::Static(StaticNode::U64(number)) => self.write_int(number),
+            #[cfg(feature = "128bit")]
+            Value::Static(StaticNode::U128(number)) => self.write_int(number),
+            Value::Static(StaticNode::F64(number)) => self.write_float(number),
+            Value::Static(StaticNode::Bool(true)) => self.write(b"true"),
+            Value::Static(StaticNode::Bool(false)) => self.write(b"false"),
+            Value::String(ref string) => self.write_string(string),
+            Value::Array(ref array) => {
+                stry!(self.write(b"["));
+                for (i, item) in array.iter().enumerate() {
+                    if i > 0 {
+                        stry!(self.write(b", "));
+                    }
+                    stry!(self.write_line(item));
+                }
+                self.write(b"]")
+            }
+            Value::Object(ref object) => {
+                stry!(self.write(b"{"));
+                for (i, (key, value)) in
+                    entries(object.iter(), self.opts.stable_key_order).enumerate()
+                {
+                    if i > 0 {
+                        stry!(self.write(b", "));
+                    }
+                    stry!(self.write_simple_string(key));
+                    stry!(self.write(b": "));
+                    stry!(self.write_line(value));
+                }
+                self.write(b"}")
+            }
+        }
+    }
+
+    fn fit_line(&self, json: &Value) -> Option<Vec<u8>> {
+        if self.opts.compact_threshold == 0 {
+            return None;
+        }
+        // writing stops as soon as the threshold is exceeded, so large
+        // containers are not written in full just to be discarded
+        let w = LimitedWriter::new(self.opts.compact_threshold);
+        let mut g = OptionsGenerator::new(w, self.opts);
+        g.write_line(json).ok()?;
+        Some(g.into_inner().into_inner())
+    }
+}
+
 trait Generator: BaseGenerator {
     type T: Write;
 
@@ -209,6 +392,26 @@ trait FastGenerator: BaseGenerator {
     }
 }
 
+impl<W> FastGenerator for CompactGenerator<W>
+where
+    W: Write,
+{
+    type T = W;
+
+    fn write_object(&mut self, object: &Object) -> io::Result<()> {
+        stry!(self.write(b"{"));
+        for (i, (key, value)) in entries(object.iter(), self.opts.stable_key_order).enumerate() {
+            if i > 0 {
+                stry!(self.write(b","));
+            }
+            stry!(self.write_simple_string(key));
+            stry!(self.write(b":"));
+            stry!(self.write_json(value));
+        }
+        self.write(b"}")
+    }
+}
+
 impl<'value> FastGenerator for DumpGenerator<Value<'value>> {
     type T = Vec<u8>;
 }
@@ -235,7 +438,7 @@ where
 mod test {
     use super::Value;
     use crate::prelude::*;
-    use crate::StaticNode;
+    use crate::{json, ExponentStyle, PrettyOptions, SerializeOptions, StaticNode};
 
     #[test]
     fn null() {
@@ -279,4 +482,59 @@ mod test {
             r#""this is a test a \\\"long\\\" test that should span the 32 byte boundary""#,
         );
     }
+
+    #[test]
+    fn pretty_compact_threshold() {
+        let v = Value::from(json!({"point": [1, 2], "list": [[1, 2], [3, 4]], "obj": {}}));
+        assert_eq!(v.encode_pp_with(PrettyOptions::default()), v.encode_pp());
+        let s = v.encode_pp_with(PrettyOptions::default().compact_threshold(8));
+        assert_eq!(
+            s,
+            r#"{
+  "point": [1, 2],
+  "list": [
+    [1, 2],
+    [3, 4]
+  ],
+  "obj": {}
+}"#
+        );
+        let mut d = s.into_bytes();
+        assert_eq!(crate::to_borrowed_value(&mut d).expect("valid json"), v);
+    }
+
+    #[test]
+    fn pretty_stable_key_order() {
+        let mut d1 = br#"{"b": {"y": 1, "x": [true]}, "a": null}"#.to_vec();
+        let mut d2 = br#"{"a": null, "b": {"x": [true], "y": 1}}"#.to_vec();
+        let v1 = crate::to_borrowed_value(&mut d1).expect("valid json");
+        let v2 = crate::to_borrowed_value(&mut d2).expect("valid json");
+        let opts = PrettyOptions::default().stable_key_order(true);
+        assert_ne!(v1.encode_pp_with(PrettyOptions::default()), v2.encode_pp());
+        assert_eq!(v1.encode_pp_with(opts), v2.encode_pp_with(opts));
+        assert_eq!(
+            v1.encode_pp_with(opts.compact_threshold(64)),
+            r#"{"a": null, "b": {"x": [true], "y": 1}}"#
+        );
+    }
+
+    #[test]
+    fn encode_with() {
+        let mut d1 = br#"{"b": {"y": 1e-7, "x": [true]}, "a": null}"#.to_vec();
+        let mut d2 = br#"{"a": null, "b": {"x": [true], "y": 1e-7}}"#.to_vec();
+        let v1 = crate::to_borrowed_value(&mut d1).expect("valid json");
+        let v2 = crate::to_borrowed_value(&mut d2).expect("valid json");
+        assert_eq!(v1.encode_with(SerializeOptions::default()), v1.encode());
+        let opts = SerializeOptions::default()
+            .stable_key_order(true)
+            .exponent_style(ExponentStyle::Uppercase);
+        assert_eq!(v1.encode_with(opts), v2.encode_with(opts));
+        assert_eq!(
+            v1.encode_with(opts),
+            r#"{"a":null,"b":{"x":[true],"y":1E-7}}"#
+        );
+        let mut w = Vec::new();
+        v2.write_with(&mut w, opts).expect("write to vec");
+        assert_eq!(v1.encode_with(opts).as_bytes(), w);
+    }
 }
@@ -0,0 +1,165 @@
+use super::owned::Value;
+use crate::base64::decode_standard;
+use crate::prelude::*;
+use crate::StaticNode;
+
+/// Lenient conversions from a value, used by `OwnedValue::try_coerce_to`.
+///
+/// The built in implementations are:
+///
+/// * `String` from strings, numbers and booleans, numbers are written
+///   the same way as by `encode`.
+/// * `bool` from booleans, the numbers `0` and `1` and the strings
+///   `"true"` and `"false"`.
+/// * `f64` from numbers and strings holding a JSON number.
+/// * `Vec<u8>` from base64 encoded strings, using the standard alphabet
+///   with optional padding.
+///
+/// ```rust
+/// use simd_json::json;
+/// let v = json!({"flag": "true", "ratio": "0.5", "data": "aGk="});
+/// assert_eq!(v["flag"].try_coerce_to::<bool>(), Some(true));
+/// assert_eq!(v["ratio"].try_coerce_to::<f64>(), Some(0.5));
+/// assert_eq!(v["data"].try_coerce_to::<Vec<u8>>(), Some(b"hi".to_vec()));
+/// assert_eq!(v["data"].try_coerce_to::<bool>(), None);
+/// ```
+pub trait CoerceFromJson: Sized {
+    /// Converts the value, returns `None` if it can't be converted
+    fn coerce_from(value: &Value) -> Option<Self>;
+}
+
+impl CoerceFromJson for String {
+    fn coerce_from(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Static(StaticNode::Null) | Value::Array(_) | Value::Object(_) => None,
+            Value::Static(_) => Some(value.encode()),
+        }
+    }
+}
+
+impl CoerceFromJson for bool {
+    fn coerce_from(value: &Value) -> Option<Self> {
+        match value {
+            Value::Static(StaticNode::Bool(b)) => Some(*b),
+            Value::String(s) if s == "true" => Some(true),
+            Value::String(s) if s == "false" => Some(false),
+            v if v.is_integer() => match v.as_u64() {
+                Some(0) => Some(false),
+                Some(1) => Some(true),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl CoerceFromJson for f64 {
+    fn coerce_from(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => crate::parse_number_str(s)
+                .ok()
+                .and_then(|n| Value::Static(n).cast_f64()),
+            v => v.cast_f64(),
+        }
+    }
+}
+
+impl CoerceFromJson for Vec<u8> {
+    fn coerce_from(value: &Value) -> Option<Self> {
+        value.as_str().and_then(|s| decode_standard(s.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base64::decode_standard;
+    use crate::json;
+    use crate::prelude::*;
+
+    #[test]
+    fn strings() {
+        let v = json!(["s", 1, -1.5, true, null, [], {}]);
+        let coerced: Vec<Option<String>> = v
+            .as_array()
+            .expect("array")
+            .iter()
+            .map(|v| v.try_coerce_to())
+            .collect();
+        assert_eq!(
+            coerced,
+            [
+                Some("s".to_string()),
+                Some("1".to_string()),
+                Some("-1.5".to_string()),
+                Some("true".to_string()),
+                None,
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn bools() {
+        let v = json!([true, false, 0, 1, 2, 1.0, "true", "false", "TRUE", null]);
+        let coerced: Vec<Option<bool>> = v
+            .as_array()
+            .expect("array")
+            .iter()
+            .map(|v| v.try_coerce_to())
+            .collect();
+        assert_eq!(
+            coerced,
+            [
+                Some(true),
+                Some(false),
+                Some(false),
+                Some(true),
+                None,
+                None,
+                Some(true),
+                Some(false),
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn floats() {
+        let v = json!([1, -2.5, "3", " 4e1 ", "1.5x", "", true]);
+        let coerced: Vec<Option<f64>> = v
+            .as_array()
+            .expect("array")
+            .iter()
+            .map(|v| v.try_coerce_to())
+            .collect();
+        assert_eq!(
+            coerced,
+            [
+                Some(1.0),
+                Some(-2.5),
+                Some(3.0),
+                Some(40.0),
+                None,
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn base64() {
+        assert_eq!(decode_standard(b""), Some(vec![]));
+        assert_eq!(decode_standard(b"Zg=="), Some(b"f".to_vec()));
+        assert_eq!(decode_standard(b"Zm8="), Some(b"fo".to_vec()));
+        assert_eq!(decode_standard(b"Zm9v"), Some(b"foo".to_vec()));
+        assert_eq!(decode_standard(b"Zm9vYg"), Some(b"foob".to_vec()));
+        assert_eq!(decode_standard(b"+/+/"), Some(vec![0xfb, 0xff, 0xbf]));
+        assert_eq!(decode_standard(b"Zm9vY"), None);
+        assert_eq!(decode_standard(b"Zm9v!"), None);
+        assert_eq!(decode_standard(b"Zg=a"), None);
+        assert_eq!(json!(1).try_coerce_to::<Vec<u8>>(), None);
+    }
+}
@@ -0,0 +1,237 @@
+use super::owned::Value;
+use crate::prelude::*;
+use std::fmt::Write;
+
+/// The number of unchanged lines shown around changes
+const CONTEXT: usize = 3;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const GREY: &str = "\x1b[90m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Renders the difference between two values as a colored unified diff
+/// of their pretty printed forms, for readable test failures. Removed
+/// lines are red, added lines green and unchanged context lines grey,
+/// object keys are sorted so only actual changes show up.
+///
+/// Returns an empty string if the values are equal.
+///
+/// ```rust
+/// use simd_json::{json, pretty_print_with_color_diff};
+/// let a = json!({"name": "ferris", "legs": 10});
+/// let b = json!({"name": "ferris", "legs": 8});
+/// let diff = pretty_print_with_color_diff(&a, &b);
+/// assert!(diff.contains("\x1b[31m-  \"legs\": 10,\x1b[0m"));
+/// assert!(diff.contains("\x1b[32m+  \"legs\": 8,\x1b[0m"));
+/// assert!(pretty_print_with_color_diff(&a, &a).is_empty());
+/// ```
+#[must_use]
+pub fn pretty_print_with_color_diff(a: &Value, b: &Value) -> String {
+    let mut old = Vec::new();
+    render(a, 0, "", "", &mut old);
+    let mut new = Vec::new();
+    render(b, 0, "", "", &mut new);
+    let ops = diff_lines(&old, &new);
+
+    let mut out = String::new();
+    let mut pos = 0;
+    while let Some(start) = ops[pos..].iter().position(|(op, _)| *op != Op::Equal) {
+        let start = pos + start;
+        let hunk_start = start.saturating_sub(CONTEXT).max(pos);
+        // extend the hunk over changes that are close enough for their
+        // context to overlap
+        let mut end = start;
+        loop {
+            end += ops[end..]
+                .iter()
+                .take_while(|(op, _)| *op != Op::Equal)
+                .count();
+            let unchanged = ops[end..]
+                .iter()
+                .take_while(|(op, _)| *op == Op::Equal)
+                .count();
+            if end + unchanged < ops.len() && unchanged <= 2 * CONTEXT {
+                end += unchanged;
+            } else {
+                end += unchanged.min(CONTEXT);
+                break;
+            }
+        }
+        let hunk = &ops[hunk_start..end];
+        let old_start = ops[..hunk_start]
+            .iter()
+            .filter(|(op, _)| *op != Op::Insert)
+            .count();
+        let new_start = ops[..hunk_start]
+            .iter()
+            .filter(|(op, _)| *op != Op::Delete)
+            .count();
+        let old_len = hunk.iter().filter(|(op, _)| *op != Op::Insert).count();
+        let new_len = hunk.iter().filter(|(op, _)| *op != Op::Delete).count();
+        // writing to a `String` can't fail
+        let _r = writeln!(
+            out,
+            "{CYAN}@@ -{},{old_len} +{},{new_len} @@{RESET}",
+            old_start + 1,
+            new_start + 1
+        );
+        for (op, line) in hunk {
+            let (color, marker) = match op {
+                Op::Equal => (GREY, ' '),
+                Op::Delete => (RED, '-'),
+                Op::Insert => (GREEN, '+'),
+            };
+            let _r = writeln!(out, "{color}{marker}{line}{RESET}");
+        }
+        pos = end;
+    }
+    out
+}
+
+/// Pretty prints a value line by line with sorted object keys
+fn render(v: &Value, depth: usize, prefix: &str, suffix: &str, out: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    match v {
+        Value::Array(a) if !a.is_empty() => {
+            out.push(format!("{indent}{prefix}["));
+            for (i, v) in a.iter().enumerate() {
+                let comma = if i + 1 < a.len() { "," } else { "" };
+                render(v, depth + 1, "", comma, out);
+            }
+            out.push(format!("{indent}]{suffix}"));
+        }
+        Value::Object(o) if !o.is_empty() => {
+            out.push(format!("{indent}{prefix}{{"));
+            let mut entries: Vec<_> = o.iter().collect();
+            entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            for (i, (k, v)) in entries.iter().enumerate() {
+                let comma = if i + 1 < entries.len() { "," } else { "" };
+                let key = format!("{}: ", Value::from(k.as_str()).encode());
+                render(v, depth + 1, &key, comma, out);
+            }
+            out.push(format!("{indent}}}{suffix}"));
+        }
+        v => out.push(format!("{indent}{prefix}{}{suffix}", v.encode())),
+    }
+}
+
+/// Diffs two lists of lines using their longest common subsequence,
+/// deletions are listed before insertions.
+fn diff_lines<'l>(old: &'l [String], new: &'l [String]) -> Vec<(Op, &'l str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let o = &old[prefix..old.len() - suffix];
+    let n = &new[prefix..new.len() - suffix];
+
+    // lcs[i * w + j] is the length of the longest common subsequence of
+    // o[i..] and n[j..]
+    let w = n.len() + 1;
+    let mut lcs = vec![0_usize; (o.len() + 1) * w];
+    for i in (0..o.len()).rev() {
+        for j in (0..n.len()).rev() {
+            lcs[i * w + j] = if o[i] == n[j] {
+                lcs[(i + 1) * w + j + 1] + 1
+            } else {
+                lcs[(i + 1) * w + j].max(lcs[i * w + j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<_> = old[..prefix]
+        .iter()
+        .map(|l| (Op::Equal, l.as_str()))
+        .collect();
+    let (mut i, mut j) = (0, 0);
+    while i < o.len() || j < n.len() {
+        if i < o.len() && j < n.len() && o[i] == n[j] {
+            ops.push((Op::Equal, o[i].as_str()));
+            i += 1;
+            j += 1;
+        } else if i < o.len() && (j == n.len() || lcs[(i + 1) * w + j] >= lcs[i * w + j + 1]) {
+            ops.push((Op::Delete, o[i].as_str()));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, n[j].as_str()));
+            j += 1;
+        }
+    }
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|l| (Op::Equal, l.as_str())),
+    );
+    ops
+}
+
+#[cfg(test)]
+mod test {
+    use super::{pretty_print_with_color_diff, CYAN, GREEN, GREY, RED, RESET};
+    use crate::json;
+
+    #[test]
+    fn diff() {
+        let a = json!({"b": [1, 2], "a": 1});
+        let b = json!({"a": 1, "b": [1, 3], "c": null});
+        let expected = [
+            format!("{CYAN}@@ -2,6 +2,7 @@{RESET}"),
+            format!("{GREY}   \"a\": 1,{RESET}"),
+            format!("{GREY}   \"b\": [{RESET}"),
+            format!("{GREY}     1,{RESET}"),
+            format!("{RED}-    2{RESET}"),
+            format!("{RED}-  ]{RESET}"),
+            format!("{GREEN}+    3{RESET}"),
+            format!("{GREEN}+  ],{RESET}"),
+            format!("{GREEN}+  \"c\": null{RESET}"),
+            format!("{GREY} }}{RESET}"),
+        ];
+        assert_eq!(
+            pretty_print_with_color_diff(&a, &b),
+            expected.join("\n") + "\n"
+        );
+        assert_eq!(pretty_print_with_color_diff(&a, &a), "");
+    }
+
+    #[test]
+    fn hunks() {
+        let a = json!((0..20).collect::<Vec<_>>());
+        let mut b = a.clone();
+        b[1] = json!("one");
+        b[3] = json!("three");
+        b[18] = json!("eighteen");
+        let diff = pretty_print_with_color_diff(&a, &b);
+        let headers: Vec<_> = diff.lines().filter(|l| l.contains("@@")).collect();
+        assert_eq!(
+            headers,
+            [
+                format!("{CYAN}@@ -1,8 +1,8 @@{RESET}"),
+                format!("{CYAN}@@ -17,6 +17,6 @@{RESET}")
+            ]
+        );
+        assert!(diff.contains(&format!("{RED}-  3,{RESET}")));
+        assert!(diff.contains(&format!("{GREEN}+  \"three\",{RESET}")));
+        assert!(!diff.contains("  10,"));
+    }
+
+    #[test]
+    fn scalars() {
+        let diff = pretty_print_with_color_diff(&json!(1), &json!([]));
+        assert_eq!(
+            diff,
+            format!("{CYAN}@@ -1,1 +1,1 @@{RESET}\n{RED}-1{RESET}\n{GREEN}+[]{RESET}\n")
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::io::{self, Write};
+
+/// How the exponent of floats written in exponent notation is formatted,
+/// used by `PrettyOptions` and `SerializeOptions`. Writers that take no
+/// options, like `encode`, `encode_pp` and `to_string`, always use the
+/// default `LowercaseNoPlus`.
+///
+/// Floats are written in the shortest form that parses back to the same
+/// `f64`. Finite floats with an absolute value below `1e-5` or of at least
+/// `1e16` are written in exponent notation, all others, zero included, as
+/// decimals with at least one fractional digit. Only the exponent depends
+/// on the style:
+///
+/// | value      | `LowercaseNoPlus` | `Uppercase` | `AlwaysPlus` |
+/// |------------|-------------------|-------------|--------------|
+/// | `1.5`      | `1.5`             | `1.5`       | `1.5`        |
+/// | `1e16`     | `1e16`            | `1E16`      | `1e+16`      |
+/// | `1.5e-7`   | `1.5e-7`          | `1.5E-7`    | `1.5e-7`     |
+///
+/// Non finite floats can't be represented in JSON and are written as
+/// `null`.
+///
+/// ```rust
+/// use simd_json::{json, ExponentStyle, PrettyOptions};
+/// let v = json!([1.5, 1e16, 1.5e-7]);
+/// let opts = PrettyOptions::default()
+///     .compact_threshold(100)
+///     .exponent_style(ExponentStyle::AlwaysPlus);
+/// assert_eq!(v.encode_pp_with(opts), "[1.5, 1e+16, 1.5e-7]");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExponentStyle {
+    /// A lowercase `e` and no sign for positive exponents, as in `1e16`,
+    /// this is the default.
+    LowercaseNoPlus,
+    /// An uppercase `E` and no sign for positive exponents, as in `1E16`.
+    Uppercase,
+    /// A lowercase `e` and an explicit sign for every exponent, as in
+    /// `1e+16`.
+    AlwaysPlus,
+}
+
+impl Default for ExponentStyle {
+    fn default() -> Self {
+        Self::LowercaseNoPlus
+    }
+}
+
+/// Writes `f` with the given exponent style
+pub(crate) fn write_float<W>(w: &mut W, f: f64, style: ExponentStyle) -> io::Result<()>
+where
+    W: Write + ?Sized,
+{
+    if !f.is_finite() {
+        return w.write_all(b"null");
+    }
+    let mut buffer = ryu::Buffer::new();
+    let s = buffer.format_finite(f).as_bytes();
+    match (style, s.iter().position(|c| *c == b'e')) {
+        (ExponentStyle::LowercaseNoPlus, _) | (_, None) => w.write_all(s),
+        (ExponentStyle::Uppercase, Some(e)) => {
+            w.write_all(&s[..e])?;
+            w.write_all(b"E")?;
+            w.write_all(&s[e + 1..])
+        }
+        (ExponentStyle::AlwaysPlus, Some(e)) => {
+            w.write_all(&s[..=e])?;
+            if s.get(e + 1) != Some(&b'-') {
+                w.write_all(b"+")?;
+            }
+            w.write_all(&s[e + 1..])
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{write_float, ExponentStyle};
+    use crate::{to_owned_value, OwnedValue, StaticNode};
+
+    fn write(f: f64, style: ExponentStyle) -> String {
+        let mut out = Vec::new();
+        write_float(&mut out, f, style).expect("writing to a vec");
+        String::from_utf8(out).expect("utf8")
+    }
+
+    fn parse(s: &str) -> OwnedValue {
+        let mut d = s.as_bytes().to_vec();
+        to_owned_value(&mut d).expect("valid json")
+    }
+
+    #[test]
+    #[cfg_attr(feature = "approx-number-parsing", ignore)]
+    fn exponent_forms_parse_identically() {
+        for (forms, expected) in [
+            (["1E+10", "1e10", "1e+10", "1E10"], 1e10),
+            (["1E-10", "1e-10", "1E-10", "1e-10"], 1e-10),
+            (["-2.5E+3", "-2.5e3", "-2.5e+3", "-2.5E3"], -2500.0),
+            (["0E+0", "0e0", "0e+0", "0E-0"], 0.0),
+        ] {
+            for form in forms {
+                assert_eq!(
+                    parse(form),
+                    OwnedValue::Static(StaticNode::F64(expected)),
+                    "{form}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn thresholds() {
+        use ExponentStyle::LowercaseNoPlus;
+        assert_eq!(write(0.0, LowercaseNoPlus), "0.0");
+        assert_eq!(write(-0.0, LowercaseNoPlus), "-0.0");
+        assert_eq!(write(1.0, LowercaseNoPlus), "1.0");
+        assert_eq!(write(1e-5, LowercaseNoPlus), "0.00001");
+        assert_eq!(write(9.9e-6, LowercaseNoPlus), "9.9e-6");
+        assert_eq!(
+            write(9_999_999_999_999_998.0, LowercaseNoPlus),
+            "9999999999999998.0"
+        );
+        assert_eq!(write(1e16, LowercaseNoPlus), "1e16");
+        assert_eq!(write(-1.25e300, LowercaseNoPlus), "-1.25e300");
+        assert_eq!(write(f64::NAN, LowercaseNoPlus), "null");
+        assert_eq!(write(f64::NEG_INFINITY, LowercaseNoPlus), "null");
+    }
+
+    #[test]
+    #[cfg_attr(feature = "approx-number-parsing", ignore)]
+    fn round_trip() {
+        let values = [
+            0.0,
+            1.5,
+            -1e-5,
+            1e-7,
+            -1.5e-7,
+            1e16,
+            1.25e21,
+            f64::MIN,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            5e-324,
+        ];
+        for (style, e, plus) in [
+            (ExponentStyle::LowercaseNoPlus, 'e', false),
+            (ExponentStyle::Uppercase, 'E', false),
+            (ExponentStyle::AlwaysPlus, 'e', true),
+        ] {
+            for f in values {
+                let s = write(f, style);
+                let other = if e == 'e' { 'E' } else { 'e' };
+                assert!(!s.contains(other), "{style:?} {s}");
+                if let Some(idx) = s.find(e) {
+                    let sign = s.as_bytes()[idx + 1];
+                    assert_eq!(sign == b'+', plus && f.abs() >= 1.0, "{style:?} {s}");
+                }
+                assert_eq!(
+                    parse(&s),
+                    OwnedValue::Static(StaticNode::F64(f)),
+                    "{style:?} {s}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::cow::Cow;
+use crate::{BorrowedValue, OwnedValue, StaticNode};
+
+/// Turns a value into another type bottom up, consuming it, see
+/// `OwnedValue::fold` and `BorrowedValue::fold`.
+///
+/// Leaves are handed to the folder by value, so strings are moved out of
+/// the document instead of being cloned. Arrays and objects are folded
+/// after their content, in document order, and receive the outputs of
+/// their children. `'value` is the lifetime of the strings of a borrowed
+/// value, strings of owned values are always `Cow::Owned`, so a folder
+/// for both implements the trait for any lifetime.
+///
+/// A folder that builds a `serde_json::Value`:
+///
+/// ```rust
+/// use serde_json::{Map, Value};
+/// use simd_json::cow::Cow;
+/// use simd_json::{json, StaticNode, ValueFolder};
+///
+/// struct ToSerdeJson;
+///
+/// impl<'value> ValueFolder<'value> for ToSerdeJson {
+///     type Output = Value;
+///
+///     fn scalar(&mut self, node: StaticNode) -> Value {
+///         match node {
+///             StaticNode::Null => Value::Null,
+///             StaticNode::Bool(b) => Value::Bool(b),
+///             StaticNode::I64(n) => n.into(),
+///             StaticNode::U64(n) => n.into(),
+///             StaticNode::F64(n) => n.into(),
+///             // numbers beyond 64 bit don't fit a `serde_json::Number`
+///             #[cfg(feature = "128bit")]
+///             StaticNode::I128(n) => {
+///                 i64::try_from(n).map_or_else(|_| Value::String(n.to_string()), Value::from)
+///             }
+///             #[cfg(feature = "128bit")]
+///             StaticNode::U128(n) => {
+///                 u64::try_from(n).map_or_else(|_| Value::String(n.to_string()), Value::from)
+///             }
+///         }
+///     }
+///
+///     fn string(&mut self, s: Cow<'value, str>) -> Value {
+///         Value::String(s.into_owned())
+///     }
+///
+///     fn array(&mut self, elements: Vec<Value>) -> Value {
+///         Value::Array(elements)
+///     }
+///
+///     fn object(&mut self, members: Vec<(Cow<'value, str>, Value)>) -> Value {
+///         let map: Map<String, Value> = members
+///             .into_iter()
+///             .map(|(k, v)| (k.into_owned(), v))
+///             .collect();
+///         Value::Object(map)
+///     }
+/// }
+///
+/// let v = json!({"name": "simd", "tags": ["fast", 1, 2.5, null]});
+/// assert_eq!(
+///     v.fold(&mut ToSerdeJson),
+///     serde_json::json!({"name": "simd", "tags": ["fast", 1, 2.5, null]})
+/// );
+/// ```
+pub trait ValueFolder<'value> {
+    /// The type a value is folded into
+    type Output;
+
+    /// Folds `null`, a boolean or a number
+    fn scalar(&mut self, node: StaticNode) -> Self::Output;
+
+    /// Folds a string
+    fn string(&mut self, s: Cow<'value, str>) -> Self::Output;
+
+    /// Folds an array from its folded elements
+    fn array(&mut self, elements: Vec<Self::Output>) -> Self::Output;
+
+    /// Folds an object from its keys and folded values, in the iteration
+    /// order of the object
+    fn object(&mut self, members: Vec<(Cow<'value, str>, Self::Output)>) -> Self::Output;
+}
+
+impl OwnedValue {
+    /// Consumes the value and folds it with `folder`, children before
+    /// their parents, see `ValueFolder`. Strings and keys are moved into
+    /// the folder as `Cow::Owned`.
+    pub fn fold<'value, F>(self, folder: &mut F) -> F::Output
+    where
+        F: ValueFolder<'value>,
+    {
+        match self {
+            Self::Static(s) => folder.scalar(s),
+            Self::String(s) => folder.string(Cow::from(s)),
+            Self::Array(a) => {
+                let elements = a.into_iter().map(|v| v.fold(folder)).collect();
+                folder.array(elements)
+            }
+            Self::Object(o) => {
+                let members = (*o)
+                    .into_iter()
+                    .map(|(k, v)| (Cow::from(k), v.fold(folder)))
+                    .collect();
+                folder.object(members)
+            }
+        }
+    }
+}
+
+impl<'value> BorrowedValue<'value> {
+    /// Consumes the value and folds it with `folder`, children before
+    /// their parents, see `ValueFolder`. Strings and keys are moved into
+    /// the folder as they are, borrowed from the input or owned.
+    pub fn fold<F>(self, folder: &mut F) -> F::Output
+    where
+        F: ValueFolder<'value>,
+    {
+        match self {
+            Self::Static(s) => folder.scalar(s),
+            Self::String(s) => folder.string(s),
+            Self::Array(a) => {
+                let elements = a.into_iter().map(|v| v.fold(folder)).collect();
+                folder.array(elements)
+            }
+            Self::Object(o) => {
+                let members = (*o).into_iter().map(|(k, v)| (k, v.fold(folder))).collect();
+                folder.object(members)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ValueFolder;
+    use crate::cow::Cow;
+    use crate::prelude::*;
+    use crate::{json, to_borrowed_value, BorrowedValue, StaticNode};
+
+    /// Records the order of the callbacks
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl<'value> ValueFolder<'value> for Trace {
+        type Output = usize;
+
+        fn scalar(&mut self, node: StaticNode) -> usize {
+            self.0.push(BorrowedValue::Static(node).encode());
+            1
+        }
+
+        fn string(&mut self, s: Cow<'value, str>) -> usize {
+            self.0.push(format!("string {s}"));
+            1
+        }
+
+        fn array(&mut self, elements: Vec<usize>) -> usize {
+            self.0.push(format!("array {}", elements.len()));
+            elements.iter().sum::<usize>() + 1
+        }
+
+        fn object(&mut self, members: Vec<(Cow<'value, str>, usize)>) -> usize {
+            let keys: Vec<&str> = members.iter().map(|(k, _)| &**k).collect();
+            self.0.push(format!("object {}", keys.join(",")));
+            members.iter().map(|(_, n)| n).sum::<usize>() + 1
+        }
+    }
+
+    #[test]
+    fn fold_post_order() {
+        let expected = vec![
+            "1", "string x", "null", "array 0", "array 3", "true", "object a", "array 3",
+        ];
+        let mut trace = Trace::default();
+        let nodes = json!([1, ["x", null, []], {"a": true}]).fold(&mut trace);
+        assert_eq!(nodes, 8);
+        assert_eq!(trace.0, expected);
+
+        let mut d = br#"[1, ["x", null, []], {"a": true}]"#.to_vec();
+        let v = to_borrowed_value(&mut d).expect("valid json");
+        let mut trace = Trace::default();
+        assert_eq!(v.fold(&mut trace), 8);
+        assert_eq!(trace.0, expected);
+    }
+}
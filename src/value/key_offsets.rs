@@ -0,0 +1,195 @@
+use super::borrowed::{BorrowDeserializer, Value as BorrowedValue};
+use super::owned::{OwnedDeserializer, Value as OwnedValue};
+use crate::pointer::{Pointer, Segment};
+use crate::tape::skip;
+use crate::{Deserializer, Node, Result};
+
+/// Marks tape nodes that are not object keys
+const NOT_A_KEY: u32 = u32::MAX;
+
+/// The byte offsets of the object keys of a document in its input.
+/// Offsets are those of the opening quote of the key, escapes in the key
+/// don't change it.
+///
+/// The offsets are stored as one `u32` per node, parallel to the tape of
+/// the document. The tape is kept to look up keys by the JSON pointer of
+/// their value, for keys present more than once in an object the first
+/// occurrence is found.
+///
+/// ```rust
+/// let mut d = r#"{"a": [{"bé": 1}], "c": 2}"#.as_bytes().to_vec();
+/// let (v, offsets) = simd_json::to_owned_value_with_key_offsets(&mut d).unwrap();
+/// assert_eq!(v["c"], 2);
+/// assert_eq!(offsets.get("/a"), Some(1));
+/// assert_eq!(offsets.get("/a/0/bé"), Some(8));
+/// assert_eq!(offsets.get("/c"), Some(20));
+/// assert_eq!(offsets.get("/a/0"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyOffsets<'input> {
+    tape: Vec<Node<'input>>,
+    offsets: Vec<u32>,
+}
+
+impl<'input> KeyOffsets<'input> {
+    /// The offset of the key of the value at `pointer`, pointers use the
+    /// escaped syntax of RFC 6901, so `/` in a key is `~1` and `~` is
+    /// `~0`. Returns `None` if the value is not in an object.
+    #[must_use]
+    pub fn get(&self, pointer: &str) -> Option<usize> {
+        let mut key = None;
+        // the root of a tape is at index 1
+        let mut i = 1;
+        for segment in Pointer::new(pointer).ok()?.segments() {
+            match (self.tape.get(i)?, segment) {
+                (Node::Array(len, _), Segment::Index(idx)) if idx < *len => {
+                    key = None;
+                    i = (0..idx).fold(i + 1, |k, _| skip(&self.tape, k));
+                }
+                (Node::Object(len, _), segment) => {
+                    let wanted = segment.to_key();
+                    let mut k = i + 1;
+                    let mut found = None;
+                    for _ in 0..*len {
+                        if self.tape[k] == Node::String(wanted.as_ref()) {
+                            found = Some(k);
+                            break;
+                        }
+                        k = skip(&self.tape, k + 1);
+                    }
+                    key = found;
+                    i = found? + 1;
+                }
+                _ => return None,
+            }
+        }
+        key.map(|k| self.offsets[k] as usize)
+    }
+
+    /// The number of keys in the document, including duplicates
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.offsets.iter().filter(|o| **o != NOT_A_KEY).count()
+    }
+
+    /// Returns true if the document has no keys
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the keys and their offsets in the order they appear
+    /// in the input
+    pub fn entries(&self) -> impl Iterator<Item = (&'input str, usize)> + '_ {
+        self.tape
+            .iter()
+            .zip(&self.offsets)
+            .filter_map(|(node, offset)| match node {
+                Node::String(key) if *offset != NOT_A_KEY => Some((*key, *offset as usize)),
+                _ => None,
+            })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_tape(tape: Vec<Node<'input>>, input_start: usize) -> Self {
+        let mut offsets = vec![NOT_A_KEY; tape.len()];
+        for (i, node) in tape.iter().enumerate() {
+            if let Node::Object(len, _) = node {
+                let mut k = i + 1;
+                for _ in 0..*len {
+                    if let Node::String(key) = tape[k] {
+                        // inputs are at most `u32::MAX` bytes long
+                        offsets[k] = (key.as_ptr() as usize - input_start - 1) as u32;
+                    }
+                    k = skip(&tape, k + 1);
+                }
+            }
+        }
+        Self { tape, offsets }
+    }
+}
+
+/// Parses a slice of bytes into an owned value like `to_owned_value`
+/// and records the offsets of all object keys in the input.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_owned_value_with_key_offsets(s: &mut [u8]) -> Result<(OwnedValue, KeyOffsets<'_>)> {
+    let input_start = s.as_ptr() as usize;
+    let mut de = OwnedDeserializer::from_deserializer(Deserializer::from_slice(s)?);
+    let value = de.parse();
+    Ok((value, KeyOffsets::from_tape(de.into_tape(), input_start)))
+}
+
+/// Parses a slice of bytes into a borrowed value like
+/// `to_borrowed_value` and records the offsets of all object keys in the
+/// input.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_borrowed_value_with_key_offsets(s: &mut [u8]) -> Result<(BorrowedValue, KeyOffsets<'_>)> {
+    let input_start = s.as_ptr() as usize;
+    let mut de = BorrowDeserializer::from_deserializer(Deserializer::from_slice(s)?);
+    let value = de.parse();
+    Ok((value, KeyOffsets::from_tape(de.into_tape(), input_start)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{to_borrowed_value_with_key_offsets, to_owned_value_with_key_offsets};
+    use crate::json;
+
+    /// The offset of the n-th occurrence of `key` with its quotes
+    fn find(input: &str, key: &str, n: usize) -> usize {
+        input
+            .match_indices(&format!(r#""{key}""#))
+            .nth(n)
+            .expect("key in input")
+            .0
+    }
+
+    #[test]
+    fn depths() {
+        let input = r#"{"a": {"b": [1, {"c": [[{"d": null}]]}], "e": {}}, "f": [], "": {"a": 2}}"#;
+        let mut d = input.as_bytes().to_vec();
+        let (v, offsets) = to_owned_value_with_key_offsets(&mut d).expect("valid json");
+        assert_eq!(
+            v,
+            json!({"a": {"b": [1, {"c": [[{"d": null}]]}], "e": {}}, "f": [], "": {"a": 2}})
+        );
+        assert_eq!(offsets.len(), 8);
+        assert_eq!(offsets.get(""), None);
+        assert_eq!(offsets.get("/a"), Some(find(input, "a", 0)));
+        assert_eq!(offsets.get("/a/b"), Some(find(input, "b", 0)));
+        assert_eq!(offsets.get("/a/b/1/c"), Some(find(input, "c", 0)));
+        assert_eq!(offsets.get("/a/b/1/c/0/0/d"), Some(find(input, "d", 0)));
+        assert_eq!(offsets.get("/a/e"), Some(find(input, "e", 0)));
+        assert_eq!(offsets.get("/f"), Some(find(input, "f", 0)));
+        assert_eq!(offsets.get("/"), Some(find(input, "", 0)));
+        assert_eq!(offsets.get("//a"), Some(find(input, "a", 1)));
+        assert_eq!(offsets.get("/a/b/0"), None);
+        assert_eq!(offsets.entries().count(), 8);
+    }
+
+    #[test]
+    fn escapes() {
+        let input =
+            r#"[{"x\"y": 1, "a/b": {"c~d": 2}, "é\n": 3, "k": 4}, {"x": {"x": 1, "x": 2}}]"#;
+        let mut d = input.as_bytes().to_vec();
+        let (v, offsets) = to_borrowed_value_with_key_offsets(&mut d).expect("valid json");
+        assert_eq!(v[0]["k"], 4);
+        assert_eq!(offsets.get("/0/x\"y"), Some(2));
+        assert_eq!(offsets.get("/0/a~1b"), Some(find(input, "a/b", 0)));
+        assert_eq!(offsets.get("/0/a~1b/c~0d"), Some(find(input, "c~d", 0)));
+        assert_eq!(offsets.get("/0/é\n"), Some(find(input, r"é\n", 0)));
+        // keys after escaped ones are not shifted by unescaping
+        assert_eq!(offsets.get("/0/k"), Some(find(input, "k", 0)));
+        // the first occurrence of a duplicate key is recorded
+        assert_eq!(offsets.get("/1/x"), Some(find(input, "x", 0)));
+        assert_eq!(offsets.get("/1/x/x"), Some(find(input, "x", 1)));
+        assert_eq!(offsets.len(), 8);
+        assert_eq!(offsets.entries().last(), Some(("x", find(input, "x", 2))));
+    }
+}
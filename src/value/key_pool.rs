@@ -0,0 +1,125 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The number of independently locked parts of a `KeyPool`
+const SHARDS: usize = 16;
+
+/// A pool of object keys shared by borrowed values of many documents,
+/// see [`BorrowedValue::into_pooled`](crate::BorrowedValue::into_pooled)
+/// and [`to_borrowed_value_with_pool`](crate::to_borrowed_value_with_pool).
+///
+/// Every distinct key is stored once and documents borrow their keys
+/// from the pool, so equal keys point to the same memory across
+/// documents. Memory is saved once documents no longer borrow their
+/// input: `into_pooled` copies strings but not keys, while `into_static`
+/// copies both. Keys are never removed, the pool grows with the number
+/// of distinct keys and is freed when it is dropped, after all documents
+/// borrowing from it.
+///
+/// The pool can be shared between threads, it is split into shards that
+/// are locked independently.
+///
+/// ```rust
+/// use simd_json::{to_borrowed_value_with_pool, KeyPool};
+/// use simd_json::prelude::*;
+/// let pool = KeyPool::new();
+/// let mut d1 = br#"{"name": "a", "id": 1}"#.to_vec();
+/// let mut d2 = br#"{"name": "b", "id": 2}"#.to_vec();
+/// let v1 = to_borrowed_value_with_pool(&mut d1, &pool).unwrap();
+/// let v2 = to_borrowed_value_with_pool(&mut d2, &pool).unwrap();
+/// assert_eq!(v2["name"], "b");
+/// let k1 = v1.as_object().unwrap().keys().find(|k| *k == "name").unwrap();
+/// let k2 = v2.as_object().unwrap().keys().find(|k| *k == "name").unwrap();
+/// assert_eq!(k1.as_ptr(), k2.as_ptr());
+/// assert_eq!(pool.len(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct KeyPool {
+    shards: [Mutex<HashSet<Arc<str>>>; SHARDS],
+    hasher: RandomState,
+}
+
+impl KeyPool {
+    /// Creates an empty pool
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pooled key equal to `key`, adding it to the pool if it
+    /// isn't in it yet
+    pub fn intern(&self, key: &str) -> &str {
+        let mut shard = self.shards[self.shard(key)]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let existing = shard.get(key).map(|pooled| -> *const str { &**pooled });
+        let pooled = existing.unwrap_or_else(|| {
+            let pooled: Arc<str> = key.into();
+            let ptr: *const str = &*pooled;
+            shard.insert(pooled);
+            ptr
+        });
+        // safety: keys are never removed from the pool while it is
+        // borrowed and the content of an `Arc` doesn't move when the set
+        // moves the `Arc` itself
+        unsafe { &*pooled }
+    }
+
+    /// The number of distinct keys in the pool
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| s.lock().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    /// Returns true if the pool holds no keys
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn shard(&self, key: &str) -> usize {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish() as usize % SHARDS
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyPool;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn shared_between_threads() {
+        let pool = Arc::new(KeyPool::new());
+        let keys: Vec<String> = (0..100).map(|i| format!("key{i}")).collect();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let pool = pool.clone();
+                let keys = keys.clone();
+                thread::spawn(move || {
+                    keys.iter()
+                        .map(|k| pool.intern(k).as_ptr() as usize)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let pointers: Vec<Vec<usize>> = handles
+            .into_iter()
+            .map(|h| h.join().expect("no panic"))
+            .collect();
+        assert!(pointers.iter().all(|p| p == &pointers[0]));
+        assert_eq!(pool.len(), 100);
+        assert_eq!(pool.intern("key7"), "key7");
+        assert_eq!(pool.intern("key7").as_ptr() as usize, pointers[0][7]);
+        assert!(!pool.is_empty());
+        assert!(KeyPool::new().is_empty());
+    }
+}
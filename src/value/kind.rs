@@ -0,0 +1,155 @@
+use crate::StaticNode;
+
+/// The kind of a JSON value, independent of how numbers are represented.
+///
+/// Unlike `ValueType` the kind of a number only depends on its value:
+///
+/// * `I64` and `U64` numbers are `Integer`.
+/// * 128 bit integers (with the `128bit` feature) are `Integer` if they
+///   fit into an `i64` or `u64`, and `BigNumber` otherwise.
+/// * `F64` numbers are `Float`, this includes integral floats such as
+///   `1.0` or `1e3` as numbers with a fraction or exponent are always
+///   parsed as floats.
+///
+/// ```rust
+/// use simd_json::{json, JsonKind};
+/// let v = json!({"id": 1, "score": 1.5});
+/// assert_eq!(v.kind(), JsonKind::Object);
+/// assert_eq!(v["id"].kind(), JsonKind::Integer);
+/// assert!(v["score"].is_number() && !v["score"].is_integer());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JsonKind {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool,
+    /// An integer that fits into an `i64` or `u64`
+    Integer,
+    /// A floating point number
+    Float,
+    /// An integer that doesn't fit into an `i64` or `u64`
+    BigNumber,
+    /// A string
+    String,
+    /// An array
+    Array,
+    /// An object
+    Object,
+}
+
+impl JsonKind {
+    pub(crate) fn of_static(s: StaticNode) -> Self {
+        match s {
+            StaticNode::Null => Self::Null,
+            StaticNode::Bool(_) => Self::Bool,
+            StaticNode::I64(_) | StaticNode::U64(_) => Self::Integer,
+            #[cfg(feature = "128bit")]
+            StaticNode::I128(v) => {
+                if i64::try_from(v).is_ok() || u64::try_from(v).is_ok() {
+                    Self::Integer
+                } else {
+                    Self::BigNumber
+                }
+            }
+            #[cfg(feature = "128bit")]
+            StaticNode::U128(v) => {
+                if u64::try_from(v).is_ok() {
+                    Self::Integer
+                } else {
+                    Self::BigNumber
+                }
+            }
+            StaticNode::F64(_) => Self::Float,
+        }
+    }
+
+    /// Returns true for `Integer` and `BigNumber`
+    #[inline]
+    #[must_use]
+    pub const fn is_integer(self) -> bool {
+        matches!(self, Self::Integer | Self::BigNumber)
+    }
+
+    /// Returns true for `Float`
+    #[inline]
+    #[must_use]
+    pub const fn is_float(self) -> bool {
+        matches!(self, Self::Float)
+    }
+
+    /// Returns true for `Integer`, `BigNumber` and `Float`
+    #[inline]
+    #[must_use]
+    pub const fn is_number(self) -> bool {
+        matches!(self, Self::Integer | Self::BigNumber | Self::Float)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::JsonKind;
+    use crate::StaticNode;
+
+    #[test]
+    fn statics() {
+        assert_eq!(JsonKind::of_static(StaticNode::Null), JsonKind::Null);
+        assert_eq!(JsonKind::of_static(StaticNode::Bool(false)), JsonKind::Bool);
+        for s in [
+            StaticNode::I64(i64::MIN),
+            StaticNode::I64(0),
+            StaticNode::U64(u64::MAX),
+        ] {
+            assert_eq!(JsonKind::of_static(s), JsonKind::Integer);
+        }
+        for v in [0.0, -0.0, 1e3, f64::MAX] {
+            assert_eq!(JsonKind::of_static(StaticNode::F64(v)), JsonKind::Float);
+        }
+    }
+
+    #[cfg(feature = "128bit")]
+    #[test]
+    fn big_numbers() {
+        let kind = JsonKind::of_static;
+        assert_eq!(kind(StaticNode::I128(-1)), JsonKind::Integer);
+        assert_eq!(
+            kind(StaticNode::I128(i128::from(u64::MAX))),
+            JsonKind::Integer
+        );
+        assert_eq!(
+            kind(StaticNode::I128(i128::from(i64::MIN) - 1)),
+            JsonKind::BigNumber
+        );
+        assert_eq!(
+            kind(StaticNode::U128(u128::from(u64::MAX))),
+            JsonKind::Integer
+        );
+        assert_eq!(
+            kind(StaticNode::U128(u128::from(u64::MAX) + 1)),
+            JsonKind::BigNumber
+        );
+    }
+
+    #[test]
+    fn predicates() {
+        let all = [
+            JsonKind::Null,
+            JsonKind::Bool,
+            JsonKind::Integer,
+            JsonKind::Float,
+            JsonKind::BigNumber,
+            JsonKind::String,
+            JsonKind::Array,
+            JsonKind::Object,
+        ];
+        let integers: Vec<_> = all.iter().filter(|k| k.is_integer()).collect();
+        assert_eq!(integers, [&JsonKind::Integer, &JsonKind::BigNumber]);
+        let floats: Vec<_> = all.iter().filter(|k| k.is_float()).collect();
+        assert_eq!(floats, [&JsonKind::Float]);
+        let numbers: Vec<_> = all.iter().filter(|k| k.is_number()).collect();
+        assert_eq!(
+            numbers,
+            [&JsonKind::Integer, &JsonKind::Float, &JsonKind::BigNumber]
+        );
+    }
+}
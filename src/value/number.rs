@@ -0,0 +1,278 @@
+use crate::{Error, ErrorType, StaticNode};
+use std::fmt;
+use std::str::FromStr;
+
+/// A JSON number, with the same API as `serde_json::Number` so it can be
+/// used as a drop in replacement.
+///
+/// Numbers are either unsigned integers, negative integers or finite
+/// floats, the same as the numbers in `Value`s. 128 bit integers are not
+/// represented.
+///
+/// ```rust
+/// use simd_json::Number;
+/// let n = Number::from(42u8);
+/// assert!(n.is_u64() && n.is_i64() && !n.is_f64());
+/// assert_eq!(n.as_f64(), Some(42.0));
+/// assert!(Number::from_f64(f64::NAN).is_none());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Number(N);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum N {
+    PosInt(u64),
+    /// Always less than zero
+    NegInt(i64),
+    /// Always finite
+    Float(f64),
+}
+
+impl Number {
+    /// Returns true if the number can be represented as an `i64`
+    #[inline]
+    #[must_use]
+    pub fn is_i64(&self) -> bool {
+        match self.0 {
+            N::PosInt(v) => i64::try_from(v).is_ok(),
+            N::NegInt(_) => true,
+            N::Float(_) => false,
+        }
+    }
+
+    /// Returns true if the number can be represented as an `u64`
+    #[inline]
+    #[must_use]
+    pub fn is_u64(&self) -> bool {
+        matches!(self.0, N::PosInt(_))
+    }
+
+    /// Returns true if the number is a float
+    #[inline]
+    #[must_use]
+    pub fn is_f64(&self) -> bool {
+        matches!(self.0, N::Float(_))
+    }
+
+    /// Returns the number as `i64` if it can be represented as one
+    #[inline]
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.0 {
+            N::PosInt(v) => i64::try_from(v).ok(),
+            N::NegInt(v) => Some(v),
+            N::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as `u64` if it can be represented as one
+    #[inline]
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.0 {
+            N::PosInt(v) => Some(v),
+            N::NegInt(_) | N::Float(_) => None,
+        }
+    }
+
+    /// Returns the number as `f64`, integers are converted and might
+    /// lose precision. This always returns `Some`.
+    #[inline]
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(&self) -> Option<f64> {
+        Some(match self.0 {
+            N::PosInt(v) => v as f64,
+            N::NegInt(v) => v as f64,
+            N::Float(v) => v,
+        })
+    }
+
+    /// Same as `as_i64`, for code that uses the `to_*` naming
+    #[inline]
+    #[must_use]
+    pub fn to_i64(&self) -> Option<i64> {
+        self.as_i64()
+    }
+
+    /// Same as `as_u64`, for code that uses the `to_*` naming
+    #[inline]
+    #[must_use]
+    pub fn to_u64(&self) -> Option<u64> {
+        self.as_u64()
+    }
+
+    /// Same as `as_f64`, for code that uses the `to_*` naming
+    #[inline]
+    #[must_use]
+    pub fn to_f64(&self) -> Option<f64> {
+        self.as_f64()
+    }
+
+    /// Creates a number from a float, returns `None` for `NaN` and
+    /// infinite values as JSON can't represent them.
+    #[inline]
+    #[must_use]
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if f.is_finite() {
+            Some(Self(N::Float(f)))
+        } else {
+            None
+        }
+    }
+
+    /// Converts a static node to a number, returns `None` for non
+    /// numeric nodes, 128 bit integers that don't fit in 64 bit and
+    /// floats that are not finite.
+    #[inline]
+    #[must_use]
+    pub fn from_static(s: StaticNode) -> Option<Self> {
+        match s {
+            StaticNode::I64(v) => Some(Self::from(v)),
+            StaticNode::U64(v) => Some(Self::from(v)),
+            #[cfg(feature = "128bit")]
+            StaticNode::I128(v) => i64::try_from(v)
+                .map(Self::from)
+                .or_else(|_| u64::try_from(v).map(Self::from))
+                .ok(),
+            #[cfg(feature = "128bit")]
+            StaticNode::U128(v) => u64::try_from(v).map(Self::from).ok(),
+            StaticNode::F64(v) => Self::from_f64(v),
+            StaticNode::Null | StaticNode::Bool(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            N::PosInt(v) => write!(f, "{v}"),
+            N::NegInt(v) => write!(f, "{v}"),
+            // debug formatting keeps the decimal point for whole floats
+            N::Float(v) => write!(f, "{v:?}"),
+        }
+    }
+}
+
+impl FromStr for Number {
+    type Err = Error;
+
+    /// Parses a JSON number, numbers that don't fit in 64 bit are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_static(crate::parse_number_str(s)?)
+            .ok_or_else(|| Error::generic(ErrorType::InvalidNumber))
+    }
+}
+
+impl From<Number> for StaticNode {
+    #[inline]
+    fn from(n: Number) -> Self {
+        match n.0 {
+            N::PosInt(v) => Self::U64(v),
+            N::NegInt(v) => Self::I64(v),
+            N::Float(v) => Self::F64(v),
+        }
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                #[inline]
+                fn from(u: $ty) -> Self {
+                    Self(N::PosInt(u as u64))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Number {
+                #[inline]
+                #[allow(clippy::cast_sign_loss)]
+                fn from(i: $ty) -> Self {
+                    if i < 0 {
+                        Self(N::NegInt(i as i64))
+                    } else {
+                        Self(N::PosInt(i as u64))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_unsigned!(u8, u16, u32, u64, usize);
+from_signed!(i8, i16, i32, i64, isize);
+
+#[cfg(test)]
+mod test {
+    use super::Number;
+    use crate::StaticNode;
+
+    #[test]
+    fn integers() {
+        let n = Number::from(-1);
+        assert!(n.is_i64() && !n.is_u64() && !n.is_f64());
+        assert_eq!(n.as_i64(), Some(-1));
+        assert_eq!(n.as_u64(), None);
+        assert_eq!(n.to_string(), "-1");
+
+        let n = Number::from(u64::MAX);
+        assert!(!n.is_i64() && n.is_u64());
+        assert_eq!(n.as_i64(), None);
+        assert_eq!(n.as_u64(), Some(u64::MAX));
+        assert_eq!(n.to_u64(), Some(u64::MAX));
+        assert_eq!(n.to_i64(), None);
+        assert_eq!(n.to_string(), "18446744073709551615");
+
+        // non negative signed integers are the same as unsigned ones
+        assert_eq!(Number::from(7i64), Number::from(7u8));
+        assert_eq!(StaticNode::from(Number::from(7i64)), StaticNode::U64(7));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn floats() {
+        let n = Number::from_f64(1.5).expect("finite");
+        assert!(n.is_f64() && !n.is_i64() && !n.is_u64());
+        assert_eq!(n.as_f64(), Some(1.5));
+        assert_eq!(n.as_i64(), None);
+        assert_eq!(n.to_string(), "1.5");
+        assert_eq!(Number::from_f64(2.0).expect("finite").to_string(), "2.0");
+        assert!(Number::from_f64(f64::INFINITY).is_none());
+        assert_eq!(Number::from(3).as_f64(), Some(3.0));
+        assert_eq!(Number::from(3).to_f64(), Some(3.0));
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("-3".parse::<Number>().ok(), Some(Number::from(-3)));
+        assert_eq!(
+            "18446744073709551615".parse::<Number>().ok(),
+            Some(Number::from(u64::MAX))
+        );
+        assert_eq!("1.5".parse::<Number>().ok(), Number::from_f64(1.5));
+        assert!("1.5x".parse::<Number>().is_err());
+        assert!("".parse::<Number>().is_err());
+    }
+
+    #[test]
+    fn from_static() {
+        assert_eq!(
+            Number::from_static(StaticNode::I64(-3)),
+            Some(Number::from(-3))
+        );
+        assert_eq!(
+            Number::from_static(StaticNode::U64(3)),
+            Some(Number::from(3))
+        );
+        assert_eq!(Number::from_static(StaticNode::F64(f64::NAN)), None);
+        assert_eq!(Number::from_static(StaticNode::Null), None);
+        assert_eq!(Number::from_static(StaticNode::Bool(true)), None);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::StaticNode;
+use std::fmt;
+
+/// Errors when updating numeric values in place with `incr`,
+/// `incr_with` and `incr_f64`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumericError {
+    /// The value is not a number
+    NotANumber,
+    /// The result can't be represented, either because an integer
+    /// overflowed or a float became infinite or `NaN`
+    Overflow,
+}
+
+impl fmt::Display for NumericError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotANumber => f.write_str("value is not a number"),
+            Self::Overflow => f.write_str("numeric overflow"),
+        }
+    }
+}
+
+impl std::error::Error for NumericError {}
+
+/// Adds `by` to an integer or float node in place. Integers stay
+/// integers, crossing the `i64` / `u64` boundary switches between
+/// the two, results that fit neither overflow or, with `promote`, become
+/// floats.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+pub(crate) fn incr(node: &mut StaticNode, by: i64, promote: bool) -> Result<(), NumericError> {
+    let (r, was_unsigned) = match *node {
+        StaticNode::I64(v) => (i128::from(v) + i128::from(by), false),
+        StaticNode::U64(v) => (i128::from(v) + i128::from(by), true),
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(v) => {
+            *node = match v.checked_add(i128::from(by)) {
+                Some(r) => StaticNode::I128(r),
+                None if promote => StaticNode::F64(v as f64 + by as f64),
+                None => return Err(NumericError::Overflow),
+            };
+            return Ok(());
+        }
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(v) => {
+            let by_abs = u128::from(by.unsigned_abs());
+            *node = if by >= 0 {
+                match v.checked_add(by_abs) {
+                    Some(r) => StaticNode::U128(r),
+                    None if promote => StaticNode::F64(v as f64 + by as f64),
+                    None => return Err(NumericError::Overflow),
+                }
+            } else if v >= by_abs {
+                StaticNode::U128(v - by_abs)
+            } else {
+                // `v` is smaller than `by_abs` so it fits into an i128
+                StaticNode::I128(v as i128 + i128::from(by))
+            };
+            return Ok(());
+        }
+        StaticNode::F64(v) => {
+            let r = v + by as f64;
+            if !r.is_finite() {
+                return Err(NumericError::Overflow);
+            }
+            *node = StaticNode::F64(r);
+            return Ok(());
+        }
+        StaticNode::Null | StaticNode::Bool(_) => return Err(NumericError::NotANumber),
+    };
+    // the sum of two 64 bit integers always fits into an i128
+    *node = match (i64::try_from(r), u64::try_from(r)) {
+        (Ok(i), Ok(u)) => {
+            if was_unsigned {
+                StaticNode::U64(u)
+            } else {
+                StaticNode::I64(i)
+            }
+        }
+        (Ok(i), Err(_)) => StaticNode::I64(i),
+        (Err(_), Ok(u)) => StaticNode::U64(u),
+        (Err(_), Err(_)) if promote => StaticNode::F64(r as f64),
+        (Err(_), Err(_)) => return Err(NumericError::Overflow),
+    };
+    Ok(())
+}
+
+/// Adds `by` to a number node in place, the result is always a float.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn incr_f64(node: &mut StaticNode, by: f64) -> Result<(), NumericError> {
+    let v = match *node {
+        StaticNode::I64(v) => v as f64,
+        StaticNode::U64(v) => v as f64,
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(v) => v as f64,
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(v) => v as f64,
+        StaticNode::F64(v) => v,
+        StaticNode::Null | StaticNode::Bool(_) => return Err(NumericError::NotANumber),
+    };
+    let r = v + by;
+    if r.is_finite() {
+        *node = StaticNode::F64(r);
+        Ok(())
+    } else {
+        Err(NumericError::Overflow)
+    }
+}
+
+/// The float with the same value as an integer node, `None` for floats
+/// and nodes that are not numbers. Integers that can't be represented
+/// exactly as a float overflow.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub(crate) fn exact_f64(node: StaticNode) -> Result<Option<f64>, NumericError> {
+    // converting a float back to an integer saturates, so the checks for
+    // 128 bit integers also have to rule out the rounded up maximum
+    let (f, exact) = match node {
+        StaticNode::I64(v) => {
+            let f = v as f64;
+            (f, f as i128 == i128::from(v))
+        }
+        StaticNode::U64(v) => {
+            let f = v as f64;
+            (f, f as u128 == u128::from(v))
+        }
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(v) => {
+            let f = v as f64;
+            (f, f < 2f64.powi(127) && f as i128 == v)
+        }
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(v) => {
+            let f = v as f64;
+            (f, f < 2f64.powi(128) && f as u128 == v)
+        }
+        StaticNode::F64(_) | StaticNode::Null | StaticNode::Bool(_) => return Ok(None),
+    };
+    if exact {
+        Ok(Some(f))
+    } else {
+        Err(NumericError::Overflow)
+    }
+}
+
+/// The canonical representation of a number node, the one the parser
+/// uses for the same number written as an integer: integers are `I64`
+/// if they fit and `U64` otherwise, floats without a fractional part
+/// that fit either become integers, `-0.0` included. Other floats and
+/// nodes that are not numbers are returned as they are.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub(crate) fn canonical(node: StaticNode) -> StaticNode {
+    // both bounds are powers of two and exact as f64
+    let i64_bound = -(i64::MIN as f64);
+    let u64_bound = 2.0 * i64_bound;
+    match node {
+        StaticNode::U64(v) => i64::try_from(v).map_or(node, StaticNode::I64),
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(v) => i64::try_from(v)
+            .map(StaticNode::I64)
+            .or_else(|_| u64::try_from(v).map(StaticNode::U64))
+            .unwrap_or(node),
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(v) => i64::try_from(v)
+            .map(StaticNode::I64)
+            .or_else(|_| u64::try_from(v).map(StaticNode::U64))
+            .unwrap_or(node),
+        StaticNode::F64(f) if f.fract() == 0.0 && (-i64_bound..i64_bound).contains(&f) => {
+            StaticNode::I64(f as i64)
+        }
+        StaticNode::F64(f) if f.fract() == 0.0 && (0.0..u64_bound).contains(&f) => {
+            StaticNode::U64(f as u64)
+        }
+        StaticNode::I64(_) | StaticNode::F64(_) | StaticNode::Null | StaticNode::Bool(_) => node,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{canonical, exact_f64, incr, incr_f64, NumericError};
+    use crate::StaticNode;
+
+    fn add(mut node: StaticNode, by: i64, promote: bool) -> Result<StaticNode, NumericError> {
+        incr(&mut node, by, promote).map(|()| node)
+    }
+
+    // the smallest integer that doesn't fit into an i64
+    const BOUNDARY: u64 = 1 << 63;
+
+    #[test]
+    fn integers() {
+        assert_eq!(add(StaticNode::I64(1), 2, false), Ok(StaticNode::I64(3)));
+        assert_eq!(add(StaticNode::U64(1), 2, false), Ok(StaticNode::U64(3)));
+        assert!(matches!(
+            add(StaticNode::U64(1), -2, false),
+            Ok(StaticNode::I64(-1))
+        ));
+        assert!(matches!(
+            add(StaticNode::I64(i64::MAX), 1, false),
+            Ok(StaticNode::U64(BOUNDARY))
+        ));
+        assert_eq!(
+            add(StaticNode::U64(BOUNDARY), -1, false),
+            Ok(StaticNode::U64(BOUNDARY - 1))
+        );
+        assert_eq!(
+            add(StaticNode::I64(i64::MIN), i64::MAX, false),
+            Ok(StaticNode::I64(-1))
+        );
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn overflow() {
+        assert_eq!(
+            add(StaticNode::U64(u64::MAX), 1, false),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(
+            add(StaticNode::I64(i64::MIN), -1, false),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(
+            add(StaticNode::U64(u64::MAX), 1, true),
+            Ok(StaticNode::F64(u64::MAX as f64 + 1.0))
+        );
+        assert_eq!(
+            add(StaticNode::I64(i64::MIN), -1, true),
+            Ok(StaticNode::F64(i64::MIN as f64 - 1.0))
+        );
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(
+            add(StaticNode::F64(1.5), 1, false),
+            Ok(StaticNode::F64(2.5))
+        );
+        let mut node = StaticNode::I64(1);
+        assert_eq!(incr_f64(&mut node, 0.5), Ok(()));
+        assert_eq!(node, StaticNode::F64(1.5));
+        let mut node = StaticNode::F64(f64::MAX);
+        assert_eq!(incr_f64(&mut node, f64::MAX), Err(NumericError::Overflow));
+        assert_eq!(node, StaticNode::F64(f64::MAX));
+        assert_eq!(incr_f64(&mut node, f64::NAN), Err(NumericError::Overflow));
+    }
+
+    #[test]
+    fn not_a_number() {
+        assert_eq!(
+            add(StaticNode::Null, 1, true),
+            Err(NumericError::NotANumber)
+        );
+        let mut node = StaticNode::Bool(true);
+        assert_eq!(incr_f64(&mut node, 1.0), Err(NumericError::NotANumber));
+    }
+
+    #[test]
+    fn exact() {
+        assert_eq!(exact_f64(StaticNode::I64(-3)), Ok(Some(-3.0)));
+        assert_eq!(
+            exact_f64(StaticNode::U64(1 << 53)),
+            Ok(Some(9_007_199_254_740_992.0))
+        );
+        assert_eq!(
+            exact_f64(StaticNode::I64(i64::MIN)),
+            Ok(Some(-9_223_372_036_854_775_808.0))
+        );
+        assert_eq!(
+            exact_f64(StaticNode::U64(1 << 63)),
+            Ok(Some(9_223_372_036_854_775_808.0))
+        );
+        assert_eq!(
+            exact_f64(StaticNode::U64((1 << 53) + 1)),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(
+            exact_f64(StaticNode::I64(i64::MAX)),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(
+            exact_f64(StaticNode::U64(u64::MAX)),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(exact_f64(StaticNode::F64(0.5)), Ok(None));
+        assert_eq!(exact_f64(StaticNode::Null), Ok(None));
+    }
+
+    #[test]
+    fn canonical_numbers() {
+        assert_eq!(canonical(StaticNode::F64(1000.0)), StaticNode::I64(1000));
+        assert!(matches!(
+            canonical(StaticNode::F64(-0.0)),
+            StaticNode::I64(0)
+        ));
+        assert!(matches!(
+            canonical(StaticNode::F64(-9_223_372_036_854_775_808.0)),
+            StaticNode::I64(i64::MIN)
+        ));
+        assert!(matches!(
+            canonical(StaticNode::F64(9_223_372_036_854_775_808.0)),
+            StaticNode::U64(BOUNDARY)
+        ));
+        assert!(matches!(
+            canonical(StaticNode::F64(1e20)),
+            StaticNode::F64(_)
+        ));
+        assert!(matches!(
+            canonical(StaticNode::F64(0.5)),
+            StaticNode::F64(_)
+        ));
+        assert!(matches!(
+            canonical(StaticNode::F64(f64::NAN)),
+            StaticNode::F64(f) if f.is_nan()
+        ));
+        assert!(matches!(canonical(StaticNode::U64(7)), StaticNode::I64(7)));
+        assert!(matches!(
+            canonical(StaticNode::U64(BOUNDARY)),
+            StaticNode::U64(BOUNDARY)
+        ));
+        assert_eq!(canonical(StaticNode::Bool(true)), StaticNode::Bool(true));
+    }
+}
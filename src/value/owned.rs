@@ -21,13 +21,28 @@
 /// ```
 mod cmp;
 mod from;
+#[cfg(feature = "recycle")]
+mod recycle;
 mod serialize;
 
-use super::ObjectHasher;
+use super::numeric::{self, NumericError};
+use super::pointer::{array_index, array_index_ext, push_token, JsonPointer};
+use super::redact::REDACTED;
+use super::stable_hash::StableHasher;
+use super::unordered::multiset_eq;
+use super::{JsonKind, KeyMatcher, Number, ObjectHasher};
 use crate::prelude::*;
 use crate::safer_unchecked::GetSaferUnchecked;
-use crate::{AlignedBuf, Deserializer, Node, Result, StaticNode};
+use crate::unique::check_unique_keys;
+use crate::{
+    try_vec, AlignedBuf, Deserializer, DuplicateKeys, Error, ErrorType, Node, ParseOptions, Result,
+    StaticNode,
+};
 use halfbrown::HashMap;
+#[cfg(feature = "recycle")]
+pub use recycle::to_owned_value_recycle;
+pub use serialize::{to_ndjson_bytes, write_ndjson};
+use std::cmp::Ordering;
 use std::fmt;
 use std::ops::{Index, IndexMut};
 use value_trait::{ValueAccess, ValueInto};
@@ -51,6 +66,23 @@ pub fn to_value(s: &mut [u8]) -> Result<Value> {
     }
 }
 
+/// Parses a slice of bytes into a Value dom using the given parse
+/// options. This function will rewrite the slice to de-escape strings.
+/// We do not keep any references to the raw data but re-allocate
+/// owned memory wherever required thus returning a value without
+/// a lifetime.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or violates the limits
+/// set in `opts`.
+pub fn to_value_with_opts(s: &mut [u8], opts: ParseOptions) -> Result<Value> {
+    match Deserializer::from_slice_with_opts(s, opts) {
+        Ok(de) => Ok(OwnedDeserializer::from_deserializer(de).parse()),
+        Err(e) => Err(e),
+    }
+}
+
 /// Parses a slice of bytes into a Value dom. This function will
 /// rewrite the slice to de-escape strings.
 /// We do not keep any references to the raw data but re-allocate
@@ -71,6 +103,62 @@ pub fn to_value_with_buffers(
     }
 }
 
+/// Parses a slice of bytes into a Value dom like `to_value`, but fails
+/// with `ErrorType::AllocationFailed` instead of aborting if memory runs
+/// out. The parser's buffers, the tape, arrays and strings are allocated
+/// fallibly. The hash maps of objects can't be, they are allocated as
+/// usual so documents with large objects can still abort.
+///
+/// ```rust
+/// let mut d = br#"["a", [1, 2]]"#.to_vec();
+/// let v = simd_json::try_to_owned_value(&mut d).unwrap();
+/// assert_eq!(v, simd_json::json!(["a", [1, 2]]));
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or an allocation fails.
+pub fn try_to_value(s: &mut [u8]) -> Result<Value> {
+    let de = Deserializer::try_from_slice_with_opts(s, ParseOptions::default())?;
+    OwnedDeserializer::from_deserializer(de).try_parse()
+}
+
+/// Parses a slice of bytes into a Value dom, rejecting every input
+/// that doesn't strictly conform to RFC 8259.
+///
+/// The parser itself never accepts inputs outside of the RFC 8259
+/// grammar: invalid UTF-8, a byte order mark, unescaped control
+/// characters, invalid escapes, escaped lone surrogates, invalid
+/// numbers, trailing commas or trailing data are always rejected.
+/// Beyond that `parse_strict` parses with `ParseOptions::strict()`, so
+/// no lenient options apply, and it rejects objects with the same key
+/// more than once with `ErrorType::DuplicateKey`. Section 4 of the RFC
+/// says keys SHOULD be unique and parsers disagree on which value of a
+/// repeated key wins, so validators treat them as a deviation.
+///
+/// ```rust
+/// use simd_json::{parse_strict, ErrorType};
+/// let mut d = r#"{"a": [1, "é"]}"#.as_bytes().to_vec();
+/// assert!(parse_strict(&mut d).is_ok());
+/// let mut d = br#"{"a": 1, "b": {"a": 2, "a": 3}}"#.to_vec();
+/// let e = parse_strict(&mut d).unwrap_err();
+/// assert_eq!(e.error(), &ErrorType::DuplicateKey("a".to_string()));
+/// assert_eq!(e.index(), 23);
+/// let mut d = br#"["\ud800"]"#.to_vec();
+/// assert!(parse_strict(&mut d).is_err());
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or has an object with
+/// duplicate keys.
+pub fn parse_strict(s: &mut [u8]) -> Result<Value> {
+    let input_start = s.as_ptr() as usize;
+    let de = Deserializer::from_slice_with_opts(s, ParseOptions::strict())?;
+    check_unique_keys(&de.tape, input_start)?;
+    Ok(OwnedDeserializer::from_deserializer(de).parse())
+}
+
 /// Owned JSON-DOM Value, consider using the `ValueTrait`
 /// to access it's content.
 /// This is slower then the `BorrowedValue` as a tradeoff
@@ -87,6 +175,1094 @@ pub enum Value {
     Object(Box<Object>),
 }
 
+impl Value {
+    /// Creates an empty array with space for at least `capacity` elements,
+    /// this is the same as `Builder::array_with_capacity` but doesn't
+    /// require the trait to be in scope.
+    #[inline]
+    #[must_use]
+    pub fn with_array_capacity(capacity: usize) -> Self {
+        Self::Array(Vec::with_capacity(capacity))
+    }
+
+    /// Creates an empty object with space for at least `capacity` entries,
+    /// this is the same as `Builder::object_with_capacity` but doesn't
+    /// require the trait to be in scope.
+    #[inline]
+    #[must_use]
+    pub fn with_object_capacity(capacity: usize) -> Self {
+        Self::Object(Box::new(Object::with_capacity_and_hasher(
+            capacity,
+            ObjectHasher::default(),
+        )))
+    }
+
+    /// Returns the value as a `Number` if it is a number that fits
+    /// into 64 bit.
+    #[inline]
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Self::Static(s) => Number::from_static(*s),
+            _ => None,
+        }
+    }
+
+    /// Converts the value leniently, for example `"true"` to a `bool`,
+    /// see `CoerceFromJson` for the available conversions. Returns `None`
+    /// if the value can't be converted.
+    #[cfg(feature = "coerce")]
+    #[inline]
+    #[must_use]
+    pub fn try_coerce_to<T: super::CoerceFromJson>(&self) -> Option<T> {
+        T::coerce_from(self)
+    }
+
+    /// Returns the kind of the value, see `JsonKind` for how numbers
+    /// are mapped.
+    #[inline]
+    #[must_use]
+    pub fn kind(&self) -> JsonKind {
+        match self {
+            Self::Static(s) => JsonKind::of_static(*s),
+            Self::String(_) => JsonKind::String,
+            Self::Array(_) => JsonKind::Array,
+            Self::Object(_) => JsonKind::Object,
+        }
+    }
+
+    /// Returns true if the value is an integer, regardless of its
+    /// representation
+    #[inline]
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        self.kind().is_integer()
+    }
+
+    /// Returns true if the value is a floating point number
+    #[inline]
+    #[must_use]
+    pub fn is_float(&self) -> bool {
+        self.kind().is_float()
+    }
+
+    /// Returns true if the value is a number, regardless of its
+    /// representation
+    #[inline]
+    #[must_use]
+    pub fn is_number(&self) -> bool {
+        self.kind().is_number()
+    }
+
+    /// Pairs up the elements of two arrays, like `Iterator::zip` the
+    /// result is as long as the shorter of the two arrays.
+    ///
+    /// Returns `None` if either value is not an array.
+    #[must_use]
+    pub fn zip<'v>(&'v self, other: &'v Self) -> Option<Vec<(&'v Self, &'v Self)>> {
+        match (self, other) {
+            (Self::Array(a), Self::Array(b)) => Some(a.iter().zip(b.iter()).collect()),
+            _ => None,
+        }
+    }
+
+    /// Consumes an object and iterates over its keys and values, in no
+    /// particular order. Values that are not objects have no entries.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::json;
+    /// let v = json!({"a": 1, "b": 2});
+    /// let mut sum = 0;
+    /// for (key, value) in v.into_entries() {
+    ///     assert!(key == "a" || key == "b");
+    ///     sum += value.as_u64().unwrap_or(0);
+    /// }
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn into_entries(self) -> impl Iterator<Item = (String, Self)> {
+        let o = match self {
+            Self::Object(o) => Some(*o),
+            Self::Static(_) | Self::String(_) | Self::Array(_) => None,
+        };
+        o.into_iter().flatten()
+    }
+
+    /// Consumes an array and iterates over its elements and their
+    /// indexes. Values that are not arrays have no elements.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let v = json!(["a", "b"]);
+    /// let indexed: Vec<_> = v.into_indexed().collect();
+    /// assert_eq!(indexed, vec![(0, json!("a")), (1, json!("b"))]);
+    /// ```
+    pub fn into_indexed(self) -> impl Iterator<Item = (usize, Self)> {
+        let a = match self {
+            Self::Array(a) => a,
+            Self::Static(_) | Self::String(_) | Self::Object(_) => Vec::new(),
+        };
+        a.into_iter().enumerate()
+    }
+
+    /// Consumes the value and returns every leaf with its JSON pointer
+    /// relative to this value, in document order. Leaves are all values
+    /// that are not arrays or objects, and empty arrays and objects so
+    /// they aren't lost. Object members are visited in the iteration
+    /// order of the object and a leaf at the root has the pointer `""`.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let v = json!({"a": [1, {"b/c": null}], "d": {}});
+    /// let mut entries = v.into_flatten_entries();
+    /// entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    /// assert_eq!(
+    ///     entries,
+    ///     vec![
+    ///         ("/a/0".to_string(), json!(1)),
+    ///         ("/a/1/b~1c".to_string(), json!(null)),
+    ///         ("/d".to_string(), json!({})),
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn into_flatten_entries(self) -> Vec<(String, Self)> {
+        let mut entries = Vec::new();
+        let mut stack = vec![(String::new(), self)];
+        while let Some((path, v)) = stack.pop() {
+            let start = stack.len();
+            match v {
+                Self::Array(a) if !a.is_empty() => stack.extend(
+                    a.into_iter()
+                        .enumerate()
+                        .map(|(i, v)| (format!("{path}/{i}"), v)),
+                ),
+                Self::Object(o) if !o.is_empty() => stack.extend(o.into_iter().map(|(k, v)| {
+                    let mut child = path.clone();
+                    push_token(&mut child, &k);
+                    (child, v)
+                })),
+                v => entries.push((path, v)),
+            }
+            // children are popped in order
+            stack[start..].reverse();
+        }
+        entries
+    }
+
+    /// Returns the first element of an array that matches `predicate`.
+    ///
+    /// Returns `None` if the value is not an array or no element
+    /// matches.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::json;
+    /// let v = json!([{"id": 1}, {"id": 2, "name": "b"}, {"id": 2}]);
+    /// let found = v.find_in_array(|e| e["id"] == 2).unwrap();
+    /// assert_eq!(found["name"], "b");
+    /// assert!(v.find_in_array(|e| e["id"] == 3).is_none());
+    /// ```
+    #[must_use]
+    pub fn find_in_array<F>(&self, predicate: F) -> Option<&Self>
+    where
+        F: Fn(&Self) -> bool,
+    {
+        match self {
+            Self::Array(a) => a.iter().find(|v| predicate(v)),
+            Self::Static(_) | Self::String(_) | Self::Object(_) => None,
+        }
+    }
+
+    /// Returns the first value of the document, starting with the value
+    /// itself, that matches `pred`.
+    ///
+    /// Values are visited in pre-order with an explicit stack, so deep
+    /// documents don't overflow the call stack: a value is visited before
+    /// its children and children in the order of their array or object.
+    /// Arrays and objects built by the parser keep document order, for
+    /// objects with many keys the order is the one of the underlying map.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::json;
+    /// let v = json!({"items": [{"code": 1}, {"type": "error", "code": 2}], "meta": {"code": 3}});
+    /// let error = v.find(|e| e.get_str("type") == Some("error")).unwrap();
+    /// assert_eq!(error["code"], 2);
+    /// // objects containing a key, parents before children
+    /// let with_code = v.find_all(|e| e.get("code").is_some());
+    /// assert_eq!(with_code.len(), 3);
+    /// assert_eq!(with_code[0]["code"], 1);
+    /// assert_eq!(with_code[1]["code"], 2);
+    /// ```
+    #[must_use]
+    pub fn find<'a, F>(&'a self, mut pred: F) -> Option<&'a Self>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut stack = vec![self];
+        while let Some(v) = stack.pop() {
+            if pred(v) {
+                return Some(v);
+            }
+            v.push_children(&mut stack);
+        }
+        None
+    }
+
+    /// Returns all values of the document, starting with the value
+    /// itself, that match `pred`, in the order of `find`.
+    #[must_use]
+    pub fn find_all<'a, F>(&'a self, mut pred: F) -> Vec<&'a Self>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut found = Vec::new();
+        let mut stack = vec![self];
+        while let Some(v) = stack.pop() {
+            if pred(v) {
+                found.push(v);
+            }
+            v.push_children(&mut stack);
+        }
+        found
+    }
+
+    /// Like `find`, but also returns the JSON pointer of the match
+    /// relative to this value.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let v = json!({"items": [{"c": 1}, {"type": "error", "c": 2}], "meta": {"c": 3}});
+    /// let (pointer, found) = v.find_path(|e| e == &2).unwrap();
+    /// assert_eq!(pointer.as_str(), "/items/1/c");
+    /// assert_eq!(v.pointer(&pointer), Some(found));
+    /// ```
+    #[must_use]
+    pub fn find_path<'a, F>(&'a self, mut pred: F) -> Option<(JsonPointer, &'a Self)>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut stack = vec![(String::new(), self)];
+        while let Some((path, v)) = stack.pop() {
+            if pred(v) {
+                return Some((JsonPointer::from_valid(path), v));
+            }
+            v.push_children_with_paths(&path, &mut stack);
+        }
+        None
+    }
+
+    /// Like `find_all`, but also returns the JSON pointers of the matches
+    /// relative to this value.
+    #[must_use]
+    pub fn find_all_paths<'a, F>(&'a self, mut pred: F) -> Vec<(JsonPointer, &'a Self)>
+    where
+        F: FnMut(&'a Self) -> bool,
+    {
+        let mut found = Vec::new();
+        let mut stack = vec![(String::new(), self)];
+        while let Some((path, v)) = stack.pop() {
+            v.push_children_with_paths(&path, &mut stack);
+            if pred(v) {
+                found.push((JsonPointer::from_valid(path), v));
+            }
+        }
+        found
+    }
+
+    /// Counts the leaves of the document, that is nulls, bools, numbers
+    /// and strings. Arrays and objects aren't counted, empty ones add
+    /// nothing.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let v = json!({"a": [1, "x", null], "b": {"c": true, "d": []}});
+    /// assert_eq!(v.count_leaves(), 4);
+    /// assert_eq!(json!(1.5).count_leaves(), 1);
+    /// ```
+    #[must_use]
+    pub fn count_leaves(&self) -> usize {
+        let mut leaves = 0;
+        let mut stack = vec![self];
+        while let Some(v) = stack.pop() {
+            match v {
+                Self::Static(_) | Self::String(_) => leaves += 1,
+                Self::Array(_) | Self::Object(_) => v.push_children(&mut stack),
+            }
+        }
+        leaves
+    }
+
+    /// Compares two values like `==` except that arrays are compared as
+    /// multisets: each element of one array has to be equal to a distinct
+    /// element of the other, in any order and again ignoring the order of
+    /// nested arrays. Objects, strings and numbers compare as usual, so an
+    /// integer stored signed and unsigned is equal while `1` and `1.0`
+    /// are not.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let a = json!({"tags": ["b", "a", "b"], "points": [[2, 1], [3]]});
+    /// let b = json!({"points": [[3], [1, 2]], "tags": ["a", "b", "b"]});
+    /// assert!(a.eq_unordered(&b));
+    /// assert!(a != b);
+    /// ```
+    ///
+    /// Arrays that are equal in order take as many comparisons as `==`.
+    /// Otherwise every element is compared with every element of the
+    /// other array, that is quadratic in the length of the array, plus
+    /// finding a matching of the equal pairs which is cubic in the worst
+    /// case, for example when many elements are equal to each other.
+    #[must_use]
+    pub fn eq_unordered(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Static(s1), Self::Static(s2)) => s1 == s2,
+            (Self::String(s1), Self::String(s2)) => s1 == s2,
+            (Self::Array(a1), Self::Array(a2)) => multiset_eq(a1, a2, Self::eq_unordered),
+            (Self::Object(o1), Self::Object(o2)) => {
+                o1.len() == o2.len()
+                    && o1
+                        .iter()
+                        .all(|(k, v1)| o2.get(k).map_or(false, |v2| v1.eq_unordered(v2)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Pushes the children of a value onto a traversal stack so they are
+    /// popped in order
+    fn push_children<'a>(&'a self, stack: &mut Vec<&'a Self>) {
+        let start = stack.len();
+        match self {
+            Self::Array(a) => stack.extend(a.iter()),
+            Self::Object(o) => stack.extend(o.values()),
+            Self::Static(_) | Self::String(_) => {}
+        }
+        stack[start..].reverse();
+    }
+
+    /// Like `push_children` with the pointer of each child
+    fn push_children_with_paths<'a>(&'a self, path: &str, stack: &mut Vec<(String, &'a Self)>) {
+        let start = stack.len();
+        match self {
+            Self::Array(a) => stack.extend(
+                a.iter()
+                    .enumerate()
+                    .map(|(i, v)| (format!("{path}/{i}"), v)),
+            ),
+            Self::Object(o) => stack.extend(o.iter().map(|(k, v)| {
+                let mut child = path.to_string();
+                push_token(&mut child, k);
+                (child, v)
+            })),
+            Self::Static(_) | Self::String(_) => {}
+        }
+        stack[start..].reverse();
+    }
+
+    /// Folds the elements of an array into an accumulator, like
+    /// `Iterator::fold` over the array without copying it.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::json;
+    /// let v = json!([1, 2, "x", 3]);
+    /// let sum = v.reduce_array(0, |acc, e| acc + e.as_u64().unwrap_or(0));
+    /// assert_eq!(sum, Ok(6));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not an array.
+    pub fn reduce_array<Acc, F>(&self, init: Acc, f: F) -> std::result::Result<Acc, AccessError>
+    where
+        F: FnMut(Acc, &Self) -> Acc,
+    {
+        match self {
+            Self::Array(a) => Ok(a.iter().fold(init, f)),
+            Self::Static(_) | Self::String(_) | Self::Object(_) => Err(AccessError::NotAnArray),
+        }
+    }
+
+    /// Projects an object onto CSV fields, one for each header in order.
+    /// Missing keys and `null` are empty fields, strings are written as
+    /// they are and other values as JSON. Fields with a `,`, `"` or line
+    /// break are quoted with `"` doubled. Values that are not objects
+    /// only have empty fields.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let v = json!({"name": "Smith, J", "age": 42, "tags": ["a"], "ok": true});
+    /// assert_eq!(
+    ///     v.to_csv_row(&["name", "age", "email", "ok", "tags"]),
+    ///     vec!["\"Smith, J\"", "42", "", "true", "\"[\"\"a\"\"]\""]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_csv_row(&self, headers: &[&str]) -> Vec<String> {
+        headers
+            .iter()
+            .map(|h| match self.get(*h) {
+                None | Some(Self::Static(StaticNode::Null)) => String::new(),
+                Some(Self::String(s)) => csv_field(s),
+                Some(v) => csv_field(&v.encode()),
+            })
+            .collect()
+    }
+
+    /// Groups the elements of an array by the key `key_fn` returns for
+    /// them, within a group elements keep their order.
+    ///
+    /// Returns `None` if the value is not an array.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::json;
+    /// let v = json!([1, 2, 3, 4, 5]);
+    /// let groups = v.group_by(|e| e.as_u64().map(|n| n % 2)).unwrap();
+    /// assert_eq!(groups[&Some(1)], vec![&v[0], &v[2], &v[4]]);
+    /// assert_eq!(groups[&Some(0)].len(), 2);
+    /// ```
+    #[must_use]
+    pub fn group_by<K, F>(&self, mut key_fn: F) -> Option<std::collections::HashMap<K, Vec<&Self>>>
+    where
+        K: Eq + std::hash::Hash,
+        F: FnMut(&Self) -> K,
+    {
+        match self {
+            Self::Array(a) => {
+                let mut groups = std::collections::HashMap::new();
+                for e in a {
+                    groups.entry(key_fn(e)).or_insert_with(Vec::new).push(e);
+                }
+                Some(groups)
+            }
+            Self::Static(_) | Self::String(_) | Self::Object(_) => None,
+        }
+    }
+
+    /// Groups an array of objects by the string value of the field
+    /// `key`, like SQL's `GROUP BY`. Elements that are not objects or
+    /// whose field is missing or not a string are left out.
+    ///
+    /// Returns `None` if the value is not an array.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let v = json!([
+    ///     {"team": "a", "score": 1},
+    ///     {"team": "b", "score": 2},
+    ///     {"team": "a", "score": 3},
+    ///     {"score": 4}
+    /// ]);
+    /// let groups = v.group_by_key("team").unwrap();
+    /// assert_eq!(groups["a"], vec![&v[0], &v[2]]);
+    /// assert_eq!(groups["b"], vec![&v[1]]);
+    /// assert_eq!(groups.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn group_by_key<'v>(
+        &'v self,
+        key: &str,
+    ) -> Option<std::collections::HashMap<&'v str, Vec<&'v Self>>> {
+        match self {
+            Self::Array(a) => {
+                let mut groups = std::collections::HashMap::new();
+                for e in a {
+                    if let Some(k) = e.get(key).and_then(ValueAccess::as_str) {
+                        groups.entry(k).or_insert_with(Vec::new).push(e);
+                    }
+                }
+                Some(groups)
+            }
+            Self::Static(_) | Self::String(_) | Self::Object(_) => None,
+        }
+    }
+
+    /// Sorts the elements of an array in place with a comparator, the
+    /// sort is stable. This is `slice::sort_by` on the array.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::json;
+    /// let mut v = json!([{"n": 3}, {"n": 1}, {"n": 2}]);
+    /// v.sort_array_by(|a, b| a["n"].as_i64().cmp(&b["n"].as_i64())).unwrap();
+    /// assert_eq!(v, json!([{"n": 1}, {"n": 2}, {"n": 3}]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not an array.
+    pub fn sort_array_by<F>(&mut self, cmp: F) -> std::result::Result<(), AccessError>
+    where
+        F: FnMut(&Self, &Self) -> Ordering,
+    {
+        match self {
+            Self::Array(a) => {
+                a.sort_by(cmp);
+                Ok(())
+            }
+            Self::Static(_) | Self::String(_) | Self::Object(_) => Err(AccessError::NotAnArray),
+        }
+    }
+
+    /// Removes consecutive equal elements from an array, like
+    /// `Vec::dedup`.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let mut v = json!([1, 1, 2, 1, "a", "a"]);
+    /// v.dedup_array().unwrap();
+    /// assert_eq!(v, json!([1, 2, 1, "a"]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not an array.
+    pub fn dedup_array(&mut self) -> std::result::Result<(), AccessError> {
+        match self {
+            Self::Array(a) => {
+                a.dedup();
+                Ok(())
+            }
+            Self::Static(_) | Self::String(_) | Self::Object(_) => Err(AccessError::NotAnArray),
+        }
+    }
+
+    /// Removes all elements from an array that are equal to an earlier
+    /// element, the remaining elements keep their order. Duplicates are
+    /// found by sorting the indexes of the elements, so this takes
+    /// `O(n log n)` comparisons.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let mut v = json!([3, {"a": 1}, 1, 3, {"a": 1}, 2, 1]);
+    /// v.dedup_array_stable().unwrap();
+    /// assert_eq!(v, json!([3, {"a": 1}, 1, 2]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not an array.
+    pub fn dedup_array_stable(&mut self) -> std::result::Result<(), AccessError> {
+        let a = match self {
+            Self::Array(a) => a,
+            Self::Static(_) | Self::String(_) | Self::Object(_) => {
+                return Err(AccessError::NotAnArray)
+            }
+        };
+        // the sort is stable, so equal elements stay in index order
+        let mut order: Vec<usize> = (0..a.len()).collect();
+        order.sort_by(|i, j| total_cmp(&a[*i], &a[*j]));
+        let mut keep = vec![true; a.len()];
+        let mut start = 0;
+        for p in 1..order.len() {
+            if total_cmp(&a[order[p - 1]], &a[order[p]]).is_ne() {
+                start = p;
+            } else if order[start..p]
+                .iter()
+                .any(|k| keep[*k] && a[*k] == a[order[p]])
+            {
+                keep[order[p]] = false;
+            }
+        }
+        let mut keep = keep.into_iter();
+        a.retain(|_| keep.next().unwrap_or(true));
+        Ok(())
+    }
+
+    /// Looks up the value at a JSON pointer, returns `None` if there
+    /// is no value at the pointer.
+    #[must_use]
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<&Self> {
+        pointer.tokens().try_fold(self, |v, token| match v {
+            Self::Object(o) => o.get(&*token),
+            Self::Array(a) => a.get(array_index(&token)?),
+            Self::Static(_) | Self::String(_) => None,
+        })
+    }
+
+    /// Looks up the value at a JSON pointer in the extended syntax,
+    /// returns `None` if there is no value at the pointer.
+    ///
+    /// The extended syntax is RFC 6901 with negative array indexes: `-n`
+    /// is the n-th element from the end, so `-1` is the last one, and
+    /// `-0` doesn't refer to any element. Whether a numeric token is a
+    /// key or an index depends on the container it is applied to, in
+    /// objects `3` and `-1` are the keys `"3"` and `"-1"`. `pointer` stays
+    /// strictly RFC 6901.
+    ///
+    /// ```rust
+    /// use simd_json::{json, JsonPointer};
+    /// let v = json!({"items": [1, 2, {"3": "x"}]});
+    /// let p = |s| JsonPointer::new(s).unwrap();
+    /// assert_eq!(v.pointer_ext(&p("/items/-1/3")), Some(&json!("x")));
+    /// assert_eq!(v.pointer_ext(&p("/items/-3")), Some(&json!(1)));
+    /// assert!(v.pointer_ext(&p("/items/-4")).is_none());
+    /// assert!(v.pointer(&p("/items/-1")).is_none());
+    /// ```
+    #[must_use]
+    pub fn pointer_ext(&self, pointer: &JsonPointer) -> Option<&Self> {
+        pointer
+            .tokens()
+            .try_fold(self, |v, token| v.child_ext(&token))
+    }
+
+    /// Looks up the value at a JSON pointer in the extended syntax, see
+    /// `pointer_ext`, and returns a mutable reference to it.
+    pub fn pointer_ext_mut(&mut self, pointer: &JsonPointer) -> Option<&mut Self> {
+        pointer
+            .tokens()
+            .try_fold(self, |v, token| v.child_ext_mut(&token))
+    }
+
+    /// Inserts a value at a JSON pointer in the extended syntax, see
+    /// `pointer_ext`. In an object the key is set and the previous value
+    /// is returned. In an array the value is inserted before the element
+    /// the index refers to, an index of the array's length or `-`
+    /// appends to it.
+    ///
+    /// ```rust
+    /// use simd_json::{json, JsonPointer};
+    /// let mut v = json!({"items": [1, 2]});
+    /// let p = |s| JsonPointer::new(s).unwrap();
+    /// assert_eq!(v.pointer_ext_insert(&p("/items/-"), json!(4)), Ok(None));
+    /// assert_eq!(v.pointer_ext_insert(&p("/items/-1"), json!(3)), Ok(None));
+    /// assert_eq!(v.pointer_ext_insert(&p("/n"), json!(4)), Ok(None));
+    /// assert_eq!(v, json!({"items": [1, 2, 3, 4], "n": 4}));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the pointer is empty, the value at its parent
+    /// is missing or not an array or object, or the index is out of
+    /// range.
+    pub fn pointer_ext_insert(
+        &mut self,
+        pointer: &JsonPointer,
+        value: Self,
+    ) -> Result<Option<Self>> {
+        let no_value = || Error::generic(ErrorType::NoValueAtPointer);
+        let mut tokens: Vec<_> = pointer.tokens().collect();
+        let last = tokens.pop().ok_or_else(no_value)?;
+        let parent = tokens
+            .iter()
+            .try_fold(self, |v, token| v.child_ext_mut(token))
+            .ok_or_else(no_value)?;
+        match parent {
+            Self::Object(o) => Ok(o.insert(last.into_owned(), value)),
+            Self::Array(a) => {
+                let idx = if last == "-" || array_index(&last) == Some(a.len()) {
+                    a.len()
+                } else {
+                    array_index_ext(&last, a.len())
+                        .filter(|idx| *idx < a.len())
+                        .ok_or_else(no_value)?
+                };
+                a.insert(idx, value);
+                Ok(None)
+            }
+            Self::Static(_) | Self::String(_) => Err(no_value()),
+        }
+    }
+
+    /// Follows a path of keys through nested objects, creating empty
+    /// objects for missing keys, and returns the value at its end. If the
+    /// last key is missing it is inserted with the value returned by
+    /// `default_factory`, which is only called then. An empty path
+    /// returns the value itself.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let mut config = json!({"server": {"port": 80}});
+    /// let enabled = config
+    ///     .get_or_insert_path(&["server", "tls", "enabled"], || json!(false))
+    ///     .unwrap();
+    /// *enabled = json!(true);
+    /// let port = config.get_or_insert_path(&["server", "port"], || json!(8080));
+    /// assert_eq!(port.unwrap(), &json!(80));
+    /// assert_eq!(config, json!({"server": {"port": 80, "tls": {"enabled": true}}}));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `ErrorType::ExpectedMap` if a key has to be looked up
+    /// in a value that isn't an object. Only existing values are followed
+    /// until then, so nothing has been inserted when this happens.
+    pub fn get_or_insert_path<F>(&mut self, path: &[&str], default_factory: F) -> Result<&mut Self>
+    where
+        F: FnOnce() -> Self,
+    {
+        let expected_map = || Error::generic(ErrorType::ExpectedMap);
+        let (last, parents) = if let Some(split) = path.split_last() {
+            split
+        } else {
+            return Ok(self);
+        };
+        let parent = parents.iter().try_fold(self, |v, key| match v {
+            Self::Object(o) => Ok(o.entry((*key).to_string()).or_insert_with(Self::object)),
+            Self::Static(_) | Self::String(_) | Self::Array(_) => Err(expected_map()),
+        })?;
+        match parent {
+            Self::Object(o) => Ok(o.entry((*last).to_string()).or_insert_with(default_factory)),
+            Self::Static(_) | Self::String(_) | Self::Array(_) => Err(expected_map()),
+        }
+    }
+
+    /// Removes the value at a JSON pointer in the extended syntax, see
+    /// `pointer_ext`, and returns it. Returns `None` if there is no value
+    /// at the pointer or the pointer is empty.
+    pub fn pointer_ext_remove(&mut self, pointer: &JsonPointer) -> Option<Self> {
+        let mut tokens: Vec<_> = pointer.tokens().collect();
+        let last = tokens.pop()?;
+        match tokens
+            .iter()
+            .try_fold(self, |v, token| v.child_ext_mut(token))?
+        {
+            Self::Object(o) => o.remove(&*last),
+            Self::Array(a) => {
+                let idx = array_index_ext(&last, a.len()).filter(|idx| *idx < a.len())?;
+                Some(a.remove(idx))
+            }
+            Self::Static(_) | Self::String(_) => None,
+        }
+    }
+
+    /// The child of an array or object for a token of the extended
+    /// pointer syntax
+    fn child_ext(&self, token: &str) -> Option<&Self> {
+        match self {
+            Self::Object(o) => o.get(token),
+            Self::Array(a) => a.get(array_index_ext(token, a.len())?),
+            Self::Static(_) | Self::String(_) => None,
+        }
+    }
+
+    fn child_ext_mut(&mut self, token: &str) -> Option<&mut Self> {
+        match self {
+            Self::Object(o) => o.get_mut(token),
+            Self::Array(a) => {
+                let idx = array_index_ext(token, a.len())?;
+                a.get_mut(idx)
+            }
+            Self::Static(_) | Self::String(_) => None,
+        }
+    }
+
+    /// Adds `by` to an integer or float in place, integers that
+    /// overflow are an error. This is the same as `incr_with(by, false)`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not a number or the result
+    /// can't be represented.
+    pub fn incr(&mut self, by: i64) -> std::result::Result<(), NumericError> {
+        self.incr_with(by, false)
+    }
+
+    /// Adds `by` to an integer or float in place. Integers stay integers
+    /// and switch between `i64` and `u64` as needed, results that fit
+    /// neither become floats if `promote_to_f64` is set.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::{json, NumericError};
+    /// let mut v = json!({"count": u64::MAX});
+    /// assert_eq!(v["count"].incr(1), Err(NumericError::Overflow));
+    /// assert_eq!(v["count"].incr_with(1, true), Ok(()));
+    /// assert!(v["count"].is_f64());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not a number or the result
+    /// can't be represented.
+    pub fn incr_with(
+        &mut self,
+        by: i64,
+        promote_to_f64: bool,
+    ) -> std::result::Result<(), NumericError> {
+        match self {
+            Self::Static(s) => numeric::incr(s, by, promote_to_f64),
+            Self::String(_) | Self::Array(_) | Self::Object(_) => Err(NumericError::NotANumber),
+        }
+    }
+
+    /// Adds `by` to a number in place, the result is always a float.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value is not a number or the result is
+    /// not finite.
+    pub fn incr_f64(&mut self, by: f64) -> std::result::Result<(), NumericError> {
+        match self {
+            Self::Static(s) => numeric::incr_f64(s, by),
+            Self::String(_) | Self::Array(_) | Self::Object(_) => Err(NumericError::NotANumber),
+        }
+    }
+
+    /// Replaces the values of all object keys matched by `keys` with
+    /// `"[REDACTED]"`, at any depth. Matched values are not traversed
+    /// any further.
+    pub fn redact_keys(&mut self, keys: &KeyMatcher) {
+        match self {
+            Self::Array(a) => {
+                for v in a {
+                    v.redact_keys(keys);
+                }
+            }
+            Self::Object(o) => {
+                for (k, v) in o.iter_mut() {
+                    if keys.is_match(k) {
+                        *v = Self::String(REDACTED.to_string());
+                    } else {
+                        v.redact_keys(keys);
+                    }
+                }
+            }
+            Self::Static(_) | Self::String(_) => (),
+        }
+    }
+
+    /// Replaces all integers with floats of the same value, at any depth,
+    /// for consumers that treat every number as a float. Integers that
+    /// can't be represented exactly as a `f64`, such as `2^53 + 1`, are
+    /// an error and leave the value unchanged.
+    ///
+    /// ```rust
+    /// use simd_json::prelude::*;
+    /// use simd_json::{json, NumericError};
+    /// let mut v = json!({"a": [1, 2.5], "b": -3});
+    /// v.normalize_numbers_to_f64().unwrap();
+    /// assert_eq!(v, json!({"a": [1.0, 2.5], "b": -3.0}));
+    /// let mut v = json!([1, 9_007_199_254_740_993_u64]);
+    /// assert_eq!(v.normalize_numbers_to_f64(), Err(NumericError::Overflow));
+    /// assert!(v[0].is_i64());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an integer can't be represented exactly.
+    pub fn normalize_numbers_to_f64(&mut self) -> std::result::Result<(), NumericError> {
+        // check all numbers first so an error leaves the value unchanged
+        self.numbers_to_f64(false)?;
+        self.numbers_to_f64(true)
+    }
+
+    fn numbers_to_f64(&mut self, convert: bool) -> std::result::Result<(), NumericError> {
+        match self {
+            Self::Static(s) => {
+                if let Some(f) = numeric::exact_f64(*s)? {
+                    if convert {
+                        *s = StaticNode::F64(f);
+                    }
+                }
+            }
+            Self::Array(a) => {
+                for v in a {
+                    v.numbers_to_f64(convert)?;
+                }
+            }
+            Self::Object(o) => {
+                for v in o.values_mut() {
+                    v.numbers_to_f64(convert)?;
+                }
+            }
+            Self::String(_) => (),
+        }
+        Ok(())
+    }
+
+    /// Consumes the value and returns it with every number, at any
+    /// depth, in its canonical representation, so documents that write
+    /// the same numbers differently compare equal. Floats without a
+    /// fractional part become integers if they fit into an `i64` or
+    /// `u64`, `1.0e3` becomes `1000` and `-0.0` becomes `0`, and integers
+    /// are stored as the parser stores them. Other floats are kept.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let mut d = br#"{"a": 1.0e3, "b": [2.5, -0.0, 1e30]}"#.to_vec();
+    /// let v = simd_json::to_owned_value(&mut d).unwrap();
+    /// assert_ne!(v, json!({"a": 1000, "b": [2.5, 0, 1e30]}));
+    /// assert_eq!(v.into_canonical_form(), json!({"a": 1000, "b": [2.5, 0, 1e30]}));
+    /// ```
+    #[must_use]
+    pub fn into_canonical_form(mut self) -> Self {
+        self.canonicalize();
+        self
+    }
+
+    fn canonicalize(&mut self) {
+        match self {
+            Self::Static(s) => *s = numeric::canonical(*s),
+            Self::Array(a) => a.iter_mut().for_each(Self::canonicalize),
+            Self::Object(o) => o.values_mut().for_each(Self::canonicalize),
+            Self::String(_) => (),
+        }
+    }
+
+    /// Computes a hash of the content of the value that is the same
+    /// across runs, platforms and versions of this crate, for example to
+    /// key a content addressed cache.
+    ///
+    /// Equal values have the same hash: object keys are hashed in sorted
+    /// order so the order of a map doesn't matter, integers are hashed by
+    /// value no matter how they are stored and `-0.0` hashes like `0.0`.
+    /// Integers and floats are different values, `1` and `1.0` hash
+    /// differently. The hash is SipHash-2-4 with a fixed key over an
+    /// unambiguous encoding of the value, it is not meant to resist
+    /// collisions crafted on purpose.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let a = json!({"id": 1, "tags": ["a", "b"]});
+    /// let mut d = br#"{"tags": ["a", "b"], "id": 1}"#.to_vec();
+    /// let b = simd_json::to_owned_value(&mut d).unwrap();
+    /// assert_eq!(a.compute_hash_stable(), b.compute_hash_stable());
+    /// assert_ne!(a.compute_hash_stable(), json!({"id": 1, "tags": ["b", "a"]}).compute_hash_stable());
+    /// ```
+    #[must_use]
+    pub fn compute_hash_stable(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        self.hash_stable(&mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_stable(&self, h: &mut StableHasher) {
+        fn len(h: &mut StableHasher, len: usize) {
+            h.write(&(len as u64).to_le_bytes());
+        }
+        fn string(h: &mut StableHasher, s: &str) {
+            h.write(&[5]);
+            len(h, s.len());
+            h.write(s.as_bytes());
+        }
+        match self {
+            Self::Static(StaticNode::Null) => h.write(&[0]),
+            Self::Static(StaticNode::Bool(b)) => h.write(&[1, u8::from(*b)]),
+            Self::Static(StaticNode::F64(f)) => {
+                let f = if *f == 0.0 { 0.0 } else { *f };
+                h.write(&[4]);
+                h.write(&f.to_bits().to_le_bytes());
+            }
+            Self::Static(s) => match int_key(s) {
+                Some(IntKey::Negative(n)) => {
+                    h.write(&[2]);
+                    h.write(&n.to_le_bytes());
+                }
+                Some(IntKey::Positive(n)) => {
+                    h.write(&[3]);
+                    h.write(&n.to_le_bytes());
+                }
+                None => (),
+            },
+            Self::String(s) => string(h, s),
+            Self::Array(a) => {
+                h.write(&[6]);
+                len(h, a.len());
+                for v in a {
+                    v.hash_stable(h);
+                }
+            }
+            Self::Object(o) => {
+                let mut entries: Vec<_> = o.iter().collect();
+                entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+                h.write(&[7]);
+                len(h, entries.len());
+                for (k, v) in entries {
+                    string(h, k);
+                    v.hash_stable(h);
+                }
+            }
+        }
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line
+/// break
+fn csv_field(s: &str) -> String {
+    if s.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// A total order on values that puts equal values next to each other,
+/// integers are ordered by value no matter how they are stored. Floats
+/// that can't be compared, which JSON can't represent, compare as equal.
+fn total_cmp(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Static(s1), Value::Static(s2)) => static_cmp(s1, s2),
+        (Value::String(s1), Value::String(s2)) => s1.cmp(s2),
+        (Value::Array(a1), Value::Array(a2)) => a1
+            .iter()
+            .zip(a2.iter())
+            .map(|(v1, v2)| total_cmp(v1, v2))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| a1.len().cmp(&a2.len())),
+        (Value::Object(o1), Value::Object(o2)) => o1.len().cmp(&o2.len()).then_with(|| {
+            let mut e1: Vec<_> = o1.iter().collect();
+            let mut e2: Vec<_> = o2.iter().collect();
+            e1.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            e2.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            e1.iter()
+                .zip(e2.iter())
+                .map(|((k1, v1), (k2, v2))| k1.cmp(k2).then_with(|| total_cmp(v1, v2)))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)
+        }),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// The order of the kinds of values in `total_cmp`
+fn rank(v: &Value) -> u8 {
+    match v {
+        Value::Static(StaticNode::Null) => 0,
+        Value::Static(StaticNode::Bool(_)) => 1,
+        Value::Static(StaticNode::F64(_)) => 3,
+        Value::Static(_) => 2,
+        Value::String(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+    }
+}
+
+/// Integers normalized so that the same number compares as equal no
+/// matter how it is stored
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum IntKey {
+    Negative(i128),
+    Positive(u128),
+}
+
+fn int_key(s: &StaticNode) -> Option<IntKey> {
+    let signed = |n: i128| u128::try_from(n).map_or(IntKey::Negative(n), IntKey::Positive);
+    match *s {
+        StaticNode::I64(n) => Some(signed(i128::from(n))),
+        StaticNode::U64(n) => Some(IntKey::Positive(u128::from(n))),
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(n) => Some(signed(n)),
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(n) => Some(IntKey::Positive(n)),
+        StaticNode::F64(_) | StaticNode::Bool(_) | StaticNode::Null => None,
+    }
+}
+
+fn static_cmp(s1: &StaticNode, s2: &StaticNode) -> Ordering {
+    match (s1, s2) {
+        (StaticNode::Bool(b1), StaticNode::Bool(b2)) => b1.cmp(b2),
+        (StaticNode::F64(f1), StaticNode::F64(f2)) => f1.partial_cmp(f2).unwrap_or(Ordering::Equal),
+        _ => match (int_key(s1), int_key(s2)) {
+            (Some(i1), Some(i2)) => i1.cmp(&i2),
+            _ => rank(&Value::Static(*s1)).cmp(&rank(&Value::Static(*s2))),
+        },
+    }
+}
+
 impl<'input> Builder<'input> for Value {
     #[inline]
     #[must_use]
@@ -284,6 +1460,27 @@ impl fmt::Display for Value {
     }
 }
 
+/// Wraps a value so its `Debug` output is JSON instead of the enum
+/// representation, `{:?}` prints compact JSON and `{:#?}` pretty JSON.
+///
+/// ```rust
+/// use simd_json::{json, DebugPretty};
+/// let v = json!({"key": [1]});
+/// assert_eq!(format!("{:?}", DebugPretty(&v)), r#"{"key":[1]}"#);
+/// assert_eq!(format!("{:#?}", DebugPretty(&v)), "{\n  \"key\": [\n    1\n  ]\n}");
+/// ```
+pub struct DebugPretty<'value>(pub &'value Value);
+
+impl<'value> fmt::Debug for DebugPretty<'value> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&self.0.encode_pp())
+        } else {
+            f.write_str(&self.0.encode())
+        }
+    }
+}
+
 impl Index<&str> for Value {
     type Output = Self;
     #[inline]
@@ -293,6 +1490,15 @@ impl Index<&str> for Value {
     }
 }
 
+impl Index<&JsonPointer> for Value {
+    type Output = Self;
+    #[inline]
+    #[must_use]
+    fn index(&self, index: &JsonPointer) -> &Self::Output {
+        self.pointer(index).expect("no value at pointer")
+    }
+}
+
 impl Index<usize> for Value {
     type Output = Self;
     #[inline]
@@ -326,7 +1532,7 @@ impl Default for Value {
     }
 }
 
-struct OwnedDeserializer<'de> {
+pub(crate) struct OwnedDeserializer<'de> {
     de: Deserializer<'de>,
 }
 
@@ -334,6 +1540,10 @@ impl<'de> OwnedDeserializer<'de> {
     pub fn from_deserializer(de: Deserializer<'de>) -> Self {
         Self { de }
     }
+
+    pub(crate) fn into_tape(self) -> Vec<Node<'de>> {
+        self.de.into_tape()
+    }
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     pub fn parse(&mut self) -> Value {
         match unsafe { self.de.next_() } {
@@ -363,25 +1573,97 @@ impl<'de> OwnedDeserializer<'de> {
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     fn parse_map(&mut self, len: usize) -> Value {
         let mut res = Object::with_capacity_and_hasher(len, ObjectHasher::default());
+        let duplicate_keys = self.de.duplicate_keys;
 
         for _ in 0..len {
             if let Node::String(key) = unsafe { self.de.next_() } {
-                #[cfg(not(feature = "value-no-dup-keys"))]
-                res.insert_nocheck(key.into(), self.parse());
-                #[cfg(feature = "value-no-dup-keys")]
-                res.insert(key.into(), self.parse());
+                if duplicate_keys == DuplicateKeys::Unchecked {
+                    #[cfg(not(feature = "value-no-dup-keys"))]
+                    res.insert_nocheck(key.into(), self.parse());
+                    #[cfg(feature = "value-no-dup-keys")]
+                    res.insert(key.into(), self.parse());
+                } else {
+                    let value = self.parse();
+                    insert_duplicate_keys(&mut res, key.into(), value, duplicate_keys);
+                }
             } else {
                 unreachable!();
             }
         }
         Value::from(res)
     }
+
+    /// Like `parse` but allocates arrays and strings fallibly
+    fn try_parse(&mut self) -> Result<Value> {
+        match unsafe { self.de.next_() } {
+            Node::Static(s) => Ok(Value::Static(s)),
+            Node::String(s) => {
+                let mut res: String = String::new();
+                if res.try_reserve_exact(s.len()).is_err() {
+                    return Err(Error::generic(ErrorType::AllocationFailed(s.len())));
+                }
+                res.push_str(s);
+                Ok(Value::String(res))
+            }
+            Node::Array(len, _) => {
+                let mut res = try_vec(len)?;
+                for _ in 0..len {
+                    res.push(self.try_parse()?);
+                }
+                Ok(Value::Array(res))
+            }
+            Node::Object(len, _) => {
+                let mut res = Object::with_capacity_and_hasher(len, ObjectHasher::default());
+                let duplicate_keys = self.de.duplicate_keys;
+                for _ in 0..len {
+                    if let Node::String(key) = unsafe { self.de.next_() } {
+                        let value = self.try_parse()?;
+                        if duplicate_keys == DuplicateKeys::Unchecked {
+                            #[cfg(not(feature = "value-no-dup-keys"))]
+                            res.insert_nocheck(key.into(), value);
+                            #[cfg(feature = "value-no-dup-keys")]
+                            res.insert(key.into(), value);
+                        } else {
+                            insert_duplicate_keys(&mut res, key.into(), value, duplicate_keys);
+                        }
+                    } else {
+                        unreachable!();
+                    }
+                }
+                Ok(Value::from(res))
+            }
+        }
+    }
+}
+
+/// Inserts into an object that is being built, resolving duplicate keys
+/// with the given policy
+fn insert_duplicate_keys(
+    res: &mut Object,
+    key: String,
+    value: Value,
+    duplicate_keys: DuplicateKeys,
+) {
+    if let Some(existing) = res.get_mut(&key) {
+        match (duplicate_keys, existing, value) {
+            (DuplicateKeys::FirstWins, _, _) => (),
+            (DuplicateKeys::MergeObjects, Value::Object(existing), Value::Object(value)) => {
+                for (k, v) in *value {
+                    insert_duplicate_keys(existing, k, v, duplicate_keys);
+                }
+            }
+            (_, existing, value) => *existing = value,
+        }
+    } else {
+        res.insert_nocheck(key, value);
+    }
 }
 
 #[cfg(test)]
 mod test {
     #![allow(clippy::cognitive_complexity)]
     use super::*;
+    use crate::json;
 
     #[test]
     fn object_access() {
@@ -396,6 +1678,611 @@ mod test {
         assert_eq!(v.remove("key"), Ok(Some(Value::from(3))));
     }
 
+    #[test]
+    fn with_capacity() {
+        let v = Value::with_array_capacity(16);
+        assert!(v
+            .as_array()
+            .map_or(false, |a| a.is_empty() && a.capacity() >= 16));
+        let v = Value::with_object_capacity(16);
+        assert!(v
+            .as_object()
+            .map_or(false, |o| o.is_empty() && o.capacity() >= 16));
+    }
+
+    #[test]
+    fn as_number() {
+        let n = Value::from(-7).as_number().expect("number");
+        assert_eq!(n.to_i64(), Some(-7));
+        assert_eq!(Value::from(n), Value::from(-7));
+        assert_eq!(
+            Value::from(1.5).as_number().and_then(|n| n.to_f64()),
+            Some(1.5)
+        );
+        assert!(Value::from("7").as_number().is_none());
+        assert!(Value::null().as_number().is_none());
+    }
+
+    #[test]
+    fn kind() {
+        let v = crate::json!({"a": [null, true, -1, 1.0, "s", {}]});
+        let kinds: Vec<_> = v["a"]
+            .as_array()
+            .expect("array")
+            .iter()
+            .map(Value::kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                JsonKind::Null,
+                JsonKind::Bool,
+                JsonKind::Integer,
+                JsonKind::Float,
+                JsonKind::String,
+                JsonKind::Object
+            ]
+        );
+        assert_eq!(v["a"].kind(), JsonKind::Array);
+        assert!(Value::from(u64::MAX).is_integer());
+        assert!(Value::from(1e3).is_float() && !Value::from(1e3).is_integer());
+        assert!(Value::from(-1).is_number() && !Value::from("1").is_number());
+    }
+
+    #[test]
+    fn pointer() {
+        let v = crate::json!({"list": [{"id": 1}, {"id": 2}]});
+        let p = JsonPointer::new("/list/1/id").expect("valid pointer");
+        assert_eq!(v[&p], 2);
+        assert_eq!(v.pointer(&p), Some(&Value::from(2)));
+        let p = JsonPointer::new("/list/id").expect("valid pointer");
+        assert!(v.pointer(&p).is_none());
+    }
+
+    #[test]
+    fn pointer_ext() {
+        let p = |s| JsonPointer::new(s).expect("valid pointer");
+        let mut v = json!({"items": [], "3": {"-1": "neg"}, "list": [1, 2, 3]});
+        // `-1` on empty arrays
+        assert_eq!(v.pointer_ext(&p("/items/-1")), None);
+        assert_eq!(v.pointer_ext_remove(&p("/items/-1")), None);
+        assert_eq!(
+            v.pointer_ext_insert(&p("/items/-1"), json!(1)),
+            Err(Error::generic(ErrorType::NoValueAtPointer))
+        );
+        // `-0` is never an element
+        assert_eq!(v.pointer_ext(&p("/list/-0")), None);
+        assert_eq!(v.pointer_ext(&p("/list/0")), Some(&json!(1)));
+        assert_eq!(v.pointer_ext(&p("/list/-3")), Some(&json!(1)));
+        assert_eq!(v.pointer_ext(&p("/list/-4")), None);
+        assert_eq!(v.pointer_ext(&p("/list/3")), None);
+        // numeric tokens are keys in objects
+        assert_eq!(v.pointer_ext(&p("/3/-1")), Some(&json!("neg")));
+        assert_eq!(v.pointer(&p("/3/-1")), Some(&json!("neg")));
+        assert_eq!(v.pointer(&p("/list/-1")), None);
+
+        *v.pointer_ext_mut(&p("/list/-1")).expect("an element") = json!(30);
+        assert_eq!(v["list"], json!([1, 2, 30]));
+        assert_eq!(v.pointer_ext_insert(&p("/items/0"), json!("a")), Ok(None));
+        assert_eq!(v.pointer_ext_insert(&p("/items/-1"), json!("b")), Ok(None));
+        assert_eq!(v.pointer_ext_insert(&p("/items/2"), json!("c")), Ok(None));
+        assert_eq!(v["items"], json!(["b", "a", "c"]));
+        assert!(v.pointer_ext_insert(&p("/items/4"), json!(0)).is_err());
+        assert_eq!(
+            v.pointer_ext_insert(&p("/3/-1"), json!(0)),
+            Ok(Some(json!("neg")))
+        );
+        assert!(v.pointer_ext_insert(&p("/list/0/x"), json!(0)).is_err());
+        assert!(v.pointer_ext_insert(&p(""), json!(0)).is_err());
+
+        assert_eq!(v.pointer_ext_remove(&p("/list/-2")), Some(json!(2)));
+        assert_eq!(v.pointer_ext_remove(&p("/3/-1")), Some(json!(0)));
+        assert_eq!(v.pointer_ext_remove(&p("/3/-1")), None);
+        assert_eq!(v.pointer_ext_remove(&p("")), None);
+        assert_eq!(
+            v,
+            json!({"items": ["b", "a", "c"], "3": {}, "list": [1, 30]})
+        );
+    }
+
+    #[test]
+    fn get_or_insert_path() {
+        let mut v = json!({"a": {"b": 1}, "list": [1], "s": "x"});
+        let mut calls = 0;
+        let mut default = || {
+            calls += 1;
+            json!("default")
+        };
+        // existing values are returned without calling the factory
+        assert_eq!(
+            v.get_or_insert_path(&["a", "b"], &mut default),
+            Ok(&mut json!(1))
+        );
+        assert_eq!(
+            v.get_or_insert_path(&["a"], &mut default),
+            Ok(&mut json!({"b": 1}))
+        );
+        assert!(v
+            .get_or_insert_path(&[], &mut default)
+            .expect("the value itself")
+            .get("list")
+            .is_some());
+        // missing parents become objects, the leaf the default
+        *v.get_or_insert_path(&["a", "c", "d", "e"], &mut default)
+            .expect("objects on the path") = json!(2);
+        assert_eq!(
+            v.get_or_insert_path(&["x"], &mut default),
+            Ok(&mut json!("default"))
+        );
+        assert_eq!(calls, 2);
+        // a key in an empty string is still a key
+        assert_eq!(
+            v.get_or_insert_path(&["", ""], || json!(0)),
+            Ok(&mut json!(0))
+        );
+
+        let before = v.clone();
+        for path in [&["list", "0"][..], &["s", "y", "z"], &["a", "b", "c"]] {
+            assert_eq!(
+                v.get_or_insert_path(path, || json!(0)),
+                Err(Error::generic(ErrorType::ExpectedMap)),
+                "{path:?}"
+            );
+        }
+        assert_eq!(v, before);
+        assert_eq!(
+            v,
+            json!({
+                "a": {"b": 1, "c": {"d": {"e": 2}}},
+                "list": [1],
+                "s": "x",
+                "x": "default",
+                "": {"": 0}
+            })
+        );
+        assert_eq!(
+            json!([]).get_or_insert_path(&["a"], || json!(0)),
+            Err(Error::generic(ErrorType::ExpectedMap))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no value at pointer")]
+    fn pointer_index_panics() {
+        let v = Value::from(vec![1]);
+        let _ = &v[&JsonPointer::new("/1").expect("valid pointer")];
+    }
+
+    #[test]
+    fn incr() {
+        let mut v = crate::json!({"count": 1, "name": "x"});
+        assert_eq!(v["count"].incr(2), Ok(()));
+        assert_eq!(v["count"], 3);
+        assert_eq!(v["count"].incr_f64(0.5), Ok(()));
+        assert_eq!(v["count"], 3.5);
+        assert_eq!(v["name"].incr(1), Err(NumericError::NotANumber));
+        assert_eq!(v.incr_f64(1.0), Err(NumericError::NotANumber));
+        assert_eq!(v["name"], "x");
+    }
+
+    #[test]
+    fn zip() {
+        let a = Value::from(vec![1, 2, 3]);
+        let b = Value::from(vec!["a", "b"]);
+        let pairs = a.zip(&b).expect("both are arrays");
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0], (&Value::from(1), &Value::from("a")));
+        assert_eq!(pairs[1], (&Value::from(2), &Value::from("b")));
+        assert!(a.zip(&Value::null()).is_none());
+        assert!(Value::object().zip(&a).is_none());
+    }
+
+    #[test]
+    fn find_in_array() {
+        let v = json!([1, "a", {"k": "b"}, "a", [1]]);
+        assert_eq!(v.find_in_array(Value::is_str), Some(&Value::from("a")));
+        assert!(std::ptr::eq(
+            v.find_in_array(|e| e == "a").expect("an element"),
+            &v[1]
+        ));
+        assert_eq!(
+            v.find_in_array(|e| e.get("k").is_some()),
+            Some(&json!({"k": "b"}))
+        );
+        assert_eq!(
+            v.find_in_array(|e| e.as_array().is_some()),
+            Some(&json!([1]))
+        );
+        assert_eq!(v.find_in_array(|_| false), None);
+        assert_eq!(Value::array().find_in_array(|_| true), None);
+        assert_eq!(json!({"a": 1}).find_in_array(|_| true), None);
+        assert_eq!(Value::from(1).find_in_array(|_| true), None);
+    }
+
+    #[test]
+    fn into_entries() {
+        let v = json!({"a": [1], "b": {"c": null}, "": 3});
+        let mut entries: Vec<_> = v.into_entries().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(
+            entries,
+            vec![
+                (String::new(), json!(3)),
+                ("a".to_string(), json!([1])),
+                ("b".to_string(), json!({"c": null}))
+            ]
+        );
+        assert_eq!(json!([1, 2]).into_entries().count(), 0);
+        assert_eq!(Value::object().into_entries().count(), 0);
+
+        let indexed: Vec<_> = json!([null, "x", [2]]).into_indexed().collect();
+        assert_eq!(
+            indexed,
+            vec![(0, Value::null()), (1, json!("x")), (2, json!([2]))]
+        );
+        assert_eq!(json!({"a": 1}).into_indexed().count(), 0);
+        assert_eq!(Value::from("a").into_indexed().count(), 0);
+    }
+
+    #[test]
+    fn into_flatten_entries() {
+        let v = json!([1, {"a": ["x", []], "m~n": {"": true}}, {}, [[2.5]]]);
+        let entries = v.clone().into_flatten_entries();
+        let pointers: Vec<&str> = entries.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(pointers.len(), 6);
+        assert_eq!(pointers[0], "/0");
+        assert_eq!(&pointers[4..], ["/2", "/3/0/0"]);
+        let mut object_pointers = pointers[1..4].to_vec();
+        object_pointers.sort_unstable();
+        assert_eq!(object_pointers, ["/1/a/0", "/1/a/1", "/1/m~0n/"]);
+        for (pointer, leaf) in &entries {
+            let p = JsonPointer::new(pointer).expect("valid pointer");
+            assert_eq!(v.pointer(&p), Some(leaf));
+        }
+        assert_eq!(
+            Value::from("s").into_flatten_entries(),
+            vec![(String::new(), Value::from("s"))]
+        );
+        assert_eq!(
+            Value::array().into_flatten_entries(),
+            vec![(String::new(), Value::array())]
+        );
+    }
+
+    #[test]
+    fn to_csv_row() {
+        let v = json!({
+            "s": "plain",
+            "comma": "a,b",
+            "quote": "say \"hi\"",
+            "nl": "a\nb",
+            "null": null,
+            "f": 1.5,
+            "i": -3,
+            "b": false,
+            "o": {"k": 1}
+        });
+        assert_eq!(
+            v.to_csv_row(&["s", "comma", "quote", "nl", "null", "missing"]),
+            vec!["plain", "\"a,b\"", "\"say \"\"hi\"\"\"", "\"a\nb\"", "", ""]
+        );
+        assert_eq!(
+            v.to_csv_row(&["i", "f", "b", "o", "i"]),
+            vec!["-3", "1.5", "false", "\"{\"\"k\"\":1}\"", "-3"]
+        );
+        assert!(v.to_csv_row(&[]).is_empty());
+        assert_eq!(json!([1]).to_csv_row(&["0", "a"]), vec!["", ""]);
+    }
+
+    #[test]
+    fn reduce_array() {
+        let v = json!([3, 1.5, "a", 7, "b", null]);
+        let max = v.reduce_array(None, |acc: Option<f64>, e| match e.cast_f64() {
+            Some(n) => Some(acc.map_or(n, |m| m.max(n))),
+            None => acc,
+        });
+        assert_eq!(max, Ok(Some(7.0)));
+        let joined = v.reduce_array(String::new(), |mut acc, e| {
+            if let Some(s) = e.as_str() {
+                acc.push_str(s);
+            }
+            acc
+        });
+        assert_eq!(joined.as_deref(), Ok("ab"));
+        assert_eq!(Value::array().reduce_array(1, |acc, _| acc + 1), Ok(1));
+        assert_eq!(
+            json!({"a": 1}).reduce_array(0, |acc, _| acc),
+            Err(AccessError::NotAnArray)
+        );
+    }
+
+    #[test]
+    fn compute_hash_stable() {
+        // pinned, the hash must not change between versions
+        assert_eq!(Value::null().compute_hash_stable(), 0x74f8_39c5_93dc_67fd);
+        assert_eq!(Value::array().compute_hash_stable(), 0x8839_2d62_4bb8_7b1d);
+        assert_eq!(Value::object().compute_hash_stable(), 0x3c89_307c_9694_4c8c);
+        let v = json!({"b": [1, -2, 2.5, true, null], "a": "x"});
+        assert_eq!(v.compute_hash_stable(), 0xb6fc_612c_e8cf_6ed4);
+
+        let mut d = br#"{"a": "x", "b": [1, -2, 2.5, true, null]}"#.to_vec();
+        let parsed = crate::to_owned_value(&mut d).expect("valid json");
+        assert_eq!(parsed.compute_hash_stable(), v.compute_hash_stable());
+
+        let hash = |v: Value| v.compute_hash_stable();
+        assert_eq!(
+            hash(Value::Static(StaticNode::U64(7))),
+            hash(Value::Static(StaticNode::I64(7)))
+        );
+        assert_eq!(
+            hash(Value::Static(StaticNode::F64(-0.0))),
+            hash(Value::Static(StaticNode::F64(0.0)))
+        );
+        assert_ne!(hash(json!(1)), hash(json!(1.0)));
+        assert_ne!(hash(json!(-1)), hash(json!(u64::MAX)));
+        assert_ne!(hash(json!("1")), hash(json!(1)));
+        assert_ne!(hash(json!([[]])), hash(json!([])));
+        assert_ne!(hash(json!(["ab", "c"])), hash(json!(["a", "bc"])));
+        assert_ne!(hash(json!({"a": 1})), hash(json!({"b": 1})));
+        assert_ne!(hash(json!({"a": [1]})), hash(json!([{"a": 1}])));
+    }
+
+    #[test]
+    fn into_canonical_form() {
+        let mut d =
+            br#"[1.0, 1, 18446744073709551615, 1.8446744073709552e19, {"x": [-5e0, 0.25]}]"#
+                .to_vec();
+        let v = crate::to_owned_value(&mut d)
+            .expect("valid json")
+            .into_canonical_form();
+        assert!(v[0].is_i64());
+        assert_eq!(v[0], v[1]);
+        assert!(v[2].is_u64());
+        // 2^64 doesn't fit into an u64
+        assert!(v[3].is_f64());
+        assert!(v[4]["x"][0].is_i64());
+        assert_eq!(v[4]["x"][0], -5);
+        assert_eq!(v[4]["x"][1], 0.25);
+        assert_eq!(v.clone().into_canonical_form(), v);
+    }
+
+    #[test]
+    fn count_leaves() {
+        assert_eq!(Value::null().count_leaves(), 1);
+        assert_eq!(Value::from("").count_leaves(), 1);
+        assert_eq!(Value::array().count_leaves(), 0);
+        assert_eq!(json!([[], {}, [[]], {"a": {}}]).count_leaves(), 0);
+        let v = json!({"a": [1, -2, 3.5, false], "b": {"c": "d", "e": [null, [true]]}});
+        assert_eq!(v.count_leaves(), 7);
+        assert_eq!(v.count_leaves(), v.into_flatten_entries().len());
+    }
+
+    #[test]
+    fn eq_unordered() {
+        // duplicates have to appear as often on both sides
+        assert!(json!([1, 2, 1, "a"]).eq_unordered(&json!(["a", 1, 1, 2])));
+        assert!(!json!([1, 1, 2]).eq_unordered(&json!([1, 2, 2])));
+        assert!(!json!([1, 2]).eq_unordered(&json!([2, 1, 1])));
+        // nested arrays are unordered too, objects keep comparing by key
+        let a = json!([{"k": [[3, 4], [1, 2]]}, [[], [1, [2, 3]]], null]);
+        let b = json!([null, [[1, [3, 2]], []], {"k": [[2, 1], [4, 3]]}]);
+        assert!(a.eq_unordered(&b));
+        assert!(b.eq_unordered(&a));
+        assert_ne!(a, b);
+        assert!(!a.eq_unordered(&json!([null, [[1, [3, 2]], []], {"j": [[2, 1], [4, 3]]}])));
+        assert!(!json!({"a": [1, 2]}).eq_unordered(&json!({"a": [1, 2], "b": 1})));
+        // several elements match more than one element of the other side,
+        // the first candidate of `[1, 2]` is taken by an equal one later on
+        let a = json!([[1, 2], [2, 1], [1, 2, 2], [2, 2, 1]]);
+        let b = json!([[2, 1, 2], [1, 2], [1, 2], [2, 2, 1]]);
+        assert!(a.eq_unordered(&b));
+        assert!(!a.eq_unordered(&json!([[2, 1, 2], [1, 2], [1, 1, 2], [2, 2, 1]])));
+        // numbers compare like `==`, across integer representations only
+        let signed = Value::from(vec![Value::from(1_i64), Value::from(-1_i64)]);
+        let unsigned = Value::from(vec![Value::from(-1_i64), Value::from(1_u64)]);
+        assert!(signed.eq_unordered(&unsigned));
+        assert!(!json!([1, 2]).eq_unordered(&json!([2.0, 1.0])));
+        assert!(!json!([1]).eq_unordered(&json!({"0": 1})));
+    }
+
+    #[test]
+    fn find() {
+        let v = json!({
+            "a": [{"k": 1}, [{"k": 2, "b": {"k": 3}}]],
+            "x/y": {"k": 4},
+            "c": 5
+        });
+        let has_k = |e: &Value| e.get("k").is_some();
+        assert_eq!(v.find(has_k), Some(&v["a"][0]));
+        let all: Vec<_> = v.find_all(has_k).iter().map(|e| e["k"].clone()).collect();
+        assert_eq!(all, vec![1, 2, 3, 4]);
+
+        let paths: Vec<_> = v
+            .find_all_paths(has_k)
+            .into_iter()
+            .map(|(p, e)| {
+                assert_eq!(v.pointer(&p), Some(e));
+                p.as_str().to_string()
+            })
+            .collect();
+        assert_eq!(paths, vec!["/a/0", "/a/1/0", "/a/1/0/b", "/x~1y"]);
+
+        // the value itself is visited first
+        let (p, e) = v.find_path(|_| true).expect("a value");
+        assert_eq!((p.as_str(), e), ("", &v));
+        let (p, e) = v.find_path(|e| e == &5).expect("a value");
+        assert_eq!((p.as_str(), e), ("/c", &v["c"]));
+        assert!(v.find(|e| e == &6).is_none());
+        assert!(v.find_path(|e| e == &6).is_none());
+        assert_eq!(json!(1).find_all(|_| true), vec![&json!(1)]);
+    }
+
+    #[test]
+    fn group_by() {
+        let v = json!([
+            {"kind": "a", "n": 1},
+            {"kind": "b", "n": 2},
+            {"kind": 1, "n": 3},
+            "kind",
+            {"n": 4},
+            {"kind": "a", "n": 5},
+            {"kind": "", "n": 6}
+        ]);
+        let groups = v.group_by_key("kind").expect("an array");
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["a"], vec![&v[0], &v[5]]);
+        assert_eq!(groups["b"], vec![&v[1]]);
+        assert_eq!(groups[""], vec![&v[6]]);
+        assert!(v.group_by_key("missing").expect("an array").is_empty());
+
+        let groups = v.group_by(Value::kind).expect("an array");
+        assert_eq!(groups[&JsonKind::Object].len(), 6);
+        assert_eq!(groups[&JsonKind::String], vec![&v[3]]);
+        let groups = v
+            .group_by(|e| e.get("n").and_then(ValueAccess::as_u64).map(|n| n > 3))
+            .expect("an array");
+        assert_eq!(groups[&Some(true)], vec![&v[4], &v[5], &v[6]]);
+        assert_eq!(groups[&None], vec![&v[3]]);
+
+        assert!(Value::array()
+            .group_by_key("k")
+            .expect("an array")
+            .is_empty());
+        assert_eq!(json!({"k": "v"}).group_by_key("k"), None);
+        assert_eq!(Value::from(1).group_by(|_| 0), None);
+    }
+
+    #[test]
+    fn redact_keys() {
+        let mut v: Value = crate::json!({
+            "user": "ferris",
+            "PASSWORD": {"nested": "secret"},
+            "logins": [{"password": "hunter2", "at": 1}, [{"Password": null}]]
+        });
+        v.redact_keys(&KeyMatcher::case_insensitive(["password"]));
+        assert_eq!(
+            v,
+            crate::json!({
+                "user": "ferris",
+                "PASSWORD": "[REDACTED]",
+                "logins": [{"password": "[REDACTED]", "at": 1}, [{"Password": "[REDACTED]"}]]
+            })
+        );
+        let mut v: Value = crate::json!({"password": 1, "Password": 2});
+        v.redact_keys(&KeyMatcher::new(["password"]));
+        assert_eq!(v, crate::json!({"password": "[REDACTED]", "Password": 2}));
+    }
+
+    #[test]
+    fn debug_pretty() {
+        let v: Value = crate::json!({"a": [1, "two"]});
+        assert_eq!(format!("{:?}", DebugPretty(&v)), r#"{"a":[1,"two"]}"#);
+        assert_eq!(
+            format!("{:#?}", DebugPretty(&v)),
+            "{\n  \"a\": [\n    1,\n    \"two\"\n  ]\n}"
+        );
+        assert_eq!(format!("{:#?}", DebugPretty(&Value::null())), "null");
+    }
+
+    #[test]
+    fn from_std_collections() {
+        let mut m = std::collections::HashMap::new();
+        m.insert("key".to_string(), Value::from(1));
+        let v = Value::from(m);
+        assert_eq!(v["key"], 1);
+
+        let v = Value::from(vec![Value::from(1), Value::from("two")]);
+        assert_eq!(v, Value::Array(vec![Value::from(1), Value::from("two")]));
+
+        let v: Value = (1..=3).map(Value::from).collect();
+        assert_eq!(v, Value::from(vec![1, 2, 3]));
+        let v: Value = vec![("a", Value::from(1))].into_iter().collect();
+        assert_eq!(v["a"], 1);
+    }
+
+    #[test]
+    fn dedup_array() {
+        let mut v = json!([1, 1, 1.0, 1.0, [1], [1], null, null, 1]);
+        v.dedup_array().expect("an array");
+        assert_eq!(v, json!([1, 1.0, [1], null, 1]));
+        let mut v = json!("a");
+        assert_eq!(v.dedup_array(), Err(AccessError::NotAnArray));
+        assert_eq!(v.dedup_array_stable(), Err(AccessError::NotAnArray));
+    }
+
+    #[test]
+    fn dedup_array_stable() {
+        let mut v = json!([
+            {"b": [1, 2], "a": "x"},
+            2,
+            u64::MAX,
+            -1,
+            "2",
+            2.0,
+            [2],
+            {"a": "x", "b": [1, 2]},
+            Value::from(2_u64),
+            u64::MAX,
+            -1,
+            true,
+            null,
+            false,
+            true,
+            null,
+            {"a": "x", "b": [2, 1]},
+            [2],
+            2.0,
+            "2"
+        ]);
+        v.dedup_array_stable().expect("an array");
+        assert_eq!(
+            v,
+            json!([
+                {"b": [1, 2], "a": "x"},
+                2,
+                u64::MAX,
+                -1,
+                "2",
+                2.0,
+                [2],
+                true,
+                null,
+                false,
+                {"a": "x", "b": [2, 1]}
+            ])
+        );
+        let mut v = Value::array();
+        v.dedup_array_stable().expect("an array");
+        assert_eq!(v, Value::array());
+
+        let mut v = Value::from(
+            (0..1000_i64)
+                .map(|i| Value::from(i % 7))
+                .collect::<Vec<_>>(),
+        );
+        v.dedup_array_stable().expect("an array");
+        assert_eq!(v, json!([0, 1, 2, 3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn sort_array_by() {
+        let mut v = json!([3, "b", 1, null, "a", 2]);
+        v.sort_array_by(|a, b| a.as_i64().cmp(&b.as_i64()))
+            .expect("an array");
+        // the sort is stable, values that are not integers compare equal
+        assert_eq!(v, json!(["b", null, "a", 1, 2, 3]));
+        v.sort_array_by(|a, b| b.as_str().cmp(&a.as_str()))
+            .expect("an array");
+        assert_eq!(v, json!(["b", "a", null, 1, 2, 3]));
+        let mut v = Value::array();
+        assert_eq!(v.sort_array_by(|_, _| unreachable!()), Ok(()));
+        let mut v = json!({"a": [2, 1]});
+        assert_eq!(
+            v.sort_array_by(|_, _| Ordering::Equal),
+            Err(AccessError::NotAnArray)
+        );
+    }
+
     #[test]
     fn array_access() {
         let mut v = Value::null();
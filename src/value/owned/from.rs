@@ -1,5 +1,5 @@
 use super::{Object, Value};
-use crate::{BorrowedValue, StaticNode};
+use crate::{BorrowedValue, Number, StaticNode};
 use std::iter::FromIterator;
 
 impl From<crate::BorrowedValue<'_>> for Value {
@@ -33,6 +33,14 @@ impl From<StaticNode> for Value {
         Self::Static(s)
     }
 }
+
+impl From<Number> for Value {
+    #[inline]
+    #[must_use]
+    fn from(n: Number) -> Self {
+        Self::Static(n.into())
+    }
+}
 /********* str_ **********/
 
 impl From<&str> for Value {
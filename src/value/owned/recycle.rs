@@ -0,0 +1,176 @@
+use super::{insert_duplicate_keys, Object, Value};
+use crate::value::ObjectHasher;
+use crate::{Deserializer, DuplicateKeys, Node, Result};
+
+/// Parses a slice of bytes into a Value dom like `to_owned_value`,
+/// reusing the allocations of `recycled`, a document that is no longer
+/// needed.
+///
+/// `recycled` is taken apart into free lists of strings, arrays and
+/// objects that are handed out again, in the order they were used in
+/// the recycled document, while the new one is built. The more similar
+/// the two documents are the fewer allocations are needed, for
+/// documents of the same shape only strings, arrays and objects that
+/// grew have to be reallocated. Allocations that aren't reused are
+/// freed before returning.
+///
+/// ```rust
+/// use simd_json::{json, to_owned_value_recycle, OwnedValue};
+/// let mut previous = OwnedValue::default();
+/// for i in 0..3 {
+///     let mut d = format!(r#"{{"id": {i}, "tags": ["a", "b"]}}"#).into_bytes();
+///     previous = to_owned_value_recycle(&mut d, previous).unwrap();
+///     assert_eq!(previous, json!({"id": i, "tags": ["a", "b"]}));
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `input` is invalid JSON, `recycled` is dropped
+/// then.
+pub fn to_owned_value_recycle(input: &mut [u8], recycled: Value) -> Result<Value> {
+    let de = Deserializer::from_slice(input)?;
+    let mut free = FreeLists::default();
+    free.dismantle(recycled);
+    free.strings.reverse();
+    free.arrays.reverse();
+    free.objects.reverse();
+    Ok(RecyclingDeserializer { de, free }.parse())
+}
+
+/// The allocations of a dismantled document, the last one of each list
+/// is the first one used in the document. Objects are only `None` while
+/// their entries are taken apart.
+#[derive(Default)]
+struct FreeLists {
+    strings: Vec<String>,
+    arrays: Vec<Vec<Value>>,
+    objects: Vec<Option<Box<Object>>>,
+}
+
+impl FreeLists {
+    /// Takes `v` apart, containers are added before their content
+    fn dismantle(&mut self, v: Value) {
+        match v {
+            Value::Static(_) => (),
+            Value::String(s) => self.strings.push(s),
+            Value::Array(mut a) => {
+                let idx = self.arrays.len();
+                self.arrays.push(Vec::new());
+                for v in a.drain(..) {
+                    self.dismantle(v);
+                }
+                self.arrays[idx] = a;
+            }
+            Value::Object(mut o) => {
+                let idx = self.objects.len();
+                self.objects.push(None);
+                for (k, v) in o.drain() {
+                    self.strings.push(k);
+                    self.dismantle(v);
+                }
+                self.objects[idx] = Some(o);
+            }
+        }
+    }
+}
+
+struct RecyclingDeserializer<'de> {
+    de: Deserializer<'de>,
+    free: FreeLists,
+}
+
+impl RecyclingDeserializer<'_> {
+    fn parse(&mut self) -> Value {
+        match unsafe { self.de.next_() } {
+            Node::Static(s) => Value::Static(s),
+            Node::String(s) => Value::String(self.string(s)),
+            Node::Array(len, _) => {
+                let mut res = self.free.arrays.pop().unwrap_or_default();
+                res.reserve_exact(len);
+                for _ in 0..len {
+                    let v = self.parse();
+                    res.push(v);
+                }
+                Value::Array(res)
+            }
+            Node::Object(len, _) => {
+                let mut res = self.free.objects.pop().flatten().map_or_else(
+                    || {
+                        Box::new(Object::with_capacity_and_hasher(
+                            len,
+                            ObjectHasher::default(),
+                        ))
+                    },
+                    |mut o| {
+                        o.reserve(len);
+                        o
+                    },
+                );
+                let duplicate_keys = self.de.duplicate_keys;
+                for _ in 0..len {
+                    if let Node::String(key) = unsafe { self.de.next_() } {
+                        let key = self.string(key);
+                        let value = self.parse();
+                        if duplicate_keys == DuplicateKeys::Unchecked {
+                            #[cfg(not(feature = "value-no-dup-keys"))]
+                            res.insert_nocheck(key, value);
+                            #[cfg(feature = "value-no-dup-keys")]
+                            res.insert(key, value);
+                        } else {
+                            insert_duplicate_keys(&mut res, key, value, duplicate_keys);
+                        }
+                    } else {
+                        unreachable!();
+                    }
+                }
+                Value::Object(res)
+            }
+        }
+    }
+
+    fn string(&mut self, s: &str) -> String {
+        match self.free.strings.pop() {
+            Some(mut res) => {
+                res.clear();
+                res.push_str(s);
+                res
+            }
+            None => s.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_owned_value_recycle;
+    use crate::prelude::*;
+    use crate::{json, to_owned_value, OwnedValue};
+
+    #[test]
+    fn recycled_documents_are_equal() {
+        let docs = [
+            r#"{"a": [1, "x", {"b": null}], "c": "yy"}"#,
+            r#"{"a": [2, "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx", {"b": true, "d": [1, 2, 3, 4, 5]}]}"#,
+            r#"[]"#,
+            r#""just a string""#,
+            r#"[[[[]]], {}, {"k": {"k": {"k": "v"}}}, "s"]"#,
+            r#"{"a": [1, "x", {"b": null}], "c": "yy"}"#,
+        ];
+        let mut previous = json!({"unrelated": ["structure", 1, 2, {"x": "y"}]});
+        for d in docs {
+            let mut plain = d.as_bytes().to_vec();
+            let mut recycled = d.as_bytes().to_vec();
+            let expected = to_owned_value(&mut plain).expect("valid json");
+            previous = to_owned_value_recycle(&mut recycled, previous).expect("valid json");
+            assert_eq!(previous, expected, "{d}");
+        }
+        let mut invalid = b"[1, 2".to_vec();
+        assert!(to_owned_value_recycle(&mut invalid, previous).is_err());
+        let mut d = b"null".to_vec();
+        assert_eq!(
+            to_owned_value_recycle(&mut d, OwnedValue::default()).expect("valid json"),
+            OwnedValue::null()
+        );
+    }
+}
@@ -6,7 +6,8 @@
 
 use super::{Object, Value};
 use crate::prelude::*;
-use crate::{stry, StaticNode};
+use crate::value::pretty::{entries, CompactGenerator, LimitedWriter, OptionsGenerator};
+use crate::{stry, PrettyOptions, SerializeOptions, StaticNode};
 use std::io;
 use std::io::Write;
 use value_trait::generator::{
@@ -49,6 +50,248 @@ impl Writable for Value {
     }
 }
 
+impl Value {
+    /// Encodes the value as pretty printed JSON, formatted according to
+    /// `opts`.
+    #[must_use]
+    pub fn encode_pp_with(&self, opts: PrettyOptions) -> String {
+        let mut g = OptionsGenerator::new(Vec::new(), opts);
+        let _r = g.write_json_with(self);
+        // the generator only ever writes valid UTF-8
+        unsafe { String::from_utf8_unchecked(g.into_inner()) }
+    }
+
+    /// Encodes the value as pretty printed JSON indented by
+    /// `indent_width` times `indent_char` per level, a shorthand for
+    /// `encode_pp_with` with only the indentation changed.
+    ///
+    /// ```rust
+    /// use simd_json::json;
+    /// let v = json!({"a": [1]});
+    /// assert_eq!(
+    ///     v.to_pretty_json_string_with_indent('\t', 1),
+    ///     "{\n\t\"a\": [\n\t\t1\n\t]\n}"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_pretty_json_string_with_indent(
+        &self,
+        indent_char: char,
+        indent_width: usize,
+    ) -> String {
+        self.encode_pp_with(
+            PrettyOptions::default()
+                .indent_char(indent_char)
+                .indent(indent_width),
+        )
+    }
+
+    /// Writes the value as pretty printed JSON, formatted according to
+    /// `opts`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails.
+    pub fn write_pp_with<W>(&self, w: &mut W, opts: PrettyOptions) -> io::Result<()>
+    where
+        W: Write,
+    {
+        OptionsGenerator::new(w, opts).write_json_with(self)
+    }
+
+    /// Encodes the value as compact JSON, formatted according to `opts`.
+    #[must_use]
+    pub fn encode_with(&self, opts: SerializeOptions) -> String {
+        let mut g = CompactGenerator::new(Vec::new(), opts);
+        let _r = g.write_json(self);
+        // the generator only ever writes valid UTF-8
+        unsafe { String::from_utf8_unchecked(g.into_inner()) }
+    }
+
+    /// Writes the value as compact JSON, formatted according to `opts`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails.
+    pub fn write_with<W>(&self, w: &mut W, opts: SerializeOptions) -> io::Result<()>
+    where
+        W: Write,
+    {
+        CompactGenerator::new(w, opts).write_json(self)
+    }
+}
+
+/// A writer that only counts the bytes written to it
+struct CountingWriter(usize);
+
+impl Write for CountingWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0 += data.len();
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes `values` as newline delimited JSON, one compact document
+/// per line, each followed by `\n`.
+///
+/// The values are serialized twice, once to compute the exact size of
+/// the output and once into a buffer of that size, so the output is
+/// allocated once and never grows. Use `write_ndjson` to write large
+/// batches without holding all of the output in memory.
+///
+/// ```rust
+/// use simd_json::{json, to_ndjson_bytes};
+/// let values = [json!({"id": 1}), json!("two\nlines")];
+/// assert_eq!(to_ndjson_bytes(&values), b"{\"id\":1}\n\"two\\nlines\"\n");
+/// ```
+#[must_use]
+pub fn to_ndjson_bytes(values: &[Value]) -> Vec<u8> {
+    // one newline per value
+    let mut size = CountingWriter(values.len());
+    for value in values {
+        let _r = value.write(&mut size);
+    }
+    let mut res = Vec::with_capacity(size.0);
+    // writing to a vector can't fail
+    let _r = write_ndjson(values, &mut res);
+    res
+}
+
+/// Writes `values` to `out` as newline delimited JSON, one compact
+/// document per line, each followed by `\n`. Every value is written
+/// directly to `out`, nothing is buffered, so wrap unbuffered writers
+/// like files or sockets in a `BufWriter`.
+///
+/// # Errors
+///
+/// Will return `Err` if writing to `out` fails, the lines written
+/// before the failure are not rolled back.
+pub fn write_ndjson<W>(values: &[Value], out: &mut W) -> io::Result<()>
+where
+    W: Write,
+{
+    for value in values {
+        stry!(value.write(out));
+        stry!(out.write_all(b"\n"));
+    }
+    Ok(())
+}
+
+trait OptionsWriter {
+    /// Writes a value, expanding containers that don't fit on a line
+    fn write_json_with(&mut self, json: &Value) -> io::Result<()>;
+    /// Writes a value on a single line
+    fn write_line(&mut self, json: &Value) -> io::Result<()>;
+    /// Returns the single line form of a container if it fits within
+    /// the compact threshold
+    fn fit_line(&self, json: &Value) -> Option<Vec<u8>>;
+}
+
+impl<W> OptionsWriter for OptionsGenerator<W>
+where
+    W: Write,
+{
+    fn write_json_with(&mut self, json: &Value) -> io::Result<()> {
+        match json {
+            Value::Array(array) if !array.is_empty() => {
+                if let Some(line) = self.fit_line(json) {
+                    return self.write(&line);
+                }
+                stry!(self.write(b"["));
+                self.indent();
+                for (i, item) in array.iter().enumerate() {
+                    if i > 0 {
+                        stry!(self.write(b","));
+                    }
+                    stry!(self.new_line());
+                    stry!(self.write_json_with(item));
+                }
+                self.dedent();
+                stry!(self.new_line());
+                self.write(b"]")
+            }
+            Value::Object(object) if !object.is_empty() => {
+                if let Some(line) = self.fit_line(json) {
+                    return self.write(&line);
+                }
+                stry!(self.write(b"{"));
+                self.indent();
+                for (i, (key, value)) in
+                    entries(object.iter(), self.opts.stable_key_order).enumerate()
+                {
+                    if i > 0 {
+                        stry!(self.write(b","));
+                    }
+                    stry!(self.new_line());
+                    stry!(self.write_simple_string(key));
+                    stry!(self.write(b": "));
+                    stry!(self.write_json_with(value));
+                }
+                self.dedent();
+                stry!(self.new_line());
+                self.write(b"}")
+            }
+            _ => self.write_line(json),
+        }
+    }
+
+    fn write_line(&mut self, json: &Value) -> io::Result<()> {
+        match *json {
+            Value::Static(StaticNode::Null) => self.write(b"null"),
+            Value::Static(StaticNode::I64(number)) => self.write_int(number),
+            #[cfg(feature = "128bit")]
+            Value::Static(StaticNode::I128(number)) => self.write_int(number),
+            Value::Static(StaticNode::U64(number)) => self.write_int(number),
+            #[cfg(feature = "128bit")]
+            Value::Static(StaticNode::U128(number)) => self.write_int(number),
+            Value::Static(StaticNode::F64(number)) => self.write_float(number),
+            Value::Static(StaticNode::Bool(true)) => self.write(b"true"),
+            Value::Static(StaticNode::Bool(false)) => self.write(b"false"),
+            Value::String(ref string) => self.write_string(string),
+            Value::Array(ref array) => {
+                stry!(self.write(b"["));
+                for (i, item) in array.iter().enumerate() {
+                    if i > 0 {
+                        stry!(self.write(b", "));
+                    }
+                    stry!(self.write_line(item));
+                }
+                self.write(b"]")
+            }
+            Value::Object(ref object) => {
+                stry!(self.write(b"{"));
+                for (i, (key, value)) in
+                    entries(object.iter(), self.opts.stable_key_order).enumerate()
+                {
+                    if i > 0 {
+                        stry!(self.write(b", "));
+                    }
+                    stry!(self.write_simple_string(key));
+                    stry!(self.write(b": "));
+                    stry!(self.write_line(value));
+                }
+                self.write(b"}")
+            }
+        }
+    }
+
+    fn fit_line(&self, json: &Value) -> Option<Vec<u8>> {
+        if self.opts.compact_threshold == 0 {
+            return None;
+        }
+        // writing stops as soon as the threshold is exceeded, so large
+        // containers are not written in full just to be discarded
+        let w = LimitedWriter::new(self.opts.compact_threshold);
+        let mut g = OptionsGenerator::new(w, self.opts);
+        g.write_line(json).ok()?;
+        Some(g.into_inner().into_inner())
+    }
+}
+
 trait Generator: BaseGenerator {
     type T: Write;
 
@@ -210,6 +453,26 @@ trait FastGenerator: BaseGenerator {
     }
 }
 
+impl<W> FastGenerator for CompactGenerator<W>
+where
+    W: Write,
+{
+    type T = W;
+
+    fn write_object(&mut self, object: &Object) -> io::Result<()> {
+        stry!(self.write(b"{"));
+        for (i, (key, value)) in entries(object.iter(), self.opts.stable_key_order).enumerate() {
+            if i > 0 {
+                stry!(self.write(b","));
+            }
+            stry!(self.write_simple_string(key));
+            stry!(self.write(b":"));
+            stry!(self.write_json(value));
+        }
+        self.write(b"}")
+    }
+}
+
 impl FastGenerator for DumpGenerator<Value> {
     type T = Vec<u8>;
 }
@@ -234,9 +497,9 @@ where
 
 #[cfg(test)]
 mod test {
-    use super::Value;
+    use super::{to_ndjson_bytes, write_ndjson, Value};
     use crate::prelude::*;
-    use crate::StaticNode;
+    use crate::{json, ExponentStyle, PrettyOptions, SerializeOptions, StaticNode};
     #[test]
     fn null() {
         assert_eq!(Value::Static(StaticNode::Null).encode(), "null");
@@ -279,4 +542,185 @@ mod test {
             r#""this is a test a \\\"long\\\" test that should span the 32 byte boundary""#,
         );
     }
+
+    fn fixture() -> Value {
+        json!({
+            "name": "demo",
+            "point": [1, 2],
+            "nested": {"list": [[1, 2], [3, 4]], "empty": [], "obj": {}}
+        })
+    }
+
+    fn assert_pp(v: &Value, opts: PrettyOptions, expected: &str) {
+        let s = v.encode_pp_with(opts);
+        assert_eq!(s, expected);
+        let mut w = Vec::new();
+        v.write_pp_with(&mut w, opts).expect("write to vec");
+        assert_eq!(w, expected.as_bytes());
+        let mut d = s.into_bytes();
+        assert_eq!(&crate::to_owned_value(&mut d).expect("valid json"), v);
+    }
+
+    #[test]
+    fn ndjson() {
+        let values = [
+            fixture(),
+            Value::from("line\nbreak"),
+            Value::from(1.5),
+            Value::object(),
+        ];
+        let bytes = to_ndjson_bytes(&values);
+        // the size is computed up front, so the buffer is never grown
+        assert_eq!(bytes.len(), bytes.capacity());
+        let mut w = Vec::new();
+        write_ndjson(&values, &mut w).expect("write to vec");
+        assert_eq!(w, bytes);
+
+        let lines: Vec<&[u8]> = bytes.split(|c| *c == b'\n').collect();
+        assert_eq!(lines.len(), values.len() + 1);
+        assert_eq!(lines.last(), Some(&&b""[..]));
+        for (line, value) in lines.iter().zip(&values) {
+            assert_eq!(*line, value.encode().as_bytes());
+            let mut d = line.to_vec();
+            assert_eq!(&crate::to_owned_value(&mut d).expect("valid json"), value);
+        }
+
+        assert!(to_ndjson_bytes(&[]).is_empty());
+        let mut w = Vec::new();
+        write_ndjson(&[], &mut w).expect("write to vec");
+        assert!(w.is_empty());
+    }
+
+    #[test]
+    fn pretty_default_matches_encode_pp() {
+        let v = fixture();
+        assert_eq!(v.encode_pp_with(PrettyOptions::default()), v.encode_pp());
+        let v = Value::from(vec![1]);
+        assert_eq!(v.encode_pp_with(PrettyOptions::default()), v.encode_pp());
+    }
+
+    #[test]
+    fn pretty_compact_threshold() {
+        let v = fixture();
+        assert_pp(
+            &v,
+            PrettyOptions::default().compact_threshold(10),
+            r#"{
+  "name": "demo",
+  "point": [1, 2],
+  "nested": {
+    "list": [
+      [1, 2],
+      [3, 4]
+    ],
+    "empty": [],
+    "obj": {}
+  }
+}"#,
+        );
+        // `[[1, 2], [3, 4]]` is exactly 16 bytes wide
+        assert_pp(
+            &v,
+            PrettyOptions::default().compact_threshold(16),
+            r#"{
+  "name": "demo",
+  "point": [1, 2],
+  "nested": {
+    "list": [[1, 2], [3, 4]],
+    "empty": [],
+    "obj": {}
+  }
+}"#,
+        );
+        assert_pp(
+            &v,
+            PrettyOptions::default().compact_threshold(95),
+            r#"{"name": "demo", "point": [1, 2], "nested": {"list": [[1, 2], [3, 4]], "empty": [], "obj": {}}}"#,
+        );
+        assert_pp(
+            &v,
+            PrettyOptions::default().indent(4).compact_threshold(94),
+            r#"{
+    "name": "demo",
+    "point": [1, 2],
+    "nested": {"list": [[1, 2], [3, 4]], "empty": [], "obj": {}}
+}"#,
+        );
+    }
+
+    #[test]
+    fn pretty_indent_char() {
+        let v = fixture();
+        let expected = "{\n\t\"name\": \"demo\",\n\t\"point\": [1, 2],\n\t\"nested\": {\n\t\t\"list\": [[1, 2], [3, 4]],\n\t\t\"empty\": [],\n\t\t\"obj\": {}\n\t}\n}";
+        assert_pp(
+            &v,
+            PrettyOptions::default()
+                .indent_char('\t')
+                .indent(1)
+                .compact_threshold(16),
+            expected,
+        );
+        let v = json!({"a": [1, {}]});
+        assert_eq!(
+            v.to_pretty_json_string_with_indent('\t', 2),
+            "{\n\t\t\"a\": [\n\t\t\t\t1,\n\t\t\t\t{}\n\t\t]\n}"
+        );
+        assert_eq!(v.to_pretty_json_string_with_indent(' ', 2), v.encode_pp());
+        assert_eq!(
+            v.to_pretty_json_string_with_indent(' ', 0),
+            "{\n\"a\": [\n1,\n{}\n]\n}"
+        );
+    }
+
+    #[test]
+    fn pretty_stable_key_order() {
+        // enough keys for the objects to be stored as hash maps
+        let keys: Vec<String> = (0..40).map(|i| format!("k{i}")).collect();
+        let document = |keys: &mut dyn Iterator<Item = &String>| {
+            let members: Vec<String> = keys
+                .map(|k| format!(r#""{k}": {{"b": [1.5, "{k}"], "a": {{}}}}"#))
+                .collect();
+            format!(r#"{{"z": 1, "list": [{{{}}}], "a": 2}}"#, members.join(","))
+        };
+        let mut d1 = document(&mut keys.iter()).into_bytes();
+        let mut d2 = document(&mut keys.iter().rev()).into_bytes();
+        let v1 = crate::to_owned_value(&mut d1).expect("valid json");
+        let v2 = crate::to_owned_value(&mut d2).expect("valid json");
+        assert_eq!(v1, v2);
+
+        let opts = PrettyOptions::default().stable_key_order(true);
+        for opts in [opts, opts.compact_threshold(4096)] {
+            let s = v1.encode_pp_with(opts);
+            assert_eq!(s, v2.encode_pp_with(opts));
+            let mut w = Vec::new();
+            v2.write_pp_with(&mut w, opts).expect("write to vec");
+            assert_eq!(s.as_bytes(), w);
+            let mut d = s.into_bytes();
+            assert_eq!(crate::to_owned_value(&mut d).expect("valid json"), v1);
+        }
+        let s = v1.encode_pp_with(opts.compact_threshold(4096));
+        assert!(s.starts_with(r#"{"a": 2, "list": [{"k0": {"a": {}, "b": [1.5, "k0"]}, "k1": "#));
+        assert!(s.ends_with(r#""k9": {"a": {}, "b": [1.5, "k9"]}}], "z": 1}"#));
+
+        // without the option the order of the maps is kept
+        let v = json!({"b": 1, "a": 2});
+        assert_eq!(
+            v.encode_pp_with(PrettyOptions::default().compact_threshold(16)),
+            r#"{"b": 1, "a": 2}"#
+        );
+    }
+
+    #[test]
+    fn encode_with() {
+        let v = json!({"b": [1e16, {"d": null, "c": "x"}], "a": 2});
+        assert_eq!(v.encode_with(SerializeOptions::default()), v.encode());
+        let opts = SerializeOptions::default()
+            .stable_key_order(true)
+            .exponent_style(ExponentStyle::AlwaysPlus);
+        let s = v.encode_with(opts);
+        assert_eq!(s, r#"{"a":2,"b":[1e+16,{"c":"x","d":null}]}"#);
+        let mut w = Vec::new();
+        v.write_with(&mut w, opts).expect("write to vec");
+        assert_eq!(s.as_bytes(), w);
+    }
 }
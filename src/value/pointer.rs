@@ -0,0 +1,167 @@
+use crate::pointer::{validate, Pointer};
+use crate::Result;
+use std::borrow::Cow;
+use std::fmt;
+
+/// A JSON pointer as defined in [RFC 6901](https://tools.ietf.org/html/rfc6901),
+/// the syntax is validated when the pointer is created so looking up
+/// values can't fail because of a malformed pointer.
+///
+/// Values can be indexed with a pointer, which panics if there is no
+/// value at the pointer, or looked up with `pointer`:
+///
+/// ```rust
+/// use simd_json::{json, JsonPointer};
+/// let v = json!({"a/b": [1, {"c~d": 2}]});
+/// let p = JsonPointer::new("/a~1b/1/c~0d").unwrap();
+/// assert_eq!(v[&p], 2);
+/// assert!(v.pointer(&JsonPointer::new("/a~1b/2").unwrap()).is_none());
+/// assert!(JsonPointer::new("a/b").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JsonPointer(String);
+
+impl JsonPointer {
+    /// Creates a pointer, validating its syntax. The empty string points
+    /// to the whole document.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the pointer doesn't start with `/` or
+    /// contains a `~` that is not followed by `0` or `1`.
+    pub fn new(s: &str) -> Result<Self> {
+        validate(s)?;
+        Ok(Self(s.to_string()))
+    }
+
+    /// A pointer from a string that is known to be valid
+    pub(crate) fn from_valid(s: String) -> Self {
+        Self(s)
+    }
+
+    /// The pointer as a string, as it was passed to `new`
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Borrows the pointer to iterate over its segments
+    #[must_use]
+    pub fn as_pointer(&self) -> Pointer<'_> {
+        Pointer::from(self)
+    }
+
+    /// The unescaped reference tokens of the pointer
+    pub(crate) fn tokens(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.0.split('/').skip(1).map(|t| {
+            if t.contains('~') {
+                // `~1` has to be replaced first so `~01` becomes `~1`
+                Cow::Owned(t.replace("~1", "/").replace("~0", "~"))
+            } else {
+                Cow::Borrowed(t)
+            }
+        })
+    }
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Appends a reference token to a pointer, escaping `~` and `/`
+pub(crate) fn push_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    for c in token.chars() {
+        match c {
+            '~' => pointer.push_str("~0"),
+            '/' => pointer.push_str("~1"),
+            c => pointer.push(c),
+        }
+    }
+}
+
+/// Parses a reference token as an array index, RFC 6901 doesn't allow
+/// leading zeros and `-` refers to the (non existing) element after the
+/// last one.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    match token.as_bytes() {
+        [b'0'] => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => token.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parses a reference token as an index into an array of `len` elements
+/// for the extended pointer syntax: besides RFC 6901 indexes, `-n` counts
+/// from the end so `-1` is the last element. `-0` and negative indexes
+/// before the start of the array don't refer to an element.
+pub(crate) fn array_index_ext(token: &str, len: usize) -> Option<usize> {
+    match token.strip_prefix('-') {
+        Some(from_end) => match array_index(from_end)? {
+            0 => None,
+            n => len.checked_sub(n),
+        },
+        None => array_index(token),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{array_index, array_index_ext, JsonPointer};
+    use crate::ErrorType;
+    use std::borrow::Cow;
+
+    #[test]
+    fn validation() {
+        assert!(JsonPointer::new("").is_ok());
+        assert!(JsonPointer::new("/").is_ok());
+        assert!(JsonPointer::new("/a~0b~1c").is_ok());
+        let e = JsonPointer::new("a").expect_err("no leading slash");
+        assert_eq!(e.error(), &ErrorType::InvalidPointer);
+        let e = JsonPointer::new("/a~2").expect_err("bad escape");
+        assert_eq!((e.error(), e.index()), (&ErrorType::InvalidPointer, 2));
+        assert!(JsonPointer::new("/a~").is_err());
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens = |s| {
+            JsonPointer::new(s)
+                .expect("valid pointer")
+                .tokens()
+                .map(Cow::into_owned)
+                .collect::<Vec<_>>()
+        };
+        assert!(tokens("").is_empty());
+        assert_eq!(tokens("/"), vec![""]);
+        assert_eq!(tokens("/a/0//~01"), vec!["a", "0", "", "~1"]);
+        assert_eq!(tokens("/m~0n/a~1b"), vec!["m~n", "a/b"]);
+    }
+
+    #[test]
+    fn indexes() {
+        assert_eq!(array_index("0"), Some(0));
+        assert_eq!(array_index("10"), Some(10));
+        assert_eq!(array_index("01"), None);
+        assert_eq!(array_index("-"), None);
+        assert_eq!(array_index("-1"), None);
+        assert_eq!(array_index("1a"), None);
+        assert_eq!(array_index(""), None);
+    }
+
+    #[test]
+    fn indexes_ext() {
+        assert_eq!(array_index_ext("0", 0), Some(0));
+        assert_eq!(array_index_ext("3", 2), Some(3));
+        assert_eq!(array_index_ext("-1", 3), Some(2));
+        assert_eq!(array_index_ext("-3", 3), Some(0));
+        assert_eq!(array_index_ext("-4", 3), None);
+        assert_eq!(array_index_ext("-1", 0), None);
+        assert_eq!(array_index_ext("-0", 3), None);
+        assert_eq!(array_index_ext("-01", 3), None);
+        assert_eq!(array_index_ext("-", 3), None);
+        assert_eq!(array_index_ext("--1", 3), None);
+    }
+}
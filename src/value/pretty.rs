@@ -0,0 +1,315 @@
+use super::float::{write_float, ExponentStyle};
+use std::io::{self, Write};
+use std::ops::Deref;
+use value_trait::generator::BaseGenerator;
+
+/// Options for the configurable pretty printer used by `encode_pp_with`
+/// and `write_pp_with` on both the owned and the borrowed value.
+///
+/// The default options produce the same output as `encode_pp`, with
+/// `compact_threshold` short arrays and objects are kept on one line:
+///
+/// ```rust
+/// use simd_json::{json, PrettyOptions};
+/// let v = json!({"point": [1, 2], "tags": ["a", "b", "c", "d", "e", "f"]});
+/// let opts = PrettyOptions::default().compact_threshold(16);
+/// assert_eq!(
+///     v.encode_pp_with(opts),
+///     "{\n  \"point\": [1, 2],\n  \"tags\": [\n    \"a\",\n    \"b\",\n    \"c\",\n    \"d\",\n    \"e\",\n    \"f\"\n  ]\n}"
+/// );
+/// ```
+///
+/// With `stable_key_order` the output only depends on the content of a
+/// value, which makes it usable for content addressed caching:
+///
+/// ```rust
+/// use simd_json::PrettyOptions;
+/// let opts = PrettyOptions::default().stable_key_order(true);
+/// let mut d = br#"{"b": 1, "a": [2, {"d": 3, "c": 4}]}"#.to_vec();
+/// let v = simd_json::to_owned_value(&mut d).unwrap();
+/// assert_eq!(
+///     v.encode_pp_with(opts.compact_threshold(100)),
+///     r#"{"a": [2, {"c": 4, "d": 3}], "b": 1}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PrettyOptions {
+    /// The number of `indent_char`s per indentation level, defaults
+    /// to 2.
+    pub indent: usize,
+    /// The character indentation is made of, defaults to a space. JSON
+    /// only allows spaces, tabs and line breaks as whitespace, any other
+    /// character makes the output invalid JSON.
+    pub indent_char: char,
+    /// Arrays and objects that take at most this many bytes when written
+    /// on a single line (as `[1, 2]` or `{"a": 1}`) are not expanded,
+    /// `0` expands every non empty container. Defaults to 0.
+    pub compact_threshold: usize,
+    /// Writes object keys in lexicographic byte order instead of the
+    /// order of the map, so equal values always produce the same output
+    /// no matter how their maps were built. The values themselves are
+    /// not changed. Defaults to false.
+    pub stable_key_order: bool,
+    /// How exponents of floats are written, see `ExponentStyle`.
+    /// Defaults to `ExponentStyle::LowercaseNoPlus`.
+    pub exponent_style: ExponentStyle,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            indent_char: ' ',
+            compact_threshold: 0,
+            stable_key_order: false,
+            exponent_style: ExponentStyle::LowercaseNoPlus,
+        }
+    }
+}
+
+impl PrettyOptions {
+    /// Sets the number of characters per indentation level
+    #[must_use]
+    pub const fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the character used for indentation, usually `' '` or `'\t'`
+    #[must_use]
+    pub const fn indent_char(mut self, indent_char: char) -> Self {
+        self.indent_char = indent_char;
+        self
+    }
+
+    /// Sets the width in bytes up to which containers are kept on a
+    /// single line
+    #[must_use]
+    pub const fn compact_threshold(mut self, compact_threshold: usize) -> Self {
+        self.compact_threshold = compact_threshold;
+        self
+    }
+
+    /// Sets whether object keys are written in sorted order
+    #[must_use]
+    pub const fn stable_key_order(mut self, stable_key_order: bool) -> Self {
+        self.stable_key_order = stable_key_order;
+        self
+    }
+
+    /// Sets how exponents of floats are written
+    #[must_use]
+    pub const fn exponent_style(mut self, exponent_style: ExponentStyle) -> Self {
+        self.exponent_style = exponent_style;
+        self
+    }
+}
+
+/// Options for `encode_with` and `write_with` on both the owned and the
+/// borrowed value and for the serde `to_*_with_opts` and
+/// `to_*_pretty_with_opts` functions. The default options produce the
+/// same output as `encode`, `to_vec` and `to_vec_pretty`.
+///
+/// ```rust
+/// use simd_json::{json, ExponentStyle, SerializeOptions};
+/// let v = json!({"b": 1e-7, "a": 2.5});
+/// let opts = SerializeOptions::default()
+///     .stable_key_order(true)
+///     .exponent_style(ExponentStyle::Uppercase);
+/// assert_eq!(v.encode_with(opts), r#"{"a":2.5,"b":1E-7}"#);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct SerializeOptions {
+    /// How exponents of floats are written, see `ExponentStyle`.
+    pub exponent_style: ExponentStyle,
+    /// Writes object keys in lexicographic byte order, like
+    /// `PrettyOptions::stable_key_order`. The serde serializer sorts the
+    /// entries of maps and structs, they are serialized one by one and
+    /// buffered until the object ends. Defaults to false.
+    pub stable_key_order: bool,
+}
+
+impl SerializeOptions {
+    /// Sets how exponents of floats are written
+    #[must_use]
+    pub const fn exponent_style(mut self, exponent_style: ExponentStyle) -> Self {
+        self.exponent_style = exponent_style;
+        self
+    }
+
+    /// Sets whether object keys are written in sorted order
+    #[must_use]
+    pub const fn stable_key_order(mut self, stable_key_order: bool) -> Self {
+        self.stable_key_order = stable_key_order;
+        self
+    }
+}
+
+/// The entries of an object in the order they are written, see
+/// `entries`
+pub(crate) enum Entries<I: Iterator> {
+    /// The order of the map
+    Unsorted(I),
+    /// Sorted by key
+    Sorted(std::vec::IntoIter<I::Item>),
+}
+
+impl<I: Iterator> Iterator for Entries<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Unsorted(iter) => iter.next(),
+            Self::Sorted(iter) => iter.next(),
+        }
+    }
+}
+
+/// The entries of an object in the order they are written, references to
+/// them are only collected and sorted for a stable key order
+pub(crate) fn entries<'o, K, V, I>(iter: I, stable_key_order: bool) -> Entries<I>
+where
+    I: Iterator<Item = (&'o K, &'o V)>,
+    K: Deref<Target = str> + 'o,
+    V: 'o,
+{
+    if stable_key_order {
+        let mut entries: Vec<_> = iter.collect();
+        entries.sort_by(|(k1, _), (k2, _)| k1.as_bytes().cmp(k2.as_bytes()));
+        Entries::Sorted(entries.into_iter())
+    } else {
+        Entries::Unsorted(iter)
+    }
+}
+
+/// A writer that fails once more than `limit` bytes are written to it,
+/// used to find out if a container fits on a single line without
+/// writing all of it.
+pub(crate) struct LimitedWriter {
+    buf: Vec<u8>,
+    limit: usize,
+}
+
+impl LimitedWriter {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(limit),
+            limit,
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + data.len() > self.limit {
+            return Err(io::Error::new(io::ErrorKind::Other, "limit exceeded"));
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The generator behind `PrettyOptions`, writing values is implemented
+/// per value type in their `serialize` modules.
+pub(crate) struct OptionsGenerator<W> {
+    writer: W,
+    dent: usize,
+    pub(crate) opts: PrettyOptions,
+}
+
+impl<W: Write> OptionsGenerator<W> {
+    pub(crate) fn new(writer: W, opts: PrettyOptions) -> Self {
+        Self {
+            writer,
+            dent: 0,
+            opts,
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> BaseGenerator for OptionsGenerator<W> {
+    type T = W;
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    #[inline]
+    fn write_min(&mut self, slice: &[u8], _: u8) -> io::Result<()> {
+        self.writer.write_all(slice)
+    }
+
+    #[inline]
+    fn write_float(&mut self, num: f64) -> io::Result<()> {
+        write_float(&mut self.writer, num, self.opts.exponent_style)
+    }
+
+    fn new_line(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\n")?;
+        let mut buf = [0; 4];
+        let indent_char = self.opts.indent_char.encode_utf8(&mut buf).as_bytes();
+        for _ in 0..(self.dent * self.opts.indent) {
+            self.writer.write_all(indent_char)?;
+        }
+        Ok(())
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn dedent(&mut self) {
+        self.dent -= 1;
+    }
+}
+
+/// The generator behind `SerializeOptions` for values, writing values is
+/// implemented per value type in their `serialize` modules.
+pub(crate) struct CompactGenerator<W> {
+    writer: W,
+    pub(crate) opts: SerializeOptions,
+}
+
+impl<W: Write> CompactGenerator<W> {
+    pub(crate) fn new(writer: W, opts: SerializeOptions) -> Self {
+        Self { writer, opts }
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> BaseGenerator for CompactGenerator<W> {
+    type T = W;
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    #[inline]
+    fn write_min(&mut self, _: &[u8], min: u8) -> io::Result<()> {
+        self.writer.write_all(&[min])
+    }
+
+    #[inline]
+    fn write_float(&mut self, num: f64) -> io::Result<()> {
+        write_float(&mut self.writer, num, self.opts.exponent_style)
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+/// The string redacted values are replaced with
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// A precompiled set of object keys, used to redact values with
+/// `redact_keys` on both the owned and the borrowed value.
+///
+/// Case insensitive matching only folds ASCII characters.
+///
+/// ```rust
+/// use simd_json::{json, KeyMatcher};
+/// let mut v = json!({"user": "ferris", "Password": "hunter2", "tokens": [{"token": 42}]});
+/// v.redact_keys(&KeyMatcher::case_insensitive(["password", "token"]));
+/// assert_eq!(v["Password"], "[REDACTED]");
+/// assert_eq!(v["tokens"][0]["token"], "[REDACTED]");
+/// assert_eq!(v["user"], "ferris");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMatcher {
+    keys: HashSet<Box<str>>,
+    case_insensitive: bool,
+    max_len: usize,
+}
+
+impl KeyMatcher {
+    /// Creates a matcher that matches keys exactly
+    #[must_use]
+    pub fn new<I, S>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::build(keys, false)
+    }
+
+    /// Creates a matcher that ignores ASCII case when matching keys
+    #[must_use]
+    pub fn case_insensitive<I, S>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::build(keys, true)
+    }
+
+    fn build<I, S>(keys: I, case_insensitive: bool) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let keys: HashSet<Box<str>> = keys
+            .into_iter()
+            .map(|k| {
+                let k = k.as_ref();
+                if case_insensitive {
+                    k.to_ascii_lowercase().into_boxed_str()
+                } else {
+                    k.into()
+                }
+            })
+            .collect();
+        let max_len = keys.iter().map(|k| k.len()).max().unwrap_or_default();
+        Self {
+            keys,
+            case_insensitive,
+            max_len,
+        }
+    }
+
+    /// Tests if a key is part of the set
+    #[must_use]
+    pub fn is_match(&self, key: &str) -> bool {
+        // keys longer then any key in the set never match, this also
+        // saves us from lowercasing long keys
+        if key.len() > self.max_len {
+            return false;
+        }
+        if self.case_insensitive && key.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.keys.contains(key.to_ascii_lowercase().as_str())
+        } else {
+            self.keys.contains(key)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::KeyMatcher;
+
+    #[test]
+    fn exact() {
+        let m = KeyMatcher::new(["password", "Token"]);
+        assert!(m.is_match("password"));
+        assert!(m.is_match("Token"));
+        assert!(!m.is_match("Password"));
+        assert!(!m.is_match("token"));
+        assert!(!m.is_match("passwords"));
+        assert!(!KeyMatcher::new(Vec::<String>::new()).is_match(""));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let m = KeyMatcher::case_insensitive(["password", "Token"]);
+        assert!(m.is_match("password"));
+        assert!(m.is_match("PassWord"));
+        assert!(m.is_match("token"));
+        assert!(m.is_match("TOKEN"));
+        assert!(!m.is_match("tokens"));
+    }
+}
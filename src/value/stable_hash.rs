@@ -0,0 +1,123 @@
+/// SipHash-2-4 with a fixed key, used for hashes that have to be the same
+/// across runs, platforms and Rust versions, unlike those of the std
+/// `DefaultHasher`. Integers have to be written in a fixed byte order by
+/// the caller.
+pub(crate) struct StableHasher {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    /// Bytes written that don't fill a word yet, little endian
+    tail: u64,
+    ntail: usize,
+    len: usize,
+}
+
+/// The key of the reference test vectors, the bytes 0 to 15
+const K0: u64 = 0x0706_0504_0302_0100;
+const K1: u64 = 0x0f0e_0d0c_0b0a_0908;
+
+impl StableHasher {
+    pub(crate) fn new() -> Self {
+        Self {
+            v0: K0 ^ 0x736f_6d65_7073_6575,
+            v1: K1 ^ 0x646f_7261_6e64_6f6d,
+            v2: K0 ^ 0x6c79_6765_6e65_7261,
+            v3: K1 ^ 0x7465_6462_7974_6573,
+            tail: 0,
+            ntail: 0,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13) ^ self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16) ^ self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21) ^ self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17) ^ self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+
+    #[inline]
+    fn compress(&mut self, m: u64) {
+        self.v3 ^= m;
+        self.round();
+        self.round();
+        self.v0 ^= m;
+    }
+
+    pub(crate) fn write(&mut self, mut bytes: &[u8]) {
+        self.len += bytes.len();
+        while self.ntail > 0 && !bytes.is_empty() {
+            self.push(bytes[0]);
+            bytes = &bytes[1..];
+        }
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            let mut buf = [0; 8];
+            buf.copy_from_slice(word);
+            self.compress(u64::from_le_bytes(buf));
+        }
+        for b in words.remainder() {
+            self.push(*b);
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, b: u8) {
+        self.tail |= u64::from(b) << (8 * self.ntail);
+        self.ntail += 1;
+        if self.ntail == 8 {
+            self.compress(self.tail);
+            self.tail = 0;
+            self.ntail = 0;
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn finish(mut self) -> u64 {
+        // only the lowest byte of the length is used
+        let b = (u64::from(self.len as u8) << 56) | self.tail;
+        self.compress(b);
+        self.v2 ^= 0xff;
+        self.round();
+        self.round();
+        self.round();
+        self.round();
+        self.v0 ^ self.v1 ^ self.v2 ^ self.v3
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StableHasher;
+
+    fn hash(chunks: &[&[u8]]) -> u64 {
+        let mut h = StableHasher::new();
+        for c in chunks {
+            h.write(c);
+        }
+        h.finish()
+    }
+
+    #[test]
+    fn reference_vectors() {
+        let input: Vec<u8> = (0..63).collect();
+        assert_eq!(hash(&[]), 0x726f_db47_dd0e_0e31);
+        assert_eq!(hash(&[&input[..1]]), 0x74f8_39c5_93dc_67fd);
+        assert_eq!(hash(&[&input[..8]]), 0x93f5_f579_9a93_2462);
+        assert_eq!(hash(&[&input[..15]]), 0xa129_ca61_49be_45e5);
+        assert_eq!(hash(&[&input]), 0x958a_324c_eb06_4572);
+        // the result doesn't depend on how the input is split up
+        assert_eq!(
+            hash(&[&input[..3], &input[3..4], &input[4..20], &input[20..]]),
+            0x958a_324c_eb06_4572
+        );
+    }
+}
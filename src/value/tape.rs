@@ -23,6 +23,14 @@ pub enum Node<'input> {
     Static(StaticNode),
 }
 
+/// The index of the next node after the value at `i`
+pub(crate) fn skip(tape: &[Node], i: usize) -> usize {
+    match tape[i] {
+        Node::Array(_, end) | Node::Object(_, end) => end,
+        Node::String(_) | Node::Static(_) => i + 1,
+    }
+}
+
 #[cfg(test)]
 mod test {
     #![allow(clippy::cognitive_complexity)]
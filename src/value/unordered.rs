@@ -0,0 +1,88 @@
+/// Checks if `a` and `b` contain the same elements regardless of their
+/// order, each element of `a` has to be equal to a distinct element of
+/// `b` according to `eq`.
+///
+/// `eq` doesn't have to be transitive, approximate float equality isn't,
+/// so elements can't simply be sorted or hashed. Instead the pairs of
+/// equal elements are collected and a perfect matching between them is
+/// searched for with augmenting paths, an element that was matched
+/// greedily is moved to another partner when that frees up a match. This
+/// takes up to `a.len() * b.len()` calls to `eq`, arrays that are already
+/// equal in order are recognized with `a.len()` calls.
+pub(crate) fn multiset_eq<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    if a.iter().zip(b).all(|(x, y)| eq(x, y)) {
+        return true;
+    }
+    // the elements of `b` each element of `a` is equal to
+    let mut candidates = Vec::with_capacity(a.len());
+    for x in a {
+        let c: Vec<usize> = b
+            .iter()
+            .enumerate()
+            .filter(|(_, y)| eq(x, y))
+            .map(|(j, _)| j)
+            .collect();
+        if c.is_empty() {
+            return false;
+        }
+        candidates.push(c);
+    }
+    // the element of `a` each element of `b` is matched with
+    let mut matched = vec![None; b.len()];
+    for i in 0..a.len() {
+        let mut visited = vec![false; b.len()];
+        if !augment(i, &candidates, &mut matched, &mut visited) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Tries to match `i` with one of its candidates, moving the element
+/// a candidate is already matched with to another one if needed
+fn augment(
+    i: usize,
+    candidates: &[Vec<usize>],
+    matched: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &j in &candidates[i] {
+        if visited[j] {
+            continue;
+        }
+        visited[j] = true;
+        let free = match matched[j] {
+            None => true,
+            Some(k) => augment(k, candidates, matched, visited),
+        };
+        if free {
+            matched[j] = Some(i);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::multiset_eq;
+
+    #[test]
+    fn matching() {
+        let eq = |a: &i32, b: &i32| a == b;
+        assert!(multiset_eq::<i32>(&[], &[], eq));
+        assert!(multiset_eq(&[1, 2, 2, 3], &[2, 3, 1, 2], eq));
+        assert!(!multiset_eq(&[1, 2, 2, 3], &[1, 2, 3, 3], eq));
+        assert!(!multiset_eq(&[1, 2], &[1, 2, 2], eq));
+
+        // `0` is close to everything, `3` only to `2` and `4`: matching
+        // `0` to the first element it is close to, `2`, leaves nothing
+        // for `3` unless `0` is moved on
+        let close = |a: &i32, b: &i32| (a - b).abs() <= 1 || *a == 0 || *b == 0;
+        assert!(multiset_eq(&[0, 3], &[2, 9], close));
+        assert!(!multiset_eq(&[7, 3], &[2, 9], close));
+    }
+}
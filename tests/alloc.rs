@@ -32,3 +32,51 @@ test!(marine_ik, 5, 1, 4);
 test!(twitter, 5, 0, 4);
 test!(twitterescaped, 5, 0, 4);
 test!(numbers, 5, 0, 4);
+
+#[cfg(all(feature = "alloc", feature = "serde_impl"))]
+mod strings {
+    use alloc_counter::count_alloc;
+    use serde::Deserialize;
+    use simd_json::Deserializer;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[derive(Deserialize)]
+    struct Strings {
+        string: String,
+        boxed: Box<str>,
+        #[serde(deserialize_with = "simd_json::serde::deserialize_arc_str")]
+        arc: Arc<str>,
+        #[serde(deserialize_with = "simd_json::serde::deserialize_rc_str")]
+        rc: Rc<str>,
+    }
+
+    /// Deserializes `Strings` from an already parsed document and returns
+    /// the number of allocations it took
+    fn allocations(json: &str, expected: [&str; 4]) -> usize {
+        let mut d = json.as_bytes().to_vec();
+        let mut de = Deserializer::from_slice(&mut d).unwrap();
+        let (count, s) = count_alloc(|| Strings::deserialize(&mut de).unwrap());
+        assert_eq!(count.1, 0, "no reallocations");
+        assert_eq!([&*s.string, &*s.boxed, &*s.arc, &*s.rc], expected);
+        count.0
+    }
+
+    #[test]
+    fn one_allocation_per_string() {
+        assert_eq!(
+            allocations(
+                r#"{"string": "a", "boxed": "b", "arc": "c", "rc": "d"}"#,
+                ["a", "b", "c", "d"]
+            ),
+            4
+        );
+        assert_eq!(
+            allocations(
+                r#"{"string": "a\t", "boxed": "b\"", "arc": "c\u00e9", "rc": "d\n"}"#,
+                ["a\t", "b\"", "c\u{e9}", "d\n"]
+            ),
+            4
+        );
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(feature = "alloc")]
+use alloc_counter::{count_alloc, AllocCounterSystem};
+#[cfg(feature = "alloc")]
+use simd_json::{to_owned_value, Batch};
+
+#[cfg(feature = "alloc")]
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+/// Documents of the same length, every fifth one is invalid
+#[cfg(feature = "alloc")]
+fn documents(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| {
+            if i % 5 == 4 {
+                format!(r#"{{"id": {i:04}, "name": "doc{i:04}", "ok": tru}}"#).into_bytes()
+            } else {
+                format!(r#"{{"id": {i:04}, "name": "doc{i:04}", "ok": true}}"#).into_bytes()
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn buffers_are_reused() {
+    let n = 1000;
+    let mut plain_docs = documents(n);
+    let ((plain_allocations, _, _), plain) = count_alloc(|| {
+        plain_docs
+            .iter_mut()
+            .map(|d| to_owned_value(d.as_mut_slice()))
+            .collect::<Vec<_>>()
+    });
+    let mut batch_docs = documents(n);
+    let mut batch = Batch::new();
+    let ((batch_allocations, _, _), res) =
+        count_alloc(|| batch.to_owned_values(batch_docs.iter_mut().map(Vec::as_mut_slice)));
+
+    assert_eq!(res.values.len(), n / 5 * 4);
+    assert_eq!(res.errors.len(), n / 5);
+    let mut errors = res.errors.iter();
+    let mut values = res.values.iter();
+    for (i, plain) in plain.into_iter().enumerate() {
+        match plain {
+            Ok(v) => assert_eq!(values.next().map(|(j, v)| (*j, v)), Some((i, &v))),
+            Err(e) => {
+                let (j, batch_e) = errors.next().expect("an error");
+                assert_eq!((*j, batch_e), (i, &e));
+            }
+        }
+    }
+
+    // the input and string buffers are allocated once instead of once
+    // per document, the growing error vector takes back a few of them
+    assert!(
+        batch_allocations + n <= plain_allocations,
+        "{batch_allocations} vs {plain_allocations}"
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "big-document-tests")]
+//! Inputs larger than `MAX_INPUT_LEN`, they are generated in memory so
+//! these tests need more than 4 GiB of RAM and are ignored by default:
+//!
+//! `cargo test --release --features big-document-tests -- --ignored`
+use alloc_counter::{count_alloc, AllocCounterSystem};
+use simd_json::{
+    first_document_end, split_array, tape_node_count, to_borrowed_value, to_owned_value, to_tape,
+    Documents, ErrorType, MAX_INPUT_LEN,
+};
+
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+/// Collects the chunks of a generator into a buffer of exactly `len`
+/// bytes, without a fixture on disk
+fn generate(len: usize, chunks: impl Iterator<Item = Vec<u8>>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len);
+    for chunk in chunks {
+        out.extend_from_slice(&chunk);
+    }
+    assert_eq!(out.len(), len);
+    out
+}
+
+/// An array of `n` zeros, `2 * n + 1` bytes long, in chunks of up to
+/// 2 MiB
+fn zeros(n: usize) -> impl Iterator<Item = Vec<u8>> {
+    const CHUNK: usize = 1 << 20;
+    let rest = n - 1;
+    let chunks = (0..rest)
+        .step_by(CHUNK)
+        .map(move |i| b",0".repeat(CHUNK.min(rest - i)));
+    std::iter::once(b"[0".to_vec())
+        .chain(chunks)
+        .chain(std::iter::once(b"]".to_vec()))
+}
+
+#[test]
+#[ignore = "needs more than 4 GiB of memory"]
+fn oversized_input_is_rejected_up_front() {
+    // the smallest array of zeros that is too large
+    let n = MAX_INPUT_LEN / 2 + 1;
+    let len = 2 * n + 1;
+    assert!(len > MAX_INPUT_LEN);
+    let mut input = generate(len, zeros(n));
+
+    let errors = vec![
+        count_alloc(|| to_owned_value(&mut input).map(|_| ())),
+        count_alloc(|| to_borrowed_value(&mut input).map(|_| ())),
+        count_alloc(|| to_tape(&mut input).map(|_| ())),
+        count_alloc(|| simd_json::from_slice::<Vec<u8>>(&mut input).map(|_| ())),
+        count_alloc(|| tape_node_count(&input).map(|_| ())),
+        count_alloc(|| first_document_end(&input).map(|_| ())),
+        count_alloc(|| split_array(&input).map(|_| ())),
+    ];
+    for ((allocations, reallocations, _), e) in errors {
+        let e = e.expect_err("input too large");
+        assert_eq!(e.error(), &ErrorType::InputTooLarge);
+        // nothing was allocated before failing
+        assert_eq!((allocations, reallocations), (0, 0));
+    }
+}
+
+#[test]
+#[ignore = "needs more than 4 GiB of memory"]
+fn large_streams_are_split_into_documents() {
+    // lines of a million zeros, 2 MiB each, add up to more than 4 GiB
+    let n = 1 << 20;
+    let line = 2 * n + 1 + 1;
+    let lines = MAX_INPUT_LEN / line + 1;
+    let input = generate(
+        lines * line,
+        (0..lines).flat_map(|_| zeros(n).chain(std::iter::once(b"\n".to_vec()))),
+    );
+    assert!(input.len() > MAX_INPUT_LEN);
+
+    let mut docs = 0;
+    for doc in Documents::ndjson(&input) {
+        let doc = doc.expect("valid document");
+        assert_eq!(doc[n - 1], 0);
+        docs += 1;
+    }
+    assert_eq!(docs, lines);
+}
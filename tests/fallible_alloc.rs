@@ -0,0 +1,94 @@
+use simd_json::{to_owned_value, try_to_owned_value, ErrorType};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Fails allocations on the capped thread once the allocated bytes
+/// would exceed `LIMIT`
+struct Capped;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+thread_local! {
+    static CAPPED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn exceeds(size: usize) -> bool {
+    CAPPED.with(Cell::get) && CURRENT.load(Ordering::SeqCst) + size > LIMIT.load(Ordering::SeqCst)
+}
+
+unsafe impl GlobalAlloc for Capped {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if exceeds(layout.size()) {
+            return std::ptr::null_mut();
+        }
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            CURRENT.fetch_add(layout.size(), Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() && exceeds(new_size - layout.size()) {
+            return std::ptr::null_mut();
+        }
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+            CURRENT.fetch_add(new_size, Ordering::SeqCst);
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static A: Capped = Capped;
+
+#[test]
+fn allocation_failures_are_errors() {
+    let values: Vec<String> = (0..1000).map(|i| format!("\"value {i}\"")).collect();
+    let input = format!("[{}]", values.join(",")).into_bytes();
+    let expected = to_owned_value(&mut input.clone()).expect("valid json");
+
+    let mut failed_sizes = Vec::new();
+    let mut cap = 0;
+    let parsed = loop {
+        let mut d = input.clone();
+        LIMIT.store(CURRENT.load(Ordering::SeqCst) + cap, Ordering::SeqCst);
+        CAPPED.with(|c| c.set(true));
+        let res = try_to_owned_value(&mut d);
+        CAPPED.with(|c| c.set(false));
+        match res {
+            Ok(v) => break v,
+            Err(e) => match e.error() {
+                ErrorType::AllocationFailed(size) => failed_sizes.push(*size),
+                e => panic!("unexpected error {e:?} with a cap of {cap} bytes"),
+            },
+        }
+        cap += 512;
+        assert!(cap < input.len() * 64, "parsing never succeeded");
+    };
+    assert_eq!(parsed, expected);
+
+    // the parser's buffers and the tape failed at some cap, the values
+    // fit into the memory the parser frees before they are built
+    failed_sizes.dedup();
+    assert!(failed_sizes.len() > 3, "{failed_sizes:?}");
+    assert!(failed_sizes.iter().any(|size| *size > input.len()));
+}
+
+#[cfg(feature = "serde_impl")]
+#[test]
+fn try_from_slice() {
+    let mut d = br#"{"a": [1, 2]}"#.to_vec();
+    let v: std::collections::HashMap<String, Vec<u8>> =
+        simd_json::try_from_slice(&mut d).expect("valid json");
+    assert_eq!(v["a"], vec![1, 2]);
+}
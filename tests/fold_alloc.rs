@@ -0,0 +1,103 @@
+#![cfg(all(feature = "alloc", feature = "serde_impl"))]
+use alloc_counter::{count_alloc, AllocCounterSystem};
+use serde_json::{Map, Value};
+use simd_json::cow::Cow;
+use simd_json::{to_owned_value, OwnedValue, StaticNode, ValueFolder};
+
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+fn scalar(node: StaticNode) -> Value {
+    match node {
+        StaticNode::Null => Value::Null,
+        StaticNode::Bool(b) => Value::Bool(b),
+        StaticNode::I64(n) => n.into(),
+        StaticNode::U64(n) => n.into(),
+        StaticNode::F64(n) => n.into(),
+        // numbers beyond 64 bit don't fit a `serde_json::Number`
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(n) => {
+            i64::try_from(n).map_or_else(|_| Value::String(n.to_string()), Value::from)
+        }
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(n) => {
+            u64::try_from(n).map_or_else(|_| Value::String(n.to_string()), Value::from)
+        }
+    }
+}
+
+/// Builds a `serde_json::Value`, moving strings and keys
+struct ToSerdeJson;
+
+impl<'value> ValueFolder<'value> for ToSerdeJson {
+    type Output = Value;
+
+    fn scalar(&mut self, node: StaticNode) -> Value {
+        scalar(node)
+    }
+
+    fn string(&mut self, s: Cow<'value, str>) -> Value {
+        Value::String(s.into_owned())
+    }
+
+    fn array(&mut self, elements: Vec<Value>) -> Value {
+        Value::Array(elements)
+    }
+
+    fn object(&mut self, members: Vec<(Cow<'value, str>, Value)>) -> Value {
+        Value::Object(
+            members
+                .into_iter()
+                .map(|(k, v)| (k.into_owned(), v))
+                .collect(),
+        )
+    }
+}
+
+/// Builds a `serde_json::Value` from a reference, cloning strings and
+/// keys
+fn clone_walk(v: &OwnedValue) -> Value {
+    match v {
+        OwnedValue::Static(s) => scalar(*s),
+        OwnedValue::String(s) => Value::String(s.clone()),
+        OwnedValue::Array(a) => Value::Array(a.iter().map(clone_walk).collect()),
+        OwnedValue::Object(o) => {
+            let map: Map<String, Value> =
+                o.iter().map(|(k, v)| (k.clone(), clone_walk(v))).collect();
+            Value::Object(map)
+        }
+    }
+}
+
+/// An array of records with a handful of strings each
+fn fixture(n: usize) -> OwnedValue {
+    let records: Vec<String> = (0..n)
+        .map(|i| {
+            format!(
+                r#"{{"id": {i}, "name": "user{i}", "email": "user{i}@example.com", "tags": ["a{i}", "b{i}"], "score": {}.5, "active": {}}}"#,
+                i % 100,
+                i % 2 == 0
+            )
+        })
+        .collect();
+    let mut d = format!("[{}]", records.join(",")).into_bytes();
+    to_owned_value(&mut d).expect("valid json")
+}
+
+#[test]
+fn fold_moves_strings() {
+    let n = 1000;
+    let v = fixture(n);
+    let ((clone_allocations, _, _), walked) = count_alloc(|| clone_walk(&v));
+    let ((fold_allocations, _, _), folded) = count_alloc(|| v.fold(&mut ToSerdeJson));
+    assert_eq!(folded, walked);
+
+    // every record has 6 keys and 4 strings that are moved instead of
+    // cloned, folding only allocates one more vector per record for the
+    // members of its object
+    assert!(fold_allocations < clone_allocations);
+    assert!(
+        fold_allocations + n * 9 <= clone_allocations,
+        "{fold_allocations} vs {clone_allocations}"
+    );
+}
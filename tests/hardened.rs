@@ -0,0 +1,93 @@
+#![cfg(feature = "alloc")]
+use alloc_counter::{count_alloc, AllocCounterSystem};
+use simd_json::{to_owned_value_with_opts, Error, ErrorType, ParseOptions};
+
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+const MAX: usize = 1 << 20;
+
+/// Parses `input` with the hardened profile and returns the error and
+/// the number of allocations
+fn parse(mut input: Vec<u8>) -> (Error, usize) {
+    let ((allocations, _, _), res) =
+        count_alloc(|| to_owned_value_with_opts(&mut input, ParseOptions::hardened(MAX)));
+    let e = res.expect_err("the document violates a limit");
+    // parsing stops before any value is built, only the parser's own
+    // buffers are allocated
+    assert!(allocations <= 8, "{allocations} allocations");
+    (e, allocations)
+}
+
+fn join(parts: impl Iterator<Item = String>, open: &str, close: &str) -> Vec<u8> {
+    format!("{open}{}{close}", parts.collect::<Vec<_>>().join(",")).into_bytes()
+}
+
+#[test]
+fn adversarial_documents() {
+    // too long, rejected before anything is allocated
+    let (e, allocations) = parse(vec![b' '; MAX + 1]);
+    assert_eq!(
+        e.error(),
+        &ErrorType::InputLengthLimit {
+            limit: MAX,
+            actual: MAX + 1
+        }
+    );
+    assert_eq!(allocations, 0);
+
+    // deep nesting
+    let deep = format!("{}{}", "[".repeat(400_000), "]".repeat(400_000));
+    let (e, _) = parse(deep.into_bytes());
+    assert_eq!(
+        e.error(),
+        &ErrorType::DepthLimit {
+            limit: 64,
+            actual: 65
+        }
+    );
+
+    // the same huge string over and over
+    let huge = format!("\"{}\"", "x".repeat(1000));
+    let (e, _) = parse(join(std::iter::repeat(huge).take(600), "[", "]"));
+    assert_eq!(
+        e.error(),
+        &ErrorType::StringBytesLimit {
+            limit: MAX / 2,
+            actual: 525_000
+        }
+    );
+
+    // an enormous single object
+    let entries = (0..50_000).map(|i| format!("\"k{i}\":{i}"));
+    let (e, _) = parse(join(entries, "{", "}"));
+    assert_eq!(
+        e.error(),
+        &ErrorType::ObjectEntriesLimit {
+            limit: MAX / 64,
+            actual: MAX / 64 + 1
+        }
+    );
+
+    // an enormous single array
+    let elements = std::iter::repeat("0".to_string()).take(200_000);
+    let (e, _) = parse(join(elements, "[", "]"));
+    assert_eq!(
+        e.error(),
+        &ErrorType::ArrayLengthLimit {
+            limit: MAX / 16,
+            actual: MAX / 16 + 1
+        }
+    );
+
+    // many small values that stay under the array limit
+    let pairs = std::iter::repeat("[0,0]".to_string()).take(100_000);
+    let (e, _) = parse(join(pairs, "[", "]"));
+    assert_eq!(
+        e.error(),
+        &ErrorType::TapeNodesLimit {
+            limit: MAX / 8,
+            actual: MAX / 8 + 1
+        }
+    );
+}
@@ -0,0 +1,52 @@
+#![cfg(all(feature = "alloc", feature = "serde_impl"))]
+use alloc_counter::{count_alloc, AllocCounterSystem};
+use simd_json::serde::{from_slice_seed, Interned};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+#[test]
+fn one_allocation_per_distinct_string() {
+    let values = ["ACTIVE", "INACTIVE", "EU-WEST-1", "US-EAST-2"];
+    let records: Vec<String> = (0..2000)
+        .map(|i| format!("\"{}\"", values[i % values.len()]))
+        .collect();
+    let input = format!("[{}]", records.join(",")).into_bytes();
+
+    let mut pool: HashSet<Arc<str>> = HashSet::with_capacity(16);
+    let mut d = input.clone();
+    let ((interned_allocations, _, _), interned) = count_alloc(|| {
+        from_slice_seed(&mut d, Interned::<Vec<Arc<str>>>::new(&mut pool)).expect("valid json")
+    });
+    let mut d = input.clone();
+    let ((plain_allocations, _, _), plain) =
+        count_alloc(|| simd_json::from_slice::<Vec<String>>(&mut d).expect("valid json"));
+
+    assert!(interned
+        .iter()
+        .map(|s| &**s)
+        .eq(plain.iter().map(String::as_str)));
+    assert_eq!(pool.len(), values.len());
+    for (i, s) in interned.iter().enumerate() {
+        assert!(Arc::ptr_eq(s, &interned[i % values.len()]));
+    }
+    // both parse into the same buffers and vector, only the strings
+    // differ, every `String` is allocated
+    assert!(plain_allocations >= 2000, "{plain_allocations}");
+    assert!(
+        interned_allocations + 2000 - values.len() <= plain_allocations,
+        "{interned_allocations} vs {plain_allocations}"
+    );
+
+    // with a warm pool no string is allocated
+    let mut d = input.clone();
+    let ((warm_allocations, _, _), _) = count_alloc(|| {
+        from_slice_seed(&mut d, Interned::<Vec<Arc<str>>>::new(&mut pool)).expect("valid json")
+    });
+    assert!(
+        warm_allocations + values.len() <= interned_allocations,
+        "{warm_allocations} vs {interned_allocations}"
+    );
+}
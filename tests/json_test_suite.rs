@@ -0,0 +1,472 @@
+//! Conformance against the parsing cases of
+//! [JSONTestSuite](https://github.com/nst/JSONTestSuite).
+//!
+//! The suite sorts its cases by prefix:
+//!
+//! * `y_` cases are valid JSON and have to be accepted
+//! * `n_` cases are invalid JSON and have to be rejected
+//! * `i_` cases are left to the implementation by RFC 8259
+//!
+//! Our policy for `i_` cases is to reject what we can't represent
+//! exactly instead of guessing:
+//!
+//! * strings have to be valid UTF-8 and escapes may not encode lone or
+//!   inverted surrogates, inputs in UTF-16 and byte order marks are
+//!   rejected
+//! * integers have to fit into an `i64` or `u64` (`i128` or `u128` with
+//!   the `128bit` feature), they are not turned into floats
+//! * floats that overflow to infinity are rejected, floats that underflow
+//!   become `0.0`
+//! * nesting is not limited unless `ParseOptions::max_depth` is set
+//!
+//! The cases below are inlined from the suite's `test_parsing` directory,
+//! their files are kept in `data/JSONTestSuite/test_parsing`. Every file
+//! in that directory is checked as well, so more of the suite's files can
+//! be dropped in there.
+
+use simd_json::{
+    parse_strict, to_borrowed_value, to_owned_value, BorrowedValue, ErrorType, OwnedValue,
+};
+use std::fs;
+
+const Y_CASES: &[(&str, &[u8])] = &[
+    ("y_array_arraysWithSpaces", b"[[]   ]"),
+    ("y_array_empty", b"[]"),
+    ("y_array_empty-string", br#"[""]"#),
+    ("y_array_false", b"[false]"),
+    ("y_array_heterogeneous", br#"[null, 1, "1", {}]"#),
+    ("y_array_null", b"[null]"),
+    ("y_array_with_1_and_newline", b"[1\n]"),
+    ("y_array_with_leading_space", b" [1]"),
+    ("y_array_with_several_null", b"[1,null,null,null,2]"),
+    ("y_array_with_trailing_space", b"[2] "),
+    ("y_number", b"[123e65]"),
+    ("y_number_0e+1", b"[0e+1]"),
+    ("y_number_0e1", b"[0e1]"),
+    ("y_number_after_space", b"[ 4]"),
+    (
+        "y_number_double_close_to_zero",
+        b"[-0.000000000000000000000000000000000000000000000000000000000000000000000000000001]",
+    ),
+    ("y_number_int_with_exp", b"[20e1]"),
+    ("y_number_minus_zero", b"[-0]"),
+    ("y_number_negative_int", b"[-123]"),
+    ("y_number_negative_one", b"[-1]"),
+    ("y_number_real_capital_e", b"[1E22]"),
+    ("y_number_real_capital_e_neg_exp", b"[1E-2]"),
+    ("y_number_real_capital_e_pos_exp", b"[1E+2]"),
+    ("y_number_real_exponent", b"[123e45]"),
+    ("y_number_real_fraction_exponent", b"[123.456e78]"),
+    ("y_number_real_neg_exp", b"[1e-2]"),
+    ("y_number_real_pos_exponent", b"[1e+2]"),
+    ("y_number_simple_int", b"[123]"),
+    ("y_number_simple_real", b"[123.456789]"),
+    ("y_object", br#"{"asd":"sdf", "dfg":"fgh"}"#),
+    ("y_object_basic", br#"{"asd":"sdf"}"#),
+    ("y_object_duplicated_key", br#"{"a":"b","a":"c"}"#),
+    ("y_object_empty", b"{}"),
+    ("y_object_empty_key", br#"{"":0}"#),
+    ("y_object_escaped_null_in_key", br#"{"foo\u0000bar": 42}"#),
+    ("y_object_simple", br#"{"a":[]}"#),
+    ("y_object_with_newlines", b"{\n\"a\": \"b\"\n}"),
+    (
+        "y_string_1_2_3_bytes_UTF-8_sequences",
+        br#"["\u0060\u012a\u12AB"]"#,
+    ),
+    ("y_string_accepted_surrogate_pair", br#"["\uD801\udc37"]"#),
+    (
+        "y_string_accepted_surrogate_pairs",
+        br#"["\ud83d\ude39\ud83d\udc8d"]"#,
+    ),
+    ("y_string_allowed_escapes", br#"["\"\\\/\b\f\n\r\t"]"#),
+    ("y_string_backslash_and_u_escaped_zero", br#"["\\u0000"]"#),
+    ("y_string_backslash_doublequotes", br#"["\""]"#),
+    ("y_string_comments", br#"["a/*b*/c/*d//e"]"#),
+    ("y_string_double_escape_a", br#"["\\a"]"#),
+    ("y_string_escaped_control_character", br#"["\u0012"]"#),
+    ("y_string_escaped_noncharacter", br#"["\uFFFF"]"#),
+    ("y_string_last_surrogates_1_and_2", br#"["\uDBFF\uDFFF"]"#),
+    ("y_string_nbsp_uescaped", br#"["new\u00A0line"]"#),
+    ("y_string_nonCharacterInUTF-8_U+FFFF", b"[\"\xef\xbf\xbf\"]"),
+    ("y_string_null_escape", br#"["\u0000"]"#),
+    ("y_string_pi", "[\"π\"]".as_bytes()),
+    ("y_string_space", br#"" ""#),
+    ("y_string_unicode_U+10FFFE_nonchar", br#"["\uDBFF\uDFFE"]"#),
+    ("y_string_unicode_escaped_double_quote", br#"["\u0022"]"#),
+    ("y_string_utf8", "[\"€𝄞\"]".as_bytes()),
+    ("y_string_with_del_character", b"[\"a\x7fa\"]"),
+    ("y_structure_lonely_false", b"false"),
+    ("y_structure_lonely_int", b"42"),
+    ("y_structure_lonely_negative_real", b"-0.1"),
+    ("y_structure_lonely_null", b"null"),
+    ("y_structure_lonely_string", br#""asd""#),
+    ("y_structure_lonely_true", b"true"),
+    ("y_structure_string_empty", br#""""#),
+    ("y_structure_trailing_newline", b"[\"a\"]\n"),
+    ("y_structure_true_in_array", b"[true]"),
+    ("y_structure_whitespace_array", b" [] "),
+];
+
+const N_CASES: &[(&str, &[u8])] = &[
+    ("n_array_1_true_without_comma", b"[1 true]"),
+    ("n_array_comma_and_number", b"[,1]"),
+    ("n_array_double_comma", b"[1,,2]"),
+    ("n_array_extra_close", br#"["x"]]"#),
+    ("n_array_extra_comma", br#"["",]"#),
+    ("n_array_incomplete", br#"["x""#),
+    ("n_array_inner_array_no_comma", b"[3[4]]"),
+    ("n_array_items_separated_by_semicolon", b"[1:2]"),
+    ("n_array_just_comma", b"[,]"),
+    ("n_array_just_minus", b"[-]"),
+    ("n_array_missing_value", br#"[   , ""]"#),
+    ("n_array_number_and_comma", b"[1,]"),
+    ("n_array_unclosed", br#"["""#),
+    ("n_array_unclosed_trailing_comma", b"[1,"),
+    ("n_incomplete_false", b"[fals]"),
+    ("n_incomplete_null", b"[nul]"),
+    ("n_incomplete_true", b"[tru]"),
+    ("n_number_++", b"[++1234]"),
+    ("n_number_+1", b"[+1]"),
+    ("n_number_-01", b"[-01]"),
+    ("n_number_-1.0.", b"[-1.0.]"),
+    ("n_number_-2.", b"[-2.]"),
+    ("n_number_-NaN", b"[-NaN]"),
+    ("n_number_.-1", b"[.-1]"),
+    ("n_number_.2e-3", b"[.2e-3]"),
+    ("n_number_0.1.2", b"[0.1.2]"),
+    ("n_number_0.3e+", b"[0.3e+]"),
+    ("n_number_0.e1", b"[0.e1]"),
+    ("n_number_0e", b"[0e]"),
+    ("n_number_1.0e", b"[1.0e]"),
+    ("n_number_1_000", b"[1 000.0]"),
+    ("n_number_2.e3", b"[2.e3]"),
+    ("n_number_9.e+", b"[9.e+]"),
+    ("n_number_Inf", b"[Inf]"),
+    ("n_number_NaN", b"[NaN]"),
+    ("n_number_expression", b"[1+2]"),
+    ("n_number_hex_1_digit", b"[0x1]"),
+    ("n_number_infinity", b"[Infinity]"),
+    ("n_number_minus_infinity", b"[-Infinity]"),
+    ("n_number_minus_space_1", b"[- 1]"),
+    ("n_number_neg_int_starting_with_zero", b"[-012]"),
+    ("n_number_real_without_fractional_part", b"[1.]"),
+    ("n_number_starting_with_dot", b"[.123]"),
+    ("n_number_with_alpha", b"[1.2a-3]"),
+    ("n_number_with_leading_zero", b"[012]"),
+    ("n_object_bad_value", br#"["x", truth]"#),
+    ("n_object_comma_instead_of_colon", br#"{"x", null}"#),
+    ("n_object_double_colon", br#"{"x"::"b"}"#),
+    ("n_object_missing_colon", br#"{"a" b}"#),
+    ("n_object_missing_key", br#"{:"b"}"#),
+    ("n_object_missing_value", br#"{"a":"#),
+    ("n_object_no-colon", br#"{"a""#),
+    ("n_object_non_string_key", b"{1:1}"),
+    ("n_object_single_quote", b"{'a':0}"),
+    ("n_object_trailing_comma", br#"{"id":0,}"#),
+    ("n_object_trailing_comment", br#"{"a":"b"}/**/"#),
+    ("n_object_unquoted_key", br#"{a: "b"}"#),
+    ("n_single_space", b" "),
+    ("n_string_1_surrogate_then_escape", br#"["\uD800\"]"#),
+    ("n_string_escape_x", br#"["\x00"]"#),
+    ("n_string_escaped_backslash_bad", br#"["\\\"]"#),
+    ("n_string_escaped_emoji", "[\"\\🌀\"]".as_bytes()),
+    ("n_string_incomplete_escape", br#"["\"]"#),
+    ("n_string_incomplete_surrogate", br#"["\uD834\uDd"]"#),
+    ("n_string_invalid_unicode_escape", br#"["\uqqqq"]"#),
+    ("n_string_no_quotes_with_bad_escape", br"[\n]"),
+    ("n_string_single_quote", b"['single quote']"),
+    ("n_string_unescaped_newline", b"[\"new\nline\"]"),
+    ("n_string_unescaped_tab", b"[\"\t\"]"),
+    ("n_structure_U+2060_word_joined", "[\u{2060}]".as_bytes()),
+    ("n_structure_array_with_extra_array_close", b"[1]]"),
+    ("n_structure_close_unopened_array", b"1]"),
+    ("n_structure_double_array", b"[][]"),
+    ("n_structure_end_array", b"]"),
+    ("n_structure_no_data", b""),
+    ("n_structure_null-byte-outside-string", b"[\0]"),
+    ("n_structure_number_with_trailing_garbage", b"2@"),
+    (
+        "n_structure_object_with_trailing_garbage",
+        br#"{"a": true} "x""#,
+    ),
+    ("n_structure_unclosed_array", b"[1"),
+    ("n_structure_unclosed_object", br#"{"asd":"asd""#),
+    ("n_structure_whitespace_formfeed", b"[\x0c]"),
+];
+
+/// The `i_` cases with if we accept them, see the policy at the top
+const I_CASES: &[(&str, &[u8], bool)] = &[
+    ("i_number_double_huge_neg_exp", b"[123.456e-789]", true),
+    ("i_number_neg_int_huge_exp", b"[-1e+9999]", false),
+    ("i_number_pos_double_huge_exp", b"[1.5e+9999]", false),
+    ("i_number_real_neg_overflow", b"[-123123e100000]", false),
+    ("i_number_real_pos_overflow", b"[123123e100000]", false),
+    ("i_number_real_underflow", b"[123e-10000000]", true),
+    (
+        "i_number_too_big_neg_int",
+        b"[-123123123123123123123123123123]",
+        cfg!(feature = "128bit"),
+    ),
+    (
+        "i_number_too_big_pos_int",
+        b"[100000000000000000000]",
+        cfg!(feature = "128bit"),
+    ),
+    (
+        "i_number_very_big_negative_int",
+        b"[-237462374673276894279832749832423479823246327846]",
+        false,
+    ),
+    (
+        "i_string_1st_surrogate_but_2nd_missing",
+        br#"["\uDADA"]"#,
+        false,
+    ),
+    (
+        "i_string_1st_valid_surrogate_2nd_invalid",
+        br#"["\uD888\u1234"]"#,
+        false,
+    ),
+    (
+        "i_string_UTF-16LE_with_BOM",
+        b"\xff\xfe[\0\"\0\xe9\0\"\0]\0",
+        false,
+    ),
+    (
+        "i_string_UTF-8_invalid_sequence",
+        b"[\"\xe6\x97\xa5\xd1\x88\xfa\"]",
+        false,
+    ),
+    (
+        "i_string_incomplete_surrogate_and_escape_valid",
+        br#"["\uD800\n"]"#,
+        false,
+    ),
+    (
+        "i_string_incomplete_surrogate_pair",
+        br#"["\uDd1ea"]"#,
+        false,
+    ),
+    (
+        "i_string_incomplete_surrogates_escape_valid",
+        br#"["\uD800\uD800\n"]"#,
+        false,
+    ),
+    ("i_string_invalid_lonely_surrogate", br#"["\ud800"]"#, false),
+    ("i_string_invalid_surrogate", br#"["\ud800abc"]"#, false),
+    ("i_string_invalid_utf-8", b"[\"\xff\"]", false),
+    (
+        "i_string_inverted_surrogates_U+1D11E",
+        br#"["\uDd1e\uD834"]"#,
+        false,
+    ),
+    ("i_string_lone_second_surrogate", br#"["\uDFAA"]"#, false),
+    ("i_string_lone_utf8_continuation_byte", b"[\"\x81\"]", false),
+    (
+        "i_string_overlong_sequence_2_bytes",
+        b"[\"\xc0\xaf\"]",
+        false,
+    ),
+    ("i_string_truncated-utf-8", b"[\"\xe0\xff\"]", false),
+    ("i_string_utf16BE_no_BOM", b"\0[\0\"\0\xe9\0\"\0]", false),
+    (
+        "i_structure_UTF-8_BOM_empty_object",
+        b"\xef\xbb\xbf{}",
+        false,
+    ),
+];
+
+/// Cases that are too large to inline, generated like the suite's files
+fn generated_cases() -> Vec<(&'static str, Vec<u8>, bool)> {
+    vec![
+        (
+            "n_structure_100000_opening_arrays",
+            "[".repeat(100_000).into_bytes(),
+            false,
+        ),
+        (
+            "n_structure_open_array_object",
+            r#"[{"":"#.repeat(50_000).into_bytes(),
+            false,
+        ),
+        (
+            "i_structure_500_nested_arrays",
+            format!("{}{}", "[".repeat(500), "]".repeat(500)).into_bytes(),
+            true,
+        ),
+    ]
+}
+
+/// The names and inputs of all cases above
+fn all_cases() -> impl Iterator<Item = (&'static str, Vec<u8>)> {
+    Y_CASES
+        .iter()
+        .chain(N_CASES)
+        .map(|(name, input)| (*name, input.to_vec()))
+        .chain(
+            I_CASES
+                .iter()
+                .map(|(name, input, _)| (*name, input.to_vec())),
+        )
+        .chain(
+            generated_cases()
+                .into_iter()
+                .map(|(name, input, _)| (name, input)),
+        )
+}
+
+/// If both value parsers accept `input`, they have to agree on it
+fn accepts(input: &[u8]) -> bool {
+    let mut v1 = input.to_vec();
+    let mut v2 = input.to_vec();
+    let parsed = (to_borrowed_value(&mut v1), to_owned_value(&mut v2));
+    match parsed {
+        (Ok(borrowed), Ok(owned)) => {
+            // both parsers keep duplicate keys but the conversion drops
+            // them, so the owned value goes through the same conversion
+            let owned = OwnedValue::from(BorrowedValue::from(owned));
+            assert_eq!(OwnedValue::from(borrowed), owned);
+            true
+        }
+        (Err(_), Err(_)) => false,
+        (borrowed, owned) => panic!("parsers disagree: {borrowed:?} vs {owned:?}"),
+    }
+}
+
+/// The results of the number cases depend on how numbers are parsed
+fn skipped(name: &str) -> bool {
+    cfg!(feature = "approx-number-parsing") && name.starts_with("i_number")
+}
+
+#[test]
+fn y_cases() {
+    for (name, input) in Y_CASES {
+        assert!(accepts(input), "{name} is rejected");
+    }
+}
+
+#[test]
+fn n_cases() {
+    for (name, input) in N_CASES {
+        assert!(!accepts(input), "{name} is accepted");
+    }
+}
+
+#[test]
+fn strict() {
+    // duplicate keys are valid JSON but rejected by `parse_strict`
+    for (name, input) in Y_CASES {
+        let expected = if *name == "y_object_duplicated_key" {
+            None
+        } else {
+            to_owned_value(&mut input.to_vec()).ok()
+        };
+        assert_eq!(parse_strict(&mut input.to_vec()).ok(), expected, "{name}");
+    }
+    for (name, input) in N_CASES {
+        assert!(
+            parse_strict(&mut input.to_vec()).is_err(),
+            "{name} is accepted"
+        );
+    }
+    for input in [
+        r#"{"a": {"b": 1, "c": 2, "b": 3}}"#,
+        r#"[{"a": 1}, {"a": 2, "\u0061": 3}]"#,
+        r#"{"x": [], "y": {}, "x": null}"#,
+    ] {
+        let e = parse_strict(&mut input.as_bytes().to_vec()).expect_err(input);
+        assert!(matches!(e.error(), ErrorType::DuplicateKey(_)), "{input}");
+    }
+    let mut d = br#"[{"a": 1}, {"a": 2}, {"b": {"a": [{"a": 1}]}}]"#.to_vec();
+    assert!(parse_strict(&mut d).is_ok());
+}
+
+#[test]
+fn i_cases() {
+    for (name, input, expected) in I_CASES {
+        if !skipped(name) {
+            assert_eq!(accepts(input), *expected, "{name}");
+        }
+    }
+}
+
+#[test]
+fn generated() {
+    for (name, input, expected) in generated_cases() {
+        assert_eq!(accepts(&input), expected, "{name}");
+    }
+}
+
+/// Pins the accept/reject table, update the counts only together with
+/// the policy at the top of this file
+#[test]
+fn summary() {
+    let mut table = [[0_usize; 2]; 3];
+    for (name, input) in all_cases() {
+        if skipped(name) {
+            continue;
+        }
+        let kind = match &name[..2] {
+            "y_" => 0,
+            "n_" => 1,
+            _ => 2,
+        };
+        table[kind][usize::from(!accepts(&input))] += 1;
+    }
+    let big_ints = if cfg!(feature = "128bit") { 2 } else { 0 };
+    let i_accepted = if cfg!(feature = "approx-number-parsing") {
+        1
+    } else {
+        3 + big_ints
+    };
+    let i_rejected = if cfg!(feature = "approx-number-parsing") {
+        17
+    } else {
+        24 - big_ints
+    };
+    // [accepted, rejected] for y_, n_ and i_
+    assert_eq!(table, [[66, 0], [0, 83], [i_accepted, i_rejected]]);
+}
+
+/// serde_json is spec conformant for the `y_` and `n_` cases, so we have
+/// to agree with it
+#[cfg(feature = "serde_impl")]
+#[test]
+fn same_as_serde_json() {
+    for (name, input) in Y_CASES.iter().chain(N_CASES) {
+        let serde_json_accepts = serde_json::from_slice::<serde_json::Value>(input).is_ok();
+        assert_eq!(accepts(input), serde_json_accepts, "{name}");
+    }
+}
+
+/// Checks every file of the corpus, it has to contain at least the
+/// cases above
+#[test]
+fn corpus() {
+    const DIR: &str = "data/JSONTestSuite/test_parsing";
+    for (name, input) in all_cases() {
+        let file = fs::read(format!("{DIR}/{name}.json"))
+            .unwrap_or_else(|e| panic!("{name} is missing from {DIR}: {e}"));
+        assert_eq!(file, input, "{name} differs from its file");
+    }
+
+    for entry in fs::read_dir(DIR).expect("the corpus is in the repository") {
+        let path = entry.expect("readable directory").path();
+        let name = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .expect("utf-8 file name")
+            .to_string();
+        let input = fs::read(&path).expect("readable file");
+        let accepted = accepts(&input);
+        if name.starts_with("y_") {
+            assert!(accepted, "{name} is rejected");
+        } else if name.starts_with("n_") {
+            assert!(!accepted, "{name} is accepted");
+        } else if let Some((_, _, expected)) = I_CASES.iter().find(|(n, _, _)| *n == name) {
+            if !skipped(&name) {
+                assert_eq!(accepted, *expected, "{name}");
+            }
+        }
+    }
+}
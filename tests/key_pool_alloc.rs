@@ -0,0 +1,116 @@
+#[cfg(feature = "alloc")]
+use alloc_counter::{count_alloc, AllocCounterSystem};
+use simd_json::prelude::*;
+use simd_json::{to_borrowed_value, to_borrowed_value_with_pool, BorrowedValue, KeyPool};
+
+#[cfg(feature = "alloc")]
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+fn documents(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| {
+            format!(r#"{{"id": {i}, "name": "doc{i}", "tags": [{{"key": "a"}}], "ok": true}}"#)
+                .into_bytes()
+        })
+        .collect()
+}
+
+fn key<'v>(v: &'v BorrowedValue, name: &str) -> &'v str {
+    v.as_object()
+        .expect("an object")
+        .keys()
+        .find(|k| &***k == name)
+        .expect("a key")
+}
+
+#[test]
+fn shared_keys() {
+    let pool = KeyPool::new();
+    let mut inputs = documents(1000);
+    let values: Vec<BorrowedValue> = inputs
+        .iter_mut()
+        .map(|d| to_borrowed_value_with_pool(d, &pool).expect("valid json"))
+        .collect();
+    assert_eq!(pool.len(), 5);
+    for (i, v) in values.iter().enumerate() {
+        assert_eq!(v["name"], format!("doc{i}").as_str());
+        assert_eq!(v["tags"][0]["key"], "a");
+        assert_eq!(key(v, "name").as_ptr(), key(&values[0], "name").as_ptr());
+    }
+    // documents only borrow the pool, they can be dropped in any order
+    let mut values = values;
+    let last = values.pop().expect("a document");
+    drop(values);
+    assert_eq!(last["id"], 999);
+    assert_eq!(key(&last, "ok"), "ok");
+}
+
+#[test]
+fn pooled_values_outlive_their_input() {
+    let pool = KeyPool::new();
+    let values: Vec<BorrowedValue> = documents(100)
+        .into_iter()
+        .map(|mut d| {
+            let v = to_borrowed_value(&mut d).expect("valid json");
+            v.into_pooled(&pool)
+        })
+        .collect();
+    assert_eq!(pool.len(), 5);
+    for (i, v) in values.iter().enumerate() {
+        assert_eq!(v["name"], format!("doc{i}").as_str());
+        assert_eq!(key(&v["tags"][0], "key"), "key");
+        assert_eq!(key(v, "tags").as_ptr(), key(&values[0], "tags").as_ptr());
+    }
+    let mut d = documents(1).remove(0);
+    assert_eq!(
+        values[0],
+        to_borrowed_value(&mut d).expect("valid json").into_static()
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn pooled_keys_are_not_copied() {
+    let n = 1000;
+    let pool = KeyPool::new();
+    // warm the pool
+    let mut d = documents(1).remove(0);
+    drop(
+        to_borrowed_value(&mut d)
+            .expect("valid json")
+            .into_pooled(&pool),
+    );
+
+    let mut static_inputs = documents(n);
+    let parsed: Vec<BorrowedValue> = static_inputs
+        .iter_mut()
+        .map(|d| to_borrowed_value(d).expect("valid json"))
+        .collect();
+    let ((static_allocations, _, _), owned) = count_alloc(|| {
+        parsed
+            .into_iter()
+            .map(BorrowedValue::into_static)
+            .collect::<Vec<_>>()
+    });
+    let mut pooled_inputs = documents(n);
+    let parsed: Vec<BorrowedValue> = pooled_inputs
+        .iter_mut()
+        .map(|d| to_borrowed_value(d).expect("valid json"))
+        .collect();
+    let ((pooled_allocations, _, _), pooled) = count_alloc(|| {
+        parsed
+            .into_iter()
+            .map(|v| v.into_pooled(&pool))
+            .collect::<Vec<_>>()
+    });
+    drop(pooled_inputs);
+    assert_eq!(owned, pooled);
+    assert_eq!(pool.len(), 5);
+    // every document has 5 keys, `into_static` copies all of them while
+    // a warm pool copies none, strings and containers are copied by both
+    assert!(
+        pooled_allocations + n * 5 <= static_allocations,
+        "{pooled_allocations} vs {static_allocations}"
+    );
+}
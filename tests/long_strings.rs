@@ -0,0 +1,63 @@
+#![cfg(feature = "alloc")]
+use alloc_counter::{count_alloc, AllocCounterSystem};
+use simd_json::prelude::*;
+use simd_json::{to_owned_value_with_opts, ErrorType, OwnedValue, ParseOptions};
+
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+/// 8 MiB and a few bytes
+const LEN: usize = (8 << 20) + 3;
+
+fn document() -> Vec<u8> {
+    // escapes make the unescaped string shorter than its token
+    let mut d = br#"{"k": ""#.to_vec();
+    d.extend(std::iter::repeat(b'a').take(LEN - 1));
+    d.extend_from_slice(br#"\n"}"#);
+    d
+}
+
+#[test]
+fn long_string_is_allocated_once() {
+    let mut d = document();
+    let ((allocations, reallocations, _), v) =
+        count_alloc(|| to_owned_value_with_opts(&mut d, ParseOptions::default()));
+    let v = v.expect("valid json");
+    assert_eq!(v["k"].as_str().map(str::len), Some(LEN));
+    // the string is allocated with its final length right away
+    assert!(matches!(&v["k"], OwnedValue::String(s) if s.capacity() == LEN));
+    // the parser buffers, the tape, the object and the string itself,
+    // nothing grows with the string
+    assert!(allocations < 16, "{allocations} allocations");
+    assert_eq!(reallocations, 0);
+}
+
+#[test]
+fn string_limit_fires_before_the_string_is_allocated() {
+    let mut d = document();
+    let opts = ParseOptions::default().max_string_len(LEN);
+    let ((ok_allocations, _, _), res) = count_alloc(|| to_owned_value_with_opts(&mut d, opts));
+    assert!(res.is_ok());
+
+    let mut d = document();
+    let opts = ParseOptions::default().max_string_len(LEN - 1);
+    let ((allocations, reallocations, _), res) =
+        count_alloc(|| to_owned_value_with_opts(&mut d, opts));
+    let e = res.expect_err("string too long");
+    assert_eq!(
+        e.error(),
+        &ErrorType::StringLengthLimit {
+            limit: LEN - 1,
+            actual: LEN
+        }
+    );
+    // the offset of the string's opening quote
+    assert_eq!(e.index(), 6);
+    assert_eq!(reallocations, 0);
+    // parsing stops while the tape is built, neither the object nor its
+    // key and string are allocated
+    assert!(
+        allocations + 3 <= ok_allocations,
+        "{allocations} vs {ok_allocations}"
+    );
+}
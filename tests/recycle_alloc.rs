@@ -0,0 +1,57 @@
+#![cfg(all(feature = "recycle", feature = "alloc"))]
+use alloc_counter::{count_alloc, AllocCounterSystem};
+use simd_json::{to_owned_value, to_owned_value_recycle, OwnedValue};
+
+#[global_allocator]
+static A: AllocCounterSystem = AllocCounterSystem;
+
+/// Documents of the same shape with strings and arrays of varying length
+fn documents(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| {
+            let tags: Vec<String> = (0..=i % 3).map(|t| format!(r#""tag{t}""#)).collect();
+            format!(
+                r#"{{"id": {i}, "name": "user{i}", "email": "user{i}@example.com", "active": {}, "tags": [{}], "address": {{"city": "city{}", "zip": "{:05}"}}}}"#,
+                i % 2 == 0,
+                tags.join(", "),
+                i % 10,
+                i * 7
+            )
+            .into_bytes()
+        })
+        .collect()
+}
+
+#[test]
+fn allocations_are_recycled() {
+    let n = 10_000;
+    let docs = documents(n);
+    let mut expected_docs = docs.clone();
+    let expected: Vec<OwnedValue> = expected_docs
+        .iter_mut()
+        .map(|d| to_owned_value(d).expect("valid json"))
+        .collect();
+
+    let mut plain_docs = docs.clone();
+    let ((plain_allocations, _, _), ()) = count_alloc(|| {
+        for d in &mut plain_docs {
+            drop(to_owned_value(d).expect("valid json"));
+        }
+    });
+    let mut recycled_docs = docs;
+    let ((recycled_allocations, _, _), ()) = count_alloc(|| {
+        let mut previous = OwnedValue::default();
+        for (d, expected) in recycled_docs.iter_mut().zip(&expected) {
+            previous = to_owned_value_recycle(d, previous).expect("valid json");
+            assert_eq!(&previous, expected);
+        }
+    });
+
+    // every document has 8 keys, 5 to 7 strings, an array and two objects
+    // that are reused, only the parser's own buffers are still allocated
+    // per document
+    assert!(
+        recycled_allocations + n * 15 <= plain_allocations,
+        "{recycled_allocations} vs {plain_allocations}"
+    );
+}